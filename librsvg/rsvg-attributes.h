@@ -8,12 +8,26 @@
 
 /* Keep this in sync with rust/src/build.rs */
 typedef enum {
+    RSVG_ATTRIBUTE_ADDITIVE,
     RSVG_ATTRIBUTE_ALTERNATE,
     RSVG_ATTRIBUTE_AMPLITUDE,
+    RSVG_ATTRIBUTE_ANIMATION,
+    RSVG_ATTRIBUTE_ANIMATION_DELAY,
+    RSVG_ATTRIBUTE_ANIMATION_DIRECTION,
+    RSVG_ATTRIBUTE_ANIMATION_DURATION,
+    RSVG_ATTRIBUTE_ANIMATION_FILL_MODE,
+    RSVG_ATTRIBUTE_ANIMATION_ITERATION_COUNT,
+    RSVG_ATTRIBUTE_ANIMATION_NAME,
+    RSVG_ATTRIBUTE_ANIMATION_TIMING_FUNCTION,
+    RSVG_ATTRIBUTE_ATTRIBUTE_NAME,
+    RSVG_ATTRIBUTE_ATTRIBUTE_TYPE,
     RSVG_ATTRIBUTE_AZIMUTH,
     RSVG_ATTRIBUTE_BASE_FREQUENCY,
     RSVG_ATTRIBUTE_BASELINE_SHIFT,
+    RSVG_ATTRIBUTE_BEGIN,
     RSVG_ATTRIBUTE_BIAS,
+    RSVG_ATTRIBUTE_BY,
+    RSVG_ATTRIBUTE_CALC_MODE,
     RSVG_ATTRIBUTE_CLASS,
     RSVG_ATTRIBUTE_CLIP_PATH,
     RSVG_ATTRIBUTE_CLIP_RULE,
@@ -27,6 +41,7 @@ typedef enum {
     RSVG_ATTRIBUTE_DIRECTION,
     RSVG_ATTRIBUTE_DISPLAY,
     RSVG_ATTRIBUTE_DIVISOR,
+    RSVG_ATTRIBUTE_DUR,
     RSVG_ATTRIBUTE_DX,
     RSVG_ATTRIBUTE_DY,
     RSVG_ATTRIBUTE_EDGE_MODE,
@@ -42,27 +57,43 @@ typedef enum {
     RSVG_ATTRIBUTE_FLOOD_COLOR,
     RSVG_ATTRIBUTE_FLOOD_OPACITY,
     RSVG_ATTRIBUTE_FONT_FAMILY,
+    RSVG_ATTRIBUTE_FONT_FEATURE_SETTINGS,
+    RSVG_ATTRIBUTE_FONT_KERNING,
     RSVG_ATTRIBUTE_FONT_SIZE,
     RSVG_ATTRIBUTE_FONT_STRETCH,
     RSVG_ATTRIBUTE_FONT_STYLE,
     RSVG_ATTRIBUTE_FONT_VARIANT,
+    RSVG_ATTRIBUTE_FONT_VARIANT_CAPS,
+    RSVG_ATTRIBUTE_FONT_VARIANT_LIGATURES,
+    RSVG_ATTRIBUTE_FONT_VARIANT_NUMERIC,
+    RSVG_ATTRIBUTE_FONT_VARIANT_POSITION,
     RSVG_ATTRIBUTE_FONT_WEIGHT,
+    RSVG_ATTRIBUTE_FROM,
     RSVG_ATTRIBUTE_FX,
     RSVG_ATTRIBUTE_FY,
+    RSVG_ATTRIBUTE_G1,
+    RSVG_ATTRIBUTE_G2,
+    RSVG_ATTRIBUTE_GLYPH_NAME,
+    RSVG_ATTRIBUTE_GLYPH_ORIENTATION_VERTICAL,
     RSVG_ATTRIBUTE_GRADIENT_TRANSFORM,
     RSVG_ATTRIBUTE_GRADIENT_UNITS,
     RSVG_ATTRIBUTE_HEIGHT,
+    RSVG_ATTRIBUTE_HORIZ_ADV_X,
     RSVG_ATTRIBUTE_HREF,
     RSVG_ATTRIBUTE_ID,
     RSVG_ATTRIBUTE_IN,
     RSVG_ATTRIBUTE_IN2,
+    RSVG_ATTRIBUTE_INLINE_SIZE,
     RSVG_ATTRIBUTE_INTERCEPT,
+    RSVG_ATTRIBUTE_K,
     RSVG_ATTRIBUTE_K1,
     RSVG_ATTRIBUTE_K2,
     RSVG_ATTRIBUTE_K3,
     RSVG_ATTRIBUTE_K4,
     RSVG_ATTRIBUTE_KERNEL_MATRIX,
     RSVG_ATTRIBUTE_KERNEL_UNIT_LENGTH,
+    RSVG_ATTRIBUTE_KEY_SPLINES,
+    RSVG_ATTRIBUTE_KEY_TIMES,
     RSVG_ATTRIBUTE_LETTER_SPACING,
     RSVG_ATTRIBUTE_LIGHTING_COLOR,
     RSVG_ATTRIBUTE_LIMITING_CONE_ANGLE,
@@ -86,9 +117,11 @@ typedef enum {
     RSVG_ATTRIBUTE_OVERFLOW,
     RSVG_ATTRIBUTE_PARSE,
     RSVG_ATTRIBUTE_PATH,
+    RSVG_ATTRIBUTE_PATH_LENGTH,
     RSVG_ATTRIBUTE_PATTERN_CONTENT_UNITS,
     RSVG_ATTRIBUTE_PATTERN_TRANSFORM,
     RSVG_ATTRIBUTE_PATTERN_UNITS,
+    RSVG_ATTRIBUTE_POINTER_EVENTS,
     RSVG_ATTRIBUTE_POINTS,
     RSVG_ATTRIBUTE_POINTS_AT_X,
     RSVG_ATTRIBUTE_POINTS_AT_Y,
@@ -100,13 +133,17 @@ typedef enum {
     RSVG_ATTRIBUTE_RADIUS,
     RSVG_ATTRIBUTE_REF_X,
     RSVG_ATTRIBUTE_REF_Y,
+    RSVG_ATTRIBUTE_REPEAT_COUNT,
+    RSVG_ATTRIBUTE_REPEAT_DUR,
     RSVG_ATTRIBUTE_REQUIRED_EXTENSIONS,
     RSVG_ATTRIBUTE_REQUIRED_FEATURES,
     RSVG_ATTRIBUTE_RESULT,
+    RSVG_ATTRIBUTE_ROTATE,
     RSVG_ATTRIBUTE_RX,
     RSVG_ATTRIBUTE_RY,
     RSVG_ATTRIBUTE_SCALE,
     RSVG_ATTRIBUTE_SEED,
+    RSVG_ATTRIBUTE_SHAPE_INSIDE,
     RSVG_ATTRIBUTE_SHAPE_RENDERING,
     RSVG_ATTRIBUTE_SLOPE,
     RSVG_ATTRIBUTE_SPECULAR_CONSTANT,
@@ -132,15 +169,29 @@ typedef enum {
     RSVG_ATTRIBUTE_TARGET_Y,
     RSVG_ATTRIBUTE_TEXT_ANCHOR,
     RSVG_ATTRIBUTE_TEXT_DECORATION,
+    RSVG_ATTRIBUTE_TEXT_DECORATION_COLOR,
+    RSVG_ATTRIBUTE_TEXT_DECORATION_STYLE,
+    RSVG_ATTRIBUTE_TEXT_ORIENTATION,
     RSVG_ATTRIBUTE_TEXT_RENDERING,
+    RSVG_ATTRIBUTE_TO,
     RSVG_ATTRIBUTE_TRANSFORM,
+    RSVG_ATTRIBUTE_TRANSITION,
+    RSVG_ATTRIBUTE_TRANSITION_DELAY,
+    RSVG_ATTRIBUTE_TRANSITION_DURATION,
+    RSVG_ATTRIBUTE_TRANSITION_PROPERTY,
+    RSVG_ATTRIBUTE_TRANSITION_TIMING_FUNCTION,
     RSVG_ATTRIBUTE_TYPE,
+    RSVG_ATTRIBUTE_U1,
+    RSVG_ATTRIBUTE_U2,
+    RSVG_ATTRIBUTE_UNICODE,
     RSVG_ATTRIBUTE_UNICODE_BIDI,
+    RSVG_ATTRIBUTE_UNITS_PER_EM,
     RSVG_ATTRIBUTE_VALUES,
     RSVG_ATTRIBUTE_VERTS,
     RSVG_ATTRIBUTE_VIEW_BOX,
     RSVG_ATTRIBUTE_VISIBILITY,
     RSVG_ATTRIBUTE_WIDTH,
+    RSVG_ATTRIBUTE_WORD_SPACING,
     RSVG_ATTRIBUTE_WRITING_MODE,
     RSVG_ATTRIBUTE_X,
     RSVG_ATTRIBUTE_X1,
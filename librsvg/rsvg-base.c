@@ -122,6 +122,9 @@ rsvg_drawing_ctx_free (RsvgDrawingCtx * handle)
     g_warn_if_fail (handle->acquired_nodes == NULL);
     g_slist_free (handle->acquired_nodes);
 
+    if (handle->text_runs != NULL)
+        g_ptr_array_unref (handle->text_runs);
+
     if (handle->pango_context != NULL)
         g_object_unref (handle->pango_context);
 
@@ -416,6 +419,26 @@ rsvg_drawing_ctx_acquire_node (RsvgDrawingCtx * ctx, const char *url)
   return node;
 }
 
+/*
+ * rsvg_drawing_ctx_lookup_node:
+ * @ctx: The drawing context in use
+ * @url: The IRI to lookup, or %NULL
+ *
+ * Unlike rsvg_drawing_ctx_acquire_node(), this does not check for recursion,
+ * so it can be used to find out whether @url references an element at all.
+ *
+ * Returns: (transfer none): The node referenced by @url; or %NULL if the @url
+ *          is %NULL or it does not reference a node.
+ */
+RsvgNode *
+rsvg_drawing_ctx_lookup_node (RsvgDrawingCtx * ctx, const char *url)
+{
+    if (url == NULL)
+        return NULL;
+
+    return rsvg_defs_lookup (ctx->defs, url);
+}
+
 /**
  * rsvg_drawing_ctx_acquire_node_of_type:
  * @ctx: The drawing context in use
@@ -540,6 +563,145 @@ rsvg_drawing_ctx_get_pango_context (RsvgDrawingCtx *draw_ctx)
     return rsvg_cairo_get_pango_context (draw_ctx);
 }
 
+const char * const *
+rsvg_drawing_ctx_get_fallback_font_families (RsvgDrawingCtx *draw_ctx)
+{
+    return draw_ctx->fallback_font_families;
+}
+
+const cairo_font_options_t *
+rsvg_drawing_ctx_get_font_options (RsvgDrawingCtx *draw_ctx)
+{
+    return draw_ctx->font_options;
+}
+
+gboolean
+rsvg_drawing_ctx_is_collecting_text_runs (RsvgDrawingCtx *draw_ctx)
+{
+    return draw_ctx->text_runs != NULL;
+}
+
+/* Takes ownership of the run's strings and character boxes */
+void
+rsvg_drawing_ctx_add_text_run (RsvgDrawingCtx *draw_ctx, RsvgTextRun *run)
+{
+    g_return_if_fail (draw_ctx->text_runs != NULL);
+
+    g_ptr_array_add (draw_ctx->text_runs, g_memdup (run, sizeof (RsvgTextRun)));
+}
+
+gpointer
+rsvg_drawing_ctx_get_text_outlines (RsvgDrawingCtx *draw_ctx)
+{
+    return draw_ctx->text_outlines;
+}
+
+gpointer
+rsvg_drawing_ctx_get_normalizer (RsvgDrawingCtx *draw_ctx)
+{
+    return draw_ctx->normalizer;
+}
+
+void
+rsvg_drawing_ctx_set_normalizer (RsvgDrawingCtx *draw_ctx, gpointer normalizer)
+{
+    draw_ctx->normalizer = normalizer;
+}
+
+gpointer
+rsvg_drawing_ctx_get_hit_test (RsvgDrawingCtx *draw_ctx)
+{
+    return draw_ctx->hit_test;
+}
+
+void
+rsvg_drawing_ctx_set_hit_test (RsvgDrawingCtx *draw_ctx, gpointer hit_test)
+{
+    draw_ctx->hit_test = hit_test;
+}
+
+gpointer
+rsvg_drawing_ctx_get_geometry (RsvgDrawingCtx *draw_ctx)
+{
+    return draw_ctx->geometry;
+}
+
+void
+rsvg_drawing_ctx_set_geometry (RsvgDrawingCtx *draw_ctx, gpointer geometry)
+{
+    draw_ctx->geometry = geometry;
+}
+
+void
+rsvg_text_run_free (RsvgTextRun *run)
+{
+    g_free (run->text);
+    g_free (run->font);
+    g_free (run->char_boxes);
+    g_free (run);
+}
+
+void
+rsvg_diagnostic_free (RsvgDiagnostic *diagnostic)
+{
+    g_free (diagnostic->element);
+    g_free (diagnostic->id);
+    g_free (diagnostic->attribute);
+    g_free (diagnostic->message);
+    g_free (diagnostic);
+}
+
+static gboolean
+rsvg_diagnostic_equal (const RsvgDiagnostic *a, const RsvgDiagnostic *b)
+{
+    return (a->severity == b->severity
+            && a->line == b->line
+            && a->column == b->column
+            && g_strcmp0 (a->element, b->element) == 0
+            && g_strcmp0 (a->id, b->id) == 0
+            && g_strcmp0 (a->attribute, b->attribute) == 0
+            && g_strcmp0 (a->message, b->message) == 0);
+}
+
+/* Takes ownership of the strings in @diagnostic.  Elements may get rendered
+ * many times, so a problem that is already known is not added again.
+ */
+void
+rsvg_drawing_ctx_add_diagnostic (RsvgDrawingCtx *draw_ctx, RsvgDiagnostic *diagnostic)
+{
+    RsvgDiagnostic *copy;
+    guint i;
+
+    copy = g_memdup (diagnostic, sizeof (RsvgDiagnostic));
+
+    if (draw_ctx->diagnostics == NULL) {
+        rsvg_diagnostic_free (copy);
+        return;
+    }
+
+    for (i = 0; i < draw_ctx->diagnostics->len; i++) {
+        if (rsvg_diagnostic_equal (g_ptr_array_index (draw_ctx->diagnostics, i), copy)) {
+            rsvg_diagnostic_free (copy);
+            return;
+        }
+    }
+
+    g_ptr_array_add (draw_ctx->diagnostics, copy);
+}
+
+void
+rsvg_diagnostics_add (GPtrArray *diagnostics, const RsvgDiagnostic *diagnostic)
+{
+    g_ptr_array_add (diagnostics, g_memdup (diagnostic, sizeof (RsvgDiagnostic)));
+}
+
+void
+rsvg_unsupported_feature_free (RsvgUnsupportedFeature *feature)
+{
+    g_free (feature->name);
+    g_free (feature);
+}
+
 void
 rsvg_drawing_ctx_insert_bbox (RsvgDrawingCtx *draw_ctx, RsvgBbox *bbox)
 {
@@ -756,6 +918,19 @@ rsvg_allow_load (GFile       *base_gfile,
     return FALSE;
 }
 
+gboolean
+rsvg_handle_allow_load_url (RsvgHandle *handle, const char *url)
+{
+    char *uri;
+    gboolean allow;
+
+    uri = rsvg_handle_resolve_uri (handle, url);
+    allow = uri != NULL && rsvg_allow_load (handle->priv->base_gfile, uri, NULL);
+    g_free (uri);
+
+    return allow;
+}
+
 char *
 rsvg_handle_resolve_uri (RsvgHandle *handle,
                          const char *uri)
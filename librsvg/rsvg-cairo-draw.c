@@ -34,6 +34,7 @@
 #include "rsvg-cairo-clip.h"
 #include "rsvg-styles.h"
 #include "rsvg-filter.h"
+#include "rsvg-fonts.h"
 #include "rsvg-structure.h"
 
 #include <math.h>
@@ -87,25 +88,10 @@ set_font_options_for_testing (PangoContext *context)
 static void
 create_font_config_for_testing (RsvgCairoRender *render)
 {
-    const char *font_paths[] = {
-        SRCDIR "/tests/resources/Roboto-Regular.ttf",
-        SRCDIR "/tests/resources/Roboto-Italic.ttf",
-        SRCDIR "/tests/resources/Roboto-Bold.ttf",
-        SRCDIR "/tests/resources/Roboto-BoldItalic.ttf",
-    };
-
-    int i;
-
     if (render->font_config_for_testing != NULL)
         return;
 
-    render->font_config_for_testing = FcConfigCreate ();
-
-    for (i = 0; i < G_N_ELEMENTS(font_paths); i++) {
-        if (!FcConfigAppFontAddFile (render->font_config_for_testing, (const FcChar8 *) font_paths[i])) {
-            g_error ("Could not load font file \"%s\" for tests; aborting", font_paths[i]);
-        }
-    }
+    render->font_config_for_testing = rsvg_fonts_create_config_for_testing ();
 }
 
 static PangoFontMap *
@@ -131,15 +117,20 @@ rsvg_cairo_get_pango_context (RsvgDrawingCtx * ctx)
     RsvgCairoRender *render = RSVG_CAIRO_RENDER (ctx->render);
     double dpi_y;
 
-#ifdef HAVE_PANGOFT2
-    if (ctx->is_testing) {
-        fontmap = get_font_map_for_testing (render);
+    if (ctx->font_map) {
+        /* the document has fonts of its own from @font-face rules */
+        fontmap = ctx->font_map;
     } else {
+#ifdef HAVE_PANGOFT2
+        if (ctx->is_testing) {
+            fontmap = get_font_map_for_testing (render);
+        } else {
 #endif
-        fontmap = pango_cairo_font_map_get_default ();
+            fontmap = pango_cairo_font_map_get_default ();
 #ifdef HAVE_PANGOFT2
-    }
+        }
 #endif
+    }
 
     context = pango_font_map_create_context (fontmap);
     pango_cairo_update_context (render->cr, context);
@@ -196,6 +187,9 @@ rsvg_cairo_generate_mask (cairo_t * cr, RsvgNode *mask, RsvgDrawingCtx *ctx, Rsv
     RsvgCairoRender *render = RSVG_CAIRO_RENDER (ctx->render);
     cairo_surface_t *surface;
     cairo_t *mask_cr, *save_cr;
+    gpointer save_normalizer;
+    gpointer save_hit_test;
+    gpointer save_geometry;
     RsvgState *state;
     guint8 opacity;
     guint8 *pixels;
@@ -272,10 +266,24 @@ rsvg_cairo_generate_mask (cairo_t * cr, RsvgNode *mask, RsvgDrawingCtx *ctx, Rsv
         rsvg_drawing_ctx_push_view_box (ctx, 1, 1);
     }
 
+    /* The contents of the mask are not shapes of their own in a normalized
+     * document, they can't be hit, and they are not part of the geometry of
+     * the masked element */
+    save_normalizer = ctx->normalizer;
+    save_hit_test = ctx->hit_test;
+    save_geometry = ctx->geometry;
+    ctx->normalizer = NULL;
+    ctx->hit_test = NULL;
+    ctx->geometry = NULL;
+
     rsvg_drawing_ctx_state_push (ctx);
     rsvg_node_draw_children (mask, ctx, 0, FALSE);
     rsvg_drawing_ctx_state_pop (ctx);
 
+    ctx->normalizer = save_normalizer;
+    ctx->hit_test = save_hit_test;
+    ctx->geometry = save_geometry;
+
     if (content_units == objectBoundingBox) {
         RsvgState *mask_state;
 
@@ -410,6 +418,8 @@ rsvg_cairo_push_render_stack (RsvgDrawingCtx * ctx)
 
     affine = rsvg_state_get_affine (state);
     rsvg_bbox_init (&render->bbox, &affine);
+
+    rsvg_hit_test_push_layer (ctx);
 }
 
 void
@@ -500,6 +510,8 @@ rsvg_cairo_pop_render_stack (RsvgDrawingCtx * ctx)
         rsvg_drawing_ctx_release_node (ctx, lateclip);
     }
 
+    rsvg_hit_test_pop_layer (ctx, render->cr);
+
     cairo_set_operator (render->cr, comp_op);
 
     if (mask) {
@@ -550,6 +562,9 @@ rsvg_cairo_get_surface_of_node (RsvgDrawingCtx *ctx,
 {
     cairo_surface_t *surface;
     cairo_t *cr;
+    gpointer save_normalizer;
+    gpointer save_hit_test;
+    gpointer save_geometry;
 
     RsvgCairoRender *save_render = (RsvgCairoRender *) ctx->render;
     RsvgCairoRender *render;
@@ -565,8 +580,22 @@ rsvg_cairo_get_surface_of_node (RsvgDrawingCtx *ctx,
     render = rsvg_cairo_render_new (cr, width, height);
     ctx->render = (RsvgRender *) render;
 
+    /* Nodes drawn for a filter's feImage are not shapes of their own in a
+     * normalized document, they can't be hit, and they are not part of the
+     * geometry of the filtered element */
+    save_normalizer = ctx->normalizer;
+    save_hit_test = ctx->hit_test;
+    save_geometry = ctx->geometry;
+    ctx->normalizer = NULL;
+    ctx->hit_test = NULL;
+    ctx->geometry = NULL;
+
     rsvg_drawing_ctx_draw_node_from_stack (ctx, drawable, 0, FALSE);
 
+    ctx->normalizer = save_normalizer;
+    ctx->hit_test = save_hit_test;
+    ctx->geometry = save_geometry;
+
     cairo_destroy (cr);
 
     rsvg_render_free (ctx->render);
@@ -35,6 +35,7 @@
 
 #include "rsvg-private.h"
 #include "rsvg-defs.h"
+#include "rsvg-fonts.h"
 #include "rsvg-cairo.h"
 #include "rsvg-cairo-draw.h"
 #include "rsvg-cairo-render.h"
@@ -170,9 +171,18 @@ rsvg_cairo_new_drawing_ctx (cairo_t * cr, RsvgHandle * handle)
     draw->vb.rect.width = data.em;
     draw->vb.rect.height = data.ex;
     draw->pango_context = NULL;
+    draw->font_map = rsvg_handle_get_font_map (handle);
+    draw->fallback_font_families = (const char * const *) handle->priv->fallback_font_families;
+    draw->font_options = handle->priv->font_options;
     draw->vb_stack = NULL;
     draw->drawsub_stack = NULL;
     draw->acquired_nodes = NULL;
+    draw->text_runs = NULL;
+    draw->text_outlines = NULL;
+    draw->normalizer = NULL;
+    draw->hit_test = NULL;
+    draw->geometry = NULL;
+    draw->diagnostics = handle->priv->diagnostics;
     draw->is_testing = handle->priv->is_testing;
 
     rsvg_drawing_ctx_state_push (draw);
@@ -265,3 +275,48 @@ rsvg_handle_render_cairo (RsvgHandle * handle, cairo_t * cr)
 {
     return rsvg_handle_render_cairo_sub (handle, cr, NULL);
 }
+
+/**
+ * rsvg_handle_render_cairo_at_time:
+ * @handle: A #RsvgHandle
+ * @cr: A Cairo renderer
+ * @time: Time in seconds since the start of the document
+ *
+ * Draws a SVG to a Cairo surface like rsvg_handle_render_cairo(), with
+ * the values that the animation elements (<literal>animate</literal>,
+ * <literal>animateTransform</literal>, <literal>animateMotion</literal>
+ * and <literal>set</literal>) give to attributes and properties at @time.
+ * The handle is left as it was before the call.
+ *
+ * CSS animations, which use <literal>@keyframes</literal> rules in the
+ * document's stylesheets and the <literal>animation</literal> properties,
+ * are evaluated at @time as well, with their timing functions.  Animation
+ * elements that change the same property are applied on top of them.
+ * When an animation element begins or ends and makes a property jump to a
+ * new value, the element's CSS transition for the property, from the
+ * <literal>transition</literal> properties, runs from that time on.
+ *
+ * Animations only begin at the offsets in their
+ * <literal>begin</literal> attribute; event and syncbase values are not
+ * supported.
+ *
+ * Returns: %TRUE if drawing succeeded.
+ * Since: 2.44
+ */
+gboolean
+rsvg_handle_render_cairo_at_time (RsvgHandle * handle, cairo_t * cr, double time)
+{
+    gpointer applied;
+    gboolean result;
+
+    g_return_val_if_fail (handle != NULL, FALSE);
+
+    if (handle->priv->hstate != RSVG_HANDLE_STATE_CLOSED_OK)
+        return FALSE;
+
+    applied = rsvg_timeline_apply (handle, time);
+    result = rsvg_handle_render_cairo (handle, cr);
+    rsvg_timeline_restore (handle, applied);
+
+    return result;
+}
@@ -36,6 +36,11 @@ G_BEGIN_DECLS
 
 gboolean    rsvg_handle_render_cairo     (RsvgHandle * handle, cairo_t * cr);
 gboolean    rsvg_handle_render_cairo_sub (RsvgHandle * handle, cairo_t * cr, const char *id);
+gboolean    rsvg_handle_render_cairo_at_time (RsvgHandle * handle, cairo_t * cr, double time);
+
+gboolean    rsvg_handle_get_element_screen_ctm (RsvgHandle * handle, const char *id, cairo_matrix_t *out_ctm);
+
+void        rsvg_handle_set_font_options (RsvgHandle * handle, const cairo_font_options_t *options);
 
 G_END_DECLS
 
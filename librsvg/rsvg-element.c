@@ -0,0 +1,652 @@
+/* -*- Mode: C; indent-tabs-mode: nil; c-basic-offset: 4 -*- */
+/* vim: set sw=4 sts=4 expandtab: */
+/*
+   rsvg-element.c: Read-only access to the tree of a loaded SVG.
+
+   This program is free software; you can redistribute it and/or
+   modify it under the terms of the GNU Library General Public License as
+   published by the Free Software Foundation; either version 2 of the
+   License, or (at your option) any later version.
+
+   This program is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+   Library General Public License for more details.
+
+   You should have received a copy of the GNU Library General Public
+   License along with this program; if not, write to the
+   Free Software Foundation, Inc., 59 Temple Place - Suite 330,
+   Boston, MA 02111-1307, USA.
+*/
+
+#include "config.h"
+
+#include <string.h>
+
+#include "rsvg-private.h"
+#include "rsvg-defs.h"
+
+RsvgNode *
+rsvg_handle_get_root_node (RsvgHandle *handle)
+{
+    return handle->priv->treebase;
+}
+
+RsvgNode *
+rsvg_handle_get_node_by_id (RsvgHandle *handle, const char *id)
+{
+    char *uri;
+    RsvgNode *node;
+
+    /* Only the document's own elements; "#" would make the defs look for an
+     * external file.
+     */
+    if (!handle->priv->treebase || id[0] == '\0' || strchr (id, '#') != NULL)
+        return NULL;
+
+    uri = g_strconcat ("#", id, NULL);
+    node = rsvg_defs_lookup (handle->priv->defs, uri);
+    g_free (uri);
+
+    return node;
+}
+
+/**
+ * rsvg_handle_get_root_element:
+ * @handle: An #RsvgHandle
+ *
+ * Returns the outermost &lt;svg&gt; element of the document.
+ *
+ * Returns: (transfer none) (nullable): The root element, or %NULL if the
+ * handle has not finished loading.
+ *
+ * Since: 2.44
+ */
+RsvgElement *
+rsvg_handle_get_root_element (RsvgHandle *handle)
+{
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), NULL);
+
+    return rsvg_dom_element_from_node (rsvg_handle_get_root_node (handle));
+}
+
+/**
+ * rsvg_handle_get_element_by_id:
+ * @handle: An #RsvgHandle
+ * @id: value of the element's id attribute, without a leading "#"
+ *
+ * Looks up an element by its id.  Unlike rsvg_handle_has_sub(), @id is just
+ * the value of the attribute, for example "layer1".
+ *
+ * Returns: (transfer none) (nullable): The element, or %NULL if there is no
+ * element with that id or if the handle has not finished loading.
+ *
+ * Since: 2.44
+ */
+RsvgElement *
+rsvg_handle_get_element_by_id (RsvgHandle *handle, const char *id)
+{
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), NULL);
+    g_return_val_if_fail (id != NULL, NULL);
+
+    return rsvg_dom_element_from_node (rsvg_handle_get_node_by_id (handle, id));
+}
+
+static RsvgNode *
+lookup_node_for_change (RsvgHandle *handle, const char *id, GError **error)
+{
+    RsvgNode *node;
+
+    node = rsvg_handle_get_node_by_id (handle, id);
+    if (!node) {
+        g_set_error (error, RSVG_ERROR, RSVG_ERROR_FAILED,
+                     "No element with id \"%s\" in the loaded document", id);
+    }
+
+    return node;
+}
+
+static gboolean
+finish_change (gboolean success, char *message, GError **error)
+{
+    if (!success) {
+        g_set_error_literal (error, RSVG_ERROR, RSVG_ERROR_FAILED, message);
+        g_free (message);
+    }
+
+    return success;
+}
+
+/**
+ * rsvg_handle_set_attribute:
+ * @handle: An #RsvgHandle that has finished loading
+ * @id: value of the element's id attribute, without a leading "#"
+ * @name: name of the attribute, for example "transform"
+ * @value: new value for the attribute
+ * @error: return location for errors
+ *
+ * Changes an attribute of an element, or adds the attribute to the element,
+ * after the SVG has been loaded.  The element is updated as if the document
+ * had been loaded with the new value, including its style, so the next call
+ * to rsvg_handle_render_cairo() or rsvg_handle_get_pixbuf() reflects the
+ * change.  This is much faster than loading the document again, for example
+ * to animate parts of it.
+ *
+ * The "id" attribute cannot be changed, and attributes that librsvg does not
+ * know about cannot be set.
+ *
+ * Returns: %TRUE on success.  If the value is invalid for the attribute,
+ * returns %FALSE and leaves the element unchanged.
+ *
+ * Since: 2.44
+ */
+gboolean
+rsvg_handle_set_attribute (RsvgHandle *handle,
+                           const char *id,
+                           const char *name,
+                           const char *value,
+                           GError **error)
+{
+    RsvgNode *node;
+    char *message = NULL;
+    gboolean success;
+
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), FALSE);
+    g_return_val_if_fail (id != NULL, FALSE);
+    g_return_val_if_fail (name != NULL, FALSE);
+    g_return_val_if_fail (value != NULL, FALSE);
+    g_return_val_if_fail (error == NULL || *error == NULL, FALSE);
+
+    node = lookup_node_for_change (handle, id, error);
+    if (!node)
+        return FALSE;
+
+    success = rsvg_dom_node_set_attribute (handle, node, name, value, &message);
+
+    return finish_change (success, message, error);
+}
+
+/**
+ * rsvg_handle_set_style_property:
+ * @handle: An #RsvgHandle that has finished loading
+ * @id: value of the element's id attribute, without a leading "#"
+ * @property: name of a CSS property, for example "fill"
+ * @value: new value for the property
+ * @error: return location for errors
+ *
+ * Sets a property in the style attribute of an element after the SVG has
+ * been loaded, replacing an earlier declaration of the same property in the
+ * attribute.  See rsvg_handle_set_attribute().
+ *
+ * As with the style attribute in a document, invalid values are ignored.
+ *
+ * Returns: %TRUE on success, or %FALSE if there is no element with the
+ * given @id.
+ *
+ * Since: 2.44
+ */
+gboolean
+rsvg_handle_set_style_property (RsvgHandle *handle,
+                                const char *id,
+                                const char *property,
+                                const char *value,
+                                GError **error)
+{
+    RsvgNode *node;
+    char *message = NULL;
+    gboolean success;
+
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), FALSE);
+    g_return_val_if_fail (id != NULL, FALSE);
+    g_return_val_if_fail (property != NULL, FALSE);
+    g_return_val_if_fail (value != NULL, FALSE);
+    g_return_val_if_fail (error == NULL || *error == NULL, FALSE);
+
+    node = lookup_node_for_change (handle, id, error);
+    if (!node)
+        return FALSE;
+
+    success = rsvg_dom_node_set_style_property (handle, node, property, value, &message);
+
+    return finish_change (success, message, error);
+}
+
+/**
+ * rsvg_handle_set_text_content:
+ * @handle: An #RsvgHandle that has finished loading
+ * @id: value of the element's id attribute, without a leading "#"
+ * @text: new text for the element
+ * @error: return location for errors
+ *
+ * Replaces the text of a &lt;text&gt; or &lt;tspan&gt; element after the SVG
+ * has been loaded, for example to update a label.  See
+ * rsvg_handle_set_attribute().
+ *
+ * Only elements without child elements can be changed; to change part of
+ * the text of an element, put that part in a &lt;tspan&gt; with an id.
+ *
+ * Returns: %TRUE on success, or %FALSE if there is no element with the
+ * given @id, or if its text cannot be replaced.
+ *
+ * Since: 2.44
+ */
+gboolean
+rsvg_handle_set_text_content (RsvgHandle *handle,
+                              const char *id,
+                              const char *text,
+                              GError **error)
+{
+    RsvgNode *node;
+    char *message = NULL;
+    gboolean success;
+
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), FALSE);
+    g_return_val_if_fail (id != NULL, FALSE);
+    g_return_val_if_fail (text != NULL, FALSE);
+    g_return_val_if_fail (error == NULL || *error == NULL, FALSE);
+
+    node = lookup_node_for_change (handle, id, error);
+    if (!node)
+        return FALSE;
+
+    success = rsvg_dom_node_set_text_content (node, text, &message);
+
+    return finish_change (success, message, error);
+}
+
+/**
+ * rsvg_handle_serialize:
+ * @handle: An #RsvgHandle that has finished loading
+ * @flags: flags from #RsvgSerializeFlags
+ * @error: return location for errors
+ *
+ * Writes the tree of the loaded document as SVG XML, including the changes
+ * made with rsvg_handle_set_attribute(), rsvg_handle_set_style_property() and
+ * rsvg_handle_set_text_content().
+ *
+ * Only the elements and attributes that librsvg knows about are written;
+ * for example, &lt;title&gt; and &lt;metadata&gt; are left out.  The rules
+ * from the document's stylesheets are written in a single &lt;style&gt;
+ * element at the start of the root element.  Loading the result with the
+ * same base URI renders the same image as the original document.
+ *
+ * Returns: (transfer full) (nullable): A UTF-8 string with the document, or
+ * %NULL if the handle has not finished loading.  Free it with g_free().
+ *
+ * Since: 2.44
+ */
+char *
+rsvg_handle_serialize (RsvgHandle *handle, RsvgSerializeFlags flags, GError **error)
+{
+    char *result;
+
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), NULL);
+    g_return_val_if_fail (error == NULL || *error == NULL, NULL);
+
+    result = rsvg_dom_serialize (handle,
+                                 (flags & RSVG_SERIALIZE_FLAG_PRETTY) != 0,
+                                 (flags & RSVG_SERIALIZE_FLAG_DROP_NODES_IN_ERROR) != 0);
+    if (!result) {
+        g_set_error_literal (error, RSVG_ERROR, RSVG_ERROR_FAILED,
+                             "The handle has not finished loading");
+    }
+
+    return result;
+}
+
+void
+rsvg_sanitize_removals_add (GPtrArray *removals, const RsvgSanitizeRemoval *removal)
+{
+    g_ptr_array_add (removals, g_memdup (removal, sizeof (RsvgSanitizeRemoval)));
+}
+
+static void
+rsvg_sanitize_removal_free (RsvgSanitizeRemoval *removal)
+{
+    g_free (removal->name);
+    g_free (removal->element);
+    g_free (removal->id);
+    g_free (removal->value);
+    g_free (removal->reason);
+    g_free (removal);
+}
+
+/**
+ * rsvg_handle_sanitize:
+ * @handle: An #RsvgHandle that has finished loading
+ * @flags: flags from #RsvgSerializeFlags
+ * @out_removals: (out) (optional) (transfer full) (element-type RsvgSanitizeRemoval):
+ *   return location for what was left out of the document, or %NULL
+ * @error: return location for errors
+ *
+ * Writes the loaded document as SVG XML, like rsvg_handle_serialize(), but
+ * leaves out the parts that are unsafe to serve to a web browser: scripts,
+ * event handler attributes, &lt;foreignObject&gt;, elements from unknown
+ * namespaces, and references to files that librsvg itself would refuse to
+ * load, like http: URLs or files outside the directory of the document.
+ * References within the document and data: URLs are kept.
+ *
+ * If @out_removals is not %NULL, it is set to an array that describes each
+ * part of the document that was left out.  Free it with g_ptr_array_unref().
+ *
+ * Returns: (transfer full) (nullable): A UTF-8 string with the document, or
+ * %NULL if the handle has not finished loading.  Free it with g_free().
+ *
+ * Since: 2.44
+ */
+char *
+rsvg_handle_sanitize (RsvgHandle *handle,
+                      RsvgSerializeFlags flags,
+                      GPtrArray **out_removals,
+                      GError **error)
+{
+    GPtrArray *removals;
+    char *result;
+
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), NULL);
+    g_return_val_if_fail (error == NULL || *error == NULL, NULL);
+
+    removals = g_ptr_array_new_with_free_func ((GDestroyNotify) rsvg_sanitize_removal_free);
+
+    result = rsvg_dom_sanitize (handle,
+                                (flags & RSVG_SERIALIZE_FLAG_PRETTY) != 0,
+                                (flags & RSVG_SERIALIZE_FLAG_DROP_NODES_IN_ERROR) != 0,
+                                removals);
+    if (!result) {
+        g_set_error_literal (error, RSVG_ERROR, RSVG_ERROR_FAILED,
+                             "The handle has not finished loading");
+        g_ptr_array_unref (removals);
+        return NULL;
+    }
+
+    if (out_removals)
+        *out_removals = removals;
+    else
+        g_ptr_array_unref (removals);
+
+    return result;
+}
+
+/**
+ * rsvg_handle_normalize:
+ * @handle: An #RsvgHandle that has finished loading
+ * @flags: flags from #RsvgNormalizeFlags
+ * @out_diagnostics: (out) (optional) (transfer full) (element-type RsvgDiagnostic):
+ *   return location for the parts of the document that could not be
+ *   normalized, or %NULL
+ * @error: return location for errors
+ *
+ * Writes the loaded document in a normalized form, for renderers that only
+ * understand a minimal subset of SVG.
+ *
+ * The document is drawn in the same way as rsvg_handle_render_cairo() would
+ * do with an identity transformation, and each element is written as it gets
+ * drawn.  Groups are kept, with their opacity, clipping path, mask and
+ * filter.  &lt;use&gt; elements are replaced by a group with the elements
+ * that they instantiate, markers are replaced by their contents, and basic
+ * shapes become paths.  The styles that come from classes, stylesheets and
+ * inheritance become explicit presentation attributes on paths, text and
+ * images.  The clipping paths, masks, filters, gradients and patterns that
+ * the document uses are copied into a &lt;defs&gt;, with the styles of their
+ * children made explicit in the same way.
+ *
+ * If @out_diagnostics is not %NULL, it is set to an array with an
+ * #RsvgDiagnostic of severity #RSVG_DIAGNOSTIC_SEVERITY_WARNING for each
+ * part of the document that the result can't express, like a compositing
+ * operator, a reference to an element that is not in the document, or a
+ * &lt;use&gt; or basic shape inside a copied clipping path, mask or
+ * pattern.  Free it with g_ptr_array_unref().
+ *
+ * Returns: (transfer full) (nullable): A UTF-8 string with the normalized
+ * document, or %NULL if the handle has not finished loading.  Free it with
+ * g_free().
+ *
+ * Since: 2.44
+ */
+char *
+rsvg_handle_normalize (RsvgHandle *handle,
+                       RsvgNormalizeFlags flags,
+                       GPtrArray **out_diagnostics,
+                       GError **error)
+{
+    GPtrArray *diagnostics;
+    char *result;
+
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), NULL);
+    g_return_val_if_fail (error == NULL || *error == NULL, NULL);
+
+    diagnostics = g_ptr_array_new_with_free_func ((GDestroyNotify) rsvg_diagnostic_free);
+
+    result = rsvg_dom_normalize (handle,
+                                 (flags & RSVG_NORMALIZE_FLAG_PRETTY) != 0,
+                                 (flags & RSVG_NORMALIZE_FLAG_BAKE_TRANSFORMS) != 0,
+                                 diagnostics);
+    if (!result) {
+        g_set_error_literal (error, RSVG_ERROR, RSVG_ERROR_FAILED,
+                             "The handle has not finished loading");
+        g_ptr_array_unref (diagnostics);
+        return NULL;
+    }
+
+    if (out_diagnostics)
+        *out_diagnostics = diagnostics;
+    else
+        g_ptr_array_unref (diagnostics);
+
+    return result;
+}
+
+/**
+ * rsvg_handle_get_element_bbox:
+ * @handle: An #RsvgHandle that has finished loading
+ * @id: value of the element's id attribute, without a leading "#"
+ * @flags: flags from #RsvgBboxFlags, to choose what the box includes
+ * @out_bbox: (out): return location for the bounding box
+ *
+ * Computes the bounding box of an element in its own user space, like the
+ * getBBox() method of the SVG DOM.  The user space of an element includes
+ * its transform attribute; use rsvg_handle_get_element_screen_ctm() to get
+ * to the coordinates of the rendered image.  Unlike
+ * rsvg_handle_get_dimensions_sub(), the box is not rounded to integers.
+ *
+ * The element is laid out in the same way as rsvg_handle_render_cairo()
+ * would do, but nothing gets rendered.  The bounding boxes of shapes come
+ * from their paths and strokes, and those of text and images from their
+ * extents.  Clipping paths, masks and filters do not change the box, and the
+ * contents of patterns are not part of it.  An element that has nothing to
+ * draw, like an empty group, gets an empty box at the origin.
+ *
+ * Returns: %TRUE on success; %FALSE if there is no element with that id, if
+ * it is not rendered, for example because it is in a &lt;defs&gt; or because
+ * of its display property, if its transformation is not invertible, or if
+ * the handle has not finished loading.
+ *
+ * Since: 2.44
+ */
+gboolean
+rsvg_handle_get_element_bbox (RsvgHandle *handle,
+                              const char *id,
+                              RsvgBboxFlags flags,
+                              RsvgRectangle *out_bbox)
+{
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), FALSE);
+    g_return_val_if_fail (id != NULL, FALSE);
+    g_return_val_if_fail (out_bbox != NULL, FALSE);
+
+    return rsvg_geometry_get_bbox (handle,
+                                   id,
+                                   (flags & RSVG_BBOX_FLAG_FILL) != 0,
+                                   (flags & RSVG_BBOX_FLAG_STROKE) != 0,
+                                   (flags & RSVG_BBOX_FLAG_MARKERS) != 0,
+                                   out_bbox);
+}
+
+/**
+ * rsvg_handle_get_element_screen_ctm:
+ * @handle: An #RsvgHandle that has finished loading
+ * @id: value of the element's id attribute, without a leading "#"
+ * @out_ctm: (out): return location for the transformation
+ *
+ * Computes the transformation from the user space of an element to the
+ * coordinates of the image that rsvg_handle_render_cairo() renders with an
+ * identity transformation, like the getScreenCTM() method of the SVG DOM.
+ * The transformation includes the element's own transform attribute, and for
+ * &lt;svg&gt; elements, the one from their viewBox.
+ *
+ * Returns: %TRUE on success; %FALSE if there is no element with that id, if
+ * it is not rendered, or if the handle has not finished loading.
+ *
+ * Since: 2.44
+ */
+gboolean
+rsvg_handle_get_element_screen_ctm (RsvgHandle *handle, const char *id, cairo_matrix_t *out_ctm)
+{
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), FALSE);
+    g_return_val_if_fail (id != NULL, FALSE);
+    g_return_val_if_fail (out_ctm != NULL, FALSE);
+
+    return rsvg_geometry_get_screen_ctm (handle, id, out_ctm);
+}
+
+/**
+ * rsvg_element_get_name:
+ * @element: An #RsvgElement
+ *
+ * Returns the name of the element as it appears in the document, for example
+ * "circle".
+ *
+ * Returns: (transfer full): The element's name.  Free it with g_free().
+ *
+ * Since: 2.44
+ */
+char *
+rsvg_element_get_name (RsvgElement *element)
+{
+    g_return_val_if_fail (element != NULL, NULL);
+
+    return rsvg_dom_element_get_name (element);
+}
+
+/**
+ * rsvg_element_get_parent:
+ * @element: An #RsvgElement
+ *
+ * Returns: (transfer none) (nullable): The element's parent, or %NULL for the
+ * root element.
+ *
+ * Since: 2.44
+ */
+RsvgElement *
+rsvg_element_get_parent (RsvgElement *element)
+{
+    g_return_val_if_fail (element != NULL, NULL);
+
+    return rsvg_dom_element_get_parent (element);
+}
+
+/**
+ * rsvg_element_get_n_children:
+ * @element: An #RsvgElement
+ *
+ * Returns the number of child elements.  Text content is not counted.
+ *
+ * Returns: The number of child elements.
+ *
+ * Since: 2.44
+ */
+guint
+rsvg_element_get_n_children (RsvgElement *element)
+{
+    g_return_val_if_fail (element != NULL, 0);
+
+    return rsvg_dom_element_get_n_children (element);
+}
+
+/**
+ * rsvg_element_get_child:
+ * @element: An #RsvgElement
+ * @index_: position of the child, starting at 0
+ *
+ * Returns: (transfer none) (nullable): The child element at @index_, in
+ * document order, or %NULL if @index_ is not less than
+ * rsvg_element_get_n_children().
+ *
+ * Since: 2.44
+ */
+RsvgElement *
+rsvg_element_get_child (RsvgElement *element, guint index_)
+{
+    g_return_val_if_fail (element != NULL, NULL);
+
+    return rsvg_dom_element_get_child (element, index_);
+}
+
+/**
+ * rsvg_element_get_attribute:
+ * @element: An #RsvgElement
+ * @name: name of an attribute, for example "cx" or "xlink:href"
+ *
+ * Returns the value of an attribute as it appears in the document.  This
+ * includes attributes that librsvg does not support, which are also listed by
+ * rsvg_handle_get_unsupported_features().
+ *
+ * Returns: (transfer full) (nullable): The attribute's value, or %NULL if the
+ * element does not have that attribute.  Free it with g_free().
+ *
+ * Since: 2.44
+ */
+char *
+rsvg_element_get_attribute (RsvgElement *element, const char *name)
+{
+    g_return_val_if_fail (element != NULL, NULL);
+    g_return_val_if_fail (name != NULL, NULL);
+
+    return rsvg_dom_element_get_attribute (element, name);
+}
+
+/**
+ * rsvg_element_get_attribute_names:
+ * @element: An #RsvgElement
+ *
+ * Returns the names of the attributes that rsvg_element_get_attribute() can
+ * return, in document order.
+ *
+ * Returns: (transfer full) (array zero-terminated=1): The names of the
+ * attributes.  Free them with g_strfreev().
+ *
+ * Since: 2.44
+ */
+char **
+rsvg_element_get_attribute_names (RsvgElement *element)
+{
+    g_return_val_if_fail (element != NULL, NULL);
+
+    return rsvg_dom_element_get_attribute_names (element);
+}
+
+/**
+ * rsvg_element_get_computed_style:
+ * @element: An #RsvgElement
+ * @property: name of a CSS property, for example "fill" or "font-size"
+ *
+ * Returns the computed value of a style property, after applying the
+ * element's presentation attributes, its style attribute, the document's
+ * stylesheets and inheritance.  Colors are returned as "rgb(r, g, b)" or
+ * "rgba(r, g, b, a)".  Lengths are returned in user units or in inches for
+ * physical units, for example "12" or "0.5in".  Font-relative units are
+ * resolved against the computed font size, and percentages that depend on the
+ * viewport are returned as such, for example "50%".
+ *
+ * Returns: (transfer full) (nullable): The value of the property, or %NULL if
+ * librsvg does not support the property.  Free it with g_free().
+ *
+ * Since: 2.44
+ */
+char *
+rsvg_element_get_computed_style (RsvgElement *element, const char *property)
+{
+    g_return_val_if_fail (element != NULL, NULL);
+    g_return_val_if_fail (property != NULL, NULL);
+
+    return rsvg_dom_element_get_computed_style (element, property);
+}
@@ -60,6 +60,7 @@ struct _RsvgFilterContext {
     cairo_matrix_t paffine;
     int channelmap[4];
     RsvgDrawingCtx *ctx;
+    RsvgNode *node; /* the primitive that is being rendered, for diagnostics */
 };
 
 typedef struct _RsvgFilterPrimitive RsvgFilterPrimitive;
@@ -563,7 +564,9 @@ render_child_if_filter_primitive (RsvgNode *node, RsvgFilterContext *filter_ctx)
         RsvgFilterPrimitive *primitive;
 
         primitive = rsvg_rust_cnode_get_impl (node);
+        filter_ctx->node = node;
         rsvg_filter_primitive_render (node, primitive, filter_ctx);
+        filter_ctx->node = NULL;
     }
 }
 
@@ -799,12 +802,38 @@ rsvg_filter_get_result (GString * name, RsvgFilterContext * ctx)
     return output;
 }
 
+static gboolean
+is_standard_input (const char *name)
+{
+    return (!strcmp (name, "")
+            || !strcmp (name, "none")
+            || !strcmp (name, "SourceGraphic")
+            || !strcmp (name, "SourceAlpha")
+            || !strcmp (name, "BackgroundImage")
+            || !strcmp (name, "BackgroundAlpha")
+            || !strcmp (name, "FillPaint")
+            || !strcmp (name, "StrokePaint"));
+}
+
+/* @attr_name is the attribute that @name comes from, for the diagnostic that
+ * gets reported if there is no result with that name.
+ */
 static cairo_surface_t *
-rsvg_filter_get_in (GString * name, RsvgFilterContext * ctx)
+rsvg_filter_get_in (GString * name, const char *attr_name, RsvgFilterContext * ctx)
 {
     cairo_surface_t *surface;
 
     surface = rsvg_filter_get_result (name, ctx).surface;
+
+    if (surface == NULL && ctx->node != NULL && !is_standard_input (name->str)) {
+        char *message = g_strdup_printf ("reference to nonexistent filter result \"%s\"", name->str);
+
+        rsvg_drawing_ctx_add_node_diagnostic (ctx->ctx, ctx->node,
+                                              RSVG_DIAGNOSTIC_SEVERITY_WARNING,
+                                              attr_name, message);
+        g_free (message);
+    }
+
     if (surface == NULL || cairo_surface_status (surface) != CAIRO_STATUS_SUCCESS) {
         return NULL;
     }
@@ -1049,11 +1078,11 @@ rsvg_filter_primitive_blend_render (RsvgNode *node, RsvgFilterPrimitive *primiti
 
     boundarys = rsvg_filter_primitive_get_bounds (primitive, ctx);
 
-    in = rsvg_filter_get_in (primitive->in, ctx);
+    in = rsvg_filter_get_in (primitive->in, "in", ctx);
     if (in == NULL)
       return;
 
-    in2 = rsvg_filter_get_in (blend->in2, ctx);
+    in2 = rsvg_filter_get_in (blend->in2, "in2", ctx);
     if (in2 == NULL) {
         cairo_surface_destroy (in);
         return;
@@ -1203,7 +1232,7 @@ rsvg_filter_primitive_convolve_matrix_render (RsvgNode *node, RsvgFilterPrimitiv
 
     boundarys = rsvg_filter_primitive_get_bounds (primitive, ctx);
 
-    in = rsvg_filter_get_in (primitive->in, ctx);
+    in = rsvg_filter_get_in (primitive->in, "in", ctx);
     if (in == NULL)
         return;
 
@@ -2057,7 +2086,7 @@ rsvg_filter_primitive_gaussian_blur_render (RsvgNode *node, RsvgFilterPrimitive
 
     boundarys = rsvg_filter_primitive_get_bounds (primitive, ctx);
 
-    in = rsvg_filter_get_in (primitive->in, ctx);
+    in = rsvg_filter_get_in (primitive->in, "in", ctx);
     if (in == NULL) {
         return;
     }
@@ -2194,7 +2223,7 @@ rsvg_filter_primitive_offset_render (RsvgNode *node, RsvgFilterPrimitive *primit
 
     boundarys = rsvg_filter_primitive_get_bounds (primitive, ctx);
 
-    in = rsvg_filter_get_in (primitive->in, ctx);
+    in = rsvg_filter_get_in (primitive->in, "in", ctx);
     if (in == NULL)
         return;
 
@@ -2328,7 +2357,7 @@ merge_render_child(RsvgNode          *node,
 
     fp = rsvg_rust_cnode_get_impl (node);
 
-    in = rsvg_filter_get_in (fp->in, ctx);
+    in = rsvg_filter_get_in (fp->in, "in", ctx);
     if (in == NULL)
         return;
 
@@ -2498,7 +2527,7 @@ rsvg_filter_primitive_color_matrix_render (RsvgNode *node, RsvgFilterPrimitive *
 
     boundarys = rsvg_filter_primitive_get_bounds (primitive, ctx);
 
-    in = rsvg_filter_get_in (primitive->in, ctx);
+    in = rsvg_filter_get_in (primitive->in, "in", ctx);
     if (in == NULL)
         return;
 
@@ -2888,7 +2917,7 @@ rsvg_filter_primitive_component_transfer_render (RsvgNode *node, RsvgFilterPrimi
             closure.functions[ctx->channelmap[c]] = identity_component_transfer_func;
     }
 
-    in = rsvg_filter_get_in (primitive->in, ctx);
+    in = rsvg_filter_get_in (primitive->in, "in", ctx);
     if (in == NULL)
         return;
 
@@ -3146,7 +3175,7 @@ rsvg_filter_primitive_erode_render (RsvgNode *node, RsvgFilterPrimitive *primiti
 
     boundarys = rsvg_filter_primitive_get_bounds (primitive, ctx);
 
-    in = rsvg_filter_get_in (primitive->in, ctx);
+    in = rsvg_filter_get_in (primitive->in, "in", ctx);
     if (in == NULL)
         return;
 
@@ -3325,11 +3354,11 @@ rsvg_filter_primitive_composite_render (RsvgNode *node, RsvgFilterPrimitive *pri
 
     boundarys = rsvg_filter_primitive_get_bounds (primitive, ctx);
 
-    in = rsvg_filter_get_in (primitive->in, ctx);
+    in = rsvg_filter_get_in (primitive->in, "in", ctx);
     if (in == NULL)
         return;
 
-    in2 = rsvg_filter_get_in (composite->in2, ctx);
+    in2 = rsvg_filter_get_in (composite->in2, "in2", ctx);
     if (in2 == NULL) {
         cairo_surface_destroy (in);
         return;
@@ -3651,13 +3680,13 @@ rsvg_filter_primitive_displacement_map_render (RsvgNode *node, RsvgFilterPrimiti
 
     boundarys = rsvg_filter_primitive_get_bounds (primitive, ctx);
 
-    in = rsvg_filter_get_in (primitive->in, ctx);
+    in = rsvg_filter_get_in (primitive->in, "in", ctx);
     if (in == NULL)
         return;
 
     cairo_surface_flush (in);
 
-    in2 = rsvg_filter_get_in (displacement_map->in2, ctx);
+    in2 = rsvg_filter_get_in (displacement_map->in2, "in2", ctx);
     if (in2 == NULL) {
         cairo_surface_destroy (in);
         return;
@@ -4082,7 +4111,7 @@ rsvg_filter_primitive_turbulence_render (RsvgNode *node, RsvgFilterPrimitive *pr
     if (cairo_matrix_invert (&affine) != CAIRO_STATUS_SUCCESS)
       return;
 
-    in = rsvg_filter_get_in (primitive->in, ctx);
+    in = rsvg_filter_get_in (primitive->in, "in", ctx);
     if (in == NULL)
         return;
 
@@ -4276,6 +4305,7 @@ rsvg_filter_primitive_image_render_ext (RsvgFilterPrimitive *self, RsvgFilterCon
     int channelmap[4];
     int length;
     int width, height;
+    GError *error = NULL;
 
     if (!image->href)
         return NULL;
@@ -4289,9 +4319,19 @@ rsvg_filter_primitive_image_render_ext (RsvgFilterPrimitive *self, RsvgFilterCon
 
     img = rsvg_cairo_surface_new_from_href (image->handle,
                                             image->href->str,
-                                            NULL);
-    if (!img)
+                                            &error);
+    if (!img) {
+        char *message = g_strdup_printf ("could not load image \"%s\": %s",
+                                         image->href->str,
+                                         error ? error->message : "unknown error");
+
+        rsvg_drawing_ctx_add_node_diagnostic (ctx->ctx, ctx->node,
+                                              RSVG_DIAGNOSTIC_SEVERITY_WARNING,
+                                              "xlink:href", message);
+        g_free (message);
+        g_clear_error (&error);
         return NULL;
+    }
 
     intermediate = cairo_image_surface_create (CAIRO_FORMAT_ARGB32, width, height);
     if (cairo_surface_status (intermediate) != CAIRO_STATUS_SUCCESS ||
@@ -4961,7 +5001,7 @@ rsvg_filter_primitive_diffuse_lighting_render (RsvgNode *node, RsvgFilterPrimiti
 
     boundarys = rsvg_filter_primitive_get_bounds (primitive, ctx);
 
-    in = rsvg_filter_get_in (primitive->in, ctx);
+    in = rsvg_filter_get_in (primitive->in, "in", ctx);
     if (in == NULL)
         return;
 
@@ -5170,7 +5210,7 @@ rsvg_filter_primitive_specular_lighting_render (RsvgNode *node, RsvgFilterPrimit
 
     boundarys = rsvg_filter_primitive_get_bounds (primitive, ctx);
 
-    in = rsvg_filter_get_in (primitive->in, ctx);
+    in = rsvg_filter_get_in (primitive->in, "in", ctx);
     if (in == NULL)
         return;
 
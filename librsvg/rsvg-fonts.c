@@ -0,0 +1,421 @@
+/* -*- Mode: C; indent-tabs-mode: nil; c-basic-offset: 4 -*- */
+/* vim: set sw=4 sts=4 expandtab: */
+/*
+   rsvg-fonts.c: Fonts loaded from @font-face rules.
+
+   This program is free software; you can redistribute it and/or
+   modify it under the terms of the GNU Library General Public License as
+   published by the Free Software Foundation; either version 2 of the
+   License, or (at your option) any later version.
+
+   This program is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+   Library General Public License for more details.
+
+   You should have received a copy of the GNU Library General Public
+   License along with this program; if not, write to the
+   Free Software Foundation, Inc., 59 Temple Place - Suite 330,
+   Boston, MA 02111-1307, USA.
+*/
+
+#include "config.h"
+
+#include <glib/gstdio.h>
+
+#include "rsvg-fonts.h"
+
+#include <pango/pangocairo.h>
+
+#ifdef HAVE_PANGOFT2
+#include <pango/pangofc-fontmap.h>
+#include <cairo-ft.h>
+#endif
+
+/* Fontconfig can only load fonts from files, so the font data from each
+ * @font-face rule gets written to a private temporary directory that lives as
+ * long as the handle.
+ *
+ * The fontconfig configuration is built on demand from the registered font
+ * directories and font faces, and rebuilt if they change.
+ */
+struct RsvgFonts {
+#ifdef HAVE_PANGOFT2
+    FcConfig *config;
+    PangoFontMap *font_map;
+#endif
+    GPtrArray *font_dirs;
+    GPtrArray *faces;
+    gchar *dir;
+    GPtrArray *files;
+};
+
+#ifdef HAVE_PANGOFT2
+
+typedef struct {
+    gchar *filename;
+    gchar *family;
+} RsvgFontFace;
+
+static void
+rsvg_font_face_free (RsvgFontFace *face)
+{
+    g_free (face->filename);
+    g_free (face->family);
+    g_free (face);
+}
+
+static RsvgFonts *
+rsvg_fonts_new (void)
+{
+    RsvgFonts *fonts;
+
+    fonts = g_new0 (RsvgFonts, 1);
+    fonts->font_dirs = g_ptr_array_new_with_free_func (g_free);
+    fonts->faces = g_ptr_array_new_with_free_func ((GDestroyNotify) rsvg_font_face_free);
+    fonts->files = g_ptr_array_new_with_free_func (g_free);
+
+    return fonts;
+}
+
+static RsvgFonts *
+get_fonts (RsvgHandle *handle)
+{
+    if (handle->priv->fonts == NULL)
+        handle->priv->fonts = rsvg_fonts_new ();
+
+    return handle->priv->fonts;
+}
+
+FcConfig *
+rsvg_fonts_create_config_for_testing (void)
+{
+    const char *font_paths[] = {
+        SRCDIR "/tests/resources/Roboto-Regular.ttf",
+        SRCDIR "/tests/resources/Roboto-Italic.ttf",
+        SRCDIR "/tests/resources/Roboto-Bold.ttf",
+        SRCDIR "/tests/resources/Roboto-BoldItalic.ttf",
+    };
+
+    FcConfig *config;
+    int i;
+
+    config = FcConfigCreate ();
+
+    for (i = 0; i < G_N_ELEMENTS(font_paths); i++) {
+        if (!FcConfigAppFontAddFile (config, (const FcChar8 *) font_paths[i])) {
+            g_error ("Could not load font file \"%s\" for tests; aborting", font_paths[i]);
+        }
+    }
+
+    return config;
+}
+
+static gchar *
+write_font_file (RsvgFonts *fonts, const char *data, gsize len)
+{
+    gchar *basename;
+    gchar *filename;
+
+    if (fonts->dir == NULL) {
+        fonts->dir = g_dir_make_tmp ("librsvg-fonts-XXXXXX", NULL);
+        if (fonts->dir == NULL)
+            return NULL;
+    }
+
+    basename = g_strdup_printf ("font-%u", fonts->files->len);
+    filename = g_build_filename (fonts->dir, basename, NULL);
+    g_free (basename);
+
+    if (!g_file_set_contents (filename, data, len, NULL)) {
+        g_free (filename);
+        return NULL;
+    }
+
+    g_ptr_array_add (fonts->files, filename);
+
+    return filename;
+}
+
+/* Registers the font in @filename with fontconfig, both under its own family
+ * name and under the family name that the @font-face rule declared for it.
+ */
+static gboolean
+add_font_file (FcConfig *config, const char *filename, const char *family)
+{
+    FcFontSet *font_set;
+    int first, last;
+    int i;
+
+    font_set = FcConfigGetFonts (config, FcSetApplication);
+    first = font_set ? font_set->nfont : 0;
+
+    if (!FcConfigAppFontAddFile (config, (const FcChar8 *) filename))
+        return FALSE;
+
+    font_set = FcConfigGetFonts (config, FcSetApplication);
+    if (font_set == NULL)
+        return FALSE;
+
+    last = font_set->nfont;
+
+    for (i = first; i < last; i++) {
+        FcPattern *pattern = FcPatternDuplicate (font_set->fonts[i]);
+
+        FcPatternDel (pattern, FC_FAMILY);
+        FcPatternAddString (pattern, FC_FAMILY, (const FcChar8 *) family);
+
+        if (!FcFontSetAdd (font_set, pattern))
+            FcPatternDestroy (pattern);
+    }
+
+    return last > first;
+}
+
+static void
+ensure_config (RsvgFonts *fonts, gboolean is_testing)
+{
+    guint i;
+
+    if (fonts->config != NULL)
+        return;
+
+    if (fonts->font_dirs->len > 0) {
+        /* Only the fonts that the application asked for are visible, so that
+         * results don't depend on the fonts installed in the system.
+         */
+        fonts->config = FcConfigCreate ();
+
+        for (i = 0; i < fonts->font_dirs->len; i++) {
+            const char *dir = g_ptr_array_index (fonts->font_dirs, i);
+
+            if (!FcConfigAppFontAddDir (fonts->config, (const FcChar8 *) dir)) {
+                g_warning ("could not add font directory \"%s\"", dir);
+            }
+        }
+    } else if (is_testing) {
+        /* Likewise, in testing mode only the fonts from the test suite are
+         * visible.
+         */
+        fonts->config = rsvg_fonts_create_config_for_testing ();
+    } else {
+        fonts->config = FcInitLoadConfigAndFonts ();
+    }
+
+    for (i = 0; i < fonts->faces->len; i++) {
+        RsvgFontFace *face = g_ptr_array_index (fonts->faces, i);
+
+        add_font_file (fonts->config, face->filename, face->family);
+    }
+
+    if (fonts->font_map != NULL)
+        pango_fc_font_map_set_config (PANGO_FC_FONT_MAP (fonts->font_map), fonts->config);
+}
+
+static void
+invalidate_config (RsvgFonts *fonts)
+{
+    if (fonts->config) {
+        FcConfigDestroy (fonts->config);
+        fonts->config = NULL;
+    }
+}
+
+/**
+ * rsvg_handle_add_font_dir:
+ * @handle: An #RsvgHandle
+ * @path: (type filename): Path to a directory with font files
+ * @error: (allow-none): a location to store a #GError, or %NULL
+ *
+ * Makes the fonts in @path available for rendering the @handle's text.
+ *
+ * Once a font directory has been added, the @handle no longer uses the fonts
+ * installed in the system; only the fonts from the directories added with
+ * this function, plus those which the SVG defines itself with @font-face
+ * rules, are available.  Together with rsvg_handle_set_font_options(), this
+ * lets applications render text identically on different machines.
+ *
+ * Returns: %TRUE on success, or %FALSE if @path is not a directory or
+ * librsvg was built without fontconfig support.
+ *
+ * Since: 2.44
+ */
+gboolean
+rsvg_handle_add_font_dir (RsvgHandle *handle, const char *path, GError **error)
+{
+    RsvgFonts *fonts;
+
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), FALSE);
+    g_return_val_if_fail (path != NULL, FALSE);
+    g_return_val_if_fail (error == NULL || *error == NULL, FALSE);
+
+    if (!g_file_test (path, G_FILE_TEST_IS_DIR)) {
+        g_set_error (error, RSVG_ERROR, RSVG_ERROR_FAILED,
+                     "\"%s\" is not a directory", path);
+        return FALSE;
+    }
+
+    fonts = get_fonts (handle);
+
+    g_ptr_array_add (fonts->font_dirs, g_strdup (path));
+    invalidate_config (fonts);
+
+    return TRUE;
+}
+
+gboolean
+rsvg_handle_add_font_face (RsvgHandle *handle, const char *family, GPtrArray *uris)
+{
+    RsvgFonts *fonts;
+    guint i;
+
+    g_return_val_if_fail (family != NULL, FALSE);
+    g_return_val_if_fail (uris != NULL, FALSE);
+
+    fonts = get_fonts (handle);
+    ensure_config (fonts, handle->priv->is_testing);
+
+    for (i = 0; i < uris->len; i++) {
+        const char *uri = g_ptr_array_index (uris, i);
+        char *data;
+        gsize len;
+        gchar *filename;
+
+        data = _rsvg_handle_acquire_data (handle, uri, NULL, &len, NULL);
+        if (data == NULL)
+            continue;
+
+        filename = write_font_file (fonts, data, len);
+        g_free (data);
+
+        if (filename == NULL)
+            continue;
+
+        if (add_font_file (fonts->config, filename, family)) {
+            RsvgFontFace *face = g_new0 (RsvgFontFace, 1);
+
+            face->filename = g_strdup (filename);
+            face->family = g_strdup (family);
+            g_ptr_array_add (fonts->faces, face);
+
+            if (fonts->font_map != NULL)
+                pango_fc_font_map_config_changed (PANGO_FC_FONT_MAP (fonts->font_map));
+
+            return TRUE;
+        }
+    }
+
+    return FALSE;
+}
+
+PangoFontMap *
+rsvg_handle_get_font_map (RsvgHandle *handle)
+{
+    RsvgFonts *fonts = handle->priv->fonts;
+
+    if (fonts == NULL || (fonts->font_dirs->len == 0 && fonts->faces->len == 0))
+        return NULL;
+
+    ensure_config (fonts, handle->priv->is_testing);
+
+    if (fonts->font_map == NULL) {
+        fonts->font_map = pango_cairo_font_map_new_for_font_type (CAIRO_FONT_TYPE_FT);
+        pango_fc_font_map_set_config (PANGO_FC_FONT_MAP (fonts->font_map), fonts->config);
+    }
+
+    return fonts->font_map;
+}
+
+#else /* !HAVE_PANGOFT2 */
+
+gboolean
+rsvg_handle_add_font_dir (RsvgHandle *handle, const char *path, GError **error)
+{
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), FALSE);
+    g_return_val_if_fail (path != NULL, FALSE);
+    g_return_val_if_fail (error == NULL || *error == NULL, FALSE);
+
+    g_set_error (error, RSVG_ERROR, RSVG_ERROR_FAILED,
+                 "librsvg was built without fontconfig support");
+    return FALSE;
+}
+
+gboolean
+rsvg_handle_add_font_face (RsvgHandle *handle, const char *family, GPtrArray *uris)
+{
+    /* FIXME: we can only load fonts through fontconfig */
+    return FALSE;
+}
+
+PangoFontMap *
+rsvg_handle_get_font_map (RsvgHandle *handle)
+{
+    return NULL;
+}
+
+#endif /* HAVE_PANGOFT2 */
+
+gboolean
+rsvg_pango_font_has_color_glyphs (PangoFont *font)
+{
+#if defined (HAVE_PANGOFT2) && defined (FT_HAS_COLOR)
+    cairo_scaled_font_t *scaled_font;
+    FT_Face face;
+    gboolean has_color;
+
+    if (!PANGO_IS_CAIRO_FONT (font))
+        return FALSE;
+
+    scaled_font = pango_cairo_font_get_scaled_font (PANGO_CAIRO_FONT (font));
+    if (scaled_font == NULL || cairo_scaled_font_get_type (scaled_font) != CAIRO_FONT_TYPE_FT)
+        return FALSE;
+
+    face = cairo_ft_scaled_font_lock_face (scaled_font);
+    if (face == NULL)
+        return FALSE;
+
+    /* COLR/CPAL, CBDT and sbix fonts */
+    has_color = FT_HAS_COLOR (face);
+
+    cairo_ft_scaled_font_unlock_face (scaled_font);
+
+    return has_color;
+#else
+    /* FIXME: we only know how to find out about color fonts with FreeType */
+    return FALSE;
+#endif
+}
+
+void
+rsvg_fonts_free (RsvgFonts *fonts)
+{
+    guint i;
+
+    if (fonts == NULL)
+        return;
+
+#ifdef HAVE_PANGOFT2
+    g_clear_object (&fonts->font_map);
+
+    if (fonts->config) {
+        FcConfigDestroy (fonts->config);
+        fonts->config = NULL;
+    }
+#endif
+
+    g_ptr_array_free (fonts->font_dirs, TRUE);
+    g_ptr_array_free (fonts->faces, TRUE);
+
+    for (i = 0; i < fonts->files->len; i++) {
+        g_unlink (g_ptr_array_index (fonts->files, i));
+    }
+
+    g_ptr_array_free (fonts->files, TRUE);
+
+    if (fonts->dir) {
+        g_rmdir (fonts->dir);
+        g_free (fonts->dir);
+    }
+
+    g_free (fonts);
+}
@@ -0,0 +1,67 @@
+/* -*- Mode: C; indent-tabs-mode: nil; c-basic-offset: 4 -*- */
+/* vim: set sw=4 sts=4 expandtab: */
+/*
+   rsvg-fonts.h: Fonts loaded from @font-face rules.
+
+   This program is free software; you can redistribute it and/or
+   modify it under the terms of the GNU Library General Public License as
+   published by the Free Software Foundation; either version 2 of the
+   License, or (at your option) any later version.
+
+   This program is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+   Library General Public License for more details.
+
+   You should have received a copy of the GNU Library General Public
+   License along with this program; if not, write to the
+   Free Software Foundation, Inc., 59 Temple Place - Suite 330,
+   Boston, MA 02111-1307, USA.
+*/
+
+#ifndef RSVG_FONTS_H
+#define RSVG_FONTS_H
+
+#include <glib.h>
+#include <pango/pango.h>
+
+#include "rsvg-private.h"
+
+#ifdef HAVE_PANGOFT2
+#include <fontconfig/fontconfig.h>
+#endif
+
+G_BEGIN_DECLS
+
+G_GNUC_INTERNAL
+void rsvg_fonts_free (RsvgFonts *fonts);
+
+/* Loads the first usable font among @uris (resolved against the handle's base
+ * URI; data: URLs are decoded) and registers it under @family.  Returns
+ * whether a font could be loaded.
+ */
+G_GNUC_INTERNAL
+gboolean rsvg_handle_add_font_face (RsvgHandle *handle, const char *family, GPtrArray *uris);
+
+/* Returns the handle's font map, or NULL if the document did not define any
+ * fonts of its own.  The handle keeps ownership of the result.
+ */
+G_GNUC_INTERNAL
+PangoFontMap *rsvg_handle_get_font_map (RsvgHandle *handle);
+
+/* Whether the font's glyphs are drawn in their own colors (emoji and other
+ * color fonts) instead of with the current source.
+ *
+ * Implemented in rsvg-fonts.c; called from rust/src/draw.rs
+ */
+G_GNUC_INTERNAL
+gboolean rsvg_pango_font_has_color_glyphs (PangoFont *font);
+
+#ifdef HAVE_PANGOFT2
+G_GNUC_INTERNAL
+FcConfig *rsvg_fonts_create_config_for_testing (void);
+#endif
+
+G_END_DECLS
+
+#endif /* RSVG_FONTS_H */
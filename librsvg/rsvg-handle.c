@@ -122,6 +122,8 @@
 
 #include "rsvg-private.h"
 #include "rsvg-defs.h"
+#include "rsvg-fonts.h"
+#include "rsvg-load.h"
 #include "rsvg-cairo-render.h"
 #include "rsvg-structure.h"
 
@@ -162,9 +164,18 @@ rsvg_handle_init (RsvgHandle * self)
                                                    g_str_equal,
                                                    g_free,
                                                    (GDestroyNotify) g_hash_table_destroy);
+    self->priv->keyframes = rsvg_keyframes_new ();
 
     self->priv->treebase = NULL;
 
+    self->priv->fonts = NULL;
+    self->priv->fallback_font_families = NULL;
+    self->priv->font_options = NULL;
+
+    self->priv->diagnostics = g_ptr_array_new_with_free_func ((GDestroyNotify) rsvg_diagnostic_free);
+    self->priv->unsupported_features =
+        g_ptr_array_new_with_free_func ((GDestroyNotify) rsvg_unsupported_feature_free);
+
     self->priv->cancellable = NULL;
 
     self->priv->is_disposed = FALSE;
@@ -207,9 +218,28 @@ rsvg_handle_dispose (GObject *instance)
     self->priv->defs = NULL;
 
     g_hash_table_destroy (self->priv->css_props);
+    rsvg_keyframes_free (self->priv->keyframes);
+    self->priv->keyframes = NULL;
 
     self->priv->treebase = rsvg_node_unref (self->priv->treebase);
 
+    rsvg_fonts_free (self->priv->fonts);
+    self->priv->fonts = NULL;
+
+    g_strfreev (self->priv->fallback_font_families);
+    self->priv->fallback_font_families = NULL;
+
+    if (self->priv->font_options) {
+        cairo_font_options_destroy (self->priv->font_options);
+        self->priv->font_options = NULL;
+    }
+
+    g_ptr_array_unref (self->priv->diagnostics);
+    self->priv->diagnostics = NULL;
+
+    g_ptr_array_unref (self->priv->unsupported_features);
+    self->priv->unsupported_features = NULL;
+
     if (self->priv->user_data_destroy)
         (*self->priv->user_data_destroy) (self->priv->user_data);
 
@@ -985,6 +1015,300 @@ rsvg_handle_has_sub (RsvgHandle * handle,
     return rsvg_defs_lookup (handle->priv->defs, id) != NULL;
 }
 
+/* Lays out and draws the whole SVG on a scratch surface, in the same way as
+ * rsvg_handle_render_cairo() would do with an identity transformation, while
+ * collecting the text runs, text outlines and/or normalized elements that get drawn,
+ * the shapes that get hit, or the geometry of an element.  If @node is not NULL,
+ * only that node and its ancestors get drawn.
+ */
+static gboolean
+draw_for_extraction (RsvgHandle *handle,
+                     RsvgNode *node,
+                     GPtrArray *text_runs,
+                     gpointer text_outlines,
+                     gpointer normalizer,
+                     gpointer hit_test,
+                     gpointer geometry)
+{
+    RsvgDrawingCtx *draw;
+    cairo_surface_t *target;
+    cairo_t *cr;
+
+    if (handle->priv->hstate != RSVG_HANDLE_STATE_CLOSED_OK)
+        return FALSE;
+
+    target = cairo_image_surface_create (CAIRO_FORMAT_RGB24, 1, 1);
+    cr = cairo_create (target);
+
+    draw = rsvg_cairo_new_drawing_ctx (cr, handle);
+    if (!draw) {
+        cairo_destroy (cr);
+        cairo_surface_destroy (target);
+        return FALSE;
+    }
+
+    draw->text_runs = text_runs;
+    draw->text_outlines = text_outlines;
+    draw->normalizer = normalizer;
+    draw->hit_test = hit_test;
+    draw->geometry = geometry;
+
+    rsvg_drawing_ctx_add_node_and_ancestors_to_stack (draw, node);
+    rsvg_drawing_ctx_draw_node_from_stack (draw, handle->priv->treebase, 0, FALSE);
+
+    /* the caller owns these */
+    draw->text_runs = NULL;
+    draw->text_outlines = NULL;
+    draw->normalizer = NULL;
+    draw->hit_test = NULL;
+    draw->geometry = NULL;
+
+    rsvg_drawing_ctx_free (draw);
+    cairo_destroy (cr);
+    cairo_surface_destroy (target);
+
+    return TRUE;
+}
+
+/**
+ * rsvg_handle_get_text_runs:
+ * @handle: An #RsvgHandle
+ *
+ * Lays out the text in the SVG in the same way as rsvg_handle_render_cairo()
+ * would do when rendering with an identity transformation, and returns the
+ * resulting text runs in rendering order.  Applications can use the runs'
+ * character boxes to highlight search results or to implement text selection.
+ *
+ * Text that is rendered with SVG fonts is not included.
+ *
+ * Returns: (transfer full) (element-type RsvgTextRun) (nullable): An array of
+ * #RsvgTextRun, or %NULL if the SVG has not been loaded.  Free it with
+ * g_ptr_array_unref().
+ *
+ * Since: 2.44
+ */
+GPtrArray *
+rsvg_handle_get_text_runs (RsvgHandle * handle)
+{
+    GPtrArray *runs;
+
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), NULL);
+
+    runs = g_ptr_array_new_with_free_func ((GDestroyNotify) rsvg_text_run_free);
+
+    if (!draw_for_extraction (handle, NULL, runs, NULL, NULL, NULL, NULL)) {
+        g_ptr_array_unref (runs);
+        return NULL;
+    }
+
+    return runs;
+}
+
+gboolean
+rsvg_handle_collect_text_outlines (RsvgHandle *handle, gpointer outlines)
+{
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), FALSE);
+    g_return_val_if_fail (outlines != NULL, FALSE);
+
+    return draw_for_extraction (handle, NULL, NULL, outlines, NULL, NULL, NULL);
+}
+
+gboolean
+rsvg_handle_draw_for_normalizer (RsvgHandle *handle, gpointer normalizer)
+{
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), FALSE);
+    g_return_val_if_fail (normalizer != NULL, FALSE);
+
+    return draw_for_extraction (handle, NULL, NULL, NULL, normalizer, NULL, NULL);
+}
+
+gboolean
+rsvg_handle_collect_hits (RsvgHandle *handle, gpointer hit_test)
+{
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), FALSE);
+    g_return_val_if_fail (hit_test != NULL, FALSE);
+
+    return draw_for_extraction (handle, NULL, NULL, NULL, NULL, hit_test, NULL);
+}
+
+gboolean
+rsvg_handle_collect_geometry (RsvgHandle *handle, RsvgNode *node, gpointer geometry)
+{
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), FALSE);
+    g_return_val_if_fail (node != NULL, FALSE);
+    g_return_val_if_fail (geometry != NULL, FALSE);
+
+    return draw_for_extraction (handle, node, NULL, NULL, NULL, NULL, geometry);
+}
+
+/**
+ * rsvg_handle_hit_test:
+ * @handle: An #RsvgHandle
+ * @x: X coordinate of the point
+ * @y: Y coordinate of the point
+ *
+ * Finds the topmost shape whose fill or stroke contains a point, for
+ * example to know what the user clicked on.  The point is in the same space
+ * as the image that rsvg_handle_render_cairo() renders with an identity
+ * transformation.
+ *
+ * Each shape is tested with its transformation, fill rule and stroke
+ * parameters, according to its <literal>pointer-events</literal> property,
+ * and only where its clipping paths and those of its ancestors let it be
+ * drawn.  Elements that are not rendered because of their
+ * <literal>display</literal> or <literal>visibility</literal> are never hit,
+ * and neither are text, images, or the contents of patterns and masks.  A
+ * shape that is drawn through a &lt;use&gt; element is reported as the
+ * referenced element.
+ *
+ * Returns: (transfer full) (nullable): The id of the shape under the point,
+ * or of its closest ancestor that has an id; or %NULL if there is no such
+ * element, or if the handle has not finished loading.  Free it with g_free().
+ *
+ * Since: 2.44
+ */
+char *
+rsvg_handle_hit_test (RsvgHandle * handle, double x, double y)
+{
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), NULL);
+
+    return rsvg_hit_test_get_id (handle, x, y);
+}
+
+gpointer
+rsvg_handle_get_keyframes (RsvgHandle *handle)
+{
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), NULL);
+
+    return handle->priv->keyframes;
+}
+
+static RsvgDiagnostic *
+rsvg_diagnostic_copy (const RsvgDiagnostic *diagnostic)
+{
+    RsvgDiagnostic *copy;
+
+    copy = g_new0 (RsvgDiagnostic, 1);
+    copy->severity = diagnostic->severity;
+    copy->element = g_strdup (diagnostic->element);
+    copy->id = g_strdup (diagnostic->id);
+    copy->line = diagnostic->line;
+    copy->column = diagnostic->column;
+    copy->attribute = g_strdup (diagnostic->attribute);
+    copy->message = g_strdup (diagnostic->message);
+
+    return copy;
+}
+
+/**
+ * rsvg_handle_get_diagnostics:
+ * @handle: An #RsvgHandle
+ *
+ * Returns the problems that librsvg found in the SVG's elements, in document
+ * order.  Elements with a diagnostic of severity
+ * #RSVG_DIAGNOSTIC_SEVERITY_ERROR are "in error" and librsvg does not render
+ * them, nor their children.  Applications can use this to tell their users
+ * why parts of an image are missing.
+ *
+ * Problems with the attributes of elements are found while the SVG is being
+ * loaded, so this can be called after each rsvg_handle_write() as well as
+ * after loading finishes.  Other problems are only found while rendering, and
+ * they get added when an element is first rendered: references to elements
+ * that don't exist or that have the wrong type, like a fill that references
+ * a filter, and filter primitive inputs or images that can't be found.
+ *
+ * Returns: (transfer full) (element-type RsvgDiagnostic): An array of
+ * #RsvgDiagnostic, which may be empty.  Free it with g_ptr_array_unref().
+ *
+ * Since: 2.44
+ */
+GPtrArray *
+rsvg_handle_get_diagnostics (RsvgHandle * handle)
+{
+    GPtrArray *diagnostics;
+    guint i;
+
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), NULL);
+
+    diagnostics = g_ptr_array_new_full (handle->priv->diagnostics->len,
+                                        (GDestroyNotify) rsvg_diagnostic_free);
+
+    for (i = 0; i < handle->priv->diagnostics->len; i++) {
+        RsvgDiagnostic *diagnostic = g_ptr_array_index (handle->priv->diagnostics, i);
+
+        g_ptr_array_add (diagnostics, rsvg_diagnostic_copy (diagnostic));
+    }
+
+    return diagnostics;
+}
+
+void
+rsvg_handle_add_unsupported_feature (RsvgHandle *handle,
+                                     RsvgUnsupportedFeatureKind kind,
+                                     const char *name)
+{
+    GPtrArray *features = handle->priv->unsupported_features;
+    RsvgUnsupportedFeature *feature;
+    guint i;
+
+    for (i = 0; i < features->len; i++) {
+        feature = g_ptr_array_index (features, i);
+
+        if (feature->kind == kind && strcmp (feature->name, name) == 0) {
+            feature->count++;
+            return;
+        }
+    }
+
+    feature = g_new0 (RsvgUnsupportedFeature, 1);
+    feature->kind = kind;
+    feature->name = g_strdup (name);
+    feature->count = 1;
+
+    if (handle->priv->load)
+        rsvg_load_get_location (handle->priv->load, &feature->line, &feature->column);
+
+    g_ptr_array_add (features, feature);
+}
+
+/**
+ * rsvg_handle_get_unsupported_features:
+ * @handle: An #RsvgHandle
+ *
+ * Returns the elements, attributes and style properties that the SVG uses
+ * but that librsvg ignores, in the order in which they first appear.  Unknown
+ * elements are not rendered, and neither are their children.  Applications
+ * can use this to find out which features of SVG their documents depend on.
+ *
+ * Returns: (transfer full) (element-type RsvgUnsupportedFeature): An array of
+ * #RsvgUnsupportedFeature, which may be empty.  Free it with g_ptr_array_unref().
+ *
+ * Since: 2.44
+ */
+GPtrArray *
+rsvg_handle_get_unsupported_features (RsvgHandle * handle)
+{
+    GPtrArray *features;
+    guint i;
+
+    g_return_val_if_fail (RSVG_IS_HANDLE (handle), NULL);
+
+    features = g_ptr_array_new_full (handle->priv->unsupported_features->len,
+                                     (GDestroyNotify) rsvg_unsupported_feature_free);
+
+    for (i = 0; i < handle->priv->unsupported_features->len; i++) {
+        RsvgUnsupportedFeature *feature = g_ptr_array_index (handle->priv->unsupported_features, i);
+        RsvgUnsupportedFeature *copy;
+
+        copy = g_memdup (feature, sizeof (RsvgUnsupportedFeature));
+        copy->name = g_strdup (feature->name);
+
+        g_ptr_array_add (features, copy);
+    }
+
+    return features;
+}
+
 /**
  * rsvg_handle_get_pixbuf_sub:
  * @handle: An #RsvgHandle
@@ -1110,6 +1434,52 @@ rsvg_handle_set_dpi_x_y (RsvgHandle * handle, double dpi_x, double dpi_y)
         handle->priv->dpi_y = dpi_y;
 }
 
+/**
+ * rsvg_handle_set_fallback_font_families:
+ * @handle: An #RsvgHandle
+ * @families: (nullable) (array zero-terminated=1): %NULL-terminated array of
+ *   font family names, or %NULL
+ *
+ * Sets the font families to try, in order, for text whose own font-family
+ * is not available or does not have glyphs for some characters.  Passing
+ * %NULL clears the fallback families.
+ *
+ * Since: 2.44
+ */
+void
+rsvg_handle_set_fallback_font_families (RsvgHandle *handle, const char * const *families)
+{
+    g_return_if_fail (RSVG_IS_HANDLE (handle));
+
+    g_strfreev (handle->priv->fallback_font_families);
+    handle->priv->fallback_font_families = g_strdupv ((gchar **) families);
+}
+
+/**
+ * rsvg_handle_set_font_options:
+ * @handle: An #RsvgHandle
+ * @options: (nullable): Font options, or %NULL
+ *
+ * Sets the font options (hinting, antialiasing, subpixel order, etc.) used to
+ * render the @handle's text.  These override the font options of the cairo
+ * surface being rendered to.  Passing %NULL goes back to the surface's options.
+ *
+ * Since: 2.44
+ */
+void
+rsvg_handle_set_font_options (RsvgHandle *handle, const cairo_font_options_t *options)
+{
+    g_return_if_fail (RSVG_IS_HANDLE (handle));
+
+    if (handle->priv->font_options) {
+        cairo_font_options_destroy (handle->priv->font_options);
+        handle->priv->font_options = NULL;
+    }
+
+    if (options)
+        handle->priv->font_options = cairo_font_options_copy (options);
+}
+
 /**
  * rsvg_handle_set_size_callback:
  * @handle: An #RsvgHandle
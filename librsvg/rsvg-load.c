@@ -24,6 +24,7 @@
 #include <libxml/uri.h>
 #include <libxml/parser.h>
 #include <libxml/parserInternals.h>
+#include <libxml/SAX2.h>
 #include <string.h>
 
 #include "rsvg-attributes.h"
@@ -309,10 +310,10 @@ static const NodeCreator node_creators[] = {
     /* "altGlyph",           TRUE,  */
     /* "altGlyphDef",        FALSE, */
     /* "altGlyphItem",       FALSE, */
-    /* "animate",            FALSE, */
-    /* "animateColor",       FALSE, */
-    /* "animateMotion",      FALSE, */
-    /* "animateTransform",   FALSE, */
+    { "animate",             FALSE, rsvg_node_animation_new },
+    { "animateColor",        FALSE, rsvg_node_animation_new },
+    { "animateMotion",       FALSE, rsvg_node_animation_new },
+    { "animateTransform",    FALSE, rsvg_node_animation_new },
     { "circle",              TRUE,  rsvg_node_circle_new },
     { "clipPath",            TRUE,  rsvg_node_clip_path_new },
     /* "color-profile",      FALSE, */
@@ -346,25 +347,29 @@ static const NodeCreator node_creators[] = {
     { "feTile",              TRUE,  rsvg_new_filter_primitive_tile },
     { "feTurbulence",        TRUE,  rsvg_new_filter_primitive_turbulence },
     { "filter",              TRUE,  rsvg_new_filter },
-    /* "font",               TRUE,  */
-    /* "font-face",          FALSE, */
+    { "flowPara",            TRUE,  rsvg_node_flow_para_new },
+    { "flowRegion",          TRUE,  rsvg_node_flow_region_new },
+    { "flowRoot",            TRUE,  rsvg_node_flow_root_new },
+    { "flowSpan",            TRUE,  rsvg_node_flow_span_new },
+    { "font",                TRUE,  rsvg_node_font_new },
+    { "font-face",           FALSE, rsvg_node_font_face_new },
     /* "font-face-format",   FALSE, */
     /* "font-face-name",     FALSE, */
     /* "font-face-src",      FALSE, */
     /* "font-face-uri",      FALSE, */
     /* "foreignObject",      TRUE,  */
     { "g",                   TRUE,  rsvg_node_group_new },
-    /* "glyph",              TRUE,  */
+    { "glyph",               TRUE,  rsvg_node_glyph_new },
     /* "glyphRef",           TRUE,  */
-    /* "hkern",              FALSE, */
+    { "hkern",               FALSE, rsvg_node_hkern_new },
     { "image",               TRUE,  rsvg_node_image_new },
     { "line",                TRUE,  rsvg_node_line_new },
     { "linearGradient",      TRUE,  rsvg_node_linear_gradient_new },
     { "marker",              TRUE,  rsvg_node_marker_new },
     { "mask",                TRUE,  rsvg_node_mask_new },
     /* "metadata",           FALSE, */
-    /* "missing-glyph",      TRUE,  */
-    /* "mpath"               FALSE, */
+    { "missing-glyph",       TRUE,  rsvg_node_missing_glyph_new },
+    { "mpath",               FALSE, rsvg_node_defs_new }, /* only read by its parent animateMotion */
     { "multiImage",          FALSE, rsvg_node_switch_new }, /* hack to make multiImage sort-of work */
     { "path",                TRUE,  rsvg_node_path_new },
     { "pattern",             TRUE,  rsvg_node_pattern_new },
@@ -373,7 +378,7 @@ static const NodeCreator node_creators[] = {
     { "radialGradient",      TRUE,  rsvg_node_radial_gradient_new },
     { "rect",                TRUE,  rsvg_node_rect_new },
     /* "script",             FALSE, */
-    /* "set",                FALSE, */
+    { "set",                 FALSE, rsvg_node_animation_new },
     { "stop",                TRUE,  rsvg_node_stop_new },
     /* "style",              FALSE, */
     { "subImage",            FALSE, rsvg_node_group_new },
@@ -429,29 +434,151 @@ get_node_creator_for_element_name (const char *name)
     return result;
 }
 
+void
+rsvg_load_get_location (RsvgLoad *load, int *out_line, int *out_column)
+{
+    if (load->ctxt) {
+        *out_line = xmlSAX2GetLineNumber (load->ctxt);
+        *out_column = xmlSAX2GetColumnNumber (load->ctxt);
+    } else {
+        *out_line = 0;
+        *out_column = 0;
+    }
+}
+
+static const char *
+lookup_attribute_value (RsvgPropertyBag atts, const char *attr_name)
+{
+    RsvgPropertyBagIter *iter;
+    const char *key;
+    RsvgAttribute attr;
+    const char *value;
+    const char *result = NULL;
+
+    iter = rsvg_property_bag_iter_begin (atts);
+
+    while (rsvg_property_bag_iter_next (iter, &key, &attr, &value)) {
+        if (strcmp (key, attr_name) == 0) {
+            result = value;
+            break;
+        }
+    }
+
+    rsvg_property_bag_iter_end (iter);
+
+    return result;
+}
+
+/* In strict mode, makes loading fail with an error about the diagnostic.
+ * Warnings are about content that still gets rendered, so they don't stop
+ * loading.
+ */
+static void
+stop_loading_on_error (RsvgLoad *load, RsvgDiagnostic *diagnostic, RsvgPropertyBag atts)
+{
+    const char *value;
+
+    if ((load->handle->priv->flags & RSVG_HANDLE_FLAG_STRICT) == 0)
+        return;
+
+    if (diagnostic->severity != RSVG_DIAGNOSTIC_SEVERITY_ERROR)
+        return;
+
+    if (load->error == NULL || *load->error != NULL)
+        return;
+
+    value = lookup_attribute_value (atts, diagnostic->attribute);
+
+    g_set_error (load->error, RSVG_ERROR, RSVG_ERROR_FAILED,
+                 _("Invalid value \"%s\" for attribute \"%s\" of element <%s> on line %d column %d: %s"),
+                 value ? value : "",
+                 diagnostic->attribute,
+                 diagnostic->element,
+                 diagnostic->line,
+                 diagnostic->column,
+                 diagnostic->message);
+
+    if (load->ctxt)
+        xmlStopParser (load->ctxt);
+}
+
+/* Takes ownership of @attr_name and @message */
+static void
+add_diagnostic (RsvgLoad *load,
+                RsvgDiagnosticSeverity severity,
+                const char *element_name,
+                const char *id,
+                char *attr_name,
+                char *message,
+                RsvgPropertyBag atts)
+{
+    RsvgDiagnostic *diagnostic;
+
+    diagnostic = g_new0 (RsvgDiagnostic, 1);
+    diagnostic->severity = severity;
+    diagnostic->element = g_strdup (element_name);
+    diagnostic->id = g_strdup (id);
+    diagnostic->attribute = attr_name;
+    diagnostic->message = message;
+
+    rsvg_load_get_location (load, &diagnostic->line, &diagnostic->column);
+
+    g_ptr_array_add (load->handle->priv->diagnostics, diagnostic);
+
+    stop_loading_on_error (load, diagnostic, atts);
+}
+
+/* Records diagnostics in the handle for the problems that the node found while
+ * parsing its attributes, so that applications can find out why it does not
+ * get rendered, or gets rendered only partially.
+ */
 static void
-node_set_atts (RsvgNode * node, RsvgHandle *handle, const NodeCreator *creator, RsvgPropertyBag atts)
+record_node_diagnostics (RsvgLoad *load,
+                         RsvgNode *node,
+                         const char *element_name,
+                         const char *id,
+                         RsvgPropertyBag atts)
+{
+    char *attr_name;
+    char *message;
+    guint i;
+
+    if (rsvg_node_get_error (node, &attr_name, &message)) {
+        add_diagnostic (load, RSVG_DIAGNOSTIC_SEVERITY_ERROR,
+                        element_name, id, attr_name, message, atts);
+    }
+
+    for (i = 0; rsvg_node_get_warning (node, i, &attr_name, &message); i++) {
+        add_diagnostic (load, RSVG_DIAGNOSTIC_SEVERITY_WARNING,
+                        element_name, id, attr_name, message, atts);
+    }
+}
+
+static void
+get_id_and_class (const NodeCreator *creator,
+                  RsvgPropertyBag atts,
+                  const char **out_id,
+                  const char **out_klazz)
 {
     RsvgPropertyBagIter *iter;
     const char *key;
     RsvgAttribute attr;
     const char *value;
 
-    const char *id = NULL;
-    const char *klazz = NULL;
+    *out_id = NULL;
+    *out_klazz = NULL;
 
     iter = rsvg_property_bag_iter_begin (atts);
 
     while (rsvg_property_bag_iter_next (iter, &key, &attr, &value)) {
         switch (attr) {
         case RSVG_ATTRIBUTE_ID:
-            id = value;
-            rsvg_defs_register_node_by_id (handle->priv->defs, id, node);
+            *out_id = value;
             break;
 
         case RSVG_ATTRIBUTE_CLASS:
             if (creator->supports_class_attribute) {
-                klazz = value;
+                *out_klazz = value;
             }
             break;
 
@@ -461,6 +588,28 @@ node_set_atts (RsvgNode * node, RsvgHandle *handle, const NodeCreator *creator,
     }
 
     rsvg_property_bag_iter_end (iter);
+}
+
+static void
+node_set_atts (RsvgLoad *load,
+               RsvgNode *node,
+               const char *element_name,
+               const NodeCreator *creator,
+               RsvgPropertyBag atts)
+{
+    RsvgHandle *handle = load->handle;
+    const char *id;
+    const char *klazz;
+    int line, column;
+
+    get_id_and_class (creator, atts, &id, &klazz);
+
+    if (id) {
+        rsvg_defs_register_node_by_id (handle->priv->defs, id, node);
+    }
+
+    rsvg_load_get_location (load, &line, &column);
+    rsvg_node_set_location (node, line, column);
 
     rsvg_node_set_atts (node, handle, atts);
 
@@ -471,10 +620,46 @@ node_set_atts (RsvgNode * node, RsvgHandle *handle, const NodeCreator *creator,
     if (rsvg_node_get_type (node) != RSVG_NODE_TYPE_SVG) {
         rsvg_parse_style_attrs (handle, node, creator->element_name, klazz, id, atts);
     }
+
+    record_node_diagnostics (load, node, element_name, id, atts);
+}
+
+void
+rsvg_handle_reset_node_atts (RsvgHandle *handle,
+                             RsvgNode *node,
+                             const char *element_name,
+                             RsvgPropertyBag atts)
+{
+    const NodeCreator *creator;
+    RsvgNode *fresh;
+    const char *id;
+    const char *klazz;
+
+    creator = get_node_creator_for_element_name (element_name);
+    g_assert (creator != NULL);
+
+    get_id_and_class (creator, atts, &id, &klazz);
+
+    /* The node's implementation only sets the attributes that are in @atts,
+     * so it gets the one of a new element, with all the defaults, first.
+     */
+    fresh = creator->create_fn (element_name, NULL);
+    rsvg_node_swap_impl (node, fresh);
+    fresh = rsvg_node_unref (fresh);
+
+    rsvg_state_reinit (rsvg_node_get_state (node));
+
+    rsvg_node_set_atts (node, handle, atts);
+
+    if (rsvg_node_get_type (node) == RSVG_NODE_TYPE_SVG) {
+        rsvg_node_svg_apply_atts (node, handle);
+    } else {
+        rsvg_parse_style_attrs (handle, node, creator->element_name, klazz, id, atts);
+    }
 }
 
 static void
-standard_element_start (RsvgLoad *load, const char *name, RsvgPropertyBag * atts)
+standard_element_start (RsvgLoad *load, const char *name, RsvgPropertyBag * atts, gboolean foreign)
 {
     const NodeCreator *creator;
     RsvgNode *newnode = NULL;
@@ -482,9 +667,18 @@ standard_element_start (RsvgLoad *load, const char *name, RsvgPropertyBag * atts
     creator = get_node_creator_for_element_name (name);
     g_assert (creator != NULL && creator->create_fn != NULL);
 
+    if (creator == &default_node_creator) {
+        rsvg_handle_add_unsupported_feature (load->handle, RSVG_UNSUPPORTED_FEATURE_ELEMENT, name);
+    }
+
     newnode = creator->create_fn (name, load->currentnode);
     g_assert (newnode != NULL);
 
+    rsvg_node_set_element_name (newnode, name);
+
+    if (foreign)
+        rsvg_node_set_foreign_namespace (newnode);
+
     g_assert (rsvg_node_get_type (newnode) != RSVG_NODE_TYPE_INVALID);
 
     push_element_name (load, name);
@@ -500,7 +694,7 @@ standard_element_start (RsvgLoad *load, const char *name, RsvgPropertyBag * atts
 
     load->currentnode = rsvg_node_ref (newnode);
 
-    node_set_atts (newnode, load->handle, creator, atts);
+    node_set_atts (load, newnode, name, creator, atts);
 
     newnode = rsvg_node_unref (newnode);
 }
@@ -545,7 +739,7 @@ xinclude_handler_start (RsvgSaxHandler * self, const char *name, RsvgPropertyBag
             if (!strcmp (name, "xi:include"))
                 start_xinclude (z->load, atts);
             else
-                standard_element_start (z->load, (const char *) name, atts);
+                standard_element_start (z->load, (const char *) name, atts, FALSE);
         } else if (!strcmp (name, "xi:fallback")) {
             z->in_fallback = TRUE;
         }
@@ -733,6 +927,88 @@ start_xinclude (RsvgLoad *load, RsvgPropertyBag * atts)
 
 /* end xinclude */
 
+/* Attributes that librsvg does not store because they don't affect rendering,
+ * so they are not unsupported features.
+ */
+static const char *ignored_attributes[] = {
+    "baseProfile",
+    "contentScriptType",
+    "contentStyleType",
+    "externalResourcesRequired",
+    "focusable",
+    "version",
+    "xml:base",
+    "zoomAndPan",
+};
+
+static gboolean
+is_ignored_attribute (const char *name)
+{
+    guint i;
+
+    for (i = 0; i < G_N_ELEMENTS (ignored_attributes); i++) {
+        if (strcmp (name, ignored_attributes[i]) == 0)
+            return TRUE;
+    }
+
+    return FALSE;
+}
+
+/* Counts the attributes that are not in rsvg-attributes.h; the property bag
+ * keeps them, but librsvg does nothing with them.
+ */
+static void
+add_unknown_attributes (RsvgLoad *load, const xmlChar **atts)
+{
+    RsvgAttribute attr;
+    int i;
+
+    if (atts == NULL)
+        return;
+
+    for (i = 0; atts[i] != NULL; i += 2) {
+        const char *key = (const char *) atts[i];
+
+        /* namespace declarations are not attributes */
+        if (strcmp (key, "xmlns") == 0 || g_str_has_prefix (key, "xmlns:"))
+            continue;
+
+        if (is_ignored_attribute (key))
+            continue;
+
+        if (!rsvg_attribute_from_name (key, &attr)) {
+            rsvg_handle_add_unsupported_feature (load->handle, RSVG_UNSUPPORTED_FEATURE_ATTRIBUTE, key);
+        }
+    }
+}
+
+#define SVG_NAMESPACE "http://www.w3.org/2000/svg"
+
+/* Whether an element belongs to a namespace other than SVG's.  The parser
+ * is not namespace-aware, so this only looks at the element's prefix and at
+ * a default namespace declared on the element itself.
+ */
+static gboolean
+element_is_foreign (const char *qname, const xmlChar **atts)
+{
+    int i;
+
+    if (strchr (qname, ':') != NULL)
+        return !g_str_has_prefix (qname, "svg:");
+
+    if (atts == NULL)
+        return FALSE;
+
+    for (i = 0; atts[i] != NULL; i += 2) {
+        if (strcmp ((const char *) atts[i], "xmlns") == 0
+            && atts[i + 1] != NULL
+            && strcmp ((const char *) atts[i + 1], SVG_NAMESPACE) != 0)
+            return TRUE;
+    }
+
+    return FALSE;
+}
+
 static void
 sax_start_element_cb (void *data, const xmlChar * name, const xmlChar ** atts)
 {
@@ -746,17 +1022,21 @@ sax_start_element_cb (void *data, const xmlChar * name, const xmlChar ** atts)
         if (load->handler->start_element != NULL)
             load->handler->start_element (load->handler, (const char *) name, bag);
     } else {
+        gboolean foreign = element_is_foreign ((const char *) name, atts);
         const char *tempname;
+
         for (tempname = (const char *) name; *tempname != '\0'; tempname++)
             if (*tempname == ':')
                 name = (const xmlChar *) (tempname + 1);
 
+        add_unknown_attributes (load, atts);
+
         if (!strcmp ((const char *) name, "style"))
             start_style (load, bag);
         else if (!strcmp ((const char *) name, "include"))      /* xi:include */
             start_xinclude (load, bag);
         else
-            standard_element_start (load, (const char *) name, bag);
+            standard_element_start (load, (const char *) name, bag, foreign);
     }
 
     rsvg_property_bag_free (bag);
@@ -804,6 +1084,14 @@ extern RsvgNode *rsvg_node_chars_new(RsvgNode *parent);
 /* Implemented in rust/src/text.rs */
 extern void rsvg_node_chars_append (RsvgNode *node, const char *text, gssize len);
 
+static gboolean
+node_is_span (RsvgNode *node)
+{
+    RsvgNodeType type = rsvg_node_get_type (node);
+
+    return type == RSVG_NODE_TYPE_TSPAN || type == RSVG_NODE_TYPE_FLOW_SPAN;
+}
+
 static gboolean
 node_is_text_or_tspan (RsvgNode *node)
 {
@@ -814,7 +1102,9 @@ node_is_text_or_tspan (RsvgNode *node)
     }
 
     type = rsvg_node_get_type (node);
-    return type == RSVG_NODE_TYPE_TEXT || type == RSVG_NODE_TYPE_TSPAN;
+    return (type == RSVG_NODE_TYPE_TEXT
+            || type == RSVG_NODE_TYPE_FLOW_PARA
+            || node_is_span (node));
 }
 
 /* Finds the last chars child inside a given @node to which new characters can
@@ -840,7 +1130,7 @@ find_last_chars_child (RsvgNode *node)
              * (which means there's a tspan node after any chars nodes,
              * because this is backwards iteration), return NULL.
              */
-            if (rsvg_node_get_type (temp) == RSVG_NODE_TYPE_TSPAN) {
+            if (node_is_span (temp)) {
                 temp = rsvg_node_unref (temp);
                 break;
             } else if (rsvg_node_get_type (temp) == RSVG_NODE_TYPE_CHARS) {
@@ -1164,13 +1454,18 @@ write_impl (RsvgLoad *load, const guchar * buf, gsize count, GError **error)
     }
 
     result = xmlParseChunk (load->ctxt, (char *) buf, count, 0);
+
+    load->error = NULL;
+
     if (result != 0) {
-        set_error_from_xml (error, load->ctxt);
+        if (real_error != NULL)
+            g_propagate_error (error, real_error);
+        else
+            set_error_from_xml (error, load->ctxt);
+
         return FALSE;
     }
 
-    load->error = NULL;
-
     if (real_error != NULL) {
         g_propagate_error (error, real_error);
         return FALSE;
@@ -1191,7 +1486,13 @@ close_impl (RsvgLoad *load, GError ** error)
 
         result = xmlParseChunk (load->ctxt, "", 0, TRUE);
         if (result != 0) {
-            set_error_from_xml (error, load->ctxt);
+            load->error = NULL;
+
+            if (real_error != NULL)
+                g_propagate_error (error, real_error);
+            else
+                set_error_from_xml (error, load->ctxt);
+
             load->ctxt = free_xml_parser_and_doc (load->ctxt);
             return FALSE;
         }
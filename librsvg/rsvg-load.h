@@ -37,6 +37,22 @@ gboolean rsvg_load_write (RsvgLoad *load, const guchar *buf, gsize count, GError
 G_GNUC_INTERNAL
 gboolean rsvg_load_close (RsvgLoad *load, GError **error) G_GNUC_WARN_UNUSED_RESULT;
 
+/* Returns the line and column of the XML parser, or zeros if it is not parsing */
+G_GNUC_INTERNAL
+void rsvg_load_get_location (RsvgLoad *load, int *out_line, int *out_column);
+
+/* Sets the attributes of an already-loaded node again, and recomputes its
+ * state from them in the same way as the loader does.  Attributes that are
+ * not in @atts go back to their defaults.
+ *
+ * Called from rust/src/dom.rs
+ */
+G_GNUC_INTERNAL
+void rsvg_handle_reset_node_atts (RsvgHandle *handle,
+                                  RsvgNode *node,
+                                  const char *element_name,
+                                  RsvgPropertyBag atts);
+
 G_GNUC_INTERNAL
 gboolean rsvg_load_read_stream_sync (RsvgLoad     *load,
 				     GInputStream *stream,
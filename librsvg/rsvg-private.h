@@ -125,6 +125,8 @@ typedef enum {
 
 typedef struct RsvgLoad RsvgLoad;
 
+typedef struct RsvgFonts RsvgFonts;
+
 struct RsvgHandlePrivate {
     RsvgHandleFlags flags;
 
@@ -146,6 +148,11 @@ struct RsvgHandlePrivate {
     RsvgNode *treebase;
 
     GHashTable *css_props;
+    gpointer keyframes; /* Rust KeyframesSet from css_animation.rs */
+
+    RsvgFonts *fonts; /* font dirs and @font-face fonts, created on demand */
+    gchar **fallback_font_families;
+    cairo_font_options_t *font_options;
 
     GCancellable *cancellable;
 
@@ -155,6 +162,9 @@ struct RsvgHandlePrivate {
     gchar *base_uri;
     GFile *base_gfile;
 
+    GPtrArray *diagnostics; /* RsvgDiagnostic for each problem in an element's attributes */
+    GPtrArray *unsupported_features; /* RsvgUnsupportedFeature, in order of first use */
+
     gboolean in_loop;		/* see get_dimension() */
 
     gboolean is_testing; /* Are we being run from the test suite? */
@@ -174,11 +184,20 @@ struct RsvgDrawingCtx {
     GError **error;
     RsvgDefs *defs;
     PangoContext *pango_context;
+    PangoFontMap *font_map;
+    const char * const *fallback_font_families;
+    const cairo_font_options_t *font_options;
     double dpi_x, dpi_y;
     RsvgViewBox vb;
     GSList *vb_stack;
     GSList *drawsub_stack;
     GSList *acquired_nodes;
+    GPtrArray *text_runs; /* non-NULL if collecting text runs */
+    gpointer text_outlines; /* Rust Vec<TextOutline> from text_outline.rs, if collecting them */
+    gpointer normalizer; /* Rust Normalizer from normalize.rs, if normalizing the document */
+    gpointer hit_test; /* Rust HitTest from hit_test.rs, if hit testing */
+    gpointer geometry; /* Rust Geometry from geometry.rs, if measuring an element */
+    GPtrArray *diagnostics; /* the handle's RsvgDiagnostic array, for problems found while rendering */
     gboolean is_testing;
 };
 
@@ -251,6 +270,7 @@ typedef enum {
 typedef enum {
     RSVG_NODE_TYPE_INVALID = 0,
 
+    RSVG_NODE_TYPE_ANIMATION,
     RSVG_NODE_TYPE_CHARS,
     RSVG_NODE_TYPE_CIRCLE,
     RSVG_NODE_TYPE_CLIP_PATH,
@@ -258,7 +278,15 @@ typedef enum {
     RSVG_NODE_TYPE_DEFS,
     RSVG_NODE_TYPE_ELLIPSE,
     RSVG_NODE_TYPE_FILTER,
+    RSVG_NODE_TYPE_FLOW_PARA,
+    RSVG_NODE_TYPE_FLOW_REGION,
+    RSVG_NODE_TYPE_FLOW_ROOT,
+    RSVG_NODE_TYPE_FLOW_SPAN,
+    RSVG_NODE_TYPE_FONT,
+    RSVG_NODE_TYPE_FONT_FACE,
+    RSVG_NODE_TYPE_GLYPH,
     RSVG_NODE_TYPE_GROUP,
+    RSVG_NODE_TYPE_HKERN,
     RSVG_NODE_TYPE_IMAGE,
     RSVG_NODE_TYPE_LIGHT_SOURCE,
     RSVG_NODE_TYPE_LINE,
@@ -266,6 +294,7 @@ typedef enum {
     RSVG_NODE_TYPE_LINK,
     RSVG_NODE_TYPE_MARKER,
     RSVG_NODE_TYPE_MASK,
+    RSVG_NODE_TYPE_MISSING_GLYPH,
     RSVG_NODE_TYPE_PATH,
     RSVG_NODE_TYPE_PATTERN,
     RSVG_NODE_TYPE_POLYGON,
@@ -360,6 +389,26 @@ void rsvg_node_add_child (RsvgNode *node, RsvgNode *child);
 G_GNUC_INTERNAL
 void rsvg_node_set_atts (RsvgNode *node, RsvgHandle *handle, RsvgPropertyBag atts);
 
+/* Implemented in rust/src/node.rs */
+G_GNUC_INTERNAL
+void rsvg_node_set_element_name (RsvgNode *node, const char *name);
+
+/* Implemented in rust/src/node.rs */
+G_GNUC_INTERNAL
+void rsvg_node_set_foreign_namespace (RsvgNode *node);
+
+/* Implemented in rust/src/node.rs */
+G_GNUC_INTERNAL
+void rsvg_node_swap_impl (RsvgNode *node, RsvgNode *other);
+
+/* Implemented in rust/src/node.rs
+ *
+ * Sets the position of the element's start tag in the document, for the
+ * diagnostics that get reported while rendering it.
+ */
+G_GNUC_INTERNAL
+void rsvg_node_set_location (RsvgNode *node, int line, int column);
+
 /* Implemented in rust/src/node.rs */
 G_GNUC_INTERNAL
 void rsvg_node_draw (RsvgNode *node, RsvgDrawingCtx *draw, int dominate, gboolean clipping);
@@ -368,6 +417,22 @@ void rsvg_node_draw (RsvgNode *node, RsvgDrawingCtx *draw, int dominate, gboolea
 G_GNUC_INTERNAL
 void rsvg_node_set_attribute_parse_error (RsvgNode *node, const char *attr_name, const char *description);
 
+/* Implemented in rust/src/node.rs
+ *
+ * If the node is in error, returns TRUE and the name of the offending attribute
+ * and a description of the error in newly-allocated strings.
+ */
+G_GNUC_INTERNAL
+gboolean rsvg_node_get_error (RsvgNode *node, char **out_attr_name, char **out_message);
+
+/* Implemented in rust/src/node.rs
+ *
+ * Like rsvg_node_get_error(), but for the problems that don't prevent the node
+ * from being rendered.  Returns FALSE if @index is out of range.
+ */
+G_GNUC_INTERNAL
+gboolean rsvg_node_get_warning (RsvgNode *node, guint index, char **out_attr_name, char **out_message);
+
 typedef struct RsvgNodeChildrenIter *RsvgNodeChildrenIter;
 
 /* Implemented in rust/src/node.rs */
@@ -461,6 +526,8 @@ G_GNUC_INTERNAL
 RsvgNode *rsvg_drawing_ctx_acquire_node_of_type (RsvgDrawingCtx * ctx, const char *url, RsvgNodeType type);
 G_GNUC_INTERNAL
 void rsvg_drawing_ctx_release_node              (RsvgDrawingCtx * ctx, RsvgNode *node);
+G_GNUC_INTERNAL
+RsvgNode *rsvg_drawing_ctx_lookup_node          (RsvgDrawingCtx * ctx, const char *url);
 
 G_GNUC_INTERNAL
 void rsvg_drawing_ctx_add_node_and_ancestors_to_stack (RsvgDrawingCtx *draw_ctx, RsvgNode *node);
@@ -516,6 +583,261 @@ void rsvg_drawing_ctx_set_affine_on_cr (RsvgDrawingCtx *draw_ctx, cairo_t *cr, c
 G_GNUC_INTERNAL
 PangoContext *rsvg_drawing_ctx_get_pango_context (RsvgDrawingCtx *draw_ctx);
 
+G_GNUC_INTERNAL
+const char * const *rsvg_drawing_ctx_get_fallback_font_families (RsvgDrawingCtx *draw_ctx);
+
+G_GNUC_INTERNAL
+const cairo_font_options_t *rsvg_drawing_ctx_get_font_options (RsvgDrawingCtx *draw_ctx);
+
+G_GNUC_INTERNAL
+gboolean rsvg_drawing_ctx_is_collecting_text_runs (RsvgDrawingCtx *draw_ctx);
+
+G_GNUC_INTERNAL
+void rsvg_drawing_ctx_add_text_run (RsvgDrawingCtx *draw_ctx, RsvgTextRun *run);
+
+G_GNUC_INTERNAL
+void rsvg_text_run_free (RsvgTextRun *run);
+
+G_GNUC_INTERNAL
+void rsvg_diagnostic_free (RsvgDiagnostic *diagnostic);
+
+G_GNUC_INTERNAL
+void rsvg_drawing_ctx_add_diagnostic (RsvgDrawingCtx *draw_ctx, RsvgDiagnostic *diagnostic);
+
+/* Called from rust/src/diagnostics.rs; takes ownership of the strings */
+G_GNUC_INTERNAL
+void rsvg_diagnostics_add (GPtrArray *diagnostics, const RsvgDiagnostic *diagnostic);
+
+/* Implemented in rust/src/diagnostics.rs
+ *
+ * Records a problem that @node has while it is being rendered, for example a
+ * reference to an element that does not exist.
+ */
+G_GNUC_INTERNAL
+void rsvg_drawing_ctx_add_node_diagnostic (RsvgDrawingCtx *draw_ctx,
+                                           RsvgNode *node,
+                                           RsvgDiagnosticSeverity severity,
+                                           const char *attr_name,
+                                           const char *message);
+
+G_GNUC_INTERNAL
+void rsvg_unsupported_feature_free (RsvgUnsupportedFeature *feature);
+
+/* Counts a use of an unsupported feature, at the current location of the
+ * loader if the handle is being loaded.
+ */
+G_GNUC_INTERNAL
+void rsvg_handle_add_unsupported_feature (RsvgHandle *handle,
+                                          RsvgUnsupportedFeatureKind kind,
+                                          const char *name);
+
+G_GNUC_INTERNAL
+gpointer rsvg_drawing_ctx_get_text_outlines (RsvgDrawingCtx *draw_ctx);
+
+G_GNUC_INTERNAL
+gboolean rsvg_handle_collect_text_outlines (RsvgHandle *handle, gpointer outlines);
+
+/* Called from rust/src/normalize.rs */
+G_GNUC_INTERNAL
+gpointer rsvg_drawing_ctx_get_normalizer (RsvgDrawingCtx *draw_ctx);
+
+/* Called from rust/src/normalize.rs */
+G_GNUC_INTERNAL
+void rsvg_drawing_ctx_set_normalizer (RsvgDrawingCtx *draw_ctx, gpointer normalizer);
+
+/* Called from rust/src/normalize.rs */
+G_GNUC_INTERNAL
+gboolean rsvg_handle_draw_for_normalizer (RsvgHandle *handle, gpointer normalizer);
+
+/* Called from rust/src/hit_test.rs */
+G_GNUC_INTERNAL
+gpointer rsvg_drawing_ctx_get_hit_test (RsvgDrawingCtx *draw_ctx);
+
+/* Called from rust/src/hit_test.rs */
+G_GNUC_INTERNAL
+void rsvg_drawing_ctx_set_hit_test (RsvgDrawingCtx *draw_ctx, gpointer hit_test);
+
+/* Called from rust/src/hit_test.rs */
+G_GNUC_INTERNAL
+gboolean rsvg_handle_collect_hits (RsvgHandle *handle, gpointer hit_test);
+
+/* Implemented in rust/src/hit_test.rs; called when an intermediate surface
+ * is created, and when it is composited onto @cr after setting its clip.
+ */
+G_GNUC_INTERNAL
+void rsvg_hit_test_push_layer (RsvgDrawingCtx *draw_ctx);
+G_GNUC_INTERNAL
+void rsvg_hit_test_pop_layer (RsvgDrawingCtx *draw_ctx, cairo_t *cr);
+
+/* Implemented in rust/src/hit_test.rs
+ *
+ * Returns the id of the topmost shape under (@x, @y), or of its closest
+ * ancestor with an id; or NULL.
+ */
+G_GNUC_INTERNAL
+char *rsvg_hit_test_get_id (RsvgHandle *handle, double x, double y);
+
+/* Called from rust/src/geometry.rs */
+G_GNUC_INTERNAL
+gpointer rsvg_drawing_ctx_get_geometry (RsvgDrawingCtx *draw_ctx);
+
+/* Called from rust/src/geometry.rs */
+G_GNUC_INTERNAL
+void rsvg_drawing_ctx_set_geometry (RsvgDrawingCtx *draw_ctx, gpointer geometry);
+
+/* Called from rust/src/geometry.rs; draws only @node and its ancestors */
+G_GNUC_INTERNAL
+gboolean rsvg_handle_collect_geometry (RsvgHandle *handle, RsvgNode *node, gpointer geometry);
+
+/* Implemented in rust/src/geometry.rs
+ *
+ * These return FALSE if there is no element with the @id, or if it is not
+ * rendered.
+ */
+G_GNUC_INTERNAL
+gboolean rsvg_geometry_get_bbox (RsvgHandle *handle,
+                                 const char *id,
+                                 gboolean fill,
+                                 gboolean stroke,
+                                 gboolean markers,
+                                 RsvgRectangle *out_bbox);
+G_GNUC_INTERNAL
+gboolean rsvg_geometry_get_screen_ctm (RsvgHandle *handle, const char *id, cairo_matrix_t *out_ctm);
+
+/* Implemented in rsvg-element.c; these return borrowed nodes, or NULL if the
+ * handle has not finished loading.
+ */
+G_GNUC_INTERNAL
+RsvgNode *rsvg_handle_get_root_node (RsvgHandle *handle);
+G_GNUC_INTERNAL
+RsvgNode *rsvg_handle_get_node_by_id (RsvgHandle *handle, const char *id);
+
+/* Implemented in rust/src/dom.rs */
+G_GNUC_INTERNAL
+RsvgElement *rsvg_dom_element_from_node (RsvgNode *node);
+
+/* Implemented in rust/src/dom.rs */
+G_GNUC_INTERNAL
+char *rsvg_dom_element_get_name (RsvgElement *element);
+
+/* Implemented in rust/src/dom.rs */
+G_GNUC_INTERNAL
+RsvgElement *rsvg_dom_element_get_parent (RsvgElement *element);
+
+/* Implemented in rust/src/dom.rs */
+G_GNUC_INTERNAL
+guint rsvg_dom_element_get_n_children (RsvgElement *element);
+
+/* Implemented in rust/src/dom.rs */
+G_GNUC_INTERNAL
+RsvgElement *rsvg_dom_element_get_child (RsvgElement *element, guint index);
+
+/* Implemented in rust/src/dom.rs */
+G_GNUC_INTERNAL
+char *rsvg_dom_element_get_attribute (RsvgElement *element, const char *name);
+
+/* Implemented in rust/src/dom.rs */
+G_GNUC_INTERNAL
+char **rsvg_dom_element_get_attribute_names (RsvgElement *element);
+
+/* Implemented in rust/src/dom.rs */
+G_GNUC_INTERNAL
+char *rsvg_dom_element_get_computed_style (RsvgElement *element, const char *property);
+
+/* Implemented in rust/src/dom.rs
+ *
+ * On failure, returns FALSE and a description of the error in a
+ * newly-allocated string.
+ */
+G_GNUC_INTERNAL
+gboolean rsvg_dom_node_set_attribute (RsvgHandle *handle,
+                                      RsvgNode *node,
+                                      const char *name,
+                                      const char *value,
+                                      char **out_message);
+
+/* Implemented in rust/src/dom.rs; like rsvg_dom_node_set_attribute() */
+G_GNUC_INTERNAL
+gboolean rsvg_dom_node_set_style_property (RsvgHandle *handle,
+                                           RsvgNode *node,
+                                           const char *property,
+                                           const char *value,
+                                           char **out_message);
+
+/* Implemented in rust/src/dom.rs; like rsvg_dom_node_set_attribute() */
+G_GNUC_INTERNAL
+gboolean rsvg_dom_node_set_text_content (RsvgNode *node,
+                                         const char *text,
+                                         char **out_message);
+
+/* Implemented in rust/src/serialize.rs
+ *
+ * Returns NULL if the handle has not finished loading.
+ */
+G_GNUC_INTERNAL
+char *rsvg_dom_serialize (RsvgHandle *handle,
+                          gboolean pretty,
+                          gboolean drop_nodes_in_error);
+
+/* Implemented in rust/src/sanitize.rs
+ *
+ * Adds an RsvgSanitizeRemoval to @removals for each part of the document
+ * that it leaves out.  Returns NULL if the handle has not finished loading.
+ */
+G_GNUC_INTERNAL
+char *rsvg_dom_sanitize (RsvgHandle *handle,
+                         gboolean pretty,
+                         gboolean drop_nodes_in_error,
+                         GPtrArray *removals);
+
+/* Called from rust/src/sanitize.rs; takes ownership of the strings */
+G_GNUC_INTERNAL
+void rsvg_sanitize_removals_add (GPtrArray *removals, const RsvgSanitizeRemoval *removal);
+
+/* Implemented in rust/src/normalize.rs
+ *
+ * Adds an RsvgDiagnostic to @diagnostics for each part of the document that
+ * it can't normalize.  Returns NULL if the handle has not finished loading.
+ */
+G_GNUC_INTERNAL
+char *rsvg_dom_normalize (RsvgHandle *handle,
+                          gboolean pretty,
+                          gboolean bake_transforms,
+                          GPtrArray *diagnostics);
+
+/* Implemented in rust/src/timeline.rs
+ *
+ * Sets the animated values of elements at @time, in seconds.  Returns the
+ * old values, which must be passed to rsvg_timeline_restore().
+ */
+G_GNUC_INTERNAL
+gpointer rsvg_timeline_apply (RsvgHandle *handle, double time);
+
+/* Implemented in rust/src/timeline.rs */
+G_GNUC_INTERNAL
+void rsvg_timeline_restore (RsvgHandle *handle, gpointer applied);
+
+/* Implemented in rust/src/css_animation.rs */
+G_GNUC_INTERNAL
+gpointer rsvg_keyframes_new (void);
+
+/* Implemented in rust/src/css_animation.rs */
+G_GNUC_INTERNAL
+void rsvg_keyframes_free (gpointer keyframes);
+
+/* Implemented in rust/src/css_animation.rs
+ *
+ * Adds the @keyframes rules of a stylesheet to @keyframes, and returns a
+ * copy of the stylesheet, with the same length, where they have been replaced
+ * with white space.  Free it with g_free().
+ */
+G_GNUC_INTERNAL
+char *rsvg_keyframes_parse_stylesheet (gpointer keyframes, const char *buff, size_t buflen);
+
+/* Called from rust/src/css_animation.rs */
+G_GNUC_INTERNAL
+gpointer rsvg_handle_get_keyframes (RsvgHandle *handle);
+
 /* Implemented in rust/src/length.rs */
 G_GNUC_INTERNAL
 RsvgLength rsvg_length_parse (const char *str, LengthDir dir);
@@ -549,6 +871,12 @@ gboolean rsvg_allow_load (GFile       *base_gfile,
                           const char  *uri,
                           GError     **error);
 
+/* Whether librsvg would load @url, which is relative to the handle's base
+ * URI.  Called from rust/src/sanitize.rs.
+ */
+G_GNUC_INTERNAL
+gboolean rsvg_handle_allow_load_url (RsvgHandle *handle, const char *url);
+
 G_GNUC_INTERNAL
 char *_rsvg_handle_acquire_data (RsvgHandle *handle,
                                  const char *uri,
@@ -78,6 +78,42 @@ RsvgViewBox rsvg_node_svg_get_view_box (RsvgNode *node);
 G_GNUC_INTERNAL
 void rsvg_node_svg_apply_atts (RsvgNode *node, RsvgHandle *handle);
 
+/* Implemented in rust/src/font.rs */
+G_GNUC_INTERNAL
+RsvgNode *rsvg_node_font_new (const char *element_name, RsvgNode *parent);
+
+/* Implemented in rust/src/font.rs */
+G_GNUC_INTERNAL
+RsvgNode *rsvg_node_font_face_new (const char *element_name, RsvgNode *parent);
+
+/* Implemented in rust/src/font.rs */
+G_GNUC_INTERNAL
+RsvgNode *rsvg_node_glyph_new (const char *element_name, RsvgNode *parent);
+
+/* Implemented in rust/src/font.rs */
+G_GNUC_INTERNAL
+RsvgNode *rsvg_node_hkern_new (const char *element_name, RsvgNode *parent);
+
+/* Implemented in rust/src/font.rs */
+G_GNUC_INTERNAL
+RsvgNode *rsvg_node_missing_glyph_new (const char *element_name, RsvgNode *parent);
+
+/* Implemented in rust/src/text.rs */
+G_GNUC_INTERNAL
+RsvgNode *rsvg_node_flow_para_new (const char *element_name, RsvgNode *parent);
+
+/* Implemented in rust/src/text.rs */
+G_GNUC_INTERNAL
+RsvgNode *rsvg_node_flow_region_new (const char *element_name, RsvgNode *parent);
+
+/* Implemented in rust/src/text.rs */
+G_GNUC_INTERNAL
+RsvgNode *rsvg_node_flow_root_new (const char *element_name, RsvgNode *parent);
+
+/* Implemented in rust/src/text.rs */
+G_GNUC_INTERNAL
+RsvgNode *rsvg_node_flow_span_new (const char *element_name, RsvgNode *parent);
+
 /* Implemented in rust/src/text.rs */
 G_GNUC_INTERNAL
 RsvgNode *rsvg_node_text_new (const char *element_name, RsvgNode *parent);
@@ -90,6 +126,10 @@ RsvgNode *rsvg_node_tref_new (const char *element_name, RsvgNode *parent);
 G_GNUC_INTERNAL
 RsvgNode *rsvg_node_tspan_new (const char *element_name, RsvgNode *parent);
 
+/* Implemented in rust/src/animation.rs */
+G_GNUC_INTERNAL
+RsvgNode *rsvg_node_animation_new (const char *element_name, RsvgNode *parent);
+
 G_END_DECLS
 
 #endif                          /* RSVG_STRUCTURE_H */
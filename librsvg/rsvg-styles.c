@@ -32,6 +32,7 @@
 #include "rsvg-attributes.h"
 #include "rsvg-private.h"
 #include "rsvg-filter.h"
+#include "rsvg-fonts.h"
 #include "rsvg-css.h"
 #include "rsvg-styles.h"
 #include "rsvg-shapes.h"
@@ -118,7 +119,6 @@ rsvg_state_init (RsvgState * state)
     state->flood_opacity = 255;
 
     state->text_dir = PANGO_DIRECTION_LTR;
-    state->text_gravity = PANGO_GRAVITY_SOUTH;
     state->cond_true = TRUE;
     state->filter = NULL;
     state->clip_path = NULL;
@@ -139,7 +139,6 @@ rsvg_state_init (RsvgState * state)
     state->has_stop_color = FALSE;
     state->has_stop_opacity = FALSE;
     state->has_text_dir = FALSE;
-    state->has_text_gravity = FALSE;
     state->has_startMarker = FALSE;
     state->has_middleMarker = FALSE;
     state->has_endMarker = FALSE;
@@ -311,8 +310,6 @@ rsvg_state_inherit_run (RsvgState * dst, const RsvgState * src,
         dst->cond_true = src->cond_true;
     if (function (dst->has_text_dir, src->has_text_dir))
         dst->text_dir = src->text_dir;
-    if (function (dst->has_text_gravity, src->has_text_gravity))
-        dst->text_gravity = src->text_gravity;
     if (function (dst->has_startMarker, src->has_startMarker)) {
         g_free (dst->startMarker);
         dst->startMarker = g_strdup (src->startMarker);
@@ -642,30 +639,6 @@ rsvg_parse_style_pair (RsvgState *state,
     }
     break;
 
-    case RSVG_ATTRIBUTE_WRITING_MODE:
-    {
-        /* TODO: these aren't quite right... */
-
-        state->has_text_dir = TRUE;
-        state->has_text_gravity = TRUE;
-        if (g_str_equal (value, "inherit")) {
-            state->text_dir = PANGO_DIRECTION_LTR;
-            state->has_text_dir = FALSE;
-            state->text_gravity = PANGO_GRAVITY_SOUTH;
-            state->has_text_gravity = FALSE;
-        } else if (g_str_equal (value, "lr-tb") || g_str_equal (value, "lr")) {
-            state->text_dir = PANGO_DIRECTION_LTR;
-            state->text_gravity = PANGO_GRAVITY_SOUTH;
-        } else if (g_str_equal (value, "rl-tb") || g_str_equal (value, "rl")) {
-            state->text_dir = PANGO_DIRECTION_RTL;
-            state->text_gravity = PANGO_GRAVITY_SOUTH;
-        } else if (g_str_equal (value, "tb-rl") || g_str_equal (value, "tb")) {
-            state->text_dir = PANGO_DIRECTION_LTR;
-            state->text_gravity = PANGO_GRAVITY_EAST;
-        }
-    }
-    break;
-
     case RSVG_ATTRIBUTE_STOP_COLOR:
     {
         state->has_stop_color = TRUE;
@@ -938,6 +911,36 @@ rsvg_parse_style_attribute_contents (RsvgState *state, const char *str)
     return success;
 }
 
+/* Counts the properties in a style attribute that librsvg does not know about.
+ * This splits the attribute in the same way as
+ * rsvg_parse_style_attribute_contents().
+ */
+static void
+add_unknown_style_properties (RsvgHandle *handle, const char *str)
+{
+    gchar **styles;
+    guint i;
+
+    styles = g_strsplit (str, ";", -1);
+    for (i = 0; styles[i] != NULL; i++) {
+        gchar **values;
+
+        values = g_strsplit (styles[i], ":", 2);
+
+        if (g_strv_length (values) == 2) {
+            RsvgAttribute attr;
+            gchar *name = g_strstrip (values[0]);
+
+            if (*name != '\0' && !rsvg_attribute_from_name (name, &attr)) {
+                rsvg_handle_add_unsupported_feature (handle, RSVG_UNSUPPORTED_FEATURE_PROPERTY, name);
+            }
+        }
+
+        g_strfreev (values);
+    }
+    g_strfreev (styles);
+}
+
 static void
 rsvg_css_define_style (RsvgHandle *handle,
                        const gchar *selector,
@@ -971,6 +974,11 @@ rsvg_css_define_style (RsvgHandle *handle,
 typedef struct _CSSUserData {
     RsvgHandle *handle;
     CRSelector *selector;
+
+    /* descriptors of the @font-face rule being parsed */
+    gboolean in_font_face;
+    gchar *font_face_family;
+    GPtrArray *font_face_srcs;
 } CSSUserData;
 
 static void
@@ -978,6 +986,23 @@ css_user_data_init (CSSUserData *user_data, RsvgHandle *handle)
 {
     user_data->handle = handle;
     user_data->selector = NULL;
+    user_data->in_font_face = FALSE;
+    user_data->font_face_family = NULL;
+    user_data->font_face_srcs = NULL;
+}
+
+static void
+css_user_data_clear_font_face (CSSUserData *user_data)
+{
+    user_data->in_font_face = FALSE;
+
+    g_free (user_data->font_face_family);
+    user_data->font_face_family = NULL;
+
+    if (user_data->font_face_srcs) {
+        g_ptr_array_free (user_data->font_face_srcs, TRUE);
+        user_data->font_face_srcs = NULL;
+    }
 }
 
 static void
@@ -1005,6 +1030,92 @@ ccss_end_selector (CRDocHandler * a_handler, CRSelector * a_selector_list)
     user_data->selector = NULL;
 }
 
+static void
+ccss_start_font_face (CRDocHandler * a_handler, CRParsingLocation * a_location)
+{
+    CSSUserData *user_data;
+
+    g_return_if_fail (a_handler);
+
+    user_data = (CSSUserData *) a_handler->app_data;
+
+    css_user_data_clear_font_face (user_data);
+    user_data->in_font_face = TRUE;
+    user_data->font_face_srcs = g_ptr_array_new_with_free_func (g_free);
+}
+
+static void
+ccss_end_font_face (CRDocHandler * a_handler)
+{
+    CSSUserData *user_data;
+
+    g_return_if_fail (a_handler);
+
+    user_data = (CSSUserData *) a_handler->app_data;
+
+    if (user_data->font_face_family
+        && user_data->font_face_srcs
+        && user_data->font_face_srcs->len > 0) {
+        rsvg_handle_add_font_face (user_data->handle,
+                                   user_data->font_face_family,
+                                   user_data->font_face_srcs);
+    }
+
+    css_user_data_clear_font_face (user_data);
+}
+
+/* Returns the family name from a font-family descriptor, which may be a
+ * quoted string or a sequence of identifiers.
+ */
+static gchar *
+font_face_family_from_term (CRTerm * a_expr)
+{
+    GString *family;
+    CRTerm *term;
+
+    family = g_string_new (NULL);
+
+    for (term = a_expr; term; term = term->next) {
+        if ((term->type == TERM_STRING || term->type == TERM_IDENT) && term->content.str) {
+            if (family->len > 0)
+                g_string_append_c (family, ' ');
+
+            g_string_append_len (family,
+                                 cr_string_peek_raw_str (term->content.str),
+                                 cr_string_peek_raw_str_len (term->content.str));
+        }
+    }
+
+    if (family->len == 0) {
+        g_string_free (family, TRUE);
+        return NULL;
+    }
+
+    return g_string_free (family, FALSE);
+}
+
+static void
+ccss_font_face_property (CSSUserData *user_data, const char *name, CRTerm * a_expr)
+{
+    CRTerm *term;
+
+    if (strcmp (name, "font-family") == 0) {
+        g_free (user_data->font_face_family);
+        user_data->font_face_family = font_face_family_from_term (a_expr);
+    } else if (strcmp (name, "src") == 0) {
+        /* We only handle url() sources; local() and format() hints are
+         * ignored, and the sources are tried in order.
+         */
+        for (term = a_expr; term; term = term->next) {
+            if (term->type == TERM_URI && term->content.str) {
+                g_ptr_array_add (user_data->font_face_srcs,
+                                 g_strndup (cr_string_peek_raw_str (term->content.str),
+                                            cr_string_peek_raw_str_len (term->content.str)));
+            }
+        }
+    }
+}
+
 static void
 ccss_property (CRDocHandler * a_handler, CRString * a_name, CRTerm * a_expr, gboolean a_important)
 {
@@ -1016,8 +1127,27 @@ ccss_property (CRDocHandler * a_handler, CRString * a_name, CRTerm * a_expr, gbo
 
     user_data = (CSSUserData *) a_handler->app_data;
 
-    if (a_name && a_expr && user_data->selector) {
+    if (a_name && a_expr && user_data->in_font_face) {
+        gchar *descriptor_name;
+
+        descriptor_name = g_strndup (cr_string_peek_raw_str (a_name),
+                                     cr_string_peek_raw_str_len (a_name));
+        ccss_font_face_property (user_data, descriptor_name, a_expr);
+        g_free (descriptor_name);
+    } else if (a_name && a_expr && user_data->selector) {
         CRSelector *cur;
+        RsvgAttribute attr;
+        gchar *property_name;
+
+        property_name = g_strndup (cr_string_peek_raw_str (a_name),
+                                   cr_string_peek_raw_str_len (a_name));
+        if (!rsvg_attribute_from_name (property_name, &attr)) {
+            rsvg_handle_add_unsupported_feature (user_data->handle,
+                                                 RSVG_UNSUPPORTED_FEATURE_PROPERTY,
+                                                 property_name);
+        }
+        g_free (property_name);
+
         for (cur = user_data->selector; cur; cur = cur->next) {
             if (cur->simple_sel) {
                 gchar *selector = (gchar *) cr_simple_sel_to_string (cur->simple_sel);
@@ -1073,8 +1203,8 @@ init_sac_handler (CRDocHandler * a_handler)
     a_handler->start_selector = ccss_start_selector;
     a_handler->end_selector = ccss_end_selector;
     a_handler->property = ccss_property;
-    a_handler->start_font_face = NULL;
-    a_handler->end_font_face = NULL;
+    a_handler->start_font_face = ccss_start_font_face;
+    a_handler->end_font_face = ccss_end_font_face;
     a_handler->start_media = NULL;
     a_handler->end_media = NULL;
     a_handler->start_page = NULL;
@@ -1090,10 +1220,14 @@ rsvg_parse_cssbuffer (RsvgHandle *handle, const char *buff, size_t buflen)
     CRParser *parser = NULL;
     CRDocHandler *css_handler = NULL;
     CSSUserData user_data;
+    char *rules;
 
     if (buff == NULL || buflen == 0)
         return;
 
+    /* libcroco doesn't know about @keyframes, so take them out first */
+    rules = rsvg_keyframes_parse_stylesheet (handle->priv->keyframes, buff, buflen);
+
     css_handler = cr_doc_handler_new ();
     init_sac_handler (css_handler);
 
@@ -1101,9 +1235,10 @@ rsvg_parse_cssbuffer (RsvgHandle *handle, const char *buff, size_t buflen)
     css_handler->app_data = &user_data;
 
     /* TODO: fix libcroco to take in const strings */
-    parser = cr_parser_new_from_buf ((guchar *) buff, (gulong) buflen, CR_UTF_8, FALSE);
+    parser = cr_parser_new_from_buf ((guchar *) rules, (gulong) buflen, CR_UTF_8, FALSE);
     if (parser == NULL) {
         cr_doc_handler_unref (css_handler);
+        g_free (rules);
         return;
     }
 
@@ -1113,11 +1248,14 @@ rsvg_parse_cssbuffer (RsvgHandle *handle, const char *buff, size_t buflen)
     cr_parser_set_use_core_grammar (parser, FALSE);
     cr_parser_parse (parser);
 
+    css_user_data_clear_font_face (&user_data);
+
     /* FIXME: we aren't reporting errors in the CSS; we have no way to know if
      * we should print the "buff" for diagnostics.
      */
 
     cr_parser_destroy (parser);
+    g_free (rules);
 }
 
 static void
@@ -1173,6 +1311,39 @@ rsvg_parse_transform_attr (RsvgState *state, const char *str)
     }
 }
 
+/* Calls @func for each declaration from the document's stylesheets.  The
+ * selectors and properties are sorted so that the order does not depend on
+ * the hash tables.
+ */
+void
+rsvg_handle_foreach_css_declaration (RsvgHandle *handle,
+                                     RsvgCssDeclarationFunc func,
+                                     gpointer user_data)
+{
+    GList *selectors, *s;
+
+    selectors = g_list_sort (g_hash_table_get_keys (handle->priv->css_props),
+                             (GCompareFunc) strcmp);
+
+    for (s = selectors; s; s = s->next) {
+        GHashTable *styles;
+        GList *names, *n;
+
+        styles = g_hash_table_lookup (handle->priv->css_props, s->data);
+        names = g_list_sort (g_hash_table_get_keys (styles), (GCompareFunc) strcmp);
+
+        for (n = names; n; n = n->next) {
+            StyleValueData *data = g_hash_table_lookup (styles, n->data);
+
+            func (s->data, n->data, data->value, data->important, user_data);
+        }
+
+        g_list_free (names);
+    }
+
+    g_list_free (selectors);
+}
+
 static void
 apply_style (const gchar *key, StyleValueData *value, gpointer user_data)
 {
@@ -1310,6 +1481,7 @@ rsvg_parse_style_attrs (RsvgHandle *handle,
     while (success && rsvg_property_bag_iter_next (iter, &key, &attr, &value)) {
         switch (attr) {
         case RSVG_ATTRIBUTE_STYLE:
+            add_unknown_style_properties (handle, value);
             success = rsvg_parse_style_attribute_contents (state, value);
             break;
 
@@ -1448,16 +1620,22 @@ rsvg_state_get_current_color (RsvgState *state)
     return state->current_color;
 }
 
-PangoDirection
-rsvg_state_get_text_dir (RsvgState *state)
+guint32
+rsvg_state_get_flood_color (RsvgState *state)
 {
-    return state->text_dir;
+    return state->flood_color;
 }
 
-PangoGravity
-rsvg_state_get_text_gravity (RsvgState *state)
+guint8
+rsvg_state_get_flood_opacity (RsvgState *state)
 {
-    return state->text_gravity;
+    return state->flood_opacity;
+}
+
+PangoDirection
+rsvg_state_get_text_dir (RsvgState *state)
+{
+    return state->text_dir;
 }
 
 RsvgPaintServer *
@@ -68,8 +68,6 @@ struct _RsvgState {
 
     PangoDirection text_dir;
     gboolean has_text_dir;
-    PangoGravity text_gravity;
-    gboolean has_text_gravity;
 
     guint text_offset;
 
@@ -131,6 +129,16 @@ G_GNUC_INTERNAL
 gboolean rsvg_parse_style_attribute_contents (RsvgState *state, const char *str) G_GNUC_WARN_UNUSED_RESULT;
 G_GNUC_INTERNAL
 void rsvg_parse_cssbuffer   (RsvgHandle *handle, const char *buff, size_t buflen);
+typedef void (*RsvgCssDeclarationFunc) (const char *selector,
+                                        const char *name,
+                                        const char *value,
+                                        gboolean important,
+                                        gpointer user_data);
+
+G_GNUC_INTERNAL
+void rsvg_handle_foreach_css_declaration (RsvgHandle *handle,
+                                          RsvgCssDeclarationFunc func,
+                                          gpointer user_data);
 G_GNUC_INTERNAL
 void rsvg_parse_style_attrs (RsvgHandle *handle, RsvgNode *node, const char *tag,
                              const char *klazz, const char *id, RsvgPropertyBag * atts);
@@ -195,10 +203,13 @@ G_GNUC_INTERNAL
 guint32 rsvg_state_get_current_color (RsvgState *state);
 
 G_GNUC_INTERNAL
-PangoDirection rsvg_state_get_text_dir (RsvgState *state);
+guint32 rsvg_state_get_flood_color (RsvgState *state);
 
 G_GNUC_INTERNAL
-PangoGravity rsvg_state_get_text_gravity (RsvgState *state);
+guint8 rsvg_state_get_flood_opacity (RsvgState *state);
+
+G_GNUC_INTERNAL
+PangoDirection rsvg_state_get_text_dir (RsvgState *state);
 
 G_GNUC_INTERNAL
 RsvgPaintServer *rsvg_state_get_fill (RsvgState *state);
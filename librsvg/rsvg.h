@@ -137,6 +137,9 @@ void rsvg_set_default_dpi_x_y	(double dpi_x, double dpi_y);
 void rsvg_handle_set_dpi	(RsvgHandle * handle, double dpi);
 void rsvg_handle_set_dpi_x_y	(RsvgHandle * handle, double dpi_x, double dpi_y);
 
+gboolean rsvg_handle_add_font_dir (RsvgHandle * handle, const char *path, GError ** error);
+void rsvg_handle_set_fallback_font_families (RsvgHandle * handle, const char * const *families);
+
 RsvgHandle  *rsvg_handle_new		(void);
 gboolean     rsvg_handle_write		(RsvgHandle * handle, const guchar * buf, 
                                      gsize count, GError ** error);
@@ -154,6 +157,312 @@ gboolean rsvg_handle_get_position_sub (RsvgHandle * handle, RsvgPositionData * p
 
 gboolean rsvg_handle_has_sub (RsvgHandle * handle, const char *id);
 
+/**
+ * RsvgRectangle:
+ * @x: X coordinate of the left side of the rectangle
+ * @y: Y coordinate of the the top side of the rectangle
+ * @width: width of the rectangle
+ * @height: height of the rectangle
+ *
+ * A data structure for holding a rectangle.
+ *
+ * Since: 2.44
+ */
+typedef struct {
+    double x;
+    double y;
+    double width;
+    double height;
+} RsvgRectangle;
+
+/**
+ * RsvgTextDirection:
+ * @RSVG_TEXT_DIRECTION_LTR: Horizontal text, left to right
+ * @RSVG_TEXT_DIRECTION_RTL: Horizontal text, right to left
+ * @RSVG_TEXT_DIRECTION_TTB: Vertical text, top to bottom
+ *
+ * The direction in which the characters of an #RsvgTextRun advance.
+ *
+ * Since: 2.44
+ */
+typedef enum {
+    RSVG_TEXT_DIRECTION_LTR,
+    RSVG_TEXT_DIRECTION_RTL,
+    RSVG_TEXT_DIRECTION_TTB
+} RsvgTextDirection;
+
+/**
+ * RsvgTextRun:
+ * @text: UTF-8 text of the run, after white space handling
+ * @x: X coordinate of the start of the run's baseline
+ * @y: Y coordinate of the start of the run's baseline
+ * @font: (nullable): font description, as returned by pango_font_description_to_string()
+ * @direction: direction of the text
+ * @n_chars: number of characters in @text
+ * @char_boxes: (array length=n_chars): box of each character of @text
+ *
+ * A piece of text that librsvg rendered with a single font.  All the
+ * coordinates are in the same space as the image that
+ * rsvg_handle_render_cairo() renders with an identity transformation.
+ *
+ * Since: 2.44
+ */
+typedef struct {
+    char *text;
+    double x;
+    double y;
+    char *font;
+    RsvgTextDirection direction;
+    guint n_chars;
+    RsvgRectangle *char_boxes;
+} RsvgTextRun;
+
+GPtrArray *rsvg_handle_get_text_runs (RsvgHandle * handle);
+
+char *rsvg_handle_hit_test (RsvgHandle * handle, double x, double y);
+
+/**
+ * RsvgDiagnosticSeverity:
+ * @RSVG_DIAGNOSTIC_SEVERITY_ERROR: The element is "in error" per the SVG
+ *   specification, and librsvg does not render it
+ * @RSVG_DIAGNOSTIC_SEVERITY_WARNING: librsvg renders the element, but only
+ *   partially; for example, path data gets rendered up to its first error
+ *
+ * How an #RsvgDiagnostic affects the rendering of its element.
+ *
+ * Since: 2.44
+ */
+typedef enum {
+    RSVG_DIAGNOSTIC_SEVERITY_ERROR,
+    RSVG_DIAGNOSTIC_SEVERITY_WARNING
+} RsvgDiagnosticSeverity;
+
+/**
+ * RsvgDiagnostic:
+ * @severity: whether the element gets rendered at all
+ * @element: name of the element that has the problem
+ * @id: (nullable): value of the element's id attribute
+ * @line: line number of the element's start tag, starting at 1
+ * @column: column number of the end of the element's start tag
+ * @attribute: (nullable): name of the attribute that has an invalid value,
+ *   or %NULL if the problem is with the element as a whole
+ * @message: description of the error
+ *
+ * A problem with an attribute of an element, which prevents librsvg from
+ * rendering the element as intended, or from processing it in an operation
+ * like rsvg_handle_normalize().
+ *
+ * Since: 2.44
+ */
+typedef struct {
+    RsvgDiagnosticSeverity severity;
+    char *element;
+    char *id;
+    int line;
+    int column;
+    char *attribute;
+    char *message;
+} RsvgDiagnostic;
+
+GPtrArray *rsvg_handle_get_diagnostics (RsvgHandle * handle);
+
+/**
+ * RsvgUnsupportedFeatureKind:
+ * @RSVG_UNSUPPORTED_FEATURE_ELEMENT: An element that librsvg does not render
+ * @RSVG_UNSUPPORTED_FEATURE_ATTRIBUTE: An attribute that librsvg does not know about
+ * @RSVG_UNSUPPORTED_FEATURE_PROPERTY: A property in a style attribute or in a
+ *   stylesheet that librsvg does not know about
+ *
+ * The kind of an #RsvgUnsupportedFeature.
+ *
+ * Since: 2.44
+ */
+typedef enum {
+    RSVG_UNSUPPORTED_FEATURE_ELEMENT,
+    RSVG_UNSUPPORTED_FEATURE_ATTRIBUTE,
+    RSVG_UNSUPPORTED_FEATURE_PROPERTY
+} RsvgUnsupportedFeatureKind;
+
+/**
+ * RsvgUnsupportedFeature:
+ * @kind: whether the feature is an element, an attribute or a property
+ * @name: name of the element, attribute or property, as it appears in the SVG
+ * @count: number of times that the SVG uses it
+ * @line: line number of the first use, starting at 1, or 0 if unknown
+ * @column: column number of the first use, or 0 if unknown
+ *
+ * A part of the SVG language that a document uses, but that librsvg ignores.
+ *
+ * Since: 2.44
+ */
+typedef struct {
+    RsvgUnsupportedFeatureKind kind;
+    char *name;
+    guint count;
+    int line;
+    int column;
+} RsvgUnsupportedFeature;
+
+GPtrArray *rsvg_handle_get_unsupported_features (RsvgHandle * handle);
+
+/**
+ * RsvgElement:
+ *
+ * An element in the tree of a loaded SVG.  Elements are owned by their
+ * #RsvgHandle and remain valid for as long as the handle exists.
+ *
+ * Since: 2.44
+ */
+typedef struct _RsvgElement RsvgElement;
+
+RsvgElement *rsvg_handle_get_root_element  (RsvgHandle * handle);
+RsvgElement *rsvg_handle_get_element_by_id (RsvgHandle * handle, const char *id);
+
+char        *rsvg_element_get_name             (RsvgElement * element);
+RsvgElement *rsvg_element_get_parent           (RsvgElement * element);
+guint        rsvg_element_get_n_children       (RsvgElement * element);
+RsvgElement *rsvg_element_get_child            (RsvgElement * element, guint index_);
+char        *rsvg_element_get_attribute        (RsvgElement * element, const char *name);
+char       **rsvg_element_get_attribute_names  (RsvgElement * element);
+char        *rsvg_element_get_computed_style   (RsvgElement * element, const char *property);
+
+gboolean rsvg_handle_set_attribute      (RsvgHandle * handle,
+                                         const char *id,
+                                         const char *name,
+                                         const char *value,
+                                         GError **error);
+gboolean rsvg_handle_set_style_property (RsvgHandle * handle,
+                                         const char *id,
+                                         const char *property,
+                                         const char *value,
+                                         GError **error);
+gboolean rsvg_handle_set_text_content   (RsvgHandle * handle,
+                                         const char *id,
+                                         const char *text,
+                                         GError **error);
+
+/**
+ * RsvgSerializeFlags:
+ * @RSVG_SERIALIZE_FLAGS_NONE: none
+ * @RSVG_SERIALIZE_FLAG_PRETTY: Put each element on its own line, indented
+ *   by its depth in the tree.  Elements with text content are written
+ *   without added white space, so that their text does not change.
+ * @RSVG_SERIALIZE_FLAG_DROP_NODES_IN_ERROR: Leave out the elements that
+ *   librsvg does not render because of an invalid attribute, and their
+ *   children.  See rsvg_handle_get_diagnostics().
+ *
+ * Options for rsvg_handle_serialize().
+ *
+ * Since: 2.44
+ */
+typedef enum /*< flags >*/
+{
+    RSVG_SERIALIZE_FLAGS_NONE               = 0,
+    RSVG_SERIALIZE_FLAG_PRETTY              = 1 << 0,
+    RSVG_SERIALIZE_FLAG_DROP_NODES_IN_ERROR = 1 << 1
+} RsvgSerializeFlags;
+
+char *rsvg_handle_serialize (RsvgHandle * handle, RsvgSerializeFlags flags, GError **error);
+
+/**
+ * RsvgSanitizeRemovalKind:
+ * @RSVG_SANITIZE_REMOVAL_ELEMENT: An element, together with its children
+ * @RSVG_SANITIZE_REMOVAL_ATTRIBUTE: An attribute of an element
+ * @RSVG_SANITIZE_REMOVAL_PROPERTY: A declaration in a style attribute or in
+ *   a stylesheet
+ *
+ * The kind of an #RsvgSanitizeRemoval.
+ *
+ * Since: 2.44
+ */
+typedef enum {
+    RSVG_SANITIZE_REMOVAL_ELEMENT,
+    RSVG_SANITIZE_REMOVAL_ATTRIBUTE,
+    RSVG_SANITIZE_REMOVAL_PROPERTY
+} RsvgSanitizeRemovalKind;
+
+/**
+ * RsvgSanitizeRemoval:
+ * @kind: whether an element, an attribute or a property was removed
+ * @name: name of the element, attribute or property
+ * @element: (nullable): name of the element that had the attribute or
+ *   property, or the selector of a stylesheet rule; %NULL for elements
+ * @id: (nullable): value of the element's id attribute
+ * @value: (nullable): the removed value of an attribute or property
+ * @reason: description of why it was removed
+ *
+ * A part of a document that rsvg_handle_sanitize() left out.
+ *
+ * Since: 2.44
+ */
+typedef struct {
+    RsvgSanitizeRemovalKind kind;
+    char *name;
+    char *element;
+    char *id;
+    char *value;
+    char *reason;
+} RsvgSanitizeRemoval;
+
+char *rsvg_handle_sanitize (RsvgHandle * handle,
+                            RsvgSerializeFlags flags,
+                            GPtrArray **out_removals,
+                            GError **error);
+
+/**
+ * RsvgNormalizeFlags:
+ * @RSVG_NORMALIZE_FLAGS_NONE: none
+ * @RSVG_NORMALIZE_FLAG_PRETTY: Put each element on its own line.
+ * @RSVG_NORMALIZE_FLAG_BAKE_TRANSFORMS: Apply the transformation of each
+ *   shape to the coordinates of its path, instead of writing a transform
+ *   attribute.  Shapes that are painted with a gradient or a pattern, stroked
+ *   shapes that are not scaled equally in all directions, and shapes and
+ *   groups with a clipping path, a mask or a filter keep their transform
+ *   attribute, and so do text and images.
+ *
+ * Options for rsvg_handle_normalize().
+ *
+ * Since: 2.44
+ */
+typedef enum /*< flags >*/
+{
+    RSVG_NORMALIZE_FLAGS_NONE           = 0,
+    RSVG_NORMALIZE_FLAG_PRETTY          = 1 << 0,
+    RSVG_NORMALIZE_FLAG_BAKE_TRANSFORMS = 1 << 1
+} RsvgNormalizeFlags;
+
+char *rsvg_handle_normalize (RsvgHandle * handle,
+                             RsvgNormalizeFlags flags,
+                             GPtrArray **out_diagnostics,
+                             GError **error);
+
+/**
+ * RsvgBboxFlags:
+ * @RSVG_BBOX_FLAGS_NONE: none
+ * @RSVG_BBOX_FLAG_FILL: Include the geometry of shapes, and the extents of
+ *   text and images.
+ * @RSVG_BBOX_FLAG_STROKE: Include the area that the strokes of shapes
+ *   cover.
+ * @RSVG_BBOX_FLAG_MARKERS: Include the contents of the markers of shapes.
+ *
+ * Options for rsvg_handle_get_element_bbox(), like the ones of the
+ * getBBox() method of the SVG DOM.
+ *
+ * Since: 2.44
+ */
+typedef enum /*< flags >*/
+{
+    RSVG_BBOX_FLAGS_NONE   = 0,
+    RSVG_BBOX_FLAG_FILL    = 1 << 0,
+    RSVG_BBOX_FLAG_STROKE  = 1 << 1,
+    RSVG_BBOX_FLAG_MARKERS = 1 << 2
+} RsvgBboxFlags;
+
+gboolean rsvg_handle_get_element_bbox (RsvgHandle * handle,
+                                       const char *id,
+                                       RsvgBboxFlags flags,
+                                       RsvgRectangle *out_bbox);
+
 /* GIO APIs */
 
 /**
@@ -166,12 +475,17 @@ gboolean rsvg_handle_has_sub (RsvgHandle * handle, const char *id);
  *  for use by cairo when painting to e.g. a PDF surface. This will make the
  *  resulting PDF file smaller and faster.
  *  Since: 2.40.3
+ * @RSVG_HANDLE_FLAG_STRICT: Fail loading as soon as an element has an invalid
+ *  attribute, instead of not rendering that element.  This is useful to
+ *  validate SVG files.
+ *  Since: 2.44
  */
 typedef enum /*< flags >*/ 
 {
     RSVG_HANDLE_FLAGS_NONE           = 0,
     RSVG_HANDLE_FLAG_UNLIMITED       = 1 << 0,
-    RSVG_HANDLE_FLAG_KEEP_IMAGE_DATA = 1 << 1
+    RSVG_HANDLE_FLAG_KEEP_IMAGE_DATA = 1 << 1,
+    RSVG_HANDLE_FLAG_STRICT          = 1 << 2
 } RsvgHandleFlags;
 
 RsvgHandle *rsvg_handle_new_with_flags (RsvgHandleFlags flags);
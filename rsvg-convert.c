@@ -131,6 +131,7 @@ main (int argc, char **argv)
     gboolean unlimited = FALSE;
     gboolean keep_image_data = FALSE;
     gboolean no_keep_image_data = FALSE;
+    gboolean strict = FALSE;
     GError *error = NULL;
 
     int i;
@@ -185,6 +186,7 @@ main (int argc, char **argv)
         {"unlimited", 'u', 0, G_OPTION_ARG_NONE, &unlimited, N_("Allow huge SVG files"), NULL},
         {"keep-image-data", 0, 0, G_OPTION_ARG_NONE, &keep_image_data, N_("Keep image data"), NULL},
         {"no-keep-image-data", 0, 0, G_OPTION_ARG_NONE, &no_keep_image_data, N_("Don't keep image data"), NULL},
+        {"strict", 0, 0, G_OPTION_ARG_NONE, &strict, N_("Fail on elements with invalid attributes"), NULL},
         {"version", 'v', 0, G_OPTION_ARG_NONE, &bVersion, N_("show version information"), NULL},
         {G_OPTION_REMAINING, 0, 0, G_OPTION_ARG_FILENAME_ARRAY, &args, NULL, N_("[FILE...]")},
         {NULL}
@@ -262,6 +264,9 @@ main (int argc, char **argv)
     if (keep_image_data)
         flags |= RSVG_HANDLE_FLAG_KEEP_IMAGE_DATA;
 
+    if (strict)
+        flags |= RSVG_HANDLE_FLAG_STRICT;
+
     for (i = 0; i < n_args; i++) {
         GFile *file;
         GInputStream *stream;
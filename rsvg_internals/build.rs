@@ -16,12 +16,26 @@ fn generate_phf_of_svg_attributes() {
     // Keep this in sync with rsvg-attributes.h
     #[cfg_attr(rustfmt, rustfmt_skip)]
     let attribute_defs = [
+        ( "additive",           "Additive" ),
         ( "alternate",          "Alternate" ),
         ( "amplitude",          "Amplitude" ),
+        ( "animation",          "Animation" ),
+        ( "animation-delay",    "AnimationDelay" ),
+        ( "animation-direction", "AnimationDirection" ),
+        ( "animation-duration", "AnimationDuration" ),
+        ( "animation-fill-mode", "AnimationFillMode" ),
+        ( "animation-iteration-count", "AnimationIterationCount" ),
+        ( "animation-name",     "AnimationName" ),
+        ( "animation-timing-function", "AnimationTimingFunction" ),
+        ( "attributeName",      "AttributeName" ),
+        ( "attributeType",      "AttributeType" ),
         ( "azimuth",            "Azimuth" ),
         ( "baseFrequency",      "BaseFrequency" ),
         ( "baseline-shift",     "BaselineShift" ),
+        ( "begin",              "Begin" ),
         ( "bias",               "Bias" ),
+        ( "by",                 "By" ),
+        ( "calcMode",           "CalcMode" ),
         ( "class",              "Class" ),
         ( "clip-path",          "ClipPath" ),
         ( "clip-rule",          "ClipRule" ),
@@ -35,6 +49,7 @@ fn generate_phf_of_svg_attributes() {
         ( "direction",          "Direction" ),
         ( "display",            "Display" ),
         ( "divisor",            "Divisor" ),
+        ( "dur",                "Dur" ),
         ( "dx",                 "Dx" ),
         ( "dy",                 "Dy" ),
         ( "edgeMode",           "EdgeMode" ),
@@ -50,27 +65,43 @@ fn generate_phf_of_svg_attributes() {
         ( "flood-color",        "FloodColor" ),
         ( "flood-opacity",      "FloodOpacity" ),
         ( "font-family",        "FontFamily" ),
+        ( "font-feature-settings", "FontFeatureSettings" ),
+        ( "font-kerning",       "FontKerning" ),
         ( "font-size",          "FontSize" ),
         ( "font-stretch",       "FontStretch" ),
         ( "font-style",         "FontStyle" ),
         ( "font-variant",       "FontVariant" ),
+        ( "font-variant-caps",  "FontVariantCaps" ),
+        ( "font-variant-ligatures", "FontVariantLigatures" ),
+        ( "font-variant-numeric", "FontVariantNumeric" ),
+        ( "font-variant-position", "FontVariantPosition" ),
         ( "font-weight",        "FontWeight" ),
+        ( "from",               "From" ),
         ( "fx",                 "Fx" ),
         ( "fy",                 "Fy" ),
+        ( "g1",                 "G1" ),
+        ( "g2",                 "G2" ),
+        ( "glyph-name",         "GlyphName" ),
+        ( "glyph-orientation-vertical", "GlyphOrientationVertical" ),
         ( "gradientTransform",  "GradientTransform" ),
         ( "gradientUnits",      "GradientUnits" ),
         ( "height",             "Height" ),
+        ( "horiz-adv-x",        "HorizAdvX" ),
         ( "href",               "Href" ),
         ( "id",                 "Id" ),
         ( "in",                 "In" ),
         ( "in2",                "In2" ),
+        ( "inline-size",        "InlineSize" ),
         ( "intercept",          "Intercept" ),
+        ( "k",                  "K" ),
         ( "k1",                 "K1" ),
         ( "k2",                 "K2" ),
         ( "k3",                 "K3" ),
         ( "k4",                 "K4" ),
         ( "kernelMatrix",       "KernelMatrix" ),
         ( "kernelUnitLength",   "KernelUnitLength" ),
+        ( "keySplines",         "KeySplines" ),
+        ( "keyTimes",           "KeyTimes" ),
         ( "letter-spacing",     "LetterSpacing" ),
         ( "lighting-color",     "LightingColor" ),
         ( "limitingConeAngle",  "LimitingConeAngle" ),
@@ -94,9 +125,11 @@ fn generate_phf_of_svg_attributes() {
         ( "overflow",           "Overflow" ),
         ( "parse",              "Parse" ),
         ( "path",               "Path" ),
+        ( "pathLength",         "PathLength" ),
         ( "patternContentUnits", "PatternContentUnits" ),
         ( "patternTransform",   "PatternTransform" ),
         ( "patternUnits",       "PatternUnits" ),
+        ( "pointer-events",     "PointerEvents" ),
         ( "points",             "Points" ),
         ( "pointsAtX",          "PointsAtX" ),
         ( "pointsAtY",          "PointsAtY" ),
@@ -108,13 +141,17 @@ fn generate_phf_of_svg_attributes() {
         ( "radius",             "Radius" ),
         ( "refX",               "RefX" ),
         ( "refY",               "RefY" ),
+        ( "repeatCount",        "RepeatCount" ),
+        ( "repeatDur",          "RepeatDur" ),
         ( "requiredExtensions", "RequiredExtensions" ),
         ( "requiredFeatures",   "RequiredFeatures" ),
         ( "result",             "Result" ),
+        ( "rotate",             "Rotate" ),
         ( "rx",                 "Rx" ),
         ( "ry",                 "Ry" ),
         ( "scale",              "Scale" ),
         ( "seed",               "Seed" ),
+        ( "shape-inside",       "ShapeInside" ),
         ( "shape-rendering",    "ShapeRendering" ),
         ( "slope",              "Slope" ),
         ( "specularConstant",   "SpecularConstant" ),
@@ -140,15 +177,29 @@ fn generate_phf_of_svg_attributes() {
         ( "targetY",            "TargetY" ),
         ( "text-anchor",        "TextAnchor" ),
         ( "text-decoration",    "TextDecoration" ),
+        ( "text-decoration-color", "TextDecorationColor" ),
+        ( "text-decoration-style", "TextDecorationStyle" ),
+        ( "text-orientation",   "TextOrientation" ),
         ( "text-rendering",     "TextRendering" ),
+        ( "to",                 "To" ),
         ( "transform",          "Transform" ),
+        ( "transition",         "Transition" ),
+        ( "transition-delay",   "TransitionDelay" ),
+        ( "transition-duration", "TransitionDuration" ),
+        ( "transition-property", "TransitionProperty" ),
+        ( "transition-timing-function", "TransitionTimingFunction" ),
         ( "type",               "Type" ),
+        ( "u1",                 "U1" ),
+        ( "u2",                 "U2" ),
+        ( "unicode",            "Unicode" ),
         ( "unicode-bidi",       "UnicodeBidi" ),
+        ( "units-per-em",       "UnitsPerEm" ),
         ( "values",             "Values" ),
         ( "verts",              "Verts" ),
         ( "viewBox",            "ViewBox" ),
         ( "visibility",         "Visibility" ),
         ( "width",              "Width" ),
+        ( "word-spacing",       "WordSpacing" ),
         ( "writing-mode",       "WritingMode" ),
         ( "x",                  "X" ),
         ( "x1",                 "X1" ),
@@ -0,0 +1,1361 @@
+use cssparser;
+use glib::translate::*;
+use libc;
+
+use std::cell::RefCell;
+use std::f64::consts::PI;
+
+use attributes::Attribute;
+use color::{AllowCurrentColor, AllowInherit, Color};
+use dom;
+use drawing_ctx::RsvgDrawingCtx;
+use error::*;
+use handle::RsvgHandle;
+use node::*;
+use parsers::{parse, Parse, ParseError};
+use path_builder::PathBuilder;
+use path_measure::PathMeasure;
+use path_parser;
+use property_bag::PropertyBag;
+
+// ************ Timing attributes ************
+
+/// A duration in seconds, for the `dur` and `repeatDur` attributes.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Duration {
+    Seconds(f64),
+    Indefinite,
+}
+
+impl Parse for Duration {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: ()) -> Result<Duration, AttributeError> {
+        match s.trim() {
+            "indefinite" | "media" => Ok(Duration::Indefinite),
+
+            s => {
+                let seconds = parse_clock_value(s).map_err(AttributeError::Parse)?;
+
+                if seconds > 0.0 {
+                    Ok(Duration::Seconds(seconds))
+                } else {
+                    Err(AttributeError::Value(
+                        "duration must be greater than zero".to_string(),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// The `repeatCount` attribute.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RepeatCount {
+    Count(f64),
+    Indefinite,
+}
+
+impl Parse for RepeatCount {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: ()) -> Result<RepeatCount, AttributeError> {
+        match s.trim() {
+            "indefinite" => Ok(RepeatCount::Indefinite),
+
+            s => match s.parse::<f64>() {
+                Ok(count) if count.is_finite() && count > 0.0 => Ok(RepeatCount::Count(count)),
+
+                _ => Err(AttributeError::Parse(ParseError::new(
+                    "expected a number greater than zero or \"indefinite\"",
+                ))),
+            },
+        }
+    }
+}
+
+/// Whether the last value of an animation stays in effect after it ends.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AnimationFill {
+    Remove,
+    Freeze,
+}
+
+impl Default for AnimationFill {
+    fn default() -> AnimationFill {
+        AnimationFill::Remove
+    }
+}
+
+impl Parse for AnimationFill {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: ()) -> Result<AnimationFill, AttributeError> {
+        match s.trim() {
+            "remove" => Ok(AnimationFill::Remove),
+            "freeze" => Ok(AnimationFill::Freeze),
+            _ => Err(AttributeError::Parse(ParseError::new(
+                "expected \"remove\" or \"freeze\"",
+            ))),
+        }
+    }
+}
+
+// Parses a SMIL clock value, like "02:30", "1.5s", "200ms" or "2", into seconds.
+//
+// https://www.w3.org/TR/SMIL3/smil-timing.html#q22
+fn parse_clock_value(s: &str) -> Result<f64, ParseError> {
+    let s = s.trim();
+
+    let error = || ParseError::new("expected a clock value");
+
+    let seconds = if s.contains(':') {
+        let parts: Vec<&str> = s.split(':').collect();
+
+        if parts.len() > 3 {
+            return Err(error());
+        }
+
+        let mut seconds = 0.0;
+
+        for part in parts {
+            if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit() || c == '.') {
+                return Err(error());
+            }
+
+            seconds = seconds * 60.0 + part.parse::<f64>().map_err(|_| error())?;
+        }
+
+        seconds
+    } else {
+        let (number, scale) = if s.ends_with("ms") {
+            (&s[..s.len() - 2], 0.001)
+        } else if s.ends_with("min") {
+            (&s[..s.len() - 3], 60.0)
+        } else if s.ends_with('h') {
+            (&s[..s.len() - 1], 3600.0)
+        } else if s.ends_with('s') {
+            (&s[..s.len() - 1], 1.0)
+        } else {
+            (s, 1.0)
+        };
+
+        if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            return Err(error());
+        }
+
+        number.parse::<f64>().map_err(|_| error())? * scale
+    };
+
+    Ok(seconds)
+}
+
+// Parses the `begin` attribute into offsets in seconds.  Values that depend
+// on events or on other animations are not supported, and are left out.
+fn parse_begin(s: &str) -> Vec<f64> {
+    s.split(';')
+        .map(str::trim)
+        .filter_map(|value| {
+            if value.starts_with('-') {
+                parse_clock_value(&value[1..]).ok().map(|v| -v)
+            } else if value.starts_with('+') {
+                parse_clock_value(&value[1..]).ok()
+            } else {
+                parse_clock_value(value).ok()
+            }
+        })
+        .collect()
+}
+
+// ************ Animation values ************
+
+/// How an animation goes from one of its values to the next.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CalcMode {
+    Discrete,
+    Linear,
+    Paced,
+    Spline,
+}
+
+impl Parse for CalcMode {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: ()) -> Result<CalcMode, AttributeError> {
+        match s.trim() {
+            "discrete" => Ok(CalcMode::Discrete),
+            "linear" => Ok(CalcMode::Linear),
+            "paced" => Ok(CalcMode::Paced),
+            "spline" => Ok(CalcMode::Spline),
+            _ => Err(AttributeError::Parse(ParseError::new(
+                "expected \"discrete\", \"linear\", \"paced\" or \"spline\"",
+            ))),
+        }
+    }
+}
+
+/// Whether an animation sets a property or an XML attribute.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AttributeType {
+    Auto,
+    Css,
+    Xml,
+}
+
+impl Default for AttributeType {
+    fn default() -> AttributeType {
+        AttributeType::Auto
+    }
+}
+
+impl Parse for AttributeType {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: ()) -> Result<AttributeType, AttributeError> {
+        match s.trim() {
+            "auto" => Ok(AttributeType::Auto),
+            "CSS" => Ok(AttributeType::Css),
+            "XML" => Ok(AttributeType::Xml),
+            _ => Err(AttributeError::Parse(ParseError::new(
+                "expected \"auto\", \"CSS\" or \"XML\"",
+            ))),
+        }
+    }
+}
+
+/// The `type` attribute of `<animateTransform>`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TransformType {
+    Translate,
+    Scale,
+    Rotate,
+    SkewX,
+    SkewY,
+}
+
+impl Default for TransformType {
+    fn default() -> TransformType {
+        TransformType::Translate
+    }
+}
+
+impl Parse for TransformType {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: ()) -> Result<TransformType, AttributeError> {
+        match s.trim() {
+            "translate" => Ok(TransformType::Translate),
+            "scale" => Ok(TransformType::Scale),
+            "rotate" => Ok(TransformType::Rotate),
+            "skewX" => Ok(TransformType::SkewX),
+            "skewY" => Ok(TransformType::SkewY),
+            _ => Err(AttributeError::Parse(ParseError::new(
+                "expected \"translate\", \"scale\", \"rotate\", \"skewX\" or \"skewY\"",
+            ))),
+        }
+    }
+}
+
+impl TransformType {
+    fn name(&self) -> &'static str {
+        match *self {
+            TransformType::Translate => "translate",
+            TransformType::Scale => "scale",
+            TransformType::Rotate => "rotate",
+            TransformType::SkewX => "skewX",
+            TransformType::SkewY => "skewY",
+        }
+    }
+
+    // Parameters for the identity transformation
+    fn identity(&self) -> &'static str {
+        match *self {
+            TransformType::Scale => "1",
+            _ => "0",
+        }
+    }
+}
+
+/// The `rotate` attribute of `<animateMotion>`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MotionRotate {
+    Angle(f64),
+    Auto,
+    AutoReverse,
+}
+
+impl Default for MotionRotate {
+    fn default() -> MotionRotate {
+        MotionRotate::Angle(0.0)
+    }
+}
+
+impl Parse for MotionRotate {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: ()) -> Result<MotionRotate, AttributeError> {
+        match s.trim() {
+            "auto" => Ok(MotionRotate::Auto),
+            "auto-reverse" => Ok(MotionRotate::AutoReverse),
+            s => ::parsers::angle_degrees(s)
+                .map(MotionRotate::Angle)
+                .map_err(AttributeError::Parse),
+        }
+    }
+}
+
+// Parses a semicolon-separated list, ignoring a trailing semicolon.
+fn semicolon_list(s: &str) -> Vec<String> {
+    let mut list: Vec<String> = s.split(';').map(|v| v.trim().to_string()).collect();
+
+    if list.len() > 1 && list.last().map_or(false, String::is_empty) {
+        list.pop();
+    }
+
+    list
+}
+
+fn parse_key_times(s: &str) -> Result<Vec<f64>, NodeError> {
+    let mut times = Vec::new();
+
+    for value in semicolon_list(s) {
+        let t = ::parsers::number(&value).map_err(|e| NodeError::parse_error("keyTimes", e))?;
+
+        if t < 0.0 || t > 1.0 || times.last().map_or(false, |&last| t < last) {
+            return Err(NodeError::value_error(
+                "keyTimes",
+                "must be increasing values between 0 and 1",
+            ));
+        }
+
+        times.push(t);
+    }
+
+    if times.first() != Some(&0.0) {
+        return Err(NodeError::value_error("keyTimes", "must start with 0"));
+    }
+
+    Ok(times)
+}
+
+fn parse_key_splines(s: &str) -> Result<Vec<(f64, f64, f64, f64)>, NodeError> {
+    let mut splines = Vec::new();
+
+    for value in semicolon_list(s) {
+        let n = ::parsers::number_list(&value, ::parsers::ListLength::Exact(4))
+            .map_err(|_| NodeError::value_error("keySplines", "expected four numbers"))?;
+
+        if n.iter().any(|&v| v < 0.0 || v > 1.0) {
+            return Err(NodeError::value_error(
+                "keySplines",
+                "control points must be between 0 and 1",
+            ));
+        }
+
+        splines.push((n[0], n[1], n[2], n[3]));
+    }
+
+    Ok(splines)
+}
+
+// ************ NodeAnimation ************
+
+/// Which animation element a `NodeAnimation` is.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AnimationKind {
+    Animate,
+    AnimateMotion,
+    AnimateTransform,
+    Set,
+}
+
+/// The attributes of an animation element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animation {
+    pub kind: AnimationKind,
+
+    pub attribute_name: Option<String>,
+    pub attribute_type: AttributeType,
+
+    /// The `xlink:href` to the target element; `None` to animate the parent.
+    pub href: Option<String>,
+
+    /// Begin times in seconds.
+    pub begin: Vec<f64>,
+    pub dur: Duration,
+    pub repeat_count: Option<RepeatCount>,
+    pub repeat_dur: Option<Duration>,
+    pub fill: AnimationFill,
+
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub by: Option<String>,
+    pub values: Option<Vec<String>>,
+    pub calc_mode: CalcMode,
+    pub key_times: Option<Vec<f64>>,
+    pub key_splines: Option<Vec<(f64, f64, f64, f64)>>,
+    pub additive: bool,
+
+    /// For `<animateTransform>`
+    pub transform_type: TransformType,
+
+    /// For `<animateMotion>`
+    pub path: Option<String>,
+    pub rotate: MotionRotate,
+}
+
+impl Animation {
+    fn new(kind: AnimationKind) -> Animation {
+        Animation {
+            kind,
+            attribute_name: None,
+            attribute_type: Default::default(),
+            href: None,
+            begin: vec![0.0],
+            dur: Duration::Indefinite,
+            repeat_count: None,
+            repeat_dur: None,
+            fill: Default::default(),
+            from: None,
+            to: None,
+            by: None,
+            values: None,
+            calc_mode: match kind {
+                AnimationKind::Set => CalcMode::Discrete,
+                AnimationKind::AnimateMotion => CalcMode::Paced,
+                _ => CalcMode::Linear,
+            },
+            key_times: None,
+            key_splines: None,
+            additive: false,
+            transform_type: Default::default(),
+            path: None,
+            rotate: Default::default(),
+        }
+    }
+
+    /// The name of the attribute or property that the animation changes.
+    pub fn get_target_attribute(&self) -> Option<&str> {
+        match self.kind {
+            AnimationKind::AnimateMotion => Some("transform"),
+
+            AnimationKind::AnimateTransform => Some(
+                self.attribute_name
+                    .as_ref()
+                    .map_or("transform", String::as_str),
+            ),
+
+            _ => self.attribute_name.as_ref().map(String::as_str),
+        }
+    }
+
+    // The length of time during which the animation is in effect, not
+    // counting the time that it stays frozen after it ends.
+    fn active_duration(&self) -> Duration {
+        let repeated = match (self.dur, self.repeat_count) {
+            (Duration::Seconds(d), Some(RepeatCount::Count(count))) => Duration::Seconds(d * count),
+            (_, Some(RepeatCount::Indefinite)) => Duration::Indefinite,
+            (dur, None) if self.repeat_dur.is_none() => dur,
+            (_, None) => Duration::Indefinite,
+            (Duration::Indefinite, _) => Duration::Indefinite,
+        };
+
+        match (repeated, self.repeat_dur) {
+            (Duration::Seconds(a), Some(Duration::Seconds(b))) => Duration::Seconds(a.min(b)),
+            (Duration::Indefinite, Some(repeat_dur)) => repeat_dur,
+            (repeated, _) => repeated,
+        }
+    }
+
+    /// Returns the times up to `time` at which the animation begins or its
+    /// active duration ends, which are when it can change the value of its
+    /// target in a jump.
+    pub fn get_change_times(&self, time: f64) -> Vec<f64> {
+        let mut times = Vec::new();
+
+        for &begin in self.begin.iter().filter(|&&b| b <= time) {
+            times.push(begin);
+
+            if let Duration::Seconds(active) = self.active_duration() {
+                if begin + active <= time {
+                    times.push(begin + active);
+                }
+            }
+        }
+
+        times
+    }
+
+    /// Returns how far into its simple duration the animation is at `time`,
+    /// from 0 to 1, or `None` if the animation has no effect at that time.
+    ///
+    /// The latest begin time before `time` is used, as if the animation
+    /// restarted at each of its begin times.
+    pub fn get_progress(&self, time: f64) -> Option<f64> {
+        let begin = self
+            .begin
+            .iter()
+            .filter(|&&b| b <= time)
+            .fold(None, |latest: Option<f64>, &b| {
+                Some(latest.map_or(b, |l| l.max(b)))
+            })?;
+
+        let local = time - begin;
+
+        if let Duration::Seconds(active) = self.active_duration() {
+            if local >= active {
+                return match self.fill {
+                    AnimationFill::Remove => None,
+
+                    AnimationFill::Freeze => match self.dur {
+                        Duration::Seconds(d) => {
+                            let end = (active / d).fract();
+                            Some(if end == 0.0 { 1.0 } else { end })
+                        }
+
+                        Duration::Indefinite => Some(0.0),
+                    },
+                };
+            }
+        }
+
+        match self.dur {
+            Duration::Seconds(d) => Some((local % d) / d),
+            Duration::Indefinite => Some(0.0),
+        }
+    }
+
+    /// Returns the value of the animation at `progress`, given the value
+    /// that the target attribute has without this animation.  For additive
+    /// animations, the returned value already includes `underlying`.
+    pub fn get_value(&self, progress: f64, underlying: &str) -> Option<String> {
+        match self.kind {
+            AnimationKind::Set => self.to.clone(),
+
+            AnimationKind::AnimateTransform => {
+                let identity = self.transform_type.identity();
+                let (keyframes, additive) = self.get_keyframes(identity)?;
+                let params = self.interpolate(&keyframes, progress);
+                let transform = format!("{}({})", self.transform_type.name(), params);
+
+                if additive && !underlying.trim().is_empty() {
+                    Some(format!("{} {}", underlying, transform))
+                } else {
+                    Some(transform)
+                }
+            }
+
+            AnimationKind::Animate => {
+                let (keyframes, additive) = self.get_keyframes(underlying)?;
+                let value = self.interpolate(&keyframes, progress);
+
+                if additive {
+                    Some(add_values(underlying, &value))
+                } else {
+                    Some(value)
+                }
+            }
+
+            AnimationKind::AnimateMotion => None,
+        }
+    }
+
+    /// Returns the transformation that an `<animateMotion>` adds at
+    /// `progress` along `path`, which is the path data from the `path`
+    /// attribute or from an `<mpath>`.  `path_length` is the `pathLength` of
+    /// the `<path>` that an `<mpath>` refers to.  Without a path, the motion
+    /// goes through the points of its `values`, `from`, `to` and `by`
+    /// attributes.
+    pub fn get_motion(
+        &self,
+        progress: f64,
+        path: Option<&str>,
+        path_length: Option<f64>,
+    ) -> Option<String> {
+        let (x, y, angle) = if let Some(path) = path {
+            let mut builder = PathBuilder::new();
+            let _ = path_parser::parse_path_into_builder(path, &mut builder);
+
+            // Distances along the path are in the units of its pathLength
+            let measure = PathMeasure::new(&builder, path_length);
+            let length = path_length.unwrap_or_else(|| measure.get_total_length());
+            let distance = length * progress.max(0.0).min(1.0);
+            let (x, y) = measure.get_point_at_length(distance)?;
+
+            (x, y, measure.get_angle_at_length(distance)? * 180.0 / PI)
+        } else {
+            let (keyframes, _) = self.get_keyframes("0,0")?;
+            let point_at = |p: f64| -> Option<(f64, f64)> {
+                let numbers = numbers_in(&self.interpolate(&keyframes, p));
+
+                if numbers.len() == 2 {
+                    Some((numbers[0], numbers[1]))
+                } else {
+                    None
+                }
+            };
+
+            let (x, y) = point_at(progress)?;
+
+            // Direction of the motion, for rotate="auto"
+            let (before, after) = if progress < 0.999 {
+                ((x, y), point_at(progress + 0.001)?)
+            } else {
+                (point_at(progress - 0.001)?, (x, y))
+            };
+
+            (
+                x,
+                y,
+                (after.1 - before.1).atan2(after.0 - before.0) * 180.0 / PI,
+            )
+        };
+
+        let rotate = match self.rotate {
+            MotionRotate::Angle(a) => a,
+            MotionRotate::Auto => angle,
+            MotionRotate::AutoReverse => angle + 180.0,
+        };
+
+        if rotate == 0.0 {
+            Some(format!("translate({} {})", x, y))
+        } else {
+            Some(format!("translate({} {}) rotate({})", x, y, rotate))
+        }
+    }
+
+    // Returns the list of values for the animation, and whether the value
+    // is to be added to the underlying one.
+    fn get_keyframes(&self, underlying: &str) -> Option<(Vec<String>, bool)> {
+        if let Some(ref values) = self.values {
+            if values.is_empty() {
+                return None;
+            }
+
+            return Some((values.clone(), self.additive));
+        }
+
+        match (&self.from, &self.to, &self.by) {
+            (&Some(ref from), &Some(ref to), _) => {
+                Some((vec![from.clone(), to.clone()], self.additive))
+            }
+
+            (&Some(ref from), &None, &Some(ref by)) => {
+                Some((vec![from.clone(), add_values(from, by)], self.additive))
+            }
+
+            (&None, &None, &Some(ref by)) => Some((vec![zero_value(by), by.clone()], true)),
+
+            (&None, &Some(ref to), _) => Some((vec![underlying.to_string(), to.clone()], false)),
+
+            _ => None,
+        }
+    }
+
+    // Checks that keyTimes and keySplines match the keyframes.
+    fn check_key_times(&self) -> Result<(), NodeError> {
+        // from/to/by animations have two keyframes
+        let keyframe_count = match self.values {
+            Some(ref values) => Some(values.len()),
+
+            None if self.from.is_some() || self.to.is_some() || self.by.is_some() => Some(2),
+
+            None => None,
+        };
+
+        if let (&Some(ref times), Some(count)) = (&self.key_times, keyframe_count) {
+            if times.len() != count {
+                return Err(NodeError::value_error(
+                    "keyTimes",
+                    "must have as many entries as values",
+                ));
+            }
+
+            let ends_at_one = times.last() == Some(&1.0);
+
+            if !ends_at_one
+                && (self.calc_mode == CalcMode::Linear || self.calc_mode == CalcMode::Spline)
+            {
+                return Err(NodeError::value_error("keyTimes", "must end with 1"));
+            }
+        }
+
+        if self.calc_mode == CalcMode::Spline {
+            let intervals = self.values.as_ref().map_or(1, |v| v.len().max(1) - 1);
+
+            if self.key_splines.as_ref().map(Vec::len) != Some(intervals) {
+                return Err(NodeError::value_error(
+                    "keySplines",
+                    "must have one entry for each interval between values",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn interpolate(&self, keyframes: &[String], progress: f64) -> String {
+        let n = keyframes.len();
+
+        if n == 1 {
+            return keyframes[0].clone();
+        }
+
+        if self.calc_mode == CalcMode::Discrete {
+            let index = match self.key_times {
+                Some(ref times) => times.iter().rposition(|&t| t <= progress).unwrap_or(0),
+                None => (progress * n as f64) as usize,
+            };
+
+            return keyframes[index.min(n - 1)].clone();
+        }
+
+        // check_key_times() rejects keyTimes that don't match the keyframes,
+        // but don't index out of bounds if some get here anyway
+        let times = match (self.calc_mode, &self.key_times) {
+            (CalcMode::Paced, _) => paced_key_times(keyframes),
+            (_, &Some(ref times)) if times.len() == n => times.clone(),
+            _ => (0..n).map(|i| i as f64 / (n - 1) as f64).collect(),
+        };
+
+        let i = (0..n - 1).rposition(|i| times[i] <= progress).unwrap_or(0);
+
+        let span = times[i + 1] - times[i];
+        if span <= 0.0 {
+            return keyframes[i + 1].clone();
+        }
+
+        let mut t = ((progress - times[i]) / span).min(1.0);
+
+        if self.calc_mode == CalcMode::Spline {
+            if let Some(&(x1, y1, x2, y2)) = self.key_splines.as_ref().and_then(|s| s.get(i)) {
+                t = cubic_bezier_at(t, x1, y1, x2, y2);
+            }
+        }
+
+        interpolate_values(&keyframes[i], &keyframes[i + 1], t)
+    }
+}
+
+pub struct NodeAnimation {
+    animation: RefCell<Animation>,
+}
+
+impl NodeAnimation {
+    fn new(kind: AnimationKind) -> NodeAnimation {
+        NodeAnimation {
+            animation: RefCell::new(Animation::new(kind)),
+        }
+    }
+
+    pub fn get_animation(&self) -> Animation {
+        self.animation.borrow().clone()
+    }
+}
+
+impl NodeTrait for NodeAnimation {
+    fn set_atts(&self, _: &RsvgNode, _: *const RsvgHandle, pbag: &PropertyBag) -> NodeResult {
+        let mut animation = Animation::new(self.animation.borrow().kind);
+
+        for (_key, attr, value) in pbag.iter() {
+            match attr {
+                Attribute::AttributeName => {
+                    animation.attribute_name = Some(value.trim().to_string())
+                }
+                Attribute::AttributeType => {
+                    animation.attribute_type = parse("attributeType", value, (), None)?
+                }
+                Attribute::XlinkHref => animation.href = Some(value.to_string()),
+
+                Attribute::Begin => animation.begin = parse_begin(value),
+                Attribute::Dur => animation.dur = parse("dur", value, (), None)?,
+                Attribute::RepeatCount => {
+                    animation.repeat_count = Some(parse("repeatCount", value, (), None)?)
+                }
+                Attribute::RepeatDur => {
+                    animation.repeat_dur = Some(parse("repeatDur", value, (), None)?)
+                }
+                Attribute::Fill => animation.fill = parse("fill", value, (), None)?,
+
+                Attribute::From => animation.from = Some(value.trim().to_string()),
+                Attribute::To => animation.to = Some(value.trim().to_string()),
+                Attribute::By => animation.by = Some(value.trim().to_string()),
+                Attribute::Values => animation.values = Some(semicolon_list(value)),
+                Attribute::CalcMode => animation.calc_mode = parse("calcMode", value, (), None)?,
+                Attribute::KeyTimes => animation.key_times = Some(parse_key_times(value)?),
+                Attribute::KeySplines => animation.key_splines = Some(parse_key_splines(value)?),
+                Attribute::Additive => animation.additive = value.trim() == "sum",
+
+                Attribute::Type => animation.transform_type = parse("type", value, (), None)?,
+                Attribute::Path => animation.path = Some(value.to_string()),
+                Attribute::Rotate => animation.rotate = parse("rotate", value, (), None)?,
+
+                _ => (),
+            }
+        }
+
+        animation.check_key_times()?;
+
+        *self.animation.borrow_mut() = animation;
+
+        Ok(())
+    }
+
+    fn draw(&self, _: &RsvgNode, _: *mut RsvgDrawingCtx, _: i32, _: bool) {
+        // nothing; animations are applied to the tree before it is drawn
+    }
+
+    fn get_c_impl(&self) -> *const RsvgCNodeImpl {
+        unreachable!();
+    }
+}
+
+// ************ Interpolation ************
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token<'a> {
+    Text(&'a str),
+    Number(f64),
+}
+
+// Splits a value into numbers and the text between them, so that values
+// like "10px", "0 0 100 50" or "translate(10, 20)" can be interpolated.
+// Digits that are part of a name, like in "#a1" or "h1", are kept as text.
+fn tokenize(s: &str) -> Vec<Token> {
+    let bytes = s.as_bytes();
+    let mut tokens = Vec::new();
+    let mut text_start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let starts_number = {
+            let c = bytes[i];
+            let next_is_digit = |j: usize| j < bytes.len() && bytes[j].is_ascii_digit();
+            let in_name = i > 0 && {
+                let p = bytes[i - 1];
+                p.is_ascii_alphanumeric() || p == b'_' || p == b'#'
+            };
+
+            !in_name
+                && (c.is_ascii_digit()
+                    || (c == b'.' && next_is_digit(i + 1))
+                    || ((c == b'-' || c == b'+')
+                        && (next_is_digit(i + 1)
+                            || (i + 1 < bytes.len()
+                                && bytes[i + 1] == b'.'
+                                && next_is_digit(i + 2)))))
+        };
+
+        if !starts_number {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        i += 1;
+
+        while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+            i += 1;
+        }
+
+        if i < bytes.len() && (bytes[i] == b'e' || bytes[i] == b'E') {
+            let mut j = i + 1;
+
+            if j < bytes.len() && (bytes[j] == b'-' || bytes[j] == b'+') {
+                j += 1;
+            }
+
+            if j < bytes.len() && bytes[j].is_ascii_digit() {
+                i = j;
+
+                while i < bytes.len() && bytes[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+        }
+
+        match s[start..i].parse::<f64>() {
+            Ok(n) => {
+                if text_start < start {
+                    tokens.push(Token::Text(&s[text_start..start]));
+                }
+
+                tokens.push(Token::Number(n));
+                text_start = i;
+            }
+
+            Err(_) => (),
+        }
+    }
+
+    if text_start < s.len() {
+        tokens.push(Token::Text(&s[text_start..]));
+    }
+
+    tokens
+}
+
+fn numbers_in(s: &str) -> Vec<f64> {
+    tokenize(s)
+        .into_iter()
+        .filter_map(|t| match t {
+            Token::Number(n) => Some(n),
+            Token::Text(_) => None,
+        })
+        .collect()
+}
+
+// Whether two lists of tokens only differ in their numbers.  Separators are
+// compared without white space and commas, so "10 20" matches "30,40".
+fn same_shape(a: &[Token], b: &[Token]) -> bool {
+    let separators = |s: &str| -> String {
+        s.chars()
+            .filter(|&c| !c.is_whitespace() && c != ',')
+            .collect()
+    };
+
+    a.len() == b.len()
+        && a.iter().zip(b.iter()).all(|pair| match pair {
+            (&Token::Number(_), &Token::Number(_)) => true,
+            (&Token::Text(x), &Token::Text(y)) => separators(x) == separators(y),
+            _ => false,
+        })
+}
+
+fn combine_numbers<F>(a: &str, b: &str, f: F) -> Option<String>
+where
+    F: Fn(f64, f64) -> f64,
+{
+    let ta = tokenize(a);
+    let tb = tokenize(b);
+
+    if !same_shape(&ta, &tb) {
+        return None;
+    }
+
+    let mut out = String::new();
+
+    for pair in ta.iter().zip(tb.iter()) {
+        match pair {
+            (&Token::Number(x), &Token::Number(y)) => out.push_str(&f(x, y).to_string()),
+            (&Token::Text(x), _) => out.push_str(x),
+            _ => unreachable!(),
+        }
+    }
+
+    Some(out)
+}
+
+fn parse_rgba(s: &str) -> Option<cssparser::RGBA> {
+    match Color::parse(s.trim(), (AllowInherit::No, AllowCurrentColor::No)) {
+        Ok(Color::RGBA(rgba)) => Some(rgba),
+        _ => None,
+    }
+}
+
+fn combine_colors<F>(a: &str, b: &str, f: F) -> Option<String>
+where
+    F: Fn(f64, f64) -> f64,
+{
+    let a = parse_rgba(a)?;
+    let b = parse_rgba(b)?;
+
+    let channel = |x: u8, y: u8| f(f64::from(x), f64::from(y)).round().max(0.0).min(255.0) as u8;
+
+    Some(dom::color_to_string(&Color::RGBA(cssparser::RGBA::new(
+        channel(a.red, b.red),
+        channel(a.green, b.green),
+        channel(a.blue, b.blue),
+        channel(a.alpha, b.alpha),
+    ))))
+}
+
+/// Interpolates between two attribute values, at `t` from 0 to 1.  Colors
+/// and values that only differ in their numbers are interpolated; other
+/// values switch from `a` to `b` halfway.
+pub fn interpolate_values(a: &str, b: &str, t: f64) -> String {
+    let lerp = |x: f64, y: f64| x + (y - x) * t;
+
+    combine_colors(a, b, &lerp)
+        .or_else(|| combine_numbers(a, b, &lerp))
+        .unwrap_or_else(|| if t < 0.5 { a } else { b }.to_string())
+}
+
+// Adds `b` to `a`, for additive animations and for "by" values.  Values
+// that can't be added replace `a`.
+fn add_values(a: &str, b: &str) -> String {
+    let sum = |x: f64, y: f64| x + y;
+
+    combine_colors(a, b, &sum)
+        .or_else(|| combine_numbers(a, b, &sum))
+        .unwrap_or_else(|| b.to_string())
+}
+
+// The zero of the type of `value`, which is where a "by" animation starts.
+fn zero_value(value: &str) -> String {
+    if parse_rgba(value).is_some() {
+        return "rgb(0, 0, 0)".to_string();
+    }
+
+    combine_numbers(value, value, |_, _| 0.0).unwrap_or_else(|| value.to_string())
+}
+
+fn distance(a: &str, b: &str) -> Option<f64> {
+    let (va, vb) = match (parse_rgba(a), parse_rgba(b)) {
+        (Some(x), Some(y)) => (
+            vec![f64::from(x.red), f64::from(x.green), f64::from(x.blue)],
+            vec![f64::from(y.red), f64::from(y.green), f64::from(y.blue)],
+        ),
+
+        _ => {
+            if !same_shape(&tokenize(a), &tokenize(b)) {
+                return None;
+            }
+
+            (numbers_in(a), numbers_in(b))
+        }
+    };
+
+    Some(
+        va.iter()
+            .zip(vb.iter())
+            .map(|(x, y)| (x - y) * (x - y))
+            .sum::<f64>()
+            .sqrt(),
+    )
+}
+
+// Key times for calcMode="paced", so that the value changes at a constant
+// speed.  Falls back to evenly spaced times for values without a distance.
+fn paced_key_times(keyframes: &[String]) -> Vec<f64> {
+    let n = keyframes.len();
+    let even = || (0..n).map(|i| i as f64 / (n - 1) as f64).collect();
+
+    let mut lengths = Vec::new();
+
+    for pair in keyframes.windows(2) {
+        match distance(&pair[0], &pair[1]) {
+            Some(d) => lengths.push(d),
+            None => return even(),
+        }
+    }
+
+    let total: f64 = lengths.iter().sum();
+
+    if total <= 0.0 {
+        return even();
+    }
+
+    let mut times = vec![0.0];
+    let mut acc = 0.0;
+
+    for d in lengths {
+        acc += d;
+        times.push(acc / total);
+    }
+
+    times
+}
+
+/// Evaluates a timing function given by a keySplines entry or by CSS
+/// `cubic-bezier()`: finds the point of the cubic Bézier from (0, 0) to
+/// (1, 1) whose x is `t`, and returns its y.
+pub fn cubic_bezier_at(t: f64, x1: f64, y1: f64, x2: f64, y2: f64) -> f64 {
+    let bezier = |s: f64, p1: f64, p2: f64| {
+        let u = 1.0 - s;
+        3.0 * u * u * s * p1 + 3.0 * u * s * s * p2 + s * s * s
+    };
+
+    // x is monotonic in s, since the control points are between 0 and 1
+    let mut low = 0.0;
+    let mut high = 1.0;
+
+    for _ in 0..32 {
+        let mid = (low + high) / 2.0;
+
+        if bezier(mid, x1, x2) < t {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    bezier((low + high) / 2.0, y1, y2)
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_node_animation_new(
+    element_name: *const libc::c_char,
+    raw_parent: *const RsvgNode,
+) -> *const RsvgNode {
+    let name: String = unsafe { from_glib_none(element_name) };
+
+    let kind = match name.as_str() {
+        "animateMotion" => AnimationKind::AnimateMotion,
+        "animateTransform" => AnimationKind::AnimateTransform,
+        "set" => AnimationKind::Set,
+        _ => AnimationKind::Animate,
+    };
+
+    boxed_node_new(
+        NodeType::Animation,
+        raw_parent,
+        Box::new(NodeAnimation::new(kind)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_clock_values() {
+        assert_eq!(parse_clock_value("02:30:03"), Ok(9003.0));
+        assert_eq!(parse_clock_value("02:33"), Ok(153.0));
+        assert_eq!(parse_clock_value("00:10.25"), Ok(10.25));
+        assert_eq!(parse_clock_value("3.2h"), Ok(11520.0));
+        assert_eq!(parse_clock_value("45min"), Ok(2700.0));
+        assert_eq!(parse_clock_value("30s"), Ok(30.0));
+        assert_eq!(parse_clock_value("5ms"), Ok(0.005));
+        assert_eq!(parse_clock_value("12.467"), Ok(12.467));
+
+        assert!(parse_clock_value("").is_err());
+        assert!(parse_clock_value("s").is_err());
+        assert!(parse_clock_value("-1s").is_err());
+        assert!(parse_clock_value("1:2:3:4").is_err());
+        assert!(parse_clock_value("click").is_err());
+    }
+
+    #[test]
+    fn parses_begin_offsets() {
+        assert_eq!(parse_begin("1s; -0.5s;+2s"), vec![1.0, -0.5, 2.0]);
+        assert_eq!(parse_begin("click; 3s"), vec![3.0]);
+        assert_eq!(parse_begin("indefinite"), Vec::<f64>::new());
+    }
+
+    #[test]
+    fn parses_key_times() {
+        assert_eq!(parse_key_times("0; 0.25; 1;"), Ok(vec![0.0, 0.25, 1.0]));
+        assert!(parse_key_times("0.5; 1").is_err());
+        assert!(parse_key_times("0; 0.8; 0.4").is_err());
+        assert!(parse_key_times("0; 2").is_err());
+    }
+
+    #[test]
+    fn tokenizes_values() {
+        assert_eq!(
+            tokenize("translate(10, -2.5e1)"),
+            vec![
+                Token::Text("translate("),
+                Token::Number(10.0),
+                Token::Text(", "),
+                Token::Number(-25.0),
+                Token::Text(")"),
+            ]
+        );
+
+        assert_eq!(
+            tokenize("10px"),
+            vec![Token::Number(10.0), Token::Text("px")]
+        );
+
+        assert_eq!(tokenize("url(#g1)"), vec![Token::Text("url(#g1)")]);
+    }
+
+    #[test]
+    fn interpolates_values() {
+        assert_eq!(interpolate_values("0", "10", 0.25), "2.5");
+        assert_eq!(interpolate_values("10px", "20px", 0.5), "15px");
+        assert_eq!(
+            interpolate_values("0 0 10 10", "10,10,20,20", 0.5),
+            "5 5 15 15"
+        );
+        assert_eq!(
+            interpolate_values("#ff0000", "rgb(0, 0, 255)", 0.5),
+            "rgb(128, 0, 128)"
+        );
+        assert_eq!(interpolate_values("visible", "hidden", 0.25), "visible");
+        assert_eq!(interpolate_values("visible", "hidden", 0.75), "hidden");
+        assert_eq!(interpolate_values("10px", "20em", 0.75), "20em");
+    }
+
+    #[test]
+    fn adds_values() {
+        assert_eq!(add_values("10", "5"), "15");
+        assert_eq!(add_values("1 2", "3 4"), "4 6");
+        assert_eq!(add_values("red", "none"), "none");
+        assert_eq!(zero_value("10 20"), "0 0");
+    }
+
+    fn animation(kind: AnimationKind) -> Animation {
+        let mut animation = Animation::new(kind);
+        animation.dur = Duration::Seconds(2.0);
+        animation
+    }
+
+    #[test]
+    fn computes_progress() {
+        let mut a = animation(AnimationKind::Animate);
+        a.begin = vec![1.0];
+
+        assert_eq!(a.get_progress(0.5), None);
+        assert_eq!(a.get_progress(1.0), Some(0.0));
+        assert_eq!(a.get_progress(2.0), Some(0.5));
+        assert_eq!(a.get_progress(3.0), None);
+
+        a.fill = AnimationFill::Freeze;
+        assert_eq!(a.get_progress(10.0), Some(1.0));
+
+        a.repeat_count = Some(RepeatCount::Count(2.5));
+        assert_eq!(a.get_progress(4.0), Some(0.5));
+        assert_eq!(a.get_progress(10.0), Some(0.5));
+
+        a.repeat_count = Some(RepeatCount::Indefinite);
+        assert_eq!(a.get_progress(100.5), Some(0.75));
+    }
+
+    #[test]
+    fn finds_change_times() {
+        let mut a = animation(AnimationKind::Set);
+        a.begin = vec![1.0, 5.0];
+
+        assert_eq!(a.get_change_times(0.5), Vec::<f64>::new());
+        assert_eq!(a.get_change_times(2.0), vec![1.0]);
+        assert_eq!(a.get_change_times(10.0), vec![1.0, 3.0, 5.0, 7.0]);
+
+        a.repeat_count = Some(RepeatCount::Indefinite);
+        assert_eq!(a.get_change_times(10.0), vec![1.0, 5.0]);
+    }
+
+    #[test]
+    fn interpolates_from_to_by_and_values() {
+        let mut a = animation(AnimationKind::Animate);
+        a.from = Some("10".to_string());
+        a.to = Some("20".to_string());
+        assert_eq!(a.get_value(0.5, "0"), Some("15".to_string()));
+
+        a.to = None;
+        a.by = Some("5".to_string());
+        assert_eq!(a.get_value(1.0, "0"), Some("15".to_string()));
+
+        a.from = None;
+        assert_eq!(a.get_value(0.5, "100"), Some("102.5".to_string()));
+
+        a.by = None;
+        a.to = Some("200".to_string());
+        assert_eq!(a.get_value(0.5, "100"), Some("150".to_string()));
+
+        a.values = Some(vec!["0".to_string(), "10".to_string(), "30".to_string()]);
+        assert_eq!(a.get_value(0.75, "100"), Some("20".to_string()));
+
+        a.key_times = Some(vec![0.0, 0.75, 1.0]);
+        assert_eq!(a.get_value(0.75, "100"), Some("10".to_string()));
+
+        a.calc_mode = CalcMode::Discrete;
+        assert_eq!(a.get_value(0.7, "100"), Some("0".to_string()));
+        assert_eq!(a.get_value(0.8, "100"), Some("10".to_string()));
+
+        a.key_times = None;
+        assert_eq!(a.get_value(0.7, "100"), Some("30".to_string()));
+
+        a.calc_mode = CalcMode::Paced;
+        assert_eq!(a.get_value(0.5, "100"), Some("15".to_string()));
+    }
+
+    #[test]
+    fn checks_key_times_of_from_to_animations() {
+        let mut a = animation(AnimationKind::Animate);
+        a.from = Some("0".to_string());
+        a.to = Some("10".to_string());
+
+        a.key_times = Some(vec![0.0]);
+        assert!(a.check_key_times().is_err());
+
+        a.key_times = Some(vec![0.0, 0.5]);
+        assert!(a.check_key_times().is_err());
+
+        a.key_times = Some(vec![0.0, 1.0]);
+        assert!(a.check_key_times().is_ok());
+
+        a.calc_mode = CalcMode::Discrete;
+        a.key_times = Some(vec![0.0, 0.5, 1.0]);
+        assert!(a.check_key_times().is_err());
+
+        a.key_times = Some(vec![0.0, 0.5]);
+        assert!(a.check_key_times().is_ok());
+    }
+
+    #[test]
+    fn interpolates_with_mismatched_key_times() {
+        let mut a = animation(AnimationKind::Animate);
+        a.from = Some("0".to_string());
+        a.to = Some("10".to_string());
+
+        a.key_times = Some(vec![0.0]);
+        assert_eq!(a.get_value(0.5, "0"), Some("5".to_string()));
+
+        a.calc_mode = CalcMode::Discrete;
+        a.key_times = Some(vec![0.0, 0.5, 1.0]);
+        assert_eq!(a.get_value(1.0, "0"), Some("10".to_string()));
+    }
+
+    #[test]
+    fn interpolates_with_key_splines() {
+        assert!((cubic_bezier_at(0.5, 0.0, 0.0, 1.0, 1.0) - 0.5).abs() < 1e-6);
+        assert!(cubic_bezier_at(0.25, 0.42, 0.0, 1.0, 1.0) < 0.25);
+    }
+
+    #[test]
+    fn animates_transforms() {
+        let mut a = animation(AnimationKind::AnimateTransform);
+        a.transform_type = TransformType::Rotate;
+        a.from = Some("0 50 50".to_string());
+        a.to = Some("360 50 50".to_string());
+
+        assert_eq!(
+            a.get_value(0.25, "scale(2)"),
+            Some("rotate(90 50 50)".to_string())
+        );
+
+        a.additive = true;
+        assert_eq!(
+            a.get_value(0.25, "scale(2)"),
+            Some("scale(2) rotate(90 50 50)".to_string())
+        );
+    }
+
+    #[test]
+    fn moves_along_paths() {
+        let mut a = animation(AnimationKind::AnimateMotion);
+
+        assert_eq!(
+            a.get_motion(0.75, Some("M 0 0 L 100 0 L 100 100"), None),
+            Some("translate(100 50)".to_string())
+        );
+
+        assert_eq!(
+            a.get_motion(0.75, Some("M 0 0 L 100 0 L 100 100"), Some(1.0)),
+            Some("translate(100 50)".to_string())
+        );
+
+        a.rotate = MotionRotate::Auto;
+        assert_eq!(
+            a.get_motion(0.75, Some("M 0 0 L 100 0 L 100 100"), None),
+            Some("translate(100 50) rotate(90)".to_string())
+        );
+
+        a.rotate = MotionRotate::Angle(0.0);
+        a.values = Some(vec!["0,0".to_string(), "10,20".to_string()]);
+        assert_eq!(
+            a.get_motion(0.5, None, None),
+            Some("translate(5 10)".to_string())
+        );
+    }
+}
@@ -0,0 +1,1483 @@
+use glib_sys;
+use libc;
+
+use std::f64::consts::PI;
+use std::ptr;
+use std::slice;
+
+use animation::{cubic_bezier_at, interpolate_values};
+use error::AttributeError;
+use handle::RsvgHandle;
+use parsers::{Parse, ParseError};
+use state::{self, RsvgState};
+
+extern "C" {
+    fn rsvg_handle_get_keyframes(handle: *const RsvgHandle) -> *const KeyframesSet;
+}
+
+// ************ Animation properties ************
+
+/// A timing function from `animation-timing-function`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TimingFunction {
+    CubicBezier(f64, f64, f64, f64),
+    Steps(u32, StepPosition),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StepPosition {
+    Start,
+    End,
+}
+
+impl Default for TimingFunction {
+    fn default() -> TimingFunction {
+        // ease
+        TimingFunction::CubicBezier(0.25, 0.1, 0.25, 1.0)
+    }
+}
+
+impl TimingFunction {
+    /// Maps the progress `t` through a keyframe interval, from 0 to 1, to
+    /// the fraction of the change in value at that point.
+    pub fn evaluate(&self, t: f64) -> f64 {
+        let t = t.max(0.0).min(1.0);
+
+        match *self {
+            TimingFunction::CubicBezier(x1, y1, x2, y2) => {
+                if (x1 == y1 && x2 == y2) || t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    cubic_bezier_at(t, x1, y1, x2, y2)
+                }
+            }
+
+            TimingFunction::Steps(steps, position) => {
+                let n = f64::from(steps);
+
+                let step = match position {
+                    StepPosition::Start => (t * n).floor() + 1.0,
+                    StepPosition::End => (t * n).floor(),
+                };
+
+                step.min(n) / n
+            }
+        }
+    }
+}
+
+impl Parse for TimingFunction {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: ()) -> Result<TimingFunction, AttributeError> {
+        let error = || AttributeError::Parse(ParseError::new("expected a timing function"));
+
+        match s.trim() {
+            "linear" => Ok(TimingFunction::CubicBezier(0.0, 0.0, 1.0, 1.0)),
+            "ease" => Ok(Default::default()),
+            "ease-in" => Ok(TimingFunction::CubicBezier(0.42, 0.0, 1.0, 1.0)),
+            "ease-out" => Ok(TimingFunction::CubicBezier(0.0, 0.0, 0.58, 1.0)),
+            "ease-in-out" => Ok(TimingFunction::CubicBezier(0.42, 0.0, 0.58, 1.0)),
+            "step-start" => Ok(TimingFunction::Steps(1, StepPosition::Start)),
+            "step-end" => Ok(TimingFunction::Steps(1, StepPosition::End)),
+
+            s => {
+                let (name, args) = split_function(s).ok_or_else(&error)?;
+
+                match name {
+                    "cubic-bezier" => {
+                        let n = args
+                            .iter()
+                            .map(|arg| arg.parse::<f64>())
+                            .collect::<Result<Vec<f64>, _>>()
+                            .map_err(|_| error())?;
+
+                        if n.len() != 4 || n[0] < 0.0 || n[0] > 1.0 || n[2] < 0.0 || n[2] > 1.0 {
+                            return Err(error());
+                        }
+
+                        Ok(TimingFunction::CubicBezier(n[0], n[1], n[2], n[3]))
+                    }
+
+                    "steps" => {
+                        let steps = match args.get(0).map(|arg| arg.parse::<u32>()) {
+                            Some(Ok(steps)) if steps > 0 => steps,
+                            _ => return Err(error()),
+                        };
+
+                        let position = match args.get(1).cloned() {
+                            None | Some("end") | Some("jump-end") => StepPosition::End,
+                            Some("start") | Some("jump-start") => StepPosition::Start,
+                            _ => return Err(error()),
+                        };
+
+                        Ok(TimingFunction::Steps(steps, position))
+                    }
+
+                    _ => Err(error()),
+                }
+            }
+        }
+    }
+}
+
+/// A value of `animation-iteration-count`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum IterationCount {
+    Count(f64),
+    Infinite,
+}
+
+impl Parse for IterationCount {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: ()) -> Result<IterationCount, AttributeError> {
+        match s.trim() {
+            "infinite" => Ok(IterationCount::Infinite),
+
+            s => match s.parse::<f64>() {
+                Ok(count) if count.is_finite() && count >= 0.0 => Ok(IterationCount::Count(count)),
+
+                _ => Err(AttributeError::Parse(ParseError::new(
+                    "expected a non-negative number or \"infinite\"",
+                ))),
+            },
+        }
+    }
+}
+
+/// A value of `animation-direction`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Direction {
+    Normal,
+    Reverse,
+    Alternate,
+    AlternateReverse,
+}
+
+impl Parse for Direction {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: ()) -> Result<Direction, AttributeError> {
+        match s.trim() {
+            "normal" => Ok(Direction::Normal),
+            "reverse" => Ok(Direction::Reverse),
+            "alternate" => Ok(Direction::Alternate),
+            "alternate-reverse" => Ok(Direction::AlternateReverse),
+            _ => Err(AttributeError::Parse(ParseError::new(
+                "expected \"normal\", \"reverse\", \"alternate\" or \"alternate-reverse\"",
+            ))),
+        }
+    }
+}
+
+/// A value of `animation-fill-mode`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FillMode {
+    None,
+    Forwards,
+    Backwards,
+    Both,
+}
+
+impl Parse for FillMode {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: ()) -> Result<FillMode, AttributeError> {
+        match s.trim() {
+            "none" => Ok(FillMode::None),
+            "forwards" => Ok(FillMode::Forwards),
+            "backwards" => Ok(FillMode::Backwards),
+            "both" => Ok(FillMode::Both),
+            _ => Err(AttributeError::Parse(ParseError::new(
+                "expected \"none\", \"forwards\", \"backwards\" or \"both\"",
+            ))),
+        }
+    }
+}
+
+/// Parses a `<time>`, like `2s` or `150ms`, into seconds.
+pub fn parse_time(s: &str) -> Result<f64, AttributeError> {
+    let s = s.trim();
+
+    let (number, scale) = if s.ends_with("ms") {
+        (&s[..s.len() - 2], 0.001)
+    } else if s.ends_with('s') {
+        (&s[..s.len() - 1], 1.0)
+    } else if s == "0" {
+        (s, 1.0)
+    } else {
+        return Err(AttributeError::Parse(ParseError::new("expected a time")));
+    };
+
+    match number.parse::<f64>() {
+        Ok(n) if n.is_finite() => Ok(n * scale),
+        _ => Err(AttributeError::Parse(ParseError::new("expected a time"))),
+    }
+}
+
+/// Parses an `animation-name`, which is `none`, an identifier, or a string.
+pub fn parse_name(s: &str) -> Result<String, AttributeError> {
+    let name = unquote(s.trim());
+
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        Err(AttributeError::Parse(ParseError::new(
+            "expected the name of a @keyframes rule",
+        )))
+    } else {
+        Ok(name.to_string())
+    }
+}
+
+/// Parses a comma-separated list of values for one of the `animation-*`
+/// properties.
+pub fn parse_list<T, F>(s: &str, parse_item: F) -> Result<Vec<T>, AttributeError>
+where
+    F: Fn(&str) -> Result<T, AttributeError>,
+{
+    split_top_level(s, |c| c == ',')
+        .into_iter()
+        .map(parse_item)
+        .collect()
+}
+
+/// The longhand values given by the `animation` shorthand property.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationShorthand {
+    pub names: Vec<String>,
+    pub durations: Vec<f64>,
+    pub timing_functions: Vec<TimingFunction>,
+    pub iteration_counts: Vec<IterationCount>,
+    pub directions: Vec<Direction>,
+    pub fill_modes: Vec<FillMode>,
+    pub delays: Vec<f64>,
+}
+
+/// Parses the `animation` shorthand.  In each comma-separated animation,
+/// the first time is the duration and the second one is the delay; the
+/// name is whatever is not a value of the other properties.
+///
+/// `animation-play-state` values are accepted but ignored.
+pub fn parse_shorthand(s: &str) -> Result<AnimationShorthand, AttributeError> {
+    let mut shorthand = AnimationShorthand {
+        names: Vec::new(),
+        durations: Vec::new(),
+        timing_functions: Vec::new(),
+        iteration_counts: Vec::new(),
+        directions: Vec::new(),
+        fill_modes: Vec::new(),
+        delays: Vec::new(),
+    };
+
+    for item in split_top_level(s, |c| c == ',') {
+        let mut name = None;
+        let mut duration = None;
+        let mut delay = None;
+        let mut timing_function = None;
+        let mut iteration_count = None;
+        let mut direction = None;
+        let mut fill_mode = None;
+
+        for token in split_top_level(item, char::is_whitespace) {
+            if let Ok(time) = parse_time(token) {
+                if duration.is_none() {
+                    duration = Some(time);
+                } else if delay.is_none() {
+                    delay = Some(time);
+                } else {
+                    return Err(AttributeError::Parse(ParseError::new("too many times")));
+                }
+            } else if timing_function.is_none() && TimingFunction::parse(token, ()).is_ok() {
+                timing_function = TimingFunction::parse(token, ()).ok();
+            } else if iteration_count.is_none() && IterationCount::parse(token, ()).is_ok() {
+                iteration_count = IterationCount::parse(token, ()).ok();
+            } else if direction.is_none() && Direction::parse(token, ()).is_ok() {
+                direction = Direction::parse(token, ()).ok();
+            } else if fill_mode.is_none() && FillMode::parse(token, ()).is_ok() {
+                fill_mode = FillMode::parse(token, ()).ok();
+            } else if token == "running" || token == "paused" {
+                // animation-play-state
+            } else if name.is_none() {
+                name = Some(parse_name(token)?);
+            } else {
+                return Err(AttributeError::Parse(ParseError::new(
+                    "invalid value in animation",
+                )));
+            }
+        }
+
+        shorthand
+            .names
+            .push(name.unwrap_or_else(|| "none".to_string()));
+        shorthand.durations.push(duration.unwrap_or(0.0));
+        shorthand
+            .timing_functions
+            .push(timing_function.unwrap_or_default());
+        shorthand
+            .iteration_counts
+            .push(iteration_count.unwrap_or(IterationCount::Count(1.0)));
+        shorthand
+            .directions
+            .push(direction.unwrap_or(Direction::Normal));
+        shorthand
+            .fill_modes
+            .push(fill_mode.unwrap_or(FillMode::None));
+        shorthand.delays.push(delay.unwrap_or(0.0));
+    }
+
+    Ok(shorthand)
+}
+
+// ************ Transition properties ************
+
+/// Parses an item of `transition-property`, which is `all`, `none`, or the
+/// name of a property.
+pub fn parse_transition_property(s: &str) -> Result<String, AttributeError> {
+    let name = s.trim();
+
+    if !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        Ok(name.to_lowercase())
+    } else {
+        Err(AttributeError::Parse(ParseError::new(
+            "expected the name of a property",
+        )))
+    }
+}
+
+/// The longhand values given by the `transition` shorthand property.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransitionShorthand {
+    pub properties: Vec<String>,
+    pub durations: Vec<f64>,
+    pub timing_functions: Vec<TimingFunction>,
+    pub delays: Vec<f64>,
+}
+
+/// Parses the `transition` shorthand.  In each comma-separated transition,
+/// the first time is the duration and the second one is the delay.
+pub fn parse_transition_shorthand(s: &str) -> Result<TransitionShorthand, AttributeError> {
+    let mut shorthand = TransitionShorthand {
+        properties: Vec::new(),
+        durations: Vec::new(),
+        timing_functions: Vec::new(),
+        delays: Vec::new(),
+    };
+
+    for item in split_top_level(s, |c| c == ',') {
+        let mut property = None;
+        let mut duration = None;
+        let mut delay = None;
+        let mut timing_function = None;
+
+        for token in split_top_level(item, char::is_whitespace) {
+            if let Ok(time) = parse_time(token) {
+                if duration.is_none() {
+                    duration = Some(time);
+                } else if delay.is_none() {
+                    delay = Some(time);
+                } else {
+                    return Err(AttributeError::Parse(ParseError::new("too many times")));
+                }
+            } else if timing_function.is_none() && TimingFunction::parse(token, ()).is_ok() {
+                timing_function = TimingFunction::parse(token, ()).ok();
+            } else if property.is_none() {
+                property = Some(parse_transition_property(token)?);
+            } else {
+                return Err(AttributeError::Parse(ParseError::new(
+                    "invalid value in transition",
+                )));
+            }
+        }
+
+        shorthand
+            .properties
+            .push(property.unwrap_or_else(|| "all".to_string()));
+        shorthand.durations.push(duration.unwrap_or(0.0));
+        shorthand
+            .timing_functions
+            .push(timing_function.unwrap_or_default());
+        shorthand.delays.push(delay.unwrap_or(0.0));
+    }
+
+    Ok(shorthand)
+}
+
+// ************ @keyframes ************
+
+/// One of the keyframes of a `@keyframes` rule; a rule like `0%, 50% { ... }`
+/// gives a keyframe for each offset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframe {
+    /// From 0 for `from` to 1 for `to`.
+    pub offset: f64,
+
+    /// Property names and values, in the order of the rule.
+    pub declarations: Vec<(String, String)>,
+
+    /// The `animation-timing-function` in the keyframe, which applies from
+    /// it to the next keyframe.
+    pub timing_function: Option<TimingFunction>,
+}
+
+/// A `@keyframes` rule, with the keyframes sorted by offset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframes {
+    pub name: String,
+    pub frames: Vec<Keyframe>,
+}
+
+impl Keyframes {
+    /// Returns the value of each property of the keyframes at `progress`,
+    /// from 0 to 1.  The `timing_function` of the animation applies to the
+    /// keyframes that don't have their own; `underlying` gives the value of
+    /// a property without the animation, for when there is no keyframe for
+    /// it at 0% or 100%.
+    ///
+    /// Values of `transform` are returned in the syntax of the SVG
+    /// `transform` attribute.
+    pub fn get_values<F>(
+        &self,
+        progress: f64,
+        timing_function: &TimingFunction,
+        underlying: F,
+    ) -> Vec<(String, String)>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut names: Vec<&str> = Vec::new();
+
+        for frame in &self.frames {
+            for &(ref name, _) in &frame.declarations {
+                if !names.contains(&name.as_str()) {
+                    names.push(name);
+                }
+            }
+        }
+
+        names
+            .into_iter()
+            .filter_map(|name| {
+                self.get_value(name, progress, timing_function, &underlying)
+                    .map(|value| (name.to_string(), value))
+            })
+            .collect()
+    }
+
+    fn get_value<F>(
+        &self,
+        name: &str,
+        progress: f64,
+        timing_function: &TimingFunction,
+        underlying: &F,
+    ) -> Option<String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        // Offset, value and timing function of the keyframes for the property;
+        // a later keyframe at the same offset replaces an earlier one.
+        let mut stops: Vec<(f64, String, TimingFunction)> = Vec::new();
+
+        for frame in &self.frames {
+            let value = frame
+                .declarations
+                .iter()
+                .rev()
+                .find(|&&(ref n, _)| n == name)
+                .map(|&(_, ref value)| value.clone());
+
+            if let Some(value) = value {
+                let stop = (
+                    frame.offset,
+                    value,
+                    frame.timing_function.unwrap_or(*timing_function),
+                );
+
+                if stops.last().map_or(false, |last| last.0 == frame.offset) {
+                    *stops.last_mut().unwrap() = stop;
+                } else {
+                    stops.push(stop);
+                }
+            }
+        }
+
+        if stops.is_empty() {
+            return None;
+        }
+
+        if stops[0].0 > 0.0 {
+            let value = underlying(name).unwrap_or_else(|| stops[0].1.clone());
+            stops.insert(0, (0.0, value, *timing_function));
+        }
+
+        if stops[stops.len() - 1].0 < 1.0 {
+            let value = underlying(name).unwrap_or_else(|| stops[stops.len() - 1].1.clone());
+            stops.push((1.0, value, *timing_function));
+        }
+
+        let is_transform = name == "transform";
+
+        let values: Vec<String> = stops
+            .iter()
+            .map(|stop| {
+                if is_transform {
+                    css_transform_to_svg(&stop.1)
+                } else {
+                    stop.1.clone()
+                }
+            })
+            .collect();
+
+        let i = (0..stops.len() - 1)
+            .rposition(|i| stops[i].0 <= progress)
+            .unwrap_or(0);
+
+        let span = stops[i + 1].0 - stops[i].0;
+        let t = if span > 0.0 {
+            ((progress - stops[i].0) / span).min(1.0)
+        } else {
+            1.0
+        };
+
+        let eased = stops[i].2.evaluate(t);
+
+        if is_transform {
+            Some(interpolate_transforms(&values[i], &values[i + 1], eased))
+        } else {
+            Some(interpolate_values(&values[i], &values[i + 1], eased))
+        }
+    }
+}
+
+/// The `@keyframes` rules of a document's stylesheets.
+#[derive(Default)]
+pub struct KeyframesSet {
+    rules: Vec<Keyframes>,
+}
+
+impl KeyframesSet {
+    /// Finds the `@keyframes` rule with a name; the last rule with the name
+    /// wins.
+    pub fn lookup(&self, name: &str) -> Option<&Keyframes> {
+        self.rules.iter().find(|rule| rule.name == name)
+    }
+
+    fn add(&mut self, keyframes: Keyframes) {
+        self.rules.retain(|rule| rule.name != keyframes.name);
+        self.rules.push(keyframes);
+    }
+
+    /// Adds the `@keyframes` rules of a stylesheet, including ones with
+    /// vendor prefixes like `@-webkit-keyframes`, and returns the stylesheet
+    /// with those rules replaced by white space.  Newlines are kept, so that
+    /// line numbers in the rest of the stylesheet don't change.
+    pub fn parse_stylesheet(&mut self, css: &[u8]) -> Vec<u8> {
+        let mut out = css.to_vec();
+        let mut depth = 0;
+        let mut i = 0;
+
+        while i < css.len() {
+            if let Some(next) = skip_comment_or_string(css, i) {
+                i = next;
+                continue;
+            }
+
+            match css[i] {
+                b'{' => depth += 1,
+
+                b'}' => {
+                    if depth > 0 {
+                        depth -= 1;
+                    }
+                }
+
+                b'@' if depth == 0 => {
+                    let keyword_end = i
+                        + 1
+                        + css[i + 1..]
+                            .iter()
+                            .take_while(|&&c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_')
+                            .count();
+
+                    let keyword = String::from_utf8_lossy(&css[i + 1..keyword_end]).to_lowercase();
+
+                    let is_keyframes = keyword == "keyframes"
+                        || (keyword.starts_with('-') && keyword.ends_with("-keyframes"));
+
+                    let open = css[keyword_end..]
+                        .iter()
+                        .position(|&c| c == b'{' || c == b';')
+                        .map(|pos| pos + keyword_end);
+
+                    if let (true, Some(open)) = (is_keyframes, open) {
+                        if css[open] == b'{' {
+                            let (body_end, rule_end) = match find_block_end(css, open) {
+                                Some(end) => (end, end + 1),
+                                None => (css.len(), css.len()),
+                            };
+
+                            let name = String::from_utf8_lossy(&css[keyword_end..open]);
+                            let name = unquote(name.trim());
+
+                            if !name.is_empty() {
+                                let body = String::from_utf8_lossy(&css[open + 1..body_end]);
+
+                                self.add(Keyframes {
+                                    name: name.to_string(),
+                                    frames: parse_keyframe_rules(&body),
+                                });
+                            }
+
+                            for c in &mut out[i..rule_end] {
+                                if *c != b'\n' {
+                                    *c = b' ';
+                                }
+                            }
+
+                            i = rule_end;
+                            continue;
+                        }
+                    }
+                }
+
+                _ => (),
+            }
+
+            i += 1;
+        }
+
+        out
+    }
+}
+
+// Returns the index just past the comment or string that starts at `i`, or
+// `None` if none starts there.
+fn skip_comment_or_string(css: &[u8], i: usize) -> Option<usize> {
+    if css[i..].starts_with(b"/*") {
+        let end = css[i + 2..]
+            .windows(2)
+            .position(|w| w == b"*/")
+            .map_or(css.len(), |pos| i + 2 + pos + 2);
+
+        Some(end)
+    } else if css[i] == b'"' || css[i] == b'\'' {
+        let quote = css[i];
+        let mut j = i + 1;
+
+        while j < css.len() && css[j] != quote {
+            if css[j] == b'\\' {
+                j += 1;
+            }
+
+            j += 1;
+        }
+
+        Some((j + 1).min(css.len()))
+    } else {
+        None
+    }
+}
+
+// Returns the index of the `}` that closes the block whose `{` is at `open`.
+fn find_block_end(css: &[u8], open: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = open;
+
+    while i < css.len() {
+        if let Some(next) = skip_comment_or_string(css, i) {
+            i = next;
+            continue;
+        }
+
+        match css[i] {
+            b'{' => depth += 1,
+
+            b'}' => {
+                depth -= 1;
+
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+
+            _ => (),
+        }
+
+        i += 1;
+    }
+
+    None
+}
+
+fn strip_comments(css: &str) -> String {
+    let bytes = css.as_bytes();
+    let mut out = String::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match skip_comment_or_string(bytes, i) {
+            Some(next) => {
+                if bytes[i] == b'/' {
+                    out.push(' ');
+                } else {
+                    out.push_str(&css[i..next]);
+                }
+
+                i = next;
+            }
+
+            None => {
+                let next = i + css[i..].chars().next().map_or(1, char::len_utf8);
+                out.push_str(&css[i..next]);
+                i = next;
+            }
+        }
+    }
+
+    out
+}
+
+// Parses the contents of a `@keyframes` rule.
+fn parse_keyframe_rules(body: &str) -> Vec<Keyframe> {
+    let body = strip_comments(body);
+    let bytes = body.as_bytes();
+    let mut frames = Vec::new();
+    let mut start = 0;
+
+    while let Some(open) = body[start..].find('{').map(|pos| pos + start) {
+        let end = find_block_end(bytes, open).unwrap_or_else(|| bytes.len());
+
+        let (declarations, timing_function) = parse_keyframe_declarations(&body[open + 1..end]);
+
+        for selector in body[start..open].split(',') {
+            if let Some(offset) = parse_keyframe_selector(selector) {
+                frames.push(Keyframe {
+                    offset,
+                    declarations: declarations.clone(),
+                    timing_function,
+                });
+            }
+        }
+
+        if end >= bytes.len() {
+            break;
+        }
+
+        start = end + 1;
+    }
+
+    // sort_by() is stable, so keyframes at the same offset stay in order
+    frames.sort_by(|a, b| a.offset.partial_cmp(&b.offset).unwrap());
+
+    frames
+}
+
+fn parse_keyframe_selector(selector: &str) -> Option<f64> {
+    let selector = selector.trim().to_lowercase();
+
+    match selector.as_str() {
+        "from" => Some(0.0),
+        "to" => Some(1.0),
+
+        s if s.ends_with('%') => match s[..s.len() - 1].trim().parse::<f64>() {
+            Ok(percent) if percent >= 0.0 && percent <= 100.0 => Some(percent / 100.0),
+            _ => None,
+        },
+
+        _ => None,
+    }
+}
+
+// Declarations with !important are ignored in keyframes.
+fn parse_keyframe_declarations(s: &str) -> (Vec<(String, String)>, Option<TimingFunction>) {
+    let mut declarations = Vec::new();
+    let mut timing_function = None;
+
+    for declaration in split_top_level(s, |c| c == ';') {
+        let colon = match declaration.find(':') {
+            Some(colon) => colon,
+            None => continue,
+        };
+
+        let name = declaration[..colon].trim().to_lowercase();
+        let value = declaration[colon + 1..].trim();
+
+        if name.is_empty() || value.is_empty() || value.to_lowercase().ends_with("important") {
+            continue;
+        }
+
+        if name == "animation-timing-function" {
+            timing_function = TimingFunction::parse(value, ()).ok();
+        } else {
+            declarations.push((name, value.to_string()));
+        }
+    }
+
+    (declarations, timing_function)
+}
+
+// ************ Animating ************
+
+/// One of the animations of an element, from its `animation-*` properties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CssAnimation {
+    pub name: String,
+    pub duration: f64,
+    pub delay: f64,
+    pub timing_function: TimingFunction,
+    pub iteration_count: IterationCount,
+    pub direction: Direction,
+    pub fill_mode: FillMode,
+}
+
+impl CssAnimation {
+    /// Returns how far through its keyframes the animation is at `time`,
+    /// from 0 to 1, taking its direction into account; or `None` if the
+    /// animation has no effect at that time.
+    pub fn get_progress(&self, time: f64) -> Option<f64> {
+        let local = time - self.delay;
+
+        let count = match self.iteration_count {
+            IterationCount::Count(count) => count,
+            IterationCount::Infinite => ::std::f64::INFINITY,
+        };
+
+        let (iteration, fraction) = if local < 0.0 {
+            match self.fill_mode {
+                FillMode::Backwards | FillMode::Both => (0.0, 0.0),
+                _ => return None,
+            }
+        } else if self.duration > 0.0 && local < self.duration * count {
+            let position = local / self.duration;
+            (position.floor(), position - position.floor())
+        } else {
+            match self.fill_mode {
+                FillMode::Forwards | FillMode::Both => {
+                    if count == 0.0 {
+                        (0.0, 0.0)
+                    } else if !count.is_finite() {
+                        (0.0, 1.0)
+                    } else if count.fract() == 0.0 {
+                        (count - 1.0, 1.0)
+                    } else {
+                        (count.floor(), count.fract())
+                    }
+                }
+
+                _ => return None,
+            }
+        };
+
+        let reversed = match self.direction {
+            Direction::Normal => false,
+            Direction::Reverse => true,
+            Direction::Alternate => iteration % 2.0 == 1.0,
+            Direction::AlternateReverse => iteration % 2.0 == 0.0,
+        };
+
+        Some(if reversed { 1.0 - fraction } else { fraction })
+    }
+}
+
+/// Returns the animations given by the `animation-*` properties in an
+/// element's state.  Lists that are shorter than `animation-name` repeat.
+pub fn get_css_animations(state: *const RsvgState) -> Vec<CssAnimation> {
+    let rstate = state::get_state_rust(state);
+
+    let names = rstate.animation_name.clone().unwrap_or_default().0;
+    let durations = rstate.animation_duration.clone().unwrap_or_default().0;
+    let delays = rstate.animation_delay.clone().unwrap_or_default().0;
+    let timing_functions = rstate
+        .animation_timing_function
+        .clone()
+        .unwrap_or_default()
+        .0;
+    let iteration_counts = rstate
+        .animation_iteration_count
+        .clone()
+        .unwrap_or_default()
+        .0;
+    let directions = rstate.animation_direction.clone().unwrap_or_default().0;
+    let fill_modes = rstate.animation_fill_mode.clone().unwrap_or_default().0;
+
+    names
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, ref name)| name != "none")
+        .map(|(i, name)| CssAnimation {
+            name,
+            duration: durations[i % durations.len()],
+            delay: delays[i % delays.len()],
+            timing_function: timing_functions[i % timing_functions.len()],
+            iteration_count: iteration_counts[i % iteration_counts.len()],
+            direction: directions[i % directions.len()],
+            fill_mode: fill_modes[i % fill_modes.len()],
+        })
+        .collect()
+}
+
+/// The transition of one property of an element, from its `transition-*`
+/// properties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CssTransition {
+    pub duration: f64,
+    pub delay: f64,
+    pub timing_function: TimingFunction,
+}
+
+impl CssTransition {
+    /// Returns how far from the old value to the new one a transition that
+    /// started `elapsed` seconds ago is, from 0 to 1, with its timing
+    /// function applied.
+    pub fn get_progress(&self, elapsed: f64) -> f64 {
+        let local = elapsed - self.delay;
+
+        if local < 0.0 {
+            // The old value stays until the delay is over
+            0.0
+        } else if self.duration <= 0.0 || local >= self.duration {
+            1.0
+        } else {
+            self.timing_function.evaluate(local / self.duration)
+        }
+    }
+
+    /// Returns how long after it starts the transition reaches the new value.
+    pub fn get_end(&self) -> f64 {
+        self.delay + self.duration.max(0.0)
+    }
+}
+
+/// Returns the transition that the `transition-*` properties in an element's
+/// state give to `property`, or `None` if changes of the property are not
+/// transitioned.  Lists that are shorter than `transition-property` repeat,
+/// and later entries for the same property win over earlier ones.
+pub fn get_css_transition(state: *const RsvgState, property: &str) -> Option<CssTransition> {
+    let rstate = state::get_state_rust(state);
+
+    let properties = rstate.transition_property.clone().unwrap_or_default().0;
+    let durations = rstate.transition_duration.clone().unwrap_or_default().0;
+    let delays = rstate.transition_delay.clone().unwrap_or_default().0;
+    let timing_functions = rstate
+        .transition_timing_function
+        .clone()
+        .unwrap_or_default()
+        .0;
+
+    let i = properties
+        .iter()
+        .rposition(|name| name == "all" || name == property)?;
+
+    let transition = CssTransition {
+        duration: durations[i % durations.len()],
+        delay: delays[i % delays.len()],
+        timing_function: timing_functions[i % timing_functions.len()],
+    };
+
+    // A transition that takes no time is the same as no transition
+    if transition.get_end() > 0.0 {
+        Some(transition)
+    } else {
+        None
+    }
+}
+
+/// Returns the `@keyframes` rules of a loaded SVG.
+pub fn get_keyframes<'a>(handle: *const RsvgHandle) -> &'a KeyframesSet {
+    unsafe { &*rsvg_handle_get_keyframes(handle) }
+}
+
+// ************ Transforms ************
+
+/// Converts a value of the CSS `transform` property into the syntax of the
+/// SVG `transform` attribute, which has no units, and where `translateX()`
+/// and the like don't exist.  3D transform functions are dropped.
+fn css_transform_to_svg(css: &str) -> String {
+    let css = css.trim();
+
+    if css == "none" {
+        return String::new();
+    }
+
+    let mut functions = Vec::new();
+    let mut rest = css;
+
+    while let Some(open) = rest.find('(') {
+        let close = match rest[open..].find(')') {
+            Some(pos) => open + pos,
+            None => break,
+        };
+
+        let name = rest[..open].trim().trim_left_matches(',').trim();
+        let args: Vec<f64> = rest[open + 1..close]
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|arg| !arg.is_empty())
+            .filter_map(css_number)
+            .collect();
+
+        let arg = |i: usize, default: f64| args.get(i).cloned().unwrap_or(default);
+
+        let function = match name {
+            "translate" => Some(format!("translate({} {})", arg(0, 0.0), arg(1, 0.0))),
+            "translateX" => Some(format!("translate({} 0)", arg(0, 0.0))),
+            "translateY" => Some(format!("translate(0 {})", arg(0, 0.0))),
+            "scale" => Some(format!("scale({} {})", arg(0, 1.0), arg(1, arg(0, 1.0)))),
+            "scaleX" => Some(format!("scale({} 1)", arg(0, 1.0))),
+            "scaleY" => Some(format!("scale(1 {})", arg(0, 1.0))),
+
+            "rotate" if args.len() == 3 => {
+                Some(format!("rotate({} {} {})", args[0], args[1], args[2]))
+            }
+            "rotate" => Some(format!("rotate({})", arg(0, 0.0))),
+
+            "skewX" => Some(format!("skewX({})", arg(0, 0.0))),
+            "skewY" => Some(format!("skewY({})", arg(0, 0.0))),
+
+            "skew" => Some(format!(
+                "matrix(1 {} {} 1 0 0)",
+                arg(1, 0.0).to_radians().tan(),
+                arg(0, 0.0).to_radians().tan()
+            )),
+
+            "matrix" if args.len() == 6 => Some(format!(
+                "matrix({} {} {} {} {} {})",
+                args[0], args[1], args[2], args[3], args[4], args[5]
+            )),
+
+            _ => None,
+        };
+
+        if let Some(function) = function {
+            functions.push(function);
+        }
+
+        rest = &rest[close + 1..];
+    }
+
+    functions.join(" ")
+}
+
+// Parses a number with an optional length or angle unit.  Angles are
+// returned in degrees, and lengths in user units.
+fn css_number(s: &str) -> Option<f64> {
+    let units: &[(&str, f64)] = &[
+        ("deg", 1.0),
+        ("grad", 0.9),
+        ("rad", 180.0 / PI),
+        ("turn", 360.0),
+        ("px", 1.0),
+        ("", 1.0),
+    ];
+
+    units
+        .iter()
+        .filter(|&&(unit, _)| s.ends_with(unit))
+        .filter_map(|&(unit, scale)| {
+            s[..s.len() - unit.len()]
+                .parse::<f64>()
+                .ok()
+                .map(|n| n * scale)
+        })
+        .next()
+}
+
+// Interpolates between two SVG transforms.  A missing transform is taken
+// as the identity transform made of the same functions as the other one.
+fn interpolate_transforms(a: &str, b: &str, t: f64) -> String {
+    match (a.is_empty(), b.is_empty()) {
+        (true, false) => interpolate_values(&identity_transform_like(b), b, t),
+        (false, true) => interpolate_values(a, &identity_transform_like(a), t),
+        _ => interpolate_values(a, b, t),
+    }
+}
+
+fn identity_transform_like(transform: &str) -> String {
+    transform
+        .split(')')
+        .filter_map(|function| {
+            let open = function.find('(')?;
+            let name = function[..open].trim();
+            let args = function[open + 1..]
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|arg| !arg.is_empty())
+                .count();
+
+            let params = match name {
+                "matrix" => "1 0 0 1 0 0".to_string(),
+                "scale" => vec!["1"; args].join(" "),
+
+                // keep the center of rotation
+                "rotate" => {
+                    let mut params: Vec<&str> = function[open + 1..]
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .filter(|arg| !arg.is_empty())
+                        .collect();
+
+                    if params.is_empty() {
+                        params.push("0");
+                    } else {
+                        params[0] = "0";
+                    }
+
+                    params.join(" ")
+                }
+
+                _ => vec!["0"; args].join(" "),
+            };
+
+            Some(format!("{}({})", name, params))
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// ************ Helpers ************
+
+// Splits a string at the characters for which `is_separator` returns true,
+// except inside parentheses and quotes, and trims the parts.  Empty parts
+// are left out.
+fn split_top_level<F>(s: &str, is_separator: F) -> Vec<&str>
+where
+    F: Fn(char) -> bool,
+{
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => (),
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') if depth > 0 => depth -= 1,
+
+            (None, c) if depth == 0 && is_separator(c) => {
+                parts.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+
+            _ => (),
+        }
+    }
+
+    parts.push(&s[start..]);
+
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+// Splits "name(a, b)" into the name and the arguments.
+fn split_function(s: &str) -> Option<(&str, Vec<&str>)> {
+    let open = s.find('(')?;
+
+    if !s.ends_with(')') {
+        return None;
+    }
+
+    let args = s[open + 1..s.len() - 1].split(',').map(str::trim).collect();
+
+    Some((s[..open].trim(), args))
+}
+
+fn unquote(s: &str) -> &str {
+    if s.len() >= 2
+        && ((s.starts_with('"') && s.ends_with('"')) || (s.starts_with('\'') && s.ends_with('\'')))
+    {
+        &s[1..s.len() - 1]
+    } else {
+        s
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_keyframes_new() -> *mut KeyframesSet {
+    Box::into_raw(Box::new(KeyframesSet::default()))
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_keyframes_free(raw_keyframes: *mut KeyframesSet) {
+    assert!(!raw_keyframes.is_null());
+
+    unsafe {
+        Box::from_raw(raw_keyframes);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_keyframes_parse_stylesheet(
+    raw_keyframes: *mut KeyframesSet,
+    css: *const libc::c_char,
+    len: libc::size_t,
+) -> *mut libc::c_char {
+    assert!(!raw_keyframes.is_null());
+    assert!(!css.is_null());
+
+    let keyframes = unsafe { &mut *raw_keyframes };
+    let css = unsafe { slice::from_raw_parts(css as *const u8, len) };
+
+    let rest = keyframes.parse_stylesheet(css);
+
+    unsafe {
+        let out = glib_sys::g_malloc(len + 1) as *mut u8;
+        ptr::copy_nonoverlapping(rest.as_ptr(), out, len);
+        *out.offset(len as isize) = 0;
+
+        out as *mut libc::c_char
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluates_timing_functions() {
+        let linear = TimingFunction::parse("linear", ()).unwrap();
+        assert_eq!(linear.evaluate(0.3), 0.3);
+
+        let ease_in = TimingFunction::parse("ease-in", ()).unwrap();
+        assert!(ease_in.evaluate(0.5) < 0.5);
+        assert_eq!(ease_in.evaluate(1.0), 1.0);
+
+        let steps = TimingFunction::parse("steps(4)", ()).unwrap();
+        assert_eq!(steps.evaluate(0.0), 0.0);
+        assert_eq!(steps.evaluate(0.3), 0.25);
+        assert_eq!(steps.evaluate(1.0), 1.0);
+
+        let steps = TimingFunction::parse("steps(4, start)", ()).unwrap();
+        assert_eq!(steps.evaluate(0.0), 0.25);
+        assert_eq!(steps.evaluate(0.3), 0.5);
+        assert_eq!(steps.evaluate(1.0), 1.0);
+
+        assert_eq!(
+            TimingFunction::parse("cubic-bezier(0.1, 0.7, 1.0, 0.1)", ()),
+            Ok(TimingFunction::CubicBezier(0.1, 0.7, 1.0, 0.1))
+        );
+        assert!(TimingFunction::parse("cubic-bezier(2, 0, 1, 1)", ()).is_err());
+        assert!(TimingFunction::parse("steps(0)", ()).is_err());
+        assert!(TimingFunction::parse("bouncy", ()).is_err());
+    }
+
+    #[test]
+    fn parses_times() {
+        assert_eq!(parse_time("2s"), Ok(2.0));
+        assert_eq!(parse_time("150ms"), Ok(0.15));
+        assert_eq!(parse_time("-1s"), Ok(-1.0));
+        assert_eq!(parse_time("0"), Ok(0.0));
+        assert!(parse_time("2").is_err());
+        assert!(parse_time("spin").is_err());
+    }
+
+    #[test]
+    fn parses_shorthand() {
+        let shorthand =
+            parse_shorthand("spin 2s linear infinite, fade 500ms ease-out 1s 2 alternate both")
+                .unwrap();
+
+        assert_eq!(shorthand.names, vec!["spin", "fade"]);
+        assert_eq!(shorthand.durations, vec![2.0, 0.5]);
+        assert_eq!(shorthand.delays, vec![0.0, 1.0]);
+        assert_eq!(
+            shorthand.timing_functions,
+            vec![
+                TimingFunction::CubicBezier(0.0, 0.0, 1.0, 1.0),
+                TimingFunction::CubicBezier(0.0, 0.0, 0.58, 1.0),
+            ]
+        );
+        assert_eq!(
+            shorthand.iteration_counts,
+            vec![IterationCount::Infinite, IterationCount::Count(2.0)]
+        );
+        assert_eq!(
+            shorthand.directions,
+            vec![Direction::Normal, Direction::Alternate]
+        );
+        assert_eq!(shorthand.fill_modes, vec![FillMode::None, FillMode::Both]);
+
+        let shorthand = parse_shorthand("1s cubic-bezier(0, 0, 1, 1) pulse").unwrap();
+        assert_eq!(shorthand.names, vec!["pulse"]);
+
+        assert!(parse_shorthand("spin 1s 2s 3s").is_err());
+        assert!(parse_shorthand("spin fade").is_err());
+    }
+
+    #[test]
+    fn parses_transition_shorthand() {
+        let shorthand =
+            parse_transition_shorthand("fill 1s ease-in 250ms, 2s, OPACITY 0s").unwrap();
+
+        assert_eq!(shorthand.properties, vec!["fill", "all", "opacity"]);
+        assert_eq!(shorthand.durations, vec![1.0, 2.0, 0.0]);
+        assert_eq!(shorthand.delays, vec![0.25, 0.0, 0.0]);
+        assert_eq!(
+            shorthand.timing_functions,
+            vec![
+                TimingFunction::CubicBezier(0.42, 0.0, 1.0, 1.0),
+                TimingFunction::default(),
+                TimingFunction::default(),
+            ]
+        );
+
+        assert!(parse_transition_shorthand("fill stroke 1s").is_err());
+        assert!(parse_transition_shorthand("fill 1s 2s 3s").is_err());
+    }
+
+    #[test]
+    fn computes_transition_progress() {
+        let transition = CssTransition {
+            duration: 2.0,
+            delay: 1.0,
+            timing_function: TimingFunction::parse("linear", ()).unwrap(),
+        };
+
+        assert_eq!(transition.get_end(), 3.0);
+        assert_eq!(transition.get_progress(0.5), 0.0);
+        assert_eq!(transition.get_progress(2.0), 0.5);
+        assert_eq!(transition.get_progress(5.0), 1.0);
+
+        let transition = CssTransition {
+            delay: -1.0,
+            ..transition
+        };
+
+        assert_eq!(transition.get_progress(0.0), 0.5);
+    }
+
+    #[test]
+    fn extracts_keyframes_from_stylesheet() {
+        let css = "rect { fill: red; }\n\
+                   @keyframes pulse {\n\
+                     from { opacity: 1; animation-timing-function: linear }\n\
+                     50%, 75% { opacity: 0.5 !important; fill: blue }\n\
+                     /* } */ to { opacity: 0 }\n\
+                   }\n\
+                   circle { animation: pulse 1s; }";
+
+        let mut set = KeyframesSet::default();
+        let rest = String::from_utf8(set.parse_stylesheet(css.as_bytes())).unwrap();
+
+        assert_eq!(rest.len(), css.len());
+        assert_eq!(rest.lines().count(), css.lines().count());
+        assert!(rest.contains("rect { fill: red; }"));
+        assert!(rest.contains("circle { animation: pulse 1s; }"));
+        assert!(!rest.contains("keyframes"));
+        assert!(!rest.contains("opacity"));
+
+        let pulse = set.lookup("pulse").unwrap();
+        let offsets: Vec<f64> = pulse.frames.iter().map(|f| f.offset).collect();
+        assert_eq!(offsets, vec![0.0, 0.5, 0.75, 1.0]);
+
+        assert_eq!(
+            pulse.frames[0].timing_function,
+            Some(TimingFunction::CubicBezier(0.0, 0.0, 1.0, 1.0))
+        );
+        assert_eq!(
+            pulse.frames[1].declarations,
+            vec![("fill".to_string(), "blue".to_string())]
+        );
+
+        let mut set = KeyframesSet::default();
+        set.parse_stylesheet(b"@-webkit-keyframes 'a' { to { opacity: 0 } } @keyframes a { }");
+        assert_eq!(set.lookup("a").unwrap().frames, Vec::new());
+    }
+
+    fn animation(fill_mode: FillMode, direction: Direction) -> CssAnimation {
+        CssAnimation {
+            name: "a".to_string(),
+            duration: 2.0,
+            delay: 1.0,
+            timing_function: TimingFunction::CubicBezier(0.0, 0.0, 1.0, 1.0),
+            iteration_count: IterationCount::Count(2.5),
+            direction,
+            fill_mode,
+        }
+    }
+
+    #[test]
+    fn computes_progress() {
+        let a = animation(FillMode::None, Direction::Normal);
+        assert_eq!(a.get_progress(0.5), None);
+        assert_eq!(a.get_progress(2.0), Some(0.5));
+        assert_eq!(a.get_progress(4.0), Some(0.5));
+        assert_eq!(a.get_progress(7.0), None);
+
+        let a = animation(FillMode::Both, Direction::Alternate);
+        assert_eq!(a.get_progress(0.5), Some(0.0));
+        assert_eq!(a.get_progress(2.0), Some(0.5));
+        assert_eq!(a.get_progress(3.5), Some(0.75));
+        assert_eq!(a.get_progress(7.0), Some(0.5));
+
+        let a = animation(FillMode::Forwards, Direction::Reverse);
+        assert_eq!(a.get_progress(0.5), None);
+        assert_eq!(a.get_progress(1.5), Some(0.75));
+    }
+
+    #[test]
+    fn interpolates_keyframes() {
+        let mut set = KeyframesSet::default();
+        set.parse_stylesheet(
+            b"@keyframes k {\
+                0% { opacity: 0; fill: #ff0000 }\
+                50% { opacity: 1; animation-timing-function: steps(2) }\
+                100% { fill: rgb(0, 0, 255) }\
+              }",
+        );
+
+        let k = set.lookup("k").unwrap();
+        let linear = TimingFunction::CubicBezier(0.0, 0.0, 1.0, 1.0);
+        let underlying = |name: &str| {
+            if name == "opacity" {
+                Some("0.5".to_string())
+            } else {
+                None
+            }
+        };
+
+        assert_eq!(
+            k.get_values(0.25, &linear, &underlying),
+            vec![
+                ("opacity".to_string(), "0.5".to_string()),
+                ("fill".to_string(), "rgb(191, 0, 64)".to_string()),
+            ]
+        );
+
+        assert_eq!(
+            k.get_values(0.8, &linear, &underlying),
+            vec![
+                ("opacity".to_string(), "0.75".to_string()),
+                ("fill".to_string(), "rgb(51, 0, 204)".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn converts_transforms() {
+        assert_eq!(css_transform_to_svg("none"), "");
+        assert_eq!(
+            css_transform_to_svg("translateX(10px) rotate(0.5turn) scale(2)"),
+            "translate(10 0) rotate(180) scale(2 2)"
+        );
+        assert_eq!(
+            css_transform_to_svg("translate(1px, 2px) rotate(45 10 10)"),
+            "translate(1 2) rotate(45 10 10)"
+        );
+        assert_eq!(css_transform_to_svg("rotateY(30deg)"), "");
+
+        assert_eq!(
+            interpolate_transforms("", "rotate(90 10 10) scale(3 3)", 0.5),
+            "rotate(45 10 10) scale(2 2)"
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use glib::translate::*;
+use glib_sys;
+use libc;
+
+use drawing_ctx::RsvgDrawingCtx;
+use node::{Node, NodeType, RsvgNode};
+use paint_server::PaintServer;
+use state;
+
+// Keep this in sync with ../../librsvg/rsvg.h:RsvgDiagnosticSeverity
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+// Keep this in sync with ../../librsvg/rsvg.h:RsvgDiagnostic
+#[repr(C)]
+struct RsvgDiagnostic {
+    severity: DiagnosticSeverity,
+    element: *mut libc::c_char,
+    id: *mut libc::c_char,
+    line: libc::c_int,
+    column: libc::c_int,
+    attribute: *mut libc::c_char,
+    message: *mut libc::c_char,
+}
+
+#[allow(improper_ctypes)]
+extern "C" {
+    fn rsvg_drawing_ctx_add_diagnostic(
+        draw_ctx: *const RsvgDrawingCtx,
+        diagnostic: *const RsvgDiagnostic,
+    );
+
+    fn rsvg_drawing_ctx_lookup_node(
+        draw_ctx: *const RsvgDrawingCtx,
+        url: *const libc::c_char,
+    ) -> *const RsvgNode;
+
+    fn rsvg_diagnostics_add(
+        diagnostics: *mut glib_sys::GPtrArray,
+        diagnostic: *const RsvgDiagnostic,
+    );
+}
+
+/// A problem that gets reported to the caller of an operation on the
+/// document, like `normalize()`, instead of being added to the diagnostics
+/// of the handle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: DiagnosticSeverity,
+    pub element: Option<String>,
+    pub id: Option<String>,
+    pub line: i32,
+    pub column: i32,
+    pub attribute: Option<String>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Creates a diagnostic for `node`.  `attr_name` is `None` if the problem
+    /// is with the element as a whole.
+    pub fn new(
+        node: &Node,
+        severity: DiagnosticSeverity,
+        attr_name: Option<&str>,
+        message: &str,
+    ) -> Diagnostic {
+        let (line, column) = node.get_location();
+
+        Diagnostic {
+            severity,
+            element: node.get_element_name(),
+            id: node.get_attribute("id"),
+            line,
+            column,
+            attribute: attr_name.map(str::to_string),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Adds an `RsvgDiagnostic` to a C array of them for each of `diagnostics`.
+pub fn append_to_array(array: *mut glib_sys::GPtrArray, diagnostics: &[Diagnostic]) {
+    for d in diagnostics {
+        let diagnostic = RsvgDiagnostic {
+            severity: d.severity,
+            element: d.element.to_glib_full(),
+            id: d.id.to_glib_full(),
+            line: d.line,
+            column: d.column,
+            attribute: d.attribute.to_glib_full(),
+            message: d.message.to_glib_full(),
+        };
+
+        // The C side takes ownership of the strings
+        unsafe {
+            rsvg_diagnostics_add(array, &diagnostic);
+        }
+    }
+}
+
+/// Records a problem that `node` has while it is being rendered, along with
+/// the problems that were found while loading the document.
+pub fn add_diagnostic(
+    draw_ctx: *const RsvgDrawingCtx,
+    node: &RsvgNode,
+    severity: DiagnosticSeverity,
+    attr_name: &str,
+    message: &str,
+) {
+    let (line, column) = node.get_location();
+
+    let diagnostic = RsvgDiagnostic {
+        severity,
+        element: node.get_element_name().to_glib_full(),
+        id: node.get_attribute("id").to_glib_full(),
+        line,
+        column,
+        attribute: attr_name.to_glib_full(),
+        message: message.to_glib_full(),
+    };
+
+    // The C side takes ownership of the strings
+    unsafe {
+        rsvg_drawing_ctx_add_diagnostic(draw_ctx, &diagnostic);
+    }
+}
+
+/// Records a diagnostic for `node` if the `url` in its `attr_name` does not
+/// reference an element, or if the element is not one of `node_types`.
+///
+/// An empty `node_types` accepts any element.
+pub fn check_reference(
+    draw_ctx: *const RsvgDrawingCtx,
+    node: &RsvgNode,
+    attr_name: &str,
+    url: &str,
+    node_types: &[NodeType],
+) {
+    let raw_target = unsafe { rsvg_drawing_ctx_lookup_node(draw_ctx, url.to_glib_none().0) };
+
+    let message = if raw_target.is_null() {
+        format!("reference to nonexistent element \"{}\"", url)
+    } else {
+        let target: &RsvgNode = unsafe { &*raw_target };
+
+        if node_types.is_empty() || node_types.contains(&target.get_type()) {
+            return;
+        }
+
+        format!("\"{}\" references an element of the wrong type", url)
+    };
+
+    add_diagnostic(
+        draw_ctx,
+        node,
+        DiagnosticSeverity::Warning,
+        attr_name,
+        &message,
+    );
+}
+
+/// Checks the references to other elements in the properties that `node`
+/// specifies itself, so that applications can find out why, for example, a
+/// mask or a gradient did not get applied.
+pub fn check_node_references(draw_ctx: *const RsvgDrawingCtx, node: &RsvgNode) {
+    let state = node.get_state();
+
+    let references = [
+        ("clip-path", state::get_clip_path(state), NodeType::ClipPath),
+        ("mask", state::get_mask(state), NodeType::Mask),
+        ("filter", state::get_filter(state), NodeType::Filter),
+        (
+            "marker-start",
+            state::get_start_marker(state),
+            NodeType::Marker,
+        ),
+        (
+            "marker-mid",
+            state::get_middle_marker(state),
+            NodeType::Marker,
+        ),
+        ("marker-end", state::get_end_marker(state), NodeType::Marker),
+    ];
+
+    for &(attr_name, url, node_type) in &references {
+        if let Some(url) = url {
+            check_reference(draw_ctx, node, attr_name, url, &[node_type]);
+        }
+    }
+
+    let paint_servers = [
+        ("fill", state::get_fill(state)),
+        ("stroke", state::get_stroke(state)),
+    ];
+
+    for &(attr_name, ps) in &paint_servers {
+        if let Some(&PaintServer::Iri { ref iri, .. }) = ps {
+            check_reference(
+                draw_ctx,
+                node,
+                attr_name,
+                iri,
+                &[
+                    NodeType::LinearGradient,
+                    NodeType::RadialGradient,
+                    NodeType::Pattern,
+                ],
+            );
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_drawing_ctx_add_node_diagnostic(
+    draw_ctx: *const RsvgDrawingCtx,
+    raw_node: *const RsvgNode,
+    severity: DiagnosticSeverity,
+    attr_name: *const libc::c_char,
+    message: *const libc::c_char,
+) {
+    assert!(!draw_ctx.is_null());
+
+    assert!(!raw_node.is_null());
+    let node: &RsvgNode = unsafe { &*raw_node };
+
+    assert!(!attr_name.is_null());
+    let attr_name: String = unsafe { from_glib_none(attr_name) };
+
+    assert!(!message.is_null());
+    let message: String = unsafe { from_glib_none(message) };
+
+    add_diagnostic(draw_ctx, node, severity, &attr_name, &message);
+}
@@ -0,0 +1,1059 @@
+use glib::translate::*;
+use glib_sys;
+use libc;
+use pango;
+
+use std::ffi::CString;
+use std::fmt;
+use std::ptr;
+use std::str::FromStr;
+
+use attributes::Attribute;
+use color::Color;
+use css_animation::{Direction, FillMode, IterationCount, StepPosition, TimingFunction};
+use error::NodeError;
+use handle::RsvgHandle;
+use length::{LengthDir, LengthUnit, RsvgLength, StrokeDasharray};
+use node::{Node, NodeType, RsvgNode};
+use opacity::Opacity;
+use paint_server::PaintServer;
+use property_bag::{OwnedPropertyBag, PropertyBag};
+use state::{
+    self,
+    AnimationDelay,
+    AnimationDirection,
+    AnimationDuration,
+    AnimationFillMode,
+    AnimationIterationCount,
+    AnimationName,
+    AnimationTimingFunction,
+    BaselineShift,
+    ClipRule,
+    CompOp,
+    Display,
+    EnableBackground,
+    FillRule,
+    FontFamily,
+    FontFeatureSettings,
+    FontKerning,
+    FontSize,
+    FontStretch,
+    FontStyle,
+    FontVariant,
+    FontVariantCaps,
+    FontVariantLigatures,
+    FontVariantNumeric,
+    FontVariantPosition,
+    FontWeight,
+    GlyphOrientationVertical,
+    InlineSize,
+    LetterSpacing,
+    Overflow,
+    PointerEvents,
+    RsvgState,
+    ShapeInside,
+    ShapeRendering,
+    StrokeLinecap,
+    StrokeLinejoin,
+    StrokeMiterlimit,
+    StrokeWidth,
+    TextAnchor,
+    TextDecoration,
+    TextDecorationColor,
+    TextDecorationStyle,
+    TextOrientation,
+    TextRendering,
+    TransitionDelay,
+    TransitionDuration,
+    TransitionProperty,
+    TransitionTimingFunction,
+    UnicodeBidi,
+    Visibility,
+    WordSpacing,
+    WritingMode,
+    XmlLang,
+    XmlSpace,
+};
+use text;
+
+#[allow(improper_ctypes)]
+extern "C" {
+    fn rsvg_handle_get_root_node(handle: *const RsvgHandle) -> *const RsvgNode;
+
+    fn rsvg_handle_get_node_by_id(
+        handle: *const RsvgHandle,
+        id: *const libc::c_char,
+    ) -> *const RsvgNode;
+
+    fn rsvg_handle_reset_node_atts(
+        handle: *const RsvgHandle,
+        node: *const RsvgNode,
+        element_name: *const libc::c_char,
+        pbag: *const PropertyBag,
+    );
+}
+
+/// An error from changing an attribute or the text of an element after loading.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SetAttributeError {
+    /// librsvg does not know about the attribute, so setting it would have
+    /// no effect.
+    UnsupportedAttribute(String),
+
+    /// The attribute cannot be changed after loading.
+    ReadOnlyAttribute(String),
+
+    /// The value is not valid for the attribute.  The element is left as it was.
+    InvalidValue(NodeError),
+
+    /// The element is not a text element, or it has child elements, so its
+    /// text cannot be replaced.
+    UnsupportedTextContent(String),
+}
+
+impl fmt::Display for SetAttributeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SetAttributeError::UnsupportedAttribute(ref name) => {
+                write!(f, "unsupported attribute \"{}\"", name)
+            }
+
+            SetAttributeError::ReadOnlyAttribute(ref name) => {
+                write!(f, "the \"{}\" attribute cannot be changed", name)
+            }
+
+            SetAttributeError::InvalidValue(ref e) => write!(f, "{}", e),
+
+            SetAttributeError::UnsupportedTextContent(ref name) => write!(
+                f,
+                "the text of the <{}> element cannot be set, since it is not a text \
+                 element or it has child elements",
+                name
+            ),
+        }
+    }
+}
+
+/// Returns the outermost `<svg>` element of a loaded SVG, or `None` if the
+/// handle has not finished loading.
+pub fn get_root_element(handle: *const RsvgHandle) -> Option<RsvgNode> {
+    node_from_ptr(unsafe { rsvg_handle_get_root_node(handle) })
+}
+
+/// Looks up an element by the value of its `id` attribute, without a leading `#`.
+pub fn get_element_by_id(handle: *const RsvgHandle, id: &str) -> Option<RsvgNode> {
+    node_from_ptr(unsafe { rsvg_handle_get_node_by_id(handle, id.to_glib_none().0) })
+}
+
+fn node_from_ptr(raw_node: *const RsvgNode) -> Option<RsvgNode> {
+    if raw_node.is_null() {
+        None
+    } else {
+        let node: &RsvgNode = unsafe { &*raw_node };
+        Some(node.clone())
+    }
+}
+
+/// Returns the element children of a node, i.e. its children without the
+/// nodes for character data.
+pub fn get_element_children(node: &Node) -> Vec<RsvgNode> {
+    node.children()
+        .filter(|child| child.get_type() != NodeType::Chars)
+        .collect()
+}
+
+/// Returns the computed value of a style property for an element, as a
+/// CSS string, or `None` if librsvg does not support the property.
+///
+/// The value takes into account the element's presentation attributes, its
+/// `style` attribute, the document's stylesheets, and inheritance from its
+/// ancestors.
+pub fn get_computed_value(node: &Node, property: &str) -> Option<String> {
+    let state = compute_state(node);
+    let value = state_get_value(state, &compute_font_size(node), property);
+    state::free(state);
+
+    value
+}
+
+/// Changes the value of an attribute of an element, or adds the attribute, and
+/// updates the element as if it had been loaded with the new value.  The next
+/// rendering of the handle reflects the change.
+pub fn set_attribute(
+    handle: *const RsvgHandle,
+    node: &RsvgNode,
+    name: &str,
+    value: &str,
+) -> Result<(), SetAttributeError> {
+    let unsupported = || SetAttributeError::UnsupportedAttribute(name.to_string());
+
+    let element_name = node.get_element_name().ok_or_else(&unsupported)?;
+    let attr = Attribute::from_str(name).map_err(|_| unsupported())?;
+
+    if attr == Attribute::Id {
+        return Err(SetAttributeError::ReadOnlyAttribute(name.to_string()));
+    }
+
+    let c_value = CString::new(value).map_err(|_| {
+        SetAttributeError::InvalidValue(NodeError::value_error(
+            name,
+            "must not contain a nul character",
+        ))
+    })?;
+
+    let old_attributes = node.get_attributes();
+
+    let mut attributes = old_attributes.clone();
+    attributes.set(&CString::new(name).unwrap(), attr, &c_value);
+
+    reset_atts(handle, node, &element_name, &attributes);
+
+    match node.get_result() {
+        Err(ref e) if e.get_attr_name() == name => {
+            reset_atts(handle, node, &element_name, &old_attributes);
+            Err(SetAttributeError::InvalidValue(e.clone()))
+        }
+
+        _ => Ok(()),
+    }
+}
+
+/// Sets a property in the `style` attribute of an element, replacing an
+/// earlier declaration of the same property.
+///
+/// Like in the `style` attribute itself, invalid values are ignored.
+pub fn set_style_property(
+    handle: *const RsvgHandle,
+    node: &RsvgNode,
+    property: &str,
+    value: &str,
+) -> Result<(), SetAttributeError> {
+    let style = node.get_attribute("style").unwrap_or_default();
+
+    set_attribute(
+        handle,
+        node,
+        "style",
+        &set_style_declaration(&style, property, value),
+    )
+}
+
+/// Replaces the text of a `<text>`, `<tspan>`, `<flowPara>` or `<flowSpan>`
+/// element that does not have child elements, for example to update a label.
+pub fn set_text_content(node: &RsvgNode, text: &str) -> Result<(), SetAttributeError> {
+    match node.get_type() {
+        NodeType::Text | NodeType::TSpan | NodeType::FlowPara | NodeType::FlowSpan
+            if get_element_children(node).is_empty() =>
+        {
+            text::set_text_content(node, text);
+            Ok(())
+        }
+
+        _ => Err(SetAttributeError::UnsupportedTextContent(
+            node.get_element_name().unwrap_or_default(),
+        )),
+    }
+}
+
+/// Puts back attributes that were returned by `Node::get_attributes()`
+/// before some calls to `set_attribute()` or `set_style_property()`.
+pub fn restore_attributes(
+    handle: *const RsvgHandle,
+    node: &RsvgNode,
+    attributes: &OwnedPropertyBag,
+) {
+    if let Some(element_name) = node.get_element_name() {
+        reset_atts(handle, node, &element_name, attributes);
+    }
+}
+
+fn reset_atts(
+    handle: *const RsvgHandle,
+    node: &RsvgNode,
+    element_name: &str,
+    attributes: &OwnedPropertyBag,
+) {
+    let pbag = PropertyBag::from_owned(attributes);
+
+    unsafe {
+        rsvg_handle_reset_node_atts(
+            handle,
+            node as *const RsvgNode,
+            element_name.to_glib_none().0,
+            pbag.ffi(),
+        );
+    }
+}
+
+// Declarations are split at semicolons without parsing their values, so the
+// ones that we don't touch are kept as they were, even if they have a
+// semicolon inside, as in a data: URL.
+fn set_style_declaration(style: &str, property: &str, value: &str) -> String {
+    let mut found = false;
+
+    let mut declarations: Vec<String> = style
+        .split(';')
+        .filter(|decl| !decl.trim().is_empty())
+        .map(|decl| match decl.find(':') {
+            Some(colon) if decl[..colon].trim() == property => {
+                found = true;
+                format!("{}: {}", property, value)
+            }
+
+            _ => decl.to_string(),
+        })
+        .collect();
+
+    if !found {
+        declarations.push(format!("{}: {}", property, value));
+    }
+
+    declarations.join(";")
+}
+
+/// Computes the state of an element in the same way as the drawing code, by
+/// reinheriting the element's own state from the computed state of its
+/// parent.  Free the result with `state::free()`.
+pub fn compute_state(node: &Node) -> *mut RsvgState {
+    match node.get_parent() {
+        Some(parent) => {
+            let parent_state = compute_state(&parent);
+            let state = compute_child_state(node, parent_state);
+            state::free(parent_state);
+
+            state
+        }
+
+        None => {
+            let state = state::new();
+            state::clone_from(state, node.get_state());
+
+            state
+        }
+    }
+}
+
+/// Computes the state of an element as if its parent had `parent_state`,
+/// for elements that get drawn somewhere else than in their place in the
+/// tree, like the contents of a `<use>`.  Free the result with `state::free()`.
+pub fn compute_child_state(node: &Node, parent_state: *const RsvgState) -> *mut RsvgState {
+    let state = state::new();
+    state::clone_from(state, node.get_state());
+    state::reinherit(state, parent_state);
+
+    state
+}
+
+macro_rules! computed_value {
+    ($rstate:expr, $field:ident, $type:ty) => {
+        Some(
+            $rstate
+                .$field
+                .as_ref()
+                .map_or_else(|| <$type>::default().to_string(), ToString::to_string),
+        )
+    };
+}
+
+macro_rules! computed_length {
+    ($rstate:expr, $field:ident, $type:ty, $font_size:expr) => {
+        Some(
+            absolute_length(
+                &$rstate
+                    .$field
+                    .as_ref()
+                    .map_or_else(|| <$type>::default().0, |v| v.0),
+                $font_size,
+            )
+            .to_string(),
+        )
+    };
+}
+
+macro_rules! computed_with {
+    ($rstate:expr, $field:ident, $to_string:expr) => {
+        Some($to_string(&$rstate.$field.clone().unwrap_or_default()))
+    };
+}
+
+/// Resolves the font size of an element in the same way as
+/// `drawing_ctx::normalize_font_size()`, but without a drawing context the
+/// result is kept in inches if it comes from a physical unit.
+pub fn compute_font_size(node: &Node) -> RsvgLength {
+    let parent_size = node.get_parent().map_or_else(
+        || FontSize::default().0,
+        |parent| compute_font_size(&parent),
+    );
+
+    compute_child_font_size(node, &parent_size)
+}
+
+/// Resolves the font size of an element whose parent has `parent_size`.
+pub fn compute_child_font_size(node: &Node, parent_size: &RsvgLength) -> RsvgLength {
+    let parent_size = *parent_size;
+
+    let font_size = match state::get_state_rust(node.get_state()).font_size {
+        Some(FontSize(font_size)) => font_size,
+        None => return parent_size,
+    };
+
+    match font_size.unit {
+        LengthUnit::Percent | LengthUnit::FontEm | LengthUnit::FontEx => {
+            scale_length(&parent_size, font_size.length)
+        }
+        LengthUnit::RelativeLarger => scale_length(&parent_size, 1.2),
+        LengthUnit::RelativeSmaller => scale_length(&parent_size, 1.0 / 1.2),
+
+        _ => font_size,
+    }
+}
+
+fn scale_length(length: &RsvgLength, factor: f64) -> RsvgLength {
+    RsvgLength::new(length.length * factor, length.unit, LengthDir::Both)
+}
+
+// Percentages are kept, since they depend on the viewport where the element
+// gets rendered.
+fn absolute_length(length: &RsvgLength, font_size: &RsvgLength) -> RsvgLength {
+    match length.unit {
+        LengthUnit::FontEm => scale_length(font_size, length.length),
+        LengthUnit::FontEx => scale_length(font_size, length.length / 2.0),
+        _ => *length,
+    }
+}
+
+/// Returns the computed value of a style property in `state`, as a CSS
+/// string, or `None` if librsvg does not support the property.  `font_size`
+/// is the resolved font size of the state, for properties in em and ex units.
+pub fn state_get_value(
+    state: *const RsvgState,
+    font_size: &RsvgLength,
+    property: &str,
+) -> Option<String> {
+    let rstate = state::get_state_rust(state);
+    let current_color = state::get_current_color(state);
+
+    match property {
+        "animation-delay" => computed_with!(rstate, animation_delay, |v: &AnimationDelay| {
+            list_to_string(&v.0, time_to_string)
+        }),
+        "animation-direction" => {
+            computed_with!(rstate, animation_direction, |v: &AnimationDirection| {
+                list_to_string(&v.0, animation_direction_to_string)
+            })
+        }
+        "animation-duration" => {
+            computed_with!(rstate, animation_duration, |v: &AnimationDuration| {
+                list_to_string(&v.0, time_to_string)
+            })
+        }
+        "animation-fill-mode" => {
+            computed_with!(rstate, animation_fill_mode, |v: &AnimationFillMode| {
+                list_to_string(&v.0, fill_mode_to_string)
+            })
+        }
+        "animation-iteration-count" => computed_with!(
+            rstate,
+            animation_iteration_count,
+            |v: &AnimationIterationCount| list_to_string(&v.0, iteration_count_to_string)
+        ),
+        "animation-name" => computed_with!(rstate, animation_name, |v: &AnimationName| {
+            v.0.join(", ")
+        }),
+        "animation-timing-function" => computed_with!(
+            rstate,
+            animation_timing_function,
+            |v: &AnimationTimingFunction| list_to_string(&v.0, timing_function_to_string)
+        ),
+        "baseline-shift" => computed_with!(rstate, baseline_shift, baseline_shift_to_string),
+        "clip-path" => Some(url_to_string(state::get_clip_path(state))),
+        "clip-rule" => computed_value!(rstate, clip_rule, ClipRule),
+        "color" => Some(color_to_string(&current_color)),
+        "comp-op" => computed_value!(rstate, comp_op, CompOp),
+        "direction" => Some(direction_to_string(state::get_direction(state))),
+        "display" => computed_value!(rstate, display, Display),
+        "enable-background" => computed_value!(rstate, enable_background, EnableBackground),
+        "fill" => Some(paint_to_string(state::get_fill(state))),
+        "fill-opacity" => Some(opacity_to_string(state::get_fill_opacity(state))),
+        "fill-rule" => computed_value!(rstate, fill_rule, FillRule),
+        "filter" => Some(url_to_string(state::get_filter(state))),
+        "flood-color" => Some(color_to_string(&state::get_flood_color(state))),
+        "flood-opacity" => Some(opacity_to_string(state::get_flood_opacity(state))),
+        "font-family" => computed_value!(rstate, font_family, FontFamily),
+        "font-feature-settings" => {
+            computed_with!(rstate, font_feature_settings, font_features_to_string)
+        }
+        "font-kerning" => computed_value!(rstate, font_kerning, FontKerning),
+        "font-size" => Some(font_size.to_string()),
+        "font-stretch" => computed_value!(rstate, font_stretch, FontStretch),
+        "font-style" => computed_value!(rstate, font_style, FontStyle),
+        "font-variant" => computed_value!(rstate, font_variant, FontVariant),
+        "font-variant-caps" => computed_value!(rstate, font_variant_caps, FontVariantCaps),
+        "font-variant-ligatures" => {
+            computed_with!(rstate, font_variant_ligatures, ligatures_to_string)
+        }
+        "font-variant-numeric" => computed_with!(rstate, font_variant_numeric, numeric_to_string),
+        "font-variant-position" => {
+            computed_value!(rstate, font_variant_position, FontVariantPosition)
+        }
+        "font-weight" => computed_value!(rstate, font_weight, FontWeight),
+        "glyph-orientation-vertical" => computed_with!(
+            rstate,
+            glyph_orientation_vertical,
+            glyph_orientation_to_string
+        ),
+        "inline-size" => computed_length!(rstate, inline_size, InlineSize, font_size),
+        "letter-spacing" => computed_length!(rstate, letter_spacing, LetterSpacing, font_size),
+        "marker-end" => Some(url_to_string(state::get_end_marker(state))),
+        "marker-mid" => Some(url_to_string(state::get_middle_marker(state))),
+        "marker-start" => Some(url_to_string(state::get_start_marker(state))),
+        "mask" => Some(url_to_string(state::get_mask(state))),
+        "opacity" => Some(opacity_to_string(state::get_opacity(state))),
+        "overflow" => computed_value!(rstate, overflow, Overflow),
+        "pointer-events" => computed_value!(rstate, pointer_events, PointerEvents),
+        "shape-inside" => computed_with!(rstate, shape_inside, |v: &ShapeInside| {
+            url_to_string(v.0.as_ref().map(String::as_str))
+        }),
+        "shape-rendering" => computed_value!(rstate, shape_rendering, ShapeRendering),
+        "stop-color" => Some(color_to_string(&resolve_color(
+            state::get_stop_color(state)
+                .ok()
+                .and_then(|color| color)
+                .unwrap_or_else(|| Color::from(0xff00_0000)),
+            &current_color,
+        ))),
+        "stop-opacity" => Some(match state::get_stop_opacity(state) {
+            Ok(Some(Opacity::Specified(opacity))) => opacity.to_string(),
+            _ => "1".to_string(),
+        }),
+        "stroke" => Some(paint_to_string(state::get_stroke(state))),
+        "stroke-dasharray" => Some(dasharray_to_string(
+            state::get_stroke_dasharray(state),
+            font_size,
+        )),
+        "stroke-dashoffset" => {
+            Some(absolute_length(&state::get_dash_offset(state), font_size).to_string())
+        }
+        "stroke-linecap" => computed_value!(rstate, stroke_line_cap, StrokeLinecap),
+        "stroke-linejoin" => computed_value!(rstate, stroke_line_join, StrokeLinejoin),
+        "stroke-miterlimit" => computed_value!(rstate, stroke_miterlimit, StrokeMiterlimit),
+        "stroke-opacity" => Some(opacity_to_string(state::get_stroke_opacity(state))),
+        "stroke-width" => computed_length!(rstate, stroke_width, StrokeWidth, font_size),
+        "text-anchor" => computed_value!(rstate, text_anchor, TextAnchor),
+        "text-decoration" => computed_with!(rstate, text_decoration, text_decoration_to_string),
+        "text-decoration-color" => {
+            computed_with!(rstate, text_decoration_color, |v: &TextDecorationColor| {
+                color_to_string(&resolve_color(v.0, &current_color))
+            })
+        }
+        "text-decoration-style" => {
+            computed_value!(rstate, text_decoration_style, TextDecorationStyle)
+        }
+        "text-orientation" => computed_value!(rstate, text_orientation, TextOrientation),
+        "text-rendering" => computed_value!(rstate, text_rendering, TextRendering),
+        "transition-delay" => computed_with!(rstate, transition_delay, |v: &TransitionDelay| {
+            list_to_string(&v.0, time_to_string)
+        }),
+        "transition-duration" => {
+            computed_with!(rstate, transition_duration, |v: &TransitionDuration| {
+                list_to_string(&v.0, time_to_string)
+            })
+        }
+        "transition-property" => {
+            computed_with!(rstate, transition_property, |v: &TransitionProperty| {
+                v.0.join(", ")
+            })
+        }
+        "transition-timing-function" => computed_with!(
+            rstate,
+            transition_timing_function,
+            |v: &TransitionTimingFunction| list_to_string(&v.0, timing_function_to_string)
+        ),
+        "unicode-bidi" => computed_value!(rstate, unicode_bidi, UnicodeBidi),
+        "visibility" => computed_value!(rstate, visibility, Visibility),
+        "word-spacing" => computed_length!(rstate, word_spacing, WordSpacing, font_size),
+        "writing-mode" => computed_value!(rstate, writing_mode, WritingMode),
+        "xml:lang" => computed_value!(rstate, xml_lang, XmlLang),
+        "xml:space" => computed_value!(rstate, xml_space, XmlSpace),
+        _ => None,
+    }
+}
+
+fn resolve_color(color: Color, current_color: &Color) -> Color {
+    match color {
+        Color::CurrentColor => *current_color,
+        _ => color,
+    }
+}
+
+fn url_to_string(url: Option<&str>) -> String {
+    url.map_or_else(|| "none".to_string(), |url| format!("url({})", url))
+}
+
+fn list_to_string<T, F>(items: &[T], to_string: F) -> String
+where
+    F: Fn(&T) -> String,
+{
+    items.iter().map(to_string).collect::<Vec<_>>().join(", ")
+}
+
+fn time_to_string(seconds: &f64) -> String {
+    format!("{}s", seconds)
+}
+
+fn animation_direction_to_string(direction: &Direction) -> String {
+    match *direction {
+        Direction::Normal => "normal",
+        Direction::Reverse => "reverse",
+        Direction::Alternate => "alternate",
+        Direction::AlternateReverse => "alternate-reverse",
+    }
+    .to_string()
+}
+
+fn fill_mode_to_string(fill_mode: &FillMode) -> String {
+    match *fill_mode {
+        FillMode::None => "none",
+        FillMode::Forwards => "forwards",
+        FillMode::Backwards => "backwards",
+        FillMode::Both => "both",
+    }
+    .to_string()
+}
+
+fn iteration_count_to_string(count: &IterationCount) -> String {
+    match *count {
+        IterationCount::Count(n) => n.to_string(),
+        IterationCount::Infinite => "infinite".to_string(),
+    }
+}
+
+fn timing_function_to_string(function: &TimingFunction) -> String {
+    match *function {
+        TimingFunction::CubicBezier(x1, y1, x2, y2) => {
+            format!("cubic-bezier({}, {}, {}, {})", x1, y1, x2, y2)
+        }
+
+        TimingFunction::Steps(steps, StepPosition::Start) => format!("steps({}, start)", steps),
+        TimingFunction::Steps(steps, StepPosition::End) => format!("steps({}, end)", steps),
+    }
+}
+
+// BaselineShift only stores the shifts of the keywords that we support.
+fn baseline_shift_to_string(shift: &BaselineShift) -> String {
+    if shift.0 < 0.0 {
+        "sub"
+    } else if shift.0 > 0.0 {
+        "super"
+    } else {
+        "baseline"
+    }
+    .to_string()
+}
+
+fn direction_to_string(direction: pango::Direction) -> String {
+    match direction {
+        pango::Direction::Rtl => "rtl",
+        _ => "ltr",
+    }
+    .to_string()
+}
+
+fn font_features_to_string(settings: &FontFeatureSettings) -> String {
+    if settings.0.is_empty() {
+        "normal".to_string()
+    } else {
+        list_to_string(&settings.0, |feature| {
+            format!("\"{}\" {}", feature.tag, feature.value)
+        })
+    }
+}
+
+fn keywords_to_string(keywords: &[(bool, &str)], none: &str) -> String {
+    let words: Vec<&str> = keywords
+        .iter()
+        .filter(|&&(set, _)| set)
+        .map(|&(_, word)| word)
+        .collect();
+
+    if words.is_empty() {
+        none.to_string()
+    } else {
+        words.join(" ")
+    }
+}
+
+fn ligatures_to_string(ligatures: &FontVariantLigatures) -> String {
+    let fields = [
+        (ligatures.common, "common-ligatures", "no-common-ligatures"),
+        (
+            ligatures.discretionary,
+            "discretionary-ligatures",
+            "no-discretionary-ligatures",
+        ),
+        (
+            ligatures.historical,
+            "historical-ligatures",
+            "no-historical-ligatures",
+        ),
+        (ligatures.contextual, "contextual", "no-contextual"),
+    ];
+
+    if fields.iter().all(|&(value, _, _)| value == Some(false)) {
+        return "none".to_string();
+    }
+
+    let keywords: Vec<(bool, &str)> = fields
+        .iter()
+        .filter_map(|&(value, on, off)| value.map(|v| (true, if v { on } else { off })))
+        .collect();
+
+    keywords_to_string(&keywords, "normal")
+}
+
+fn numeric_to_string(numeric: &FontVariantNumeric) -> String {
+    keywords_to_string(
+        &[
+            (numeric.lining, "lining-nums"),
+            (numeric.oldstyle, "oldstyle-nums"),
+            (numeric.proportional, "proportional-nums"),
+            (numeric.tabular, "tabular-nums"),
+            (numeric.diagonal_fractions, "diagonal-fractions"),
+            (numeric.stacked_fractions, "stacked-fractions"),
+            (numeric.ordinal, "ordinal"),
+            (numeric.slashed_zero, "slashed-zero"),
+        ],
+        "normal",
+    )
+}
+
+fn text_decoration_to_string(decoration: &TextDecoration) -> String {
+    keywords_to_string(
+        &[
+            (decoration.underline, "underline"),
+            (decoration.overline, "overline"),
+            (decoration.strike, "line-through"),
+        ],
+        "none",
+    )
+}
+
+fn glyph_orientation_to_string(orientation: &GlyphOrientationVertical) -> String {
+    match *orientation {
+        GlyphOrientationVertical::Auto => "auto",
+        GlyphOrientationVertical::Angle0 => "0deg",
+        GlyphOrientationVertical::Angle90 => "90deg",
+        GlyphOrientationVertical::Angle180 => "180deg",
+        GlyphOrientationVertical::Angle270 => "270deg",
+    }
+    .to_string()
+}
+
+fn dasharray_to_string(dasharray: Option<&StrokeDasharray>, font_size: &RsvgLength) -> String {
+    match dasharray {
+        Some(&StrokeDasharray::Dasharray(ref lengths)) => list_to_string(lengths, |length| {
+            absolute_length(length, font_size).to_string()
+        }),
+
+        _ => "none".to_string(),
+    }
+}
+
+pub fn color_to_string(color: &Color) -> String {
+    match *color {
+        Color::Inherit => "inherit".to_string(),
+        Color::CurrentColor => "currentColor".to_string(),
+
+        Color::RGBA(rgba) => {
+            if rgba.alpha == 0xff {
+                format!("rgb({}, {}, {})", rgba.red, rgba.green, rgba.blue)
+            } else {
+                format!(
+                    "rgba({}, {}, {}, {})",
+                    rgba.red,
+                    rgba.green,
+                    rgba.blue,
+                    opacity_to_string(rgba.alpha)
+                )
+            }
+        }
+    }
+}
+
+pub fn paint_to_string(paint: Option<&PaintServer>) -> String {
+    match paint {
+        None => "none".to_string(),
+
+        Some(&PaintServer::SolidColor(ref color)) => color_to_string(color),
+
+        Some(&PaintServer::Iri {
+            ref iri,
+            alternate: None,
+        }) => format!("url({})", iri),
+
+        Some(&PaintServer::Iri {
+            ref iri,
+            alternate: Some(ref color),
+        }) => format!("url({}) {}", iri, color_to_string(color)),
+    }
+}
+
+pub fn opacity_to_string(opacity: u8) -> String {
+    (f64::from(opacity) / 255.0).to_string()
+}
+
+// In the C API, an RsvgElement is a borrowed pointer to a Node.  It remains
+// valid for as long as the handle that owns the tree.
+
+fn element_ptr(node: &Node) -> *const Node {
+    node as *const Node
+}
+
+fn get_element<'a>(raw_element: *const Node) -> &'a Node {
+    assert!(!raw_element.is_null());
+    unsafe { &*raw_element }
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_dom_element_from_node(raw_node: *const RsvgNode) -> *const Node {
+    if raw_node.is_null() {
+        ptr::null()
+    } else {
+        let node: &RsvgNode = unsafe { &*raw_node };
+        element_ptr(node)
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_dom_element_get_name(raw_element: *const Node) -> *mut libc::c_char {
+    get_element(raw_element).get_element_name().to_glib_full()
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_dom_element_get_parent(raw_element: *const Node) -> *const Node {
+    get_element(raw_element)
+        .get_parent()
+        .map_or(ptr::null(), |parent| element_ptr(&parent))
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_dom_element_get_n_children(raw_element: *const Node) -> libc::c_uint {
+    get_element_children(get_element(raw_element)).len() as libc::c_uint
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_dom_element_get_child(
+    raw_element: *const Node,
+    index: libc::c_uint,
+) -> *const Node {
+    get_element_children(get_element(raw_element))
+        .get(index as usize)
+        .map_or(ptr::null(), |child| element_ptr(child))
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_dom_element_get_attribute(
+    raw_element: *const Node,
+    name: *const libc::c_char,
+) -> *mut libc::c_char {
+    assert!(!name.is_null());
+    let name: String = unsafe { from_glib_none(name) };
+
+    get_element(raw_element).get_attribute(&name).to_glib_full()
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_dom_element_get_attribute_names(
+    raw_element: *const Node,
+) -> *mut *mut libc::c_char {
+    get_element(raw_element)
+        .get_attribute_names()
+        .to_glib_full()
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_dom_element_get_computed_style(
+    raw_element: *const Node,
+    property: *const libc::c_char,
+) -> *mut libc::c_char {
+    assert!(!property.is_null());
+    let property: String = unsafe { from_glib_none(property) };
+
+    get_computed_value(get_element(raw_element), &property).to_glib_full()
+}
+
+fn set_attribute_result_to_glib(
+    result: Result<(), SetAttributeError>,
+    out_message: *mut *mut libc::c_char,
+) -> glib_sys::gboolean {
+    assert!(!out_message.is_null());
+
+    match result {
+        Ok(()) => true.to_glib(),
+
+        Err(e) => {
+            unsafe {
+                *out_message = e.to_string().to_glib_full();
+            }
+
+            false.to_glib()
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_dom_node_set_attribute(
+    handle: *const RsvgHandle,
+    raw_node: *const RsvgNode,
+    name: *const libc::c_char,
+    value: *const libc::c_char,
+    out_message: *mut *mut libc::c_char,
+) -> glib_sys::gboolean {
+    assert!(!raw_node.is_null());
+    let node: &RsvgNode = unsafe { &*raw_node };
+
+    assert!(!name.is_null());
+    assert!(!value.is_null());
+    let name: String = unsafe { from_glib_none(name) };
+    let value: String = unsafe { from_glib_none(value) };
+
+    set_attribute_result_to_glib(set_attribute(handle, node, &name, &value), out_message)
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_dom_node_set_style_property(
+    handle: *const RsvgHandle,
+    raw_node: *const RsvgNode,
+    property: *const libc::c_char,
+    value: *const libc::c_char,
+    out_message: *mut *mut libc::c_char,
+) -> glib_sys::gboolean {
+    assert!(!raw_node.is_null());
+    let node: &RsvgNode = unsafe { &*raw_node };
+
+    assert!(!property.is_null());
+    assert!(!value.is_null());
+    let property: String = unsafe { from_glib_none(property) };
+    let value: String = unsafe { from_glib_none(value) };
+
+    set_attribute_result_to_glib(
+        set_style_property(handle, node, &property, &value),
+        out_message,
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_dom_node_set_text_content(
+    raw_node: *const RsvgNode,
+    text: *const libc::c_char,
+    out_message: *mut *mut libc::c_char,
+) -> glib_sys::gboolean {
+    assert!(!raw_node.is_null());
+    let node: &RsvgNode = unsafe { &*raw_node };
+
+    assert!(!text.is_null());
+    let text: String = unsafe { from_glib_none(text) };
+
+    set_attribute_result_to_glib(set_text_content(node, &text), out_message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cssparser;
+
+    #[test]
+    fn formats_paint() {
+        let red = Color::RGBA(cssparser::RGBA::new(255, 0, 0, 255));
+        let translucent = Color::RGBA(cssparser::RGBA::new(0, 0, 255, 0));
+
+        assert_eq!(paint_to_string(None), "none");
+        assert_eq!(
+            paint_to_string(Some(&PaintServer::SolidColor(red))),
+            "rgb(255, 0, 0)"
+        );
+        assert_eq!(
+            paint_to_string(Some(&PaintServer::SolidColor(translucent))),
+            "rgba(0, 0, 255, 0)"
+        );
+        assert_eq!(
+            paint_to_string(Some(&PaintServer::Iri {
+                iri: "#grad".to_string(),
+                alternate: Some(Color::CurrentColor),
+            })),
+            "url(#grad) currentColor"
+        );
+    }
+
+    #[test]
+    fn sets_style_declarations() {
+        assert_eq!(set_style_declaration("", "fill", "red"), "fill: red");
+        assert_eq!(
+            set_style_declaration("fill: blue; stroke: green;", "fill", "red"),
+            "fill: red; stroke: green"
+        );
+        assert_eq!(
+            set_style_declaration("stroke:green", "fill", "red"),
+            "stroke:green;fill: red"
+        );
+        assert_eq!(
+            set_style_declaration(
+                "fill: url(data:image/png;base64,AAAA); opacity: 1",
+                "opacity",
+                "0.5"
+            ),
+            "fill: url(data:image/png;base64,AAAA);opacity: 0.5"
+        );
+    }
+
+    #[test]
+    fn resolves_font_relative_lengths() {
+        let font_size = RsvgLength::new(0.25, LengthUnit::Inch, LengthDir::Both);
+
+        assert_eq!(
+            absolute_length(
+                &RsvgLength::new(2.0, LengthUnit::FontEm, LengthDir::Both),
+                &font_size
+            )
+            .to_string(),
+            "0.5in"
+        );
+        assert_eq!(
+            absolute_length(
+                &RsvgLength::new(0.5, LengthUnit::Percent, LengthDir::Both),
+                &font_size
+            )
+            .to_string(),
+            "50%"
+        );
+    }
+
+    #[test]
+    fn formats_keyword_lists() {
+        let ligatures = FontVariantLigatures {
+            common: Some(false),
+            discretionary: None,
+            historical: Some(true),
+            contextual: None,
+        };
+
+        assert_eq!(
+            ligatures_to_string(&ligatures),
+            "no-common-ligatures historical-ligatures"
+        );
+        assert_eq!(
+            ligatures_to_string(&FontVariantLigatures::default()),
+            "normal"
+        );
+        assert_eq!(
+            text_decoration_to_string(&TextDecoration::default()),
+            "none"
+        );
+    }
+
+    #[test]
+    fn formats_opacity() {
+        assert_eq!(opacity_to_string(255), "1");
+        assert_eq!(opacity_to_string(0), "0");
+    }
+}
@@ -1,14 +1,21 @@
 use cairo;
+use cairo_sys;
 use glib::translate::*;
-use pango::{self, ContextExt, LayoutExt};
+use glib_sys;
+use pango::{self, LayoutExt};
 use pango_sys;
 use pangocairo;
+use std::mem;
+use std::ptr;
 
 use bbox::RsvgBbox;
 use drawing_ctx::{self, RsvgDrawingCtx};
 use float_eq_cairo::ApproxEqCairo;
+use geometry;
+use hit_test;
 use length::StrokeDasharray;
-use paint_server;
+use node::RsvgNode;
+use paint_server::{self, PaintServer};
 use path_builder::PathBuilder;
 use state::{
     self,
@@ -24,8 +31,31 @@ use state::{
     TextRendering,
 };
 use text;
+use text_outline;
+use text_run;
 
-pub fn draw_path_builder(draw_ctx: *mut RsvgDrawingCtx, builder: &PathBuilder, clipping: bool) {
+extern "C" {
+    fn rsvg_pango_font_has_color_glyphs(font: *mut pango_sys::PangoFont) -> glib_sys::gboolean;
+
+    fn pango_cairo_show_glyph_string(
+        cr: *mut cairo_sys::cairo_t,
+        font: *mut pango_sys::PangoFont,
+        glyphs: *mut pango_sys::PangoGlyphString,
+    );
+
+    fn pango_cairo_glyph_string_path(
+        cr: *mut cairo_sys::cairo_t,
+        font: *mut pango_sys::PangoFont,
+        glyphs: *mut pango_sys::PangoGlyphString,
+    );
+}
+
+pub fn draw_path_builder(
+    draw_ctx: *mut RsvgDrawingCtx,
+    builder: &PathBuilder,
+    node: Option<&RsvgNode>,
+    clipping: bool,
+) {
     if !clipping {
         drawing_ctx::push_discrete_layer(draw_ctx, clipping);
     }
@@ -43,13 +73,21 @@ pub fn draw_path_builder(draw_ctx: *mut RsvgDrawingCtx, builder: &PathBuilder, c
     } else {
         cr.set_fill_rule(cairo::FillRule::from(rstate.fill_rule.unwrap_or_default()));
 
-        stroke_and_fill(&cr, draw_ctx);
+        stroke_and_fill(&cr, draw_ctx, Some(builder), node);
 
         drawing_ctx::pop_discrete_layer(draw_ctx, clipping);
     }
 }
 
-fn stroke_and_fill(cr: &cairo::Context, draw_ctx: *mut RsvgDrawingCtx) {
+// `builder` has the path that is set on `cr`, if it is known, to compute its
+// bounding boxes.  `node` is the shape whose path it is, if any, for hit
+// testing.
+fn stroke_and_fill(
+    cr: &cairo::Context,
+    draw_ctx: *mut RsvgDrawingCtx,
+    builder: Option<&PathBuilder>,
+    node: Option<&RsvgNode>,
+) {
     let state = drawing_ctx::get_current_state(draw_ctx);
     let rstate = state::get_state_rust(state);
 
@@ -59,13 +97,28 @@ fn stroke_and_fill(cr: &cairo::Context, draw_ctx: *mut RsvgDrawingCtx) {
 
     setup_cr_for_stroke(cr, draw_ctx, state);
 
-    let bbox = compute_bbox_from_stroke_and_fill(cr, state);
+    if let Some(node) = node {
+        hit_test::test_path(draw_ctx, cr, state, node);
+    }
+
+    let (fill_bbox, stroke_bbox) = compute_fill_and_stroke_bboxes(cr, state, builder);
+
+    let mut bbox = RsvgBbox::new(&rstate.affine);
+    bbox.insert(&fill_bbox);
+    if let Some(ref stroke_bbox) = stroke_bbox {
+        bbox.insert(stroke_bbox);
+    }
 
     // Update the bbox in the rendering context.  Below, we actually set the fill/stroke
     // patterns on the cairo_t.  That process requires the rendering context to have
     // an updated bbox; for example, for the coordinate system in patterns.
     drawing_ctx::insert_bbox(draw_ctx, &bbox);
 
+    if geometry::add_bboxes(draw_ctx, &fill_bbox, stroke_bbox.as_ref()) {
+        cr.new_path();
+        return;
+    }
+
     let fill = state::get_fill(state);
     let stroke = state::get_stroke(state);
 
@@ -227,21 +280,29 @@ fn setup_cr_for_stroke(
     );
 }
 
-fn compute_bbox_from_stroke_and_fill(cr: &cairo::Context, state: *mut RsvgState) -> RsvgBbox {
+/// Computes the bounding box of the fill of the path that is set on `cr`, and
+/// the bounding box of its stroke if it has one.
+///
+/// If the path's `builder` is known, the boxes are computed exactly from its
+/// geometry; otherwise, or for dashed strokes, they come from cairo's extents,
+/// which are slightly larger around curves.
+fn compute_fill_and_stroke_bboxes(
+    cr: &cairo::Context,
+    state: *mut RsvgState,
+    builder: Option<&PathBuilder>,
+) -> (RsvgBbox, Option<RsvgBbox>) {
     let rstate = state::get_state_rust(state);
 
-    let mut bbox = RsvgBbox::new(&rstate.affine);
-
     // Dropping the precision of cairo's bezier subdivision, yielding 2x
     // _rendering_ time speedups, are these rather expensive operations
     // really needed here? */
     let backup_tolerance = cr.get_tolerance();
     cr.set_tolerance(1.0);
 
-    // FIXME: See https://www.w3.org/TR/SVG/coords.html#ObjectBoundingBox for
+    // See https://www.w3.org/TR/SVG/coords.html#ObjectBoundingBox for
     // discussion on how to compute bounding boxes to be used for viewports and
-    // clipping.  It looks like we should be using cairo_path_extents() for
-    // that, not cairo_fill_extents().
+    // clipping.  The fill bbox is the geometry of the path, like
+    // cairo_path_extents() would give, and not the area that the fill paints.
     //
     // We may need to maintain *two* sets of bounding boxes - one for
     // viewports/clipping, and one for user applications like a
@@ -258,56 +319,91 @@ fn compute_bbox_from_stroke_and_fill(cr: &cairo::Context, state: *mut RsvgState)
     // paths for the icon's shape.  We need to be able to compute the bounding
     // rectangle's extents, even when it has no fill nor stroke.
 
-    {
-        let mut fb = RsvgBbox::new(&rstate.affine);
+    let mut fb = RsvgBbox::new(&rstate.affine);
 
-        let (x, y, w, h) = cr.fill_extents();
+    let fill_rect = match builder {
+        Some(builder) => builder.get_bbox().unwrap_or(cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 0.0,
+            height: 0.0,
+        }),
 
-        fb.set_rect(&cairo::Rectangle {
-            x,
-            y,
-            width: w - x,
-            height: h - y,
-        });
+        None => {
+            let (x, y, w, h) = cr.fill_extents();
 
-        bbox.insert(&fb);
-    }
+            cairo::Rectangle {
+                x,
+                y,
+                width: w - x,
+                height: h - y,
+            }
+        }
+    };
+
+    fb.set_rect(&fill_rect);
 
     // Bounding box for stroke
 
-    if state::get_stroke(state).is_some() {
+    let sb = if state::get_stroke(state).is_some() {
         let mut sb = RsvgBbox::new(&rstate.affine);
 
-        let (x, y, w, h) = cr.stroke_extents();
+        let exact = match builder {
+            Some(builder) if cr.get_dash_count() == 0 => Some(builder.get_stroke_bbox(
+                cr.get_line_width(),
+                cr.get_line_cap(),
+                cr.get_line_join(),
+                cr.get_miter_limit(),
+            )),
 
-        sb.set_rect(&cairo::Rectangle {
-            x,
-            y,
-            width: w - x,
-            height: h - y,
-        });
+            _ => None,
+        };
 
-        bbox.insert(&sb);
-    }
+        match exact {
+            Some(Some(rect)) => sb.set_rect(&rect),
+
+            // Nothing is stroked
+            Some(None) => (),
+
+            None => {
+                let (x, y, w, h) = cr.stroke_extents();
+
+                sb.set_rect(&cairo::Rectangle {
+                    x,
+                    y,
+                    width: w - x,
+                    height: h - y,
+                });
+            }
+        }
+
+        Some(sb)
+    } else {
+        None
+    };
 
     cr.set_tolerance(backup_tolerance);
 
-    bbox
+    (fb, sb)
 }
 
+/// Draws a Pango layout whose top-left corner is at `x, y`.
+///
+/// The `gravity` comes from the text's writing mode; for vertical text the
+/// whole layout gets rotated, even if its glyphs were shaped horizontally as
+/// with `text-orientation: sideways`.
 pub fn draw_pango_layout(
     draw_ctx: *mut RsvgDrawingCtx,
     layout: &pango::Layout,
     x: f64,
     y: f64,
+    gravity: pango::Gravity,
     clipping: bool,
 ) {
     let state = drawing_ctx::get_current_state(draw_ctx);
     let rstate = state::get_state_rust(state);
 
     let cr = drawing_ctx::get_cairo_context(draw_ctx);
-    let gravity = layout.get_context().unwrap().get_gravity();
-
     let (ink, _) = layout.get_extents();
 
     if ink.width == 0 || ink.height == 0 {
@@ -321,6 +417,11 @@ pub fn draw_pango_layout(
 
     if !clipping && (fill.is_some() || stroke.is_some()) {
         drawing_ctx::insert_bbox(draw_ctx, &bbox);
+        text_run::add_text_run(draw_ctx, state, layout, x, y, gravity);
+    }
+
+    if !clipping && geometry::add_bboxes(draw_ctx, &bbox, None) {
+        return;
     }
 
     cr.set_antialias(cairo::Antialias::from(
@@ -339,6 +440,14 @@ pub fn draw_pango_layout(
         cr.rotate(-rotation);
     }
 
+    pangocairo::functions::update_layout(&cr, layout);
+
+    // The outline is taken after updating the layout for the cr, so that it
+    // has the same glyph positions and hinting as what gets painted.
+    if !clipping && (fill.is_some() || stroke.is_some()) {
+        text_outline::add_layout_outline(draw_ctx, state, layout, x, y, gravity);
+    }
+
     if !clipping {
         if let Some(fill) = fill {
             if paint_server::_set_source_rsvg_paint_server(
@@ -348,8 +457,7 @@ pub fn draw_pango_layout(
                 &bbox,
                 state::get_current_color(state),
             ) {
-                pangocairo::functions::update_layout(&cr, layout);
-                pangocairo::functions::show_layout(&cr, layout);
+                show_layout(&cr, layout);
             }
         }
     }
@@ -370,10 +478,11 @@ pub fn draw_pango_layout(
     }
 
     if need_layout_path {
-        pangocairo::functions::update_layout(&cr, layout);
-        pangocairo::functions::layout_path(&cr, layout);
-
-        if !clipping {
+        if clipping {
+            pangocairo::functions::layout_path(&cr, layout);
+        } else {
+            // Color glyphs are not stroked
+            monochrome_layout_path(&cr, layout);
             cr.stroke();
         }
     }
@@ -381,6 +490,196 @@ pub fn draw_pango_layout(
     cr.restore();
 }
 
+/// A run of glyphs in a single font, as laid out in a `pango::Layout`.
+///
+/// The pointers are owned by the layout, and they are only valid until the
+/// layout changes.
+struct GlyphRun {
+    font: *mut pango_sys::PangoFont,
+    glyphs: *mut pango_sys::PangoGlyphString,
+    x: f64,
+    baseline: f64,
+    has_color: bool,
+}
+
+fn get_glyph_runs(layout: &pango::Layout) -> Vec<GlyphRun> {
+    let pango_scale = f64::from(pango::SCALE);
+    let mut runs = Vec::new();
+
+    unsafe {
+        let iter = pango_sys::pango_layout_get_iter(layout.to_glib_none().0);
+
+        loop {
+            let run = pango_sys::pango_layout_iter_get_run_readonly(iter);
+
+            // A null run is the end of a line
+            if !run.is_null() {
+                let mut logical: pango_sys::PangoRectangle = mem::zeroed();
+                pango_sys::pango_layout_iter_get_run_extents(iter, ptr::null_mut(), &mut logical);
+
+                let font = (*(*run).item).analysis.font;
+
+                runs.push(GlyphRun {
+                    font,
+                    glyphs: (*run).glyphs,
+                    x: f64::from(logical.x) / pango_scale,
+                    baseline: f64::from(pango_sys::pango_layout_iter_get_baseline(iter))
+                        / pango_scale,
+                    has_color: from_glib(rsvg_pango_font_has_color_glyphs(font)),
+                });
+            }
+
+            let more: bool = from_glib(pango_sys::pango_layout_iter_next_run(iter));
+            if !more {
+                break;
+            }
+        }
+
+        pango_sys::pango_layout_iter_free(iter);
+    }
+
+    runs
+}
+
+/// Shows a layout at the current point, like `pangocairo::functions::show_layout()`.
+///
+/// Glyphs from color fonts (emoji, for example) are drawn in their own colors;
+/// the rest of the glyphs are drawn with the current source.
+fn show_layout(cr: &cairo::Context, layout: &pango::Layout) {
+    let runs = get_glyph_runs(layout);
+
+    if !runs.iter().any(|r| r.has_color) {
+        pangocairo::functions::show_layout(cr, layout);
+        return;
+    }
+
+    let (x, y) = cr.get_current_point();
+
+    for run in &runs {
+        cr.move_to(x + run.x, y + run.baseline);
+
+        unsafe {
+            pango_cairo_show_glyph_string(cr.to_glib_none().0, run.font, run.glyphs);
+        }
+    }
+
+    cr.move_to(x, y);
+}
+
+/// Appends the outlines of a layout's glyphs to the current path, like
+/// `pangocairo::functions::layout_path()`, except for glyphs from color fonts.
+fn monochrome_layout_path(cr: &cairo::Context, layout: &pango::Layout) {
+    let runs = get_glyph_runs(layout);
+
+    if !runs.iter().any(|r| r.has_color) {
+        pangocairo::functions::layout_path(cr, layout);
+        return;
+    }
+
+    let (x, y) = cr.get_current_point();
+
+    for run in runs.iter().filter(|r| !r.has_color) {
+        cr.move_to(x + run.x, y + run.baseline);
+
+        unsafe {
+            pango_cairo_glyph_string_path(cr.to_glib_none().0, run.font, run.glyphs);
+        }
+    }
+}
+
+/// Draws the outline of text decorations (underline, overline, line-through)
+/// that was built in the layout's coordinate system.
+///
+/// The `builder` is positioned and rotated in the same way as the Pango layout
+/// that `draw_pango_layout()` draws at `x, y`.  Per the SVG spec, decorations are
+/// painted with the fill and stroke of the element that specified
+/// `text-decoration`, which is passed as `paint_state`.  If that element also
+/// specified `text-decoration-color`, the decoration is just filled with it.
+pub fn draw_text_decoration(
+    draw_ctx: *mut RsvgDrawingCtx,
+    builder: &PathBuilder,
+    x: f64,
+    y: f64,
+    gravity: pango::Gravity,
+    paint_state: *mut RsvgState,
+    clipping: bool,
+) {
+    let state = drawing_ctx::get_current_state(draw_ctx);
+    let affine = state::get_state_rust(state).affine;
+
+    let cr = drawing_ctx::get_cairo_context(draw_ctx);
+
+    drawing_ctx::set_affine_on_cr(draw_ctx, &cr, &affine);
+
+    let rotation = unsafe { pango_sys::pango_gravity_to_rotation(gravity.to_glib()) };
+
+    // The path is stored in device space, so after restoring the cr we are
+    // left with the decoration in the coordinate system of the state's affine,
+    // just like the rest of the shapes.
+    cr.save();
+    cr.translate(x, y);
+    if !rotation.approx_eq_cairo(&0.0) {
+        cr.rotate(-rotation);
+    }
+    builder.to_cairo(&cr);
+    cr.restore();
+
+    if clipping {
+        return;
+    }
+
+    text_outline::add_decoration_outline(draw_ctx, state, paint_state, builder, x, y, gravity);
+
+    cr.set_fill_rule(cairo::FillRule::Winding);
+
+    let color = state::get_state_rust(paint_state)
+        .text_decoration_color
+        .as_ref()
+        .map(|c| c.0);
+
+    if let Some(color) = color {
+        let mut bbox = RsvgBbox::new(&affine);
+
+        let (x1, y1, x2, y2) = cr.fill_extents();
+
+        bbox.set_rect(&cairo::Rectangle {
+            x: x1,
+            y: y1,
+            width: x2 - x1,
+            height: y2 - y1,
+        });
+
+        drawing_ctx::insert_bbox(draw_ctx, &bbox);
+
+        if geometry::add_bboxes(draw_ctx, &bbox, None) {
+            cr.new_path();
+            return;
+        }
+
+        if paint_server::_set_source_rsvg_paint_server(
+            draw_ctx,
+            &PaintServer::SolidColor(color),
+            state::get_fill_opacity(paint_state),
+            &bbox,
+            state::get_current_color(paint_state),
+        ) {
+            cr.fill();
+        }
+
+        cr.new_path();
+    } else {
+        drawing_ctx::state_push(draw_ctx);
+
+        let top = drawing_ctx::get_current_state(draw_ctx);
+        state::clone_from(top, paint_state);
+        state::get_state_rust(top).affine = affine;
+
+        stroke_and_fill(&cr, draw_ctx, None, None);
+
+        drawing_ctx::state_pop(draw_ctx);
+    }
+}
+
 fn compute_text_bbox(
     ink: &pango::Rectangle,
     x: f64,
@@ -398,9 +697,11 @@ fn compute_text_bbox(
     let ink_height = f64::from(ink.height);
 
     if text::gravity_is_vertical(gravity) {
+        // The layout is rotated 90 degrees clockwise, so a point (u, v) in
+        // the layout ends up at (x - v, y + u).
         bbox.set_rect(&cairo::Rectangle {
-            x: x + (ink_x - ink_height) / pango_scale,
-            y: y + ink_y / pango_scale,
+            x: x - (ink_y + ink_height) / pango_scale,
+            y: y + ink_x / pango_scale,
             width: ink_height / pango_scale,
             height: ink_width / pango_scale,
         });
@@ -453,6 +754,21 @@ pub fn draw_surface(
         height,
     });
 
+    drawing_ctx::insert_bbox(draw_ctx, &bbox);
+
+    // The image occupies its viewport, not the size of its pixels
+    let mut image_bbox = RsvgBbox::new(&affine);
+    image_bbox.set_rect(&cairo::Rectangle {
+        x,
+        y,
+        width: w,
+        height: h,
+    });
+
+    if geometry::add_bboxes(draw_ctx, &image_bbox, None) {
+        return;
+    }
+
     drawing_ctx::set_affine_on_cr(draw_ctx, &cr, &affine);
     cr.scale(w / width, h / height);
     let x = x * width / w;
@@ -462,8 +778,6 @@ pub fn draw_surface(
 
     cr.set_source_surface(&surface, x, y);
     cr.paint();
-
-    drawing_ctx::insert_bbox(draw_ctx, &bbox);
 }
 
 pub fn add_clipping_rect(draw_ctx: *mut RsvgDrawingCtx, x: f64, y: f64, w: f64, h: f64) {
@@ -487,3 +801,34 @@ pub extern "C" fn rsvg_cairo_add_clipping_rect(
 ) {
     add_clipping_rect(draw_ctx, x, y, w, h);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cairo::MatrixTrait;
+
+    #[test]
+    fn computes_bbox_of_vertical_text() {
+        let scale = pango::SCALE;
+
+        let ink = pango::Rectangle {
+            x: scale,
+            y: 2 * scale,
+            width: 30 * scale,
+            height: 10 * scale,
+        };
+
+        let bbox = compute_text_bbox(
+            &ink,
+            100.0,
+            50.0,
+            &cairo::Matrix::identity(),
+            pango::Gravity::East,
+        );
+
+        assert!(bbox.rect.x.approx_eq_cairo(&88.0));
+        assert!(bbox.rect.y.approx_eq_cairo(&51.0));
+        assert!(bbox.rect.width.approx_eq_cairo(&10.0));
+        assert!(bbox.rect.height.approx_eq_cairo(&30.0));
+    }
+}
@@ -60,6 +60,14 @@ extern "C" {
         draw_ctx: *const RsvgDrawingCtx,
     ) -> *mut pango_sys::PangoContext;
 
+    fn rsvg_drawing_ctx_get_fallback_font_families(
+        draw_ctx: *const RsvgDrawingCtx,
+    ) -> *const *const libc::c_char;
+
+    fn rsvg_drawing_ctx_get_font_options(
+        draw_ctx: *const RsvgDrawingCtx,
+    ) -> *const cairo_sys::cairo_font_options_t;
+
     fn rsvg_drawing_ctx_insert_bbox(draw_ctx: *const RsvgDrawingCtx, bbox: *const RsvgBbox);
 
     fn rsvg_drawing_ctx_draw_node_from_stack(
@@ -262,6 +270,32 @@ pub fn get_pango_context(draw_ctx: *const RsvgDrawingCtx) -> pango::Context {
     unsafe { from_glib_full(rsvg_drawing_ctx_get_pango_context(draw_ctx)) }
 }
 
+pub fn get_fallback_font_families(draw_ctx: *const RsvgDrawingCtx) -> Vec<String> {
+    unsafe {
+        let families = rsvg_drawing_ctx_get_fallback_font_families(draw_ctx);
+
+        if families.is_null() {
+            Vec::new()
+        } else {
+            FromGlibPtrContainer::from_glib_none(families as *mut *mut libc::c_char)
+        }
+    }
+}
+
+pub fn get_font_options(draw_ctx: *const RsvgDrawingCtx) -> Option<cairo::FontOptions> {
+    unsafe {
+        let options = rsvg_drawing_ctx_get_font_options(draw_ctx);
+
+        if options.is_null() {
+            None
+        } else {
+            Some(from_glib_none(
+                options as *mut cairo_sys::cairo_font_options_t,
+            ))
+        }
+    }
+}
+
 pub fn insert_bbox(draw_ctx: *const RsvgDrawingCtx, bbox: &RsvgBbox) {
     unsafe {
         rsvg_drawing_ctx_insert_bbox(draw_ctx, bbox as *const _);
@@ -41,6 +41,23 @@ impl NodeError {
             err: error,
         }
     }
+
+    pub fn get_attr_name(&self) -> &str {
+        &self.attr_name
+    }
+
+    pub fn get_error(&self) -> &AttributeError {
+        &self.err
+    }
+}
+
+impl fmt::Display for AttributeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AttributeError::Parse(ref n) => write!(f, "{}", n.display),
+            AttributeError::Value(ref s) => write!(f, "{}", s),
+        }
+    }
 }
 
 impl error::Error for NodeError {
@@ -55,16 +72,16 @@ impl error::Error for NodeError {
 impl fmt::Display for NodeError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.err {
-            AttributeError::Parse(ref n) => write!(
+            AttributeError::Parse(_) => write!(
                 f,
                 "error parsing value for attribute \"{}\": {}",
-                self.attr_name, n.display
+                self.attr_name, self.err
             ),
 
-            AttributeError::Value(ref s) => write!(
+            AttributeError::Value(_) => write!(
                 f,
                 "invalid value for attribute \"{}\": {}",
-                self.attr_name, s
+                self.attr_name, self.err
             ),
         }
     }
@@ -0,0 +1,679 @@
+use libc;
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use attributes::Attribute;
+use drawing_ctx::RsvgDrawingCtx;
+use error::*;
+use handle::RsvgHandle;
+use node::*;
+use parsers;
+use path_builder::{PathBuilder, PathCommand};
+use path_parser;
+use property_bag::PropertyBag;
+
+/// An entry in the `unicode` lists of an `<hkern>` element; either a
+/// string of characters, or a range of code points like `U+0041-005A`.
+#[derive(Debug, PartialEq)]
+enum UnicodeEntry {
+    Chars(String),
+    Range(u32, u32),
+}
+
+impl UnicodeEntry {
+    fn matches(&self, unicode: &str) -> bool {
+        match *self {
+            UnicodeEntry::Chars(ref s) => s == unicode,
+
+            UnicodeEntry::Range(first, last) => {
+                let mut chars = unicode.chars();
+
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => first <= c as u32 && c as u32 <= last,
+                    _ => false,
+                }
+            }
+        }
+    }
+}
+
+fn parse_unicode_range(s: &str) -> Option<UnicodeEntry> {
+    let range = &s[2..];
+
+    if let Some(dash) = range.find('-') {
+        let first = u32::from_str_radix(&range[..dash], 16).ok()?;
+        let last = u32::from_str_radix(&range[dash + 1..], 16).ok()?;
+
+        Some(UnicodeEntry::Range(first, last))
+    } else {
+        // Wildcards like U+00?? stand for all the digits
+        let first = u32::from_str_radix(&range.replace('?', "0"), 16).ok()?;
+        let last = u32::from_str_radix(&range.replace('?', "F"), 16).ok()?;
+
+        Some(UnicodeEntry::Range(first, last))
+    }
+}
+
+fn parse_unicode_list(s: &str) -> Vec<UnicodeEntry> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            if entry.starts_with("U+") {
+                parse_unicode_range(entry)
+            } else {
+                Some(UnicodeEntry::Chars(entry.to_string()))
+            }
+        })
+        .collect()
+}
+
+fn parse_name_list(s: &str) -> Vec<String> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn parse_number(key: &str, value: &str) -> Result<f64, NodeError> {
+    parsers::number(value).map_err(|e| NodeError::parse_error(key, e))
+}
+
+/// A `<glyph>` or `<missing-glyph>`.  Its outline is in the font's
+/// coordinate system, where the y axis points upwards.
+pub struct Glyph {
+    unicode: Option<String>,
+    name: Option<String>,
+    horiz_adv_x: Option<f64>,
+    path: PathBuilder,
+}
+
+impl Default for Glyph {
+    fn default() -> Glyph {
+        Glyph {
+            unicode: None,
+            name: None,
+            horiz_adv_x: None,
+            path: PathBuilder::new(),
+        }
+    }
+}
+
+/// The kerning adjustment of an `<hkern>` element, which applies to pairs
+/// of glyphs whose first one is in `u1` or `g1`, and whose second one is in
+/// `u2` or `g2`.
+#[derive(Default)]
+pub struct KerningPair {
+    u1: Vec<UnicodeEntry>,
+    g1: Vec<String>,
+    u2: Vec<UnicodeEntry>,
+    g2: Vec<String>,
+    k: f64,
+}
+
+impl KerningPair {
+    fn matches(&self, first: &Glyph, second: &Glyph) -> bool {
+        glyph_in_class(first, &self.u1, &self.g1) && glyph_in_class(second, &self.u2, &self.g2)
+    }
+}
+
+fn glyph_in_class(glyph: &Glyph, unicode: &[UnicodeEntry], names: &[String]) -> bool {
+    let in_unicode = glyph
+        .unicode
+        .as_ref()
+        .map_or(false, |u| unicode.iter().any(|entry| entry.matches(u)));
+
+    let in_names = glyph
+        .name
+        .as_ref()
+        .map_or(false, |n| names.iter().any(|name| name == n));
+
+    in_unicode || in_names
+}
+
+pub struct NodeFont {
+    horiz_adv_x: Cell<f64>,
+}
+
+impl NodeFont {
+    fn new() -> NodeFont {
+        NodeFont {
+            horiz_adv_x: Cell::new(0.0),
+        }
+    }
+}
+
+impl NodeTrait for NodeFont {
+    fn set_atts(&self, _: &RsvgNode, _: *const RsvgHandle, pbag: &PropertyBag) -> NodeResult {
+        for (_key, attr, value) in pbag.iter() {
+            if attr == Attribute::HorizAdvX {
+                self.horiz_adv_x.set(parse_number("horiz-adv-x", value)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw(&self, _: &RsvgNode, _: *mut RsvgDrawingCtx, _: i32, _: bool) {
+        // nothing; fonts are only used when rendering text
+    }
+
+    fn get_c_impl(&self) -> *const RsvgCNodeImpl {
+        unreachable!();
+    }
+}
+
+pub struct NodeFontFace {
+    family: RefCell<Option<String>>,
+    units_per_em: Cell<f64>,
+}
+
+impl NodeFontFace {
+    fn new() -> NodeFontFace {
+        NodeFontFace {
+            family: RefCell::new(None),
+            units_per_em: Cell::new(1000.0),
+        }
+    }
+}
+
+impl NodeTrait for NodeFontFace {
+    fn set_atts(&self, _: &RsvgNode, _: *const RsvgHandle, pbag: &PropertyBag) -> NodeResult {
+        for (_key, attr, value) in pbag.iter() {
+            match attr {
+                Attribute::FontFamily => {
+                    let family = value.trim().trim_matches(|c| c == '"' || c == '\'');
+                    *self.family.borrow_mut() = Some(family.to_string());
+                }
+
+                Attribute::UnitsPerEm => {
+                    let units_per_em = parse_number("units-per-em", value)?;
+
+                    if units_per_em <= 0.0 {
+                        return Err(NodeError::value_error(
+                            "units-per-em",
+                            "value must be positive",
+                        ));
+                    }
+
+                    self.units_per_em.set(units_per_em);
+                }
+
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw(&self, _: &RsvgNode, _: *mut RsvgDrawingCtx, _: i32, _: bool) {
+        // nothing
+    }
+
+    fn get_c_impl(&self) -> *const RsvgCNodeImpl {
+        unreachable!();
+    }
+}
+
+/// Used for both `<glyph>` and `<missing-glyph>`; the latter just
+/// doesn't have `unicode` or `glyph-name`.
+pub struct NodeGlyph {
+    glyph: RefCell<Rc<Glyph>>,
+}
+
+impl NodeGlyph {
+    fn new() -> NodeGlyph {
+        NodeGlyph {
+            glyph: RefCell::new(Rc::new(Glyph::default())),
+        }
+    }
+}
+
+impl NodeTrait for NodeGlyph {
+    fn set_atts(&self, node: &RsvgNode, _: *const RsvgHandle, pbag: &PropertyBag) -> NodeResult {
+        let mut glyph = Glyph::default();
+
+        for (_key, attr, value) in pbag.iter() {
+            match attr {
+                Attribute::Unicode => glyph.unicode = Some(value.to_string()),
+
+                Attribute::GlyphName => glyph.name = Some(value.to_string()),
+
+                Attribute::HorizAdvX => {
+                    glyph.horiz_adv_x = Some(parse_number("horiz-adv-x", value)?)
+                }
+
+                Attribute::D => {
+                    // Creating a partial path is OK per the spec
+                    if let Err(e) = path_parser::parse_path_into_builder(value, &mut glyph.path) {
+                        if !value.trim().is_empty() {
+                            node.add_warning(NodeError::parse_error(
+                                "d",
+                                parsers::ParseError::new(e.to_string()),
+                            ));
+                        }
+                    }
+                }
+
+                _ => (),
+            }
+        }
+
+        *self.glyph.borrow_mut() = Rc::new(glyph);
+
+        Ok(())
+    }
+
+    fn draw(&self, _: &RsvgNode, _: *mut RsvgDrawingCtx, _: i32, _: bool) {
+        // nothing
+    }
+
+    fn get_c_impl(&self) -> *const RsvgCNodeImpl {
+        unreachable!();
+    }
+}
+
+pub struct NodeHKern {
+    pair: RefCell<Rc<KerningPair>>,
+}
+
+impl NodeHKern {
+    fn new() -> NodeHKern {
+        NodeHKern {
+            pair: RefCell::new(Rc::new(KerningPair::default())),
+        }
+    }
+}
+
+impl NodeTrait for NodeHKern {
+    fn set_atts(&self, _: &RsvgNode, _: *const RsvgHandle, pbag: &PropertyBag) -> NodeResult {
+        let mut pair = KerningPair::default();
+
+        for (_key, attr, value) in pbag.iter() {
+            match attr {
+                Attribute::U1 => pair.u1 = parse_unicode_list(value),
+                Attribute::G1 => pair.g1 = parse_name_list(value),
+                Attribute::U2 => pair.u2 = parse_unicode_list(value),
+                Attribute::G2 => pair.g2 = parse_name_list(value),
+                Attribute::K => pair.k = parse_number("k", value)?,
+                _ => (),
+            }
+        }
+
+        *self.pair.borrow_mut() = Rc::new(pair);
+
+        Ok(())
+    }
+
+    fn draw(&self, _: &RsvgNode, _: *mut RsvgDrawingCtx, _: i32, _: bool) {
+        // nothing
+    }
+
+    fn get_c_impl(&self) -> *const RsvgCNodeImpl {
+        unreachable!();
+    }
+}
+
+/// The glyphs and kerning pairs of a `<font>` element, gathered for
+/// laying out a piece of text.
+pub struct SvgFont {
+    units_per_em: f64,
+    horiz_adv_x: f64,
+    glyphs: Vec<Rc<Glyph>>,
+    missing_glyph: Option<Rc<Glyph>>,
+    kerning: Vec<Rc<KerningPair>>,
+}
+
+impl SvgFont {
+    fn from_node(node: &RsvgNode) -> SvgFont {
+        let mut font = SvgFont {
+            units_per_em: 1000.0,
+            horiz_adv_x: 0.0,
+            glyphs: Vec::new(),
+            missing_glyph: None,
+            kerning: Vec::new(),
+        };
+
+        node.with_impl(|f: &NodeFont| font.horiz_adv_x = f.horiz_adv_x.get());
+
+        for child in node.children() {
+            match child.get_type() {
+                NodeType::FontFace => {
+                    child.with_impl(|face: &NodeFontFace| {
+                        font.units_per_em = face.units_per_em.get();
+                    });
+                }
+
+                NodeType::Glyph => {
+                    child.with_impl(|g: &NodeGlyph| font.glyphs.push(g.glyph.borrow().clone()));
+                }
+
+                NodeType::MissingGlyph => {
+                    child.with_impl(|g: &NodeGlyph| {
+                        font.missing_glyph = Some(g.glyph.borrow().clone());
+                    });
+                }
+
+                NodeType::HKern => {
+                    child.with_impl(|h: &NodeHKern| font.kerning.push(h.pair.borrow().clone()));
+                }
+
+                _ => (),
+            }
+        }
+
+        font
+    }
+
+    /// Finds the glyph for the start of `text`.  Per the spec, the first
+    /// glyph in document order whose `unicode` matches is used, so fonts
+    /// must list ligatures before the glyphs for their individual characters.
+    fn find_glyph(&self, text: &str) -> Option<&Rc<Glyph>> {
+        self.glyphs.iter().find(|glyph| {
+            glyph
+                .unicode
+                .as_ref()
+                .map_or(false, |u| !u.is_empty() && text.starts_with(u.as_str()))
+        })
+    }
+
+    fn get_kerning(&self, first: &Glyph, second: &Glyph) -> f64 {
+        self.kerning
+            .iter()
+            .find(|pair| pair.matches(first, second))
+            .map_or(0.0, |pair| pair.k)
+    }
+
+    /// Lays out `text` on a baseline that starts at `(x, y)`.
+    ///
+    /// Returns the outlines of the glyphs in user space, and the total
+    /// advance of the text.
+    pub fn layout(
+        &self,
+        text: &str,
+        x: f64,
+        y: f64,
+        font_size: f64,
+        letter_spacing: f64,
+    ) -> (PathBuilder, f64) {
+        let scale = font_size / self.units_per_em;
+
+        let mut builder = PathBuilder::new();
+        let mut pen = x;
+        let mut prev: Option<&Rc<Glyph>> = None;
+        let mut rest = text;
+
+        while let Some(c) = rest.chars().next() {
+            let glyph = match self.find_glyph(rest) {
+                Some(glyph) => {
+                    rest = &rest[glyph.unicode.as_ref().unwrap().len()..];
+                    Some(glyph)
+                }
+
+                None => {
+                    rest = &rest[c.len_utf8()..];
+                    self.missing_glyph.as_ref()
+                }
+            };
+
+            match glyph {
+                Some(glyph) => {
+                    if let Some(prev) = prev {
+                        pen -= self.get_kerning(prev, glyph) * scale;
+                    }
+
+                    append_glyph_outline(&mut builder, &glyph.path, pen, y, scale);
+
+                    pen += glyph.horiz_adv_x.unwrap_or(self.horiz_adv_x) * scale + letter_spacing;
+                }
+
+                None => pen += self.horiz_adv_x * scale + letter_spacing,
+            }
+
+            prev = glyph;
+        }
+
+        (builder, pen - x)
+    }
+}
+
+/// Appends a glyph's outline to `builder`, converting it from the font's
+/// coordinate system to user space with its origin at `(x, y)`.
+fn append_glyph_outline(
+    builder: &mut PathBuilder,
+    outline: &PathBuilder,
+    x: f64,
+    y: f64,
+    scale: f64,
+) {
+    let transform = |px: f64, py: f64| (x + px * scale, y - py * scale);
+
+    for command in outline.get_path_commands() {
+        match *command {
+            PathCommand::MoveTo(px, py) => {
+                let (tx, ty) = transform(px, py);
+                builder.move_to(tx, ty);
+            }
+
+            PathCommand::LineTo(px, py) => {
+                let (tx, ty) = transform(px, py);
+                builder.line_to(tx, ty);
+            }
+
+            PathCommand::CurveTo((x2, y2), (x3, y3), (x4, y4)) => {
+                let (tx2, ty2) = transform(x2, y2);
+                let (tx3, ty3) = transform(x3, y3);
+                let (tx4, ty4) = transform(x4, y4);
+                builder.curve_to(tx2, ty2, tx3, ty3, tx4, ty4);
+            }
+
+            PathCommand::ClosePath => builder.close_path(),
+        }
+    }
+}
+
+fn font_has_family(node: &RsvgNode, family: &str) -> bool {
+    node.children()
+        .filter(|child| child.get_type() == NodeType::FontFace)
+        .any(|child| {
+            let mut matches = false;
+
+            child.with_impl(|face: &NodeFontFace| {
+                matches = face
+                    .family
+                    .borrow()
+                    .as_ref()
+                    .map_or(false, |f| f.eq_ignore_ascii_case(family));
+            });
+
+            matches
+        })
+}
+
+fn find_font_node(node: &RsvgNode, family: &str) -> Option<RsvgNode> {
+    for child in node.children() {
+        if child.get_type() == NodeType::Font {
+            if font_has_family(&child, family) {
+                return Some(child);
+            }
+        } else if let Some(font) = find_font_node(&child, family) {
+            return Some(font);
+        }
+    }
+
+    None
+}
+
+/// Looks for an SVG font in the document of `node` for one of the
+/// families in a `font-family` list.
+pub fn find_svg_font(node: &RsvgNode, font_family: &str) -> Option<SvgFont> {
+    let mut root = node.clone();
+
+    while let Some(parent) = root.get_parent() {
+        root = parent;
+    }
+
+    font_family
+        .split(',')
+        .map(|family| family.trim().trim_matches(|c| c == '"' || c == '\''))
+        .filter_map(|family| find_font_node(&root, family))
+        .next()
+        .map(|font| SvgFont::from_node(&font))
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_node_font_new(
+    _: *const libc::c_char,
+    raw_parent: *const RsvgNode,
+) -> *const RsvgNode {
+    boxed_node_new(NodeType::Font, raw_parent, Box::new(NodeFont::new()))
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_node_font_face_new(
+    _: *const libc::c_char,
+    raw_parent: *const RsvgNode,
+) -> *const RsvgNode {
+    boxed_node_new(
+        NodeType::FontFace,
+        raw_parent,
+        Box::new(NodeFontFace::new()),
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_node_glyph_new(
+    _: *const libc::c_char,
+    raw_parent: *const RsvgNode,
+) -> *const RsvgNode {
+    boxed_node_new(NodeType::Glyph, raw_parent, Box::new(NodeGlyph::new()))
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_node_hkern_new(
+    _: *const libc::c_char,
+    raw_parent: *const RsvgNode,
+) -> *const RsvgNode {
+    boxed_node_new(NodeType::HKern, raw_parent, Box::new(NodeHKern::new()))
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_node_missing_glyph_new(
+    _: *const libc::c_char,
+    raw_parent: *const RsvgNode,
+) -> *const RsvgNode {
+    boxed_node_new(
+        NodeType::MissingGlyph,
+        raw_parent,
+        Box::new(NodeGlyph::new()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq_cairo::ApproxEqCairo;
+
+    fn glyph(unicode: &str, horiz_adv_x: Option<f64>) -> Rc<Glyph> {
+        let mut path = PathBuilder::new();
+        path.move_to(0.0, 0.0);
+        path.line_to(100.0, 200.0);
+        path.close_path();
+
+        Rc::new(Glyph {
+            unicode: Some(unicode.to_string()),
+            name: None,
+            horiz_adv_x,
+            path,
+        })
+    }
+
+    fn test_font() -> SvgFont {
+        SvgFont {
+            units_per_em: 1000.0,
+            horiz_adv_x: 500.0,
+            glyphs: vec![glyph("fi", Some(800.0)), glyph("f", None), glyph("i", None)],
+            missing_glyph: Some(Rc::new(Glyph {
+                horiz_adv_x: Some(300.0),
+                ..Default::default()
+            })),
+            kerning: vec![Rc::new(KerningPair {
+                u1: vec![UnicodeEntry::Chars("f".to_string())],
+                u2: vec![UnicodeEntry::Range(0x66, 0x66)],
+                k: 100.0,
+                ..Default::default()
+            })],
+        }
+    }
+
+    #[test]
+    fn parses_unicode_lists() {
+        assert_eq!(
+            parse_unicode_list("a, fi,U+0041-005A, U+00??"),
+            vec![
+                UnicodeEntry::Chars("a".to_string()),
+                UnicodeEntry::Chars("fi".to_string()),
+                UnicodeEntry::Range(0x41, 0x5a),
+                UnicodeEntry::Range(0x00, 0xff),
+            ]
+        );
+
+        assert_eq!(parse_unicode_list("U+zz, "), vec![]);
+    }
+
+    #[test]
+    fn matches_unicode_entries() {
+        assert!(UnicodeEntry::Chars("fi".to_string()).matches("fi"));
+        assert!(!UnicodeEntry::Chars("fi".to_string()).matches("f"));
+
+        assert!(UnicodeEntry::Range(0x41, 0x5a).matches("M"));
+        assert!(!UnicodeEntry::Range(0x41, 0x5a).matches("m"));
+        assert!(!UnicodeEntry::Range(0x41, 0x5a).matches("MM"));
+    }
+
+    #[test]
+    fn lays_out_ligatures_first() {
+        let font = test_font();
+
+        let (builder, advance) = font.layout("fi", 10.0, 20.0, 10.0, 0.0);
+
+        assert!(advance.approx_eq_cairo(&8.0));
+        assert_eq!(
+            builder.get_path_commands(),
+            &[
+                PathCommand::MoveTo(10.0, 20.0),
+                PathCommand::LineTo(11.0, 18.0),
+                PathCommand::ClosePath,
+            ]
+        );
+    }
+
+    #[test]
+    fn applies_kerning_and_letter_spacing() {
+        let font = test_font();
+
+        let (builder, advance) = font.layout("ff", 0.0, 0.0, 10.0, 1.0);
+
+        // 5 + 1 for the first glyph, minus 1 of kerning, plus 5 + 1 for the second one
+        assert!(advance.approx_eq_cairo(&11.0));
+        assert_eq!(
+            builder.get_path_commands()[3],
+            PathCommand::MoveTo(5.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn uses_missing_glyph() {
+        let font = test_font();
+
+        let (builder, advance) = font.layout("xf", 0.0, 0.0, 10.0, 0.0);
+
+        assert!(advance.approx_eq_cairo(&8.0));
+        assert_eq!(
+            builder.get_path_commands()[0],
+            PathCommand::MoveTo(3.0, 0.0)
+        );
+    }
+}
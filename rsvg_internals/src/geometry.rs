@@ -0,0 +1,348 @@
+use cairo::{self, MatrixTrait};
+use glib::translate::*;
+use glib_sys;
+use libc;
+
+use std::rc::Rc;
+
+use bbox::RsvgBbox;
+use dom;
+use drawing_ctx::RsvgDrawingCtx;
+use float_eq_cairo::ApproxEqCairo;
+use handle::RsvgHandle;
+use node::RsvgNode;
+
+#[allow(improper_ctypes)]
+extern "C" {
+    fn rsvg_handle_collect_geometry(
+        handle: *const RsvgHandle,
+        node: *const RsvgNode,
+        geometry: *mut libc::c_void,
+    ) -> glib_sys::gboolean;
+
+    fn rsvg_drawing_ctx_get_geometry(draw_ctx: *const RsvgDrawingCtx) -> *mut libc::c_void;
+
+    fn rsvg_drawing_ctx_set_geometry(draw_ctx: *const RsvgDrawingCtx, geometry: *mut libc::c_void);
+}
+
+/// What to include in the bounding box of an element, like the options of
+/// `getBBox()` in the SVG DOM.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct BboxOptions {
+    /// The geometry of shapes, and the extents of text and images.
+    pub fill: bool,
+
+    /// The area that the strokes of shapes cover.
+    pub stroke: bool,
+
+    /// The contents of the markers of shapes.
+    pub markers: bool,
+}
+
+/// The geometry of an element as it gets rendered, from which its bounding
+/// boxes and its transformation to the canvas can be obtained.
+pub struct Geometry {
+    node: RsvgNode,
+
+    /// Transformation from the element's user space to the canvas, known
+    /// after the element is drawn.
+    ctm: Option<cairo::Matrix>,
+
+    // Each bbox has the transformation of the shape it came from
+    fill: Vec<RsvgBbox>,
+    stroke: Vec<RsvgBbox>,
+    markers: Vec<RsvgBbox>,
+
+    /// Nesting level of markers being drawn; their shapes go to `markers`.
+    markers_depth: usize,
+}
+
+impl Geometry {
+    fn new(node: &RsvgNode) -> Geometry {
+        Geometry {
+            node: node.clone(),
+            ctm: None,
+            fill: Vec::new(),
+            stroke: Vec::new(),
+            markers: Vec::new(),
+            markers_depth: 0,
+        }
+    }
+
+    /// Returns the transformation from the element's user space to the
+    /// canvas, like `getScreenCTM()` in the SVG DOM.  The user space of an
+    /// element includes its own `transform`, and for `<svg>` elements, the
+    /// transformation from their `viewBox`.
+    pub fn get_screen_ctm(&self) -> cairo::Matrix {
+        self.ctm.unwrap()
+    }
+
+    /// Returns the bounding box of the element in its user space, like
+    /// `getBBox()` in the SVG DOM.  An element that has nothing to draw
+    /// gets an empty rectangle at the origin.
+    ///
+    /// Returns `None` if the element's transformation is not invertible, as
+    /// there is no user space to put the bounding box in.
+    pub fn get_bbox(&self, options: &BboxOptions) -> Option<cairo::Rectangle> {
+        let mut bboxes = Vec::new();
+
+        if options.fill {
+            bboxes.extend(self.fill.iter());
+        }
+
+        if options.stroke {
+            bboxes.extend(self.stroke.iter());
+        }
+
+        if options.markers {
+            bboxes.extend(self.markers.iter());
+        }
+
+        union_in_user_space(&self.get_screen_ctm(), &bboxes)
+    }
+}
+
+fn union_in_user_space(ctm: &cairo::Matrix, bboxes: &[&RsvgBbox]) -> Option<cairo::Rectangle> {
+    if (ctm.xx * ctm.yy - ctm.xy * ctm.yx).approx_eq_cairo(&0.0) {
+        return None;
+    }
+
+    let mut total = RsvgBbox::new(ctm);
+
+    for bbox in bboxes {
+        total.insert(bbox);
+    }
+
+    Some(total.rect)
+}
+
+/// Draws the document up to `node` and its descendants, without painting
+/// anything, to obtain the element's geometry.
+///
+/// Returns `None` if the element is not rendered, for example because it is
+/// inside a `<defs>`, because of its `display` or `visibility`, or because
+/// it is in error; or if the handle has not finished loading.  The contents
+/// of patterns, masks, clipping paths and filters are not part of the
+/// geometry, and elements that a `<use>` instantiates keep the geometry of
+/// their original place in the document.
+pub fn get_geometry(handle: *const RsvgHandle, node: &RsvgNode) -> Option<Geometry> {
+    let mut geometry = Geometry::new(node);
+
+    let ok: bool = unsafe {
+        from_glib(rsvg_handle_collect_geometry(
+            handle,
+            node as *const RsvgNode,
+            &mut geometry as *mut Geometry as *mut libc::c_void,
+        ))
+    };
+
+    if ok && geometry.ctm.is_some() {
+        Some(geometry)
+    } else {
+        None
+    }
+}
+
+fn get_collector<'a>(draw_ctx: *const RsvgDrawingCtx) -> Option<&'a mut Geometry> {
+    unsafe {
+        let geometry = rsvg_drawing_ctx_get_geometry(draw_ctx) as *mut Geometry;
+
+        if geometry.is_null() {
+            None
+        } else {
+            Some(&mut *geometry)
+        }
+    }
+}
+
+/// Records the transformation of `node` if it is the element whose geometry
+/// is being collected.  Must be called when the node is done drawing, while
+/// its state is still the current one.
+pub fn node_drawn(draw_ctx: *const RsvgDrawingCtx, node: &RsvgNode) {
+    if let Some(geometry) = get_collector(draw_ctx) {
+        if geometry.ctm.is_none() && Rc::ptr_eq(&geometry.node, node) {
+            let state = ::drawing_ctx::get_current_state(draw_ctx);
+            geometry.ctm = Some(::state::get_state_rust(state).affine);
+        }
+    }
+}
+
+/// Records the bounding boxes of something that is being drawn, if the caller
+/// of the drawing is collecting geometry.
+///
+/// Returns `true` in that case, to indicate that nothing needs to be painted.
+pub fn add_bboxes(
+    draw_ctx: *const RsvgDrawingCtx,
+    fill: &RsvgBbox,
+    stroke: Option<&RsvgBbox>,
+) -> bool {
+    match get_collector(draw_ctx) {
+        Some(geometry) => {
+            if geometry.markers_depth > 0 {
+                geometry.markers.push(*fill);
+                geometry.markers.extend(stroke);
+            } else {
+                geometry.fill.push(*fill);
+                geometry.stroke.extend(stroke);
+            }
+
+            true
+        }
+
+        None => false,
+    }
+}
+
+/// Called around the drawing of a marker's contents.
+pub fn push_marker(draw_ctx: *const RsvgDrawingCtx) {
+    if let Some(geometry) = get_collector(draw_ctx) {
+        geometry.markers_depth += 1;
+    }
+}
+
+pub fn pop_marker(draw_ctx: *const RsvgDrawingCtx) {
+    if let Some(geometry) = get_collector(draw_ctx) {
+        geometry.markers_depth -= 1;
+    }
+}
+
+/// Stops collecting geometry while drawing something that is not part of the
+/// canvas, like the contents of a pattern.  Pass the return value to
+/// `resume()` when done.
+pub fn pause(draw_ctx: *const RsvgDrawingCtx) -> *mut libc::c_void {
+    unsafe {
+        let geometry = rsvg_drawing_ctx_get_geometry(draw_ctx);
+        rsvg_drawing_ctx_set_geometry(draw_ctx, ::std::ptr::null_mut());
+        geometry
+    }
+}
+
+pub fn resume(draw_ctx: *const RsvgDrawingCtx, paused: *mut libc::c_void) {
+    unsafe {
+        rsvg_drawing_ctx_set_geometry(draw_ctx, paused);
+    }
+}
+
+fn get_geometry_by_id(handle: *const RsvgHandle, id: *const libc::c_char) -> Option<Geometry> {
+    assert!(!id.is_null());
+
+    let id: String = unsafe { from_glib_none(id) };
+
+    dom::get_element_by_id(handle, &id).and_then(|node| get_geometry(handle, &node))
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_geometry_get_bbox(
+    handle: *const RsvgHandle,
+    id: *const libc::c_char,
+    fill: glib_sys::gboolean,
+    stroke: glib_sys::gboolean,
+    markers: glib_sys::gboolean,
+    out_bbox: *mut cairo::Rectangle,
+) -> glib_sys::gboolean {
+    assert!(!out_bbox.is_null());
+
+    let options = BboxOptions {
+        fill: from_glib(fill),
+        stroke: from_glib(stroke),
+        markers: from_glib(markers),
+    };
+
+    match get_geometry_by_id(handle, id).and_then(|geometry| geometry.get_bbox(&options)) {
+        Some(bbox) => {
+            unsafe {
+                *out_bbox = bbox;
+            }
+
+            true.to_glib()
+        }
+
+        None => false.to_glib(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_geometry_get_screen_ctm(
+    handle: *const RsvgHandle,
+    id: *const libc::c_char,
+    out_ctm: *mut cairo::Matrix,
+) -> glib_sys::gboolean {
+    assert!(!out_ctm.is_null());
+
+    match get_geometry_by_id(handle, id) {
+        Some(geometry) => {
+            unsafe {
+                *out_ctm = geometry.get_screen_ctm();
+            }
+
+            true.to_glib()
+        }
+
+        None => false.to_glib(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bbox(affine: &cairo::Matrix, x: f64, y: f64, width: f64, height: f64) -> RsvgBbox {
+        let mut bbox = RsvgBbox::new(affine);
+        bbox.set_rect(&cairo::Rectangle {
+            x,
+            y,
+            width,
+            height,
+        });
+        bbox
+    }
+
+    #[test]
+    fn computes_union_in_user_space() {
+        let ctm = cairo::Matrix::new(2.0, 0.0, 0.0, 2.0, 10.0, 10.0);
+        let mut child = ctm;
+        child.translate(5.0, 0.0);
+
+        let fill = bbox(&child, 0.0, 0.0, 10.0, 20.0);
+        let stroke = bbox(&child, -1.0, -1.0, 12.0, 22.0);
+        let marker = bbox(&ctm, 30.0, 0.0, 5.0, 5.0);
+
+        let r = union_in_user_space(&ctm, &[&fill]).unwrap();
+        assert!(r.x.approx_eq_cairo(&5.0));
+        assert!(r.y.approx_eq_cairo(&0.0));
+        assert!(r.width.approx_eq_cairo(&10.0));
+        assert!(r.height.approx_eq_cairo(&20.0));
+
+        let r = union_in_user_space(&ctm, &[&fill, &stroke, &marker]).unwrap();
+        assert!(r.x.approx_eq_cairo(&4.0));
+        assert!(r.y.approx_eq_cairo(&-1.0));
+        assert!(r.width.approx_eq_cairo(&31.0));
+        assert!(r.height.approx_eq_cairo(&22.0));
+    }
+
+    #[test]
+    fn rotated_shape_has_axis_aligned_bbox() {
+        let ctm = cairo::Matrix::identity();
+        let mut child = ctm;
+        child.rotate(::std::f64::consts::PI / 2.0);
+
+        let r = union_in_user_space(&ctm, &[&bbox(&child, 0.0, 0.0, 10.0, 20.0)]).unwrap();
+        assert!(r.x.approx_eq_cairo(&-20.0));
+        assert!(r.y.approx_eq_cairo(&0.0));
+        assert!(r.width.approx_eq_cairo(&20.0));
+        assert!(r.height.approx_eq_cairo(&10.0));
+    }
+
+    #[test]
+    fn empty_union_is_empty_rect() {
+        let r = union_in_user_space(&cairo::Matrix::identity(), &[]).unwrap();
+        assert!(r.x.approx_eq_cairo(&0.0));
+        assert!(r.width.approx_eq_cairo(&0.0));
+    }
+
+    #[test]
+    fn singular_ctm_has_no_bbox() {
+        let ctm = cairo::Matrix::new(0.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+        assert!(union_in_user_space(&ctm, &[]).is_none());
+    }
+}
@@ -0,0 +1,203 @@
+use cairo;
+use cairo_sys;
+use glib::translate::*;
+use glib_sys;
+use libc;
+
+use drawing_ctx::RsvgDrawingCtx;
+use handle::RsvgHandle;
+use node::{Node, RsvgNode};
+use state::{self, PointerEvents, RsvgState};
+
+extern "C" {
+    fn rsvg_handle_collect_hits(
+        handle: *const RsvgHandle,
+        hit_test: *mut libc::c_void,
+    ) -> glib_sys::gboolean;
+
+    fn rsvg_drawing_ctx_get_hit_test(draw_ctx: *const RsvgDrawingCtx) -> *mut libc::c_void;
+
+    fn rsvg_drawing_ctx_set_hit_test(draw_ctx: *const RsvgDrawingCtx, hit_test: *mut libc::c_void);
+
+    fn cairo_in_clip(cr: *mut cairo_sys::cairo_t, x: f64, y: f64) -> glib_sys::gboolean;
+}
+
+struct HitTest {
+    // The point, in device coordinates of the drawing
+    x: f64,
+    y: f64,
+
+    /// The shapes that contain the point, in drawing order.
+    hits: Vec<RsvgNode>,
+
+    /// For each intermediate surface that is being drawn, the number of
+    /// hits before it started.
+    layers: Vec<usize>,
+}
+
+/// Returns the topmost element whose fill or stroke contains the point
+/// (`x`, `y`), in the coordinates that `rsvg_handle_render_cairo()` uses
+/// with an identity transformation.
+///
+/// The shapes of the document are drawn in order, and each one is tested
+/// with `cairo_in_fill()` and `cairo_in_stroke()` after applying its
+/// transformation, according to its `pointer-events` property.  A hit is
+/// discarded if the point is outside of the clipping paths of the shape or
+/// its ancestors.  Elements that are not rendered, because of `display` or
+/// `visibility`, are never hit, and neither are text, images, and the
+/// contents of patterns, masks and clipping paths.  Shapes that a `<use>`
+/// instantiates are returned as the referenced elements.
+///
+/// Returns `None` if no shape is under the point, or if the handle has not
+/// finished loading.
+pub fn hit_test(handle: *const RsvgHandle, x: f64, y: f64) -> Option<RsvgNode> {
+    let mut hit_test = HitTest {
+        x,
+        y,
+        hits: Vec::new(),
+        layers: Vec::new(),
+    };
+
+    let ok: bool = unsafe {
+        from_glib(rsvg_handle_collect_hits(
+            handle,
+            &mut hit_test as *mut HitTest as *mut libc::c_void,
+        ))
+    };
+
+    if !ok {
+        return None;
+    }
+
+    hit_test.hits.pop()
+}
+
+fn get_hit_test<'a>(draw_ctx: *const RsvgDrawingCtx) -> Option<&'a mut HitTest> {
+    unsafe {
+        let hit_test = rsvg_drawing_ctx_get_hit_test(draw_ctx) as *mut HitTest;
+
+        if hit_test.is_null() {
+            None
+        } else {
+            Some(&mut *hit_test)
+        }
+    }
+}
+
+fn in_clip(cr: &cairo::Context, x: f64, y: f64) -> bool {
+    unsafe { from_glib(cairo_in_clip(cr.to_glib_none().0, x, y)) }
+}
+
+/// Tests the path that is set on `cr` for `node`, if the caller of the
+/// drawing is hit testing.  The fill rule and the stroke parameters must
+/// have been set on `cr` already.
+pub fn test_path(
+    draw_ctx: *const RsvgDrawingCtx,
+    cr: &cairo::Context,
+    state: *const RsvgState,
+    node: &RsvgNode,
+) {
+    let hit_test = match get_hit_test(draw_ctx) {
+        Some(hit_test) => hit_test,
+        None => return,
+    };
+
+    let rstate = state::get_state_rust(state);
+
+    let visible = state::is_visible(state);
+    let filled = state::get_fill(state).is_some();
+    let stroked = state::get_stroke(state).is_some();
+
+    let (test_fill, test_stroke) = match rstate.pointer_events.unwrap_or_default() {
+        PointerEvents::VisiblePainted => (visible && filled, visible && stroked),
+        PointerEvents::VisibleFill => (visible, false),
+        PointerEvents::VisibleStroke => (false, visible),
+        PointerEvents::Visible => (visible, visible),
+        PointerEvents::Painted => (filled, stroked),
+        PointerEvents::Fill => (true, false),
+        PointerEvents::Stroke => (false, true),
+        PointerEvents::All => (true, true),
+        PointerEvents::None => (false, false),
+
+        PointerEvents::BoundingBox => {
+            let (x, y) = cr.device_to_user(hit_test.x, hit_test.y);
+            let (x0, y0, x1, y1) = cr.fill_extents();
+
+            if x >= x0 && x <= x1 && y >= y0 && y <= y1 && in_clip(cr, x, y) {
+                hit_test.hits.push(node.clone());
+            }
+
+            return;
+        }
+    };
+
+    let (x, y) = cr.device_to_user(hit_test.x, hit_test.y);
+
+    let hit = (test_fill && cr.in_fill(x, y)) || (test_stroke && cr.in_stroke(x, y));
+
+    if hit && in_clip(cr, x, y) {
+        hit_test.hits.push(node.clone());
+    }
+}
+
+/// Stops hit testing while drawing something that is not part of the
+/// canvas, like the contents of a pattern.  Pass the return value to
+/// `resume()` when done.
+pub fn pause(draw_ctx: *const RsvgDrawingCtx) -> *mut libc::c_void {
+    unsafe {
+        let hit_test = rsvg_drawing_ctx_get_hit_test(draw_ctx);
+        rsvg_drawing_ctx_set_hit_test(draw_ctx, ::std::ptr::null_mut());
+        hit_test
+    }
+}
+
+pub fn resume(draw_ctx: *const RsvgDrawingCtx, paused: *mut libc::c_void) {
+    unsafe {
+        rsvg_drawing_ctx_set_hit_test(draw_ctx, paused);
+    }
+}
+
+/// Returns the `id` of an element, or of its closest ancestor that has one.
+pub fn get_id(node: &Node) -> Option<String> {
+    node.get_attribute("id")
+        .or_else(|| node.get_parent().and_then(|parent| get_id(&parent)))
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_hit_test_push_layer(draw_ctx: *const RsvgDrawingCtx) {
+    if let Some(hit_test) = get_hit_test(draw_ctx) {
+        let n = hit_test.hits.len();
+        hit_test.layers.push(n);
+    }
+}
+
+/// Called when an intermediate surface is composited onto `raw_cr`, after
+/// its clipping path has been set there; the hits on the surface that are
+/// outside of that clip are discarded.
+#[no_mangle]
+pub extern "C" fn rsvg_hit_test_pop_layer(
+    draw_ctx: *const RsvgDrawingCtx,
+    raw_cr: *mut cairo_sys::cairo_t,
+) {
+    if let Some(hit_test) = get_hit_test(draw_ctx) {
+        let start = hit_test.layers.pop().unwrap_or(0);
+
+        let cr = unsafe { cairo::Context::from_glib_none(raw_cr) };
+        let (x, y) = cr.device_to_user(hit_test.x, hit_test.y);
+
+        if !in_clip(&cr, x, y) {
+            hit_test.hits.truncate(start);
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_hit_test_get_id(
+    handle: *const RsvgHandle,
+    x: f64,
+    y: f64,
+) -> *mut libc::c_char {
+    hit_test(handle, x, y)
+        .and_then(|node| get_id(&node))
+        .to_glib_full()
+}
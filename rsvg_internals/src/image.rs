@@ -11,9 +11,11 @@ use aspect_ratio::AspectRatio;
 use attributes::Attribute;
 use draw::{add_clipping_rect, draw_surface};
 use drawing_ctx::{self, RsvgDrawingCtx};
+use error::NodeError;
 use handle::RsvgHandle;
 use length::*;
 use node::*;
+use normalize;
 use parsers::parse;
 use property_bag::PropertyBag;
 use state;
@@ -41,8 +43,13 @@ impl NodeImage {
 }
 
 impl NodeTrait for NodeImage {
-    fn set_atts(&self, _: &RsvgNode, handle: *const RsvgHandle, pbag: &PropertyBag) -> NodeResult {
-        for (_key, attr, value) in pbag.iter() {
+    fn set_atts(
+        &self,
+        node: &RsvgNode,
+        handle: *const RsvgHandle,
+        pbag: &PropertyBag,
+    ) -> NodeResult {
+        for (key, attr, value) in pbag.iter() {
             match attr {
                 Attribute::X => self.x.set(parse("x", value, LengthDir::Horizontal, None)?),
                 Attribute::Y => self.y.set(parse("y", value, LengthDir::Vertical, None)?),
@@ -85,7 +92,13 @@ impl NodeTrait for NodeImage {
                             cairo::ImageSurface::from_raw_full(raw_surface).unwrap()
                         });
                     } else {
-                        let _: glib::Error = unsafe { from_glib_full(error) }; // FIXME: we should note that the image couldn't be loaded
+                        let error: glib::Error = unsafe { from_glib_full(error) };
+
+                        // The element still gets laid out, but it paints nothing
+                        node.add_warning(NodeError::value_error(
+                            key,
+                            &format!("could not load image \"{}\": {}", value, error),
+                        ));
                     }
                 }
 
@@ -106,6 +119,11 @@ impl NodeTrait for NodeImage {
             let state = node.get_state();
 
             drawing_ctx::state_reinherit_top(draw_ctx, state, dominate);
+
+            if !clipping {
+                normalize::add_image(draw_ctx, node, x, y, w, h);
+            }
+
             drawing_ctx::push_discrete_layer(draw_ctx, clipping);
 
             let aspect = self.aspect.get();
@@ -5,6 +5,7 @@ use libc;
 use regex::Regex;
 
 use std::f64::consts::*;
+use std::fmt;
 use std::ptr;
 
 use drawing_ctx;
@@ -59,6 +60,20 @@ impl Default for RsvgLength {
     }
 }
 
+impl fmt::Display for RsvgLength {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.unit {
+            LengthUnit::Default => write!(f, "{}", self.length),
+            LengthUnit::Percent => write!(f, "{}%", self.length * 100.0),
+            LengthUnit::FontEm => write!(f, "{}em", self.length),
+            LengthUnit::FontEx => write!(f, "{}ex", self.length),
+            LengthUnit::Inch => write!(f, "{}in", self.length),
+            LengthUnit::RelativeLarger => write!(f, "larger"),
+            LengthUnit::RelativeSmaller => write!(f, "smaller"),
+        }
+    }
+}
+
 const POINTS_PER_INCH: f64 = 72.0;
 const CM_PER_INCH: f64 = 2.54;
 const MM_PER_INCH: f64 = 25.4;
@@ -634,6 +649,19 @@ mod tests {
         )));
     }
 
+    #[test]
+    fn formats_lengths() {
+        let format = |s| RsvgLength::parse(s, LengthDir::Both).unwrap().to_string();
+
+        assert_eq!(format("42"), "42");
+        assert_eq!(format("42px"), "42");
+        assert_eq!(format("50%"), "50%");
+        assert_eq!(format("1.5em"), "1.5em");
+        assert_eq!(format("2ex"), "2ex");
+        assert_eq!(format("36pt"), "0.5in");
+        assert_eq!(format("larger"), "larger");
+    }
+
     #[test]
     fn check_nonnegative_works() {
         assert!(
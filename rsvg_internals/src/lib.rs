@@ -21,6 +21,8 @@ extern crate lazy_static;
 #[macro_use]
 extern crate downcast_rs;
 
+pub use animation::rsvg_node_animation_new;
+
 pub use attributes::rsvg_attribute_from_name;
 
 pub use bbox::{rsvg_bbox_clip, rsvg_bbox_init, rsvg_bbox_insert, RsvgBbox};
@@ -37,12 +39,59 @@ pub use cond::{
     rsvg_cond_check_system_language,
 };
 
+pub use css_animation::{
+    rsvg_keyframes_free,
+    rsvg_keyframes_new,
+    rsvg_keyframes_parse_stylesheet,
+};
+
+pub use diagnostics::rsvg_drawing_ctx_add_node_diagnostic;
+
+pub use dom::{
+    get_computed_value,
+    get_element_by_id,
+    get_element_children,
+    get_root_element,
+    rsvg_dom_element_from_node,
+    rsvg_dom_element_get_attribute,
+    rsvg_dom_element_get_attribute_names,
+    rsvg_dom_element_get_child,
+    rsvg_dom_element_get_computed_style,
+    rsvg_dom_element_get_n_children,
+    rsvg_dom_element_get_name,
+    rsvg_dom_element_get_parent,
+    rsvg_dom_node_set_attribute,
+    rsvg_dom_node_set_style_property,
+    rsvg_dom_node_set_text_content,
+    set_attribute,
+    set_style_property,
+    set_text_content,
+    SetAttributeError,
+};
+
 pub use draw::rsvg_cairo_add_clipping_rect;
 
 pub use drawing_ctx::{rsvg_drawing_ctx_state_pop, rsvg_drawing_ctx_state_push};
 
+pub use font::{
+    rsvg_node_font_face_new,
+    rsvg_node_font_new,
+    rsvg_node_glyph_new,
+    rsvg_node_hkern_new,
+    rsvg_node_missing_glyph_new,
+};
+
+pub use geometry::{rsvg_geometry_get_bbox, rsvg_geometry_get_screen_ctm};
+
 pub use gradient::{rsvg_node_linear_gradient_new, rsvg_node_radial_gradient_new};
 
+pub use hit_test::{
+    hit_test,
+    rsvg_hit_test_get_id,
+    rsvg_hit_test_pop_layer,
+    rsvg_hit_test_push_layer,
+};
+
 pub use length::{
     rsvg_length_hand_normalize,
     rsvg_length_normalize,
@@ -79,16 +128,26 @@ pub use node::{
     rsvg_node_children_iter_next_back,
     rsvg_node_draw,
     rsvg_node_draw_children,
+    rsvg_node_get_error,
     rsvg_node_get_parent,
     rsvg_node_get_state,
     rsvg_node_get_type,
+    rsvg_node_get_warning,
     rsvg_node_is_same,
     rsvg_node_ref,
     rsvg_node_set_attribute_parse_error,
     rsvg_node_set_atts,
+    rsvg_node_set_element_name,
+    rsvg_node_set_foreign_namespace,
+    rsvg_node_set_location,
+    rsvg_node_swap_impl,
     rsvg_node_unref,
+    Node,
+    RsvgNode,
 };
 
+pub use normalize::{normalize, rsvg_dom_normalize, NormalizeOptions};
+
 pub use opacity::{rsvg_css_parse_opacity, OpacityKind, OpacitySpec};
 
 pub use paint_server::{
@@ -114,6 +173,19 @@ pub use property_bag::{
     rsvg_property_bag_new,
 };
 
+pub use sanitize::{rsvg_dom_sanitize, sanitize, Removal, RemovalKind, RemovalReason};
+
+pub use serialize::{
+    get_css_declarations,
+    rsvg_dom_serialize,
+    serialize,
+    serialize_element,
+    serialize_filtered,
+    CssDeclaration,
+    SerializeFilter,
+    SerializeOptions,
+};
+
 pub use shapes::{
     rsvg_node_circle_new,
     rsvg_node_ellipse_new,
@@ -155,11 +227,25 @@ pub use structure::{
 pub use text::{
     rsvg_node_chars_append,
     rsvg_node_chars_new,
+    rsvg_node_flow_para_new,
+    rsvg_node_flow_region_new,
+    rsvg_node_flow_root_new,
+    rsvg_node_flow_span_new,
     rsvg_node_text_new,
     rsvg_node_tref_new,
     rsvg_node_tspan_new,
 };
 
+pub use text_outline::{get_text_outlines, TextOutline};
+
+pub use timeline::{
+    apply_animations,
+    restore_animations,
+    rsvg_timeline_apply,
+    rsvg_timeline_restore,
+    AppliedAnimations,
+};
+
 pub use transform::rsvg_parse_transform;
 
 pub use viewbox::RsvgViewBox;
@@ -170,6 +256,7 @@ mod coord_units;
 #[macro_use]
 mod property_macros;
 
+mod animation;
 mod aspect_ratio;
 mod attributes;
 mod bbox;
@@ -177,31 +264,44 @@ mod clip_path;
 mod cnode;
 mod color;
 mod cond;
+mod css_animation;
+mod diagnostics;
+mod dom;
 mod draw;
 mod drawing_ctx;
 mod error;
 mod float_eq_cairo;
+mod font;
+mod geometry;
 mod gradient;
 mod handle;
+mod hit_test;
 mod image;
 mod length;
 mod link;
 mod marker;
 mod mask;
 mod node;
+mod normalize;
 mod opacity;
 mod paint_server;
 mod parsers;
 mod path_builder;
+mod path_measure;
 mod path_parser;
 mod pattern;
 mod property_bag;
+mod sanitize;
+mod serialize;
 mod shapes;
 mod space;
 mod state;
 mod stop;
 mod structure;
 mod text;
+mod text_outline;
+mod text_run;
+mod timeline;
 mod transform;
 mod util;
 mod viewbox;
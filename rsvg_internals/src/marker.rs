@@ -12,9 +12,11 @@ use drawing_ctx;
 use drawing_ctx::RsvgDrawingCtx;
 use error::*;
 use float_eq_cairo::ApproxEqCairo;
+use geometry;
 use handle::RsvgHandle;
 use length::{LengthDir, RsvgLength};
 use node::*;
+use normalize;
 use parsers;
 use parsers::{parse, Parse};
 use parsers::ParseError;
@@ -177,15 +179,30 @@ impl NodeMarker {
         let rstate = state::get_state_rust(state);
         rstate.affine = affine;
 
+        if !clipping {
+            normalize::begin_group(draw_ctx, node);
+        }
+
         if !state::is_overflow(state) {
-            if let Some(vbox) = self.vbox.get() {
-                add_clipping_rect(draw_ctx, vbox.0.x, vbox.0.y, vbox.0.width, vbox.0.height);
-            } else {
-                add_clipping_rect(draw_ctx, 0.0, 0.0, marker_width, marker_height);
+            let (x, y, w, h) = match self.vbox.get() {
+                Some(vbox) => (vbox.0.x, vbox.0.y, vbox.0.width, vbox.0.height),
+                None => (0.0, 0.0, marker_width, marker_height),
+            };
+
+            add_clipping_rect(draw_ctx, x, y, w, h);
+
+            if !clipping {
+                normalize::add_clipping_rect(draw_ctx, x, y, w, h);
             }
         }
 
+        geometry::push_marker(draw_ctx);
         node.draw_children(draw_ctx, -1, clipping); // dominate=-1 so it won't reinherit state / push a layer
+        geometry::pop_marker(draw_ctx);
+
+        if !clipping {
+            normalize::end_group(draw_ctx);
+        }
 
         drawing_ctx::state_pop(draw_ctx);
         drawing_ctx::pop_discrete_layer(draw_ctx, clipping);
@@ -3,16 +3,19 @@ use glib::translate::*;
 use glib_sys;
 use libc;
 
-use std::cell::{Ref, RefCell};
+use std::cell::{Cell, Ref, RefCell};
 use std::ptr;
 use std::rc::{Rc, Weak};
 
+use diagnostics;
 use drawing_ctx;
 use drawing_ctx::RsvgDrawingCtx;
 use error::*;
+use geometry;
 use handle::RsvgHandle;
+use normalize;
 use parsers::ParseError;
-use property_bag::PropertyBag;
+use property_bag::{OwnedPropertyBag, PropertyBag};
 use state::{self, RsvgState};
 
 // A *const RsvgNode is just a pointer for the C code's benefit: it
@@ -65,7 +68,12 @@ pub struct Node {
     children: RefCell<Vec<Rc<Node>>>, // strong references to children
     state: *mut RsvgState,
     result: RefCell<NodeResult>,
-    node_impl: Box<NodeTrait>,
+    warnings: RefCell<Vec<NodeError>>, // errors that don't prevent rendering, like bad path data
+    element_name: RefCell<Option<String>>, // None for character data
+    attributes: RefCell<OwnedPropertyBag>,
+    foreign_namespace: Cell<bool>, // element from a namespace other than SVG's
+    location: Cell<(i32, i32)>,    // line and column of the start tag, or zeros if unknown
+    node_impl: RefCell<Box<NodeTrait>>,
 }
 
 // An iterator over the Node's children
@@ -81,6 +89,7 @@ pub struct Children<'a> {
 pub enum NodeType {
     Invalid = 0,
 
+    Animation,
     Chars,
     Circle,
     ClipPath,
@@ -88,7 +97,15 @@ pub enum NodeType {
     Defs,
     Ellipse,
     Filter,
+    FlowPara,
+    FlowRegion,
+    FlowRoot,
+    FlowSpan,
+    Font,
+    FontFace,
+    Glyph,
     Group,
+    HKern,
     Image,
     LightSource,
     Line,
@@ -96,6 +113,7 @@ pub enum NodeType {
     Link,
     Marker,
     Mask,
+    MissingGlyph,
     Path,
     Pattern,
     Polygon,
@@ -146,7 +164,12 @@ impl Node {
             children: RefCell::new(Vec::new()),
             state,
             result: RefCell::new(Ok(())),
-            node_impl,
+            warnings: RefCell::new(Vec::new()),
+            element_name: RefCell::new(None),
+            attributes: RefCell::new(OwnedPropertyBag::default()),
+            foreign_namespace: Cell::new(false),
+            location: Cell::new((0, 0)),
+            node_impl: RefCell::new(node_impl),
         }
     }
 
@@ -184,7 +207,63 @@ impl Node {
     }
 
     pub fn set_atts(&self, node: &RsvgNode, handle: *const RsvgHandle, pbag: &PropertyBag) {
-        *self.result.borrow_mut() = self.node_impl.set_atts(node, handle, pbag);
+        // The warnings are about the previous attributes, if the element
+        // gets changed after loading.
+        self.warnings.borrow_mut().clear();
+
+        *self.attributes.borrow_mut() = pbag.to_owned();
+        *self.result.borrow_mut() = self.node_impl.borrow().set_atts(node, handle, pbag);
+    }
+
+    pub fn set_element_name(&self, name: &str) {
+        *self.element_name.borrow_mut() = Some(name.to_string());
+    }
+
+    /// Returns the name of the element as it appeared in the document, or
+    /// `None` if the node holds character data.
+    pub fn get_element_name(&self) -> Option<String> {
+        self.element_name.borrow().clone()
+    }
+
+    pub fn set_foreign_namespace(&self) {
+        self.foreign_namespace.set(true);
+    }
+
+    /// Whether the element is from a namespace other than SVG's, like
+    /// `<sodipodi:namedview>`.  Its name does not include the prefix.
+    pub fn is_in_foreign_namespace(&self) -> bool {
+        self.foreign_namespace.get()
+    }
+
+    pub fn set_location(&self, line: i32, column: i32) {
+        self.location.set((line, column));
+    }
+
+    /// Returns the line and column of the element's start tag in the
+    /// document, or zeros if the node was not created by the loader.
+    pub fn get_location(&self) -> (i32, i32) {
+        self.location.get()
+    }
+
+    /// Returns the value of an attribute as it appeared in the document,
+    /// including attributes that librsvg does not know about.
+    pub fn get_attribute(&self, name: &str) -> Option<String> {
+        PropertyBag::from_owned(&self.attributes.borrow())
+            .all_iter()
+            .find(|&(key, _)| key == name)
+            .map(|(_, value)| value.to_string())
+    }
+
+    pub fn get_attributes(&self) -> OwnedPropertyBag {
+        self.attributes.borrow().clone()
+    }
+
+    /// Returns the names of the element's attributes in document order.
+    pub fn get_attribute_names(&self) -> Vec<String> {
+        PropertyBag::from_owned(&self.attributes.borrow())
+            .all_iter()
+            .map(|(key, _)| key.to_string())
+            .collect()
     }
 
     pub fn draw(
@@ -195,7 +274,14 @@ impl Node {
         clipping: bool,
     ) {
         if self.result.borrow().is_ok() {
-            self.node_impl.draw(node, draw_ctx, dominate, clipping);
+            if !clipping {
+                diagnostics::check_node_references(draw_ctx, node);
+            }
+
+            self.node_impl
+                .borrow()
+                .draw(node, draw_ctx, dominate, clipping);
+            geometry::node_drawn(draw_ctx, node);
         }
     }
 
@@ -207,12 +293,28 @@ impl Node {
         self.result.borrow().clone()
     }
 
+    /// Records an error after which the node can still be rendered, for example
+    /// path data that is only valid up to some point.
+    pub fn add_warning(&self, warning: NodeError) {
+        self.warnings.borrow_mut().push(warning);
+    }
+
     pub fn get_c_impl(&self) -> *const RsvgCNodeImpl {
-        self.node_impl.get_c_impl()
+        self.node_impl.borrow().get_c_impl()
+    }
+
+    /// Exchanges the implementation of the element with the one of `other`,
+    /// which must be of the same type.  Giving an element the implementation
+    /// of a newly created one puts the values of its attributes back to
+    /// their defaults.
+    pub fn swap_impl(&self, other: &Node) {
+        assert!(self.node_type == other.node_type);
+
+        self.node_impl.swap(&other.node_impl);
     }
 
     pub fn with_impl<T: NodeTrait, F: FnOnce(&T)>(&self, f: F) {
-        if let Some(t) = (&self.node_impl).downcast_ref::<T>() {
+        if let Some(t) = self.node_impl.borrow().downcast_ref::<T>() {
             f(t);
         } else {
             panic!("could not downcast");
@@ -224,6 +326,10 @@ impl Node {
             drawing_ctx::state_reinherit_top(draw_ctx, self.state, dominate);
 
             drawing_ctx::push_discrete_layer(draw_ctx, clipping);
+
+            if !clipping {
+                normalize::begin_group(draw_ctx, self);
+            }
         }
 
         for child in self.children() {
@@ -235,6 +341,10 @@ impl Node {
         }
 
         if dominate != -1 {
+            if !clipping {
+                normalize::end_group(draw_ctx);
+            }
+
             drawing_ctx::pop_discrete_layer(draw_ctx, clipping);
         }
     }
@@ -432,6 +542,48 @@ pub extern "C" fn rsvg_node_set_atts(
     node.set_atts(node, handle, pbag);
 }
 
+#[no_mangle]
+pub extern "C" fn rsvg_node_swap_impl(raw_node: *const RsvgNode, raw_other: *const RsvgNode) {
+    assert!(!raw_node.is_null());
+    assert!(!raw_other.is_null());
+
+    let node: &RsvgNode = unsafe { &*raw_node };
+    let other: &RsvgNode = unsafe { &*raw_other };
+
+    node.swap_impl(other);
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_node_set_element_name(raw_node: *const RsvgNode, name: *const libc::c_char) {
+    assert!(!raw_node.is_null());
+    let node: &RsvgNode = unsafe { &*raw_node };
+
+    assert!(!name.is_null());
+    let name: String = unsafe { from_glib_none(name) };
+
+    node.set_element_name(&name);
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_node_set_foreign_namespace(raw_node: *const RsvgNode) {
+    assert!(!raw_node.is_null());
+    let node: &RsvgNode = unsafe { &*raw_node };
+
+    node.set_foreign_namespace();
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_node_set_location(
+    raw_node: *const RsvgNode,
+    line: libc::c_int,
+    column: libc::c_int,
+) {
+    assert!(!raw_node.is_null());
+    let node: &RsvgNode = unsafe { &*raw_node };
+
+    node.set_location(line, column);
+}
+
 #[no_mangle]
 pub extern "C" fn rsvg_node_draw(
     raw_node: *const RsvgNode,
@@ -465,6 +617,59 @@ pub extern "C" fn rsvg_node_set_attribute_parse_error(
     }
 }
 
+#[no_mangle]
+pub extern "C" fn rsvg_node_get_error(
+    raw_node: *const RsvgNode,
+    out_attr_name: *mut *mut libc::c_char,
+    out_message: *mut *mut libc::c_char,
+) -> glib_sys::gboolean {
+    assert!(!raw_node.is_null());
+    let node: &RsvgNode = unsafe { &*raw_node };
+
+    assert!(!out_attr_name.is_null());
+    assert!(!out_message.is_null());
+
+    match node.get_result() {
+        Ok(()) => false.to_glib(),
+
+        Err(e) => {
+            unsafe {
+                *out_attr_name = e.get_attr_name().to_glib_full();
+                *out_message = e.get_error().to_string().to_glib_full();
+            }
+
+            true.to_glib()
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_node_get_warning(
+    raw_node: *const RsvgNode,
+    index: libc::c_uint,
+    out_attr_name: *mut *mut libc::c_char,
+    out_message: *mut *mut libc::c_char,
+) -> glib_sys::gboolean {
+    assert!(!raw_node.is_null());
+    let node: &RsvgNode = unsafe { &*raw_node };
+
+    assert!(!out_attr_name.is_null());
+    assert!(!out_message.is_null());
+
+    match node.warnings.borrow().get(index as usize) {
+        None => false.to_glib(),
+
+        Some(w) => {
+            unsafe {
+                *out_attr_name = w.get_attr_name().to_glib_full();
+                *out_message = w.get_error().to_string().to_glib_full();
+            }
+
+            true.to_glib()
+        }
+    }
+}
+
 // This should really return Children<'a> where 'a is the lifetime of raw_node,
 // but raw pointers don't have lifetimes so there's not much we can do.
 #[no_mangle]
@@ -542,8 +747,10 @@ pub extern "C" fn rsvg_node_draw_children(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use attributes::Attribute;
     use drawing_ctx::RsvgDrawingCtx;
     use handle::RsvgHandle;
+    use std::ffi::CString;
     use std::{mem, ptr};
     use std::rc::Rc;
 
@@ -561,6 +768,72 @@ mod tests {
         }
     }
 
+    // Like most elements, only sets the values of the attributes that it gets
+    struct ValueNodeImpl {
+        x: Cell<Option<String>>,
+    }
+
+    impl ValueNodeImpl {
+        fn new() -> ValueNodeImpl {
+            ValueNodeImpl { x: Cell::new(None) }
+        }
+    }
+
+    impl NodeTrait for ValueNodeImpl {
+        fn set_atts(&self, _: &RsvgNode, _: *const RsvgHandle, pbag: &PropertyBag) -> NodeResult {
+            for (_key, attr, value) in pbag.iter() {
+                if attr == Attribute::X {
+                    self.x.set(Some(value.to_string()));
+                }
+            }
+
+            Ok(())
+        }
+
+        fn draw(&self, _: &RsvgNode, _: *mut RsvgDrawingCtx, _: i32, _: bool) {}
+
+        fn get_c_impl(&self) -> *const RsvgCNodeImpl {
+            unreachable!();
+        }
+    }
+
+    fn get_x(node: &RsvgNode) -> Option<String> {
+        let mut x = None;
+        node.with_impl(|v: &ValueNodeImpl| x = v.x.take());
+        x
+    }
+
+    #[test]
+    fn swapped_impl_has_default_values() {
+        let new_node = || {
+            Rc::new(Node::new(
+                NodeType::Rect,
+                None,
+                ptr::null_mut(),
+                Box::new(ValueNodeImpl::new()),
+            ))
+        };
+
+        let key = CString::new("x").unwrap();
+        let value = CString::new("50").unwrap();
+        let pairs = [key.as_ptr(), value.as_ptr(), ptr::null()];
+        let with_x = unsafe { PropertyBag::new_from_key_value_pairs(pairs.as_ptr()) };
+        let without_x = unsafe { PropertyBag::new_from_key_value_pairs(ptr::null()) };
+
+        let node = new_node();
+        node.set_atts(&node, ptr::null(), &with_x);
+        assert_eq!(get_x(&node), Some("50".to_string()));
+
+        node.set_atts(&node, ptr::null(), &with_x);
+        node.set_atts(&node, ptr::null(), &without_x);
+        assert_eq!(get_x(&node), Some("50".to_string()));
+
+        node.set_atts(&node, ptr::null(), &with_x);
+        node.swap_impl(&new_node());
+        node.set_atts(&node, ptr::null(), &without_x);
+        assert_eq!(get_x(&node), None);
+    }
+
     #[test]
     fn node_refs_and_unrefs() {
         let node = Rc::new(Node::new(
@@ -630,6 +903,128 @@ mod tests {
         rsvg_node_unref(ref2);
     }
 
+    #[test]
+    fn node_error_is_exported() {
+        let node = Rc::new(Node::new(
+            NodeType::Path,
+            None,
+            ptr::null_mut(),
+            Box::new(TestNodeImpl {}),
+        ));
+
+        let mut attr_name: *mut libc::c_char = ptr::null_mut();
+        let mut message: *mut libc::c_char = ptr::null_mut();
+
+        assert_eq!(
+            rsvg_node_get_error(&node, &mut attr_name, &mut message),
+            false.to_glib()
+        );
+
+        node.set_error(NodeError::value_error("r", "must be non-negative"));
+
+        assert_eq!(
+            rsvg_node_get_error(&node, &mut attr_name, &mut message),
+            true.to_glib()
+        );
+
+        let attr_name: String = unsafe { from_glib_full(attr_name) };
+        let message: String = unsafe { from_glib_full(message) };
+
+        assert_eq!(attr_name, "r");
+        assert_eq!(message, "must be non-negative");
+    }
+
+    #[test]
+    fn node_warnings_are_exported() {
+        let node = Rc::new(Node::new(
+            NodeType::Path,
+            None,
+            ptr::null_mut(),
+            Box::new(TestNodeImpl {}),
+        ));
+
+        node.add_warning(NodeError::value_error("d", "truncated"));
+        assert!(node.get_result().is_ok());
+
+        let mut attr_name: *mut libc::c_char = ptr::null_mut();
+        let mut message: *mut libc::c_char = ptr::null_mut();
+
+        assert_eq!(
+            rsvg_node_get_warning(&node, 0, &mut attr_name, &mut message),
+            true.to_glib()
+        );
+
+        let attr_name: String = unsafe { from_glib_full(attr_name) };
+        let message: String = unsafe { from_glib_full(message) };
+
+        assert_eq!(attr_name, "d");
+        assert_eq!(message, "truncated");
+
+        let mut attr_name: *mut libc::c_char = ptr::null_mut();
+        let mut message: *mut libc::c_char = ptr::null_mut();
+
+        assert_eq!(
+            rsvg_node_get_warning(&node, 1, &mut attr_name, &mut message),
+            false.to_glib()
+        );
+    }
+
+    #[test]
+    fn node_warnings_are_cleared_by_new_attributes() {
+        let node = Rc::new(Node::new(
+            NodeType::Path,
+            None,
+            ptr::null_mut(),
+            Box::new(TestNodeImpl {}),
+        ));
+
+        node.add_warning(NodeError::value_error("d", "truncated"));
+
+        let pbag = unsafe { PropertyBag::new_from_key_value_pairs(ptr::null()) };
+        node.set_atts(&node, ptr::null(), &pbag);
+
+        let mut attr_name: *mut libc::c_char = ptr::null_mut();
+        let mut message: *mut libc::c_char = ptr::null_mut();
+
+        assert_eq!(
+            rsvg_node_get_warning(&node, 0, &mut attr_name, &mut message),
+            false.to_glib()
+        );
+    }
+
+    #[test]
+    fn node_keeps_all_attributes() {
+        let node = Rc::new(Node::new(
+            NodeType::Circle,
+            None,
+            ptr::null_mut(),
+            Box::new(TestNodeImpl {}),
+        ));
+
+        node.set_element_name("circle");
+
+        let pairs = [
+            CString::new("id").unwrap(),
+            CString::new("anchor-1").unwrap(),
+            CString::new("frobnicate").unwrap(),
+            CString::new("yes").unwrap(),
+            CString::new("r").unwrap(),
+            CString::new("5").unwrap(),
+        ];
+
+        let mut v: Vec<*const libc::c_char> = pairs.iter().map(|s| s.as_ptr()).collect();
+        v.push(ptr::null());
+
+        let pbag = unsafe { PropertyBag::new_from_key_value_pairs(v.as_ptr()) };
+        node.set_atts(&node, ptr::null(), &pbag);
+
+        assert_eq!(node.get_element_name(), Some("circle".to_string()));
+        assert_eq!(node.get_attribute("r"), Some("5".to_string()));
+        assert_eq!(node.get_attribute("frobnicate"), Some("yes".to_string()));
+        assert_eq!(node.get_attribute("bogus"), None);
+        assert_eq!(node.get_attribute_names(), vec!["id", "frobnicate", "r"]);
+    }
+
     #[test]
     fn node_is_its_own_ancestor() {
         let node = Rc::new(Node::new(
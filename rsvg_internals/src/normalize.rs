@@ -0,0 +1,1269 @@
+use cairo::{self, MatrixTrait};
+use glib::translate::*;
+use glib_sys;
+use libc;
+
+use std::rc::Rc;
+
+use attributes::Attribute;
+use color::Color;
+use diagnostics::{self, Diagnostic, DiagnosticSeverity};
+use dom;
+use drawing_ctx::{self, RsvgDrawingCtx};
+use float_eq_cairo::ApproxEqCairo;
+use handle::RsvgHandle;
+use length::{LengthDir, LengthUnit, RsvgLength, StrokeDasharray};
+use node::{Node, NodeType, RsvgNode};
+use paint_server::PaintServer;
+use path_builder::{PathBuilder, PathCommand};
+use serialize::{self, CssDeclaration, SerializeFilter, SerializeOptions};
+use state::{
+    self,
+    CompOp,
+    FillRule,
+    FontSize,
+    RsvgState,
+    StrokeLinecap,
+    StrokeLinejoin,
+    StrokeMiterlimit,
+    StrokeWidth,
+};
+
+extern "C" {
+    fn rsvg_handle_draw_for_normalizer(
+        handle: *const RsvgHandle,
+        normalizer: *mut libc::c_void,
+    ) -> glib_sys::gboolean;
+
+    fn rsvg_handle_get_dimensions(handle: *const RsvgHandle, dimensions: *mut RsvgDimensionData);
+
+    fn rsvg_drawing_ctx_get_normalizer(draw_ctx: *const RsvgDrawingCtx) -> *mut libc::c_void;
+
+    fn rsvg_drawing_ctx_set_normalizer(
+        draw_ctx: *const RsvgDrawingCtx,
+        normalizer: *mut libc::c_void,
+    );
+}
+
+// Keep this in sync with ../../librsvg/rsvg.h:RsvgDimensionData
+#[repr(C)]
+struct RsvgDimensionData {
+    width: libc::c_int,
+    height: libc::c_int,
+    em: f64,
+    ex: f64,
+}
+
+// The style properties that get written as presentation attributes on text
+// and on the elements that are copied into the `<defs>`.  The marker
+// properties are left out, since markers get replaced by their contents, and
+// so are animations and `comp-op`, which can't be written.
+const PROPERTIES: &[&str] = &[
+    "baseline-shift",
+    "clip-path",
+    "clip-rule",
+    "color",
+    "direction",
+    "display",
+    "fill",
+    "fill-opacity",
+    "fill-rule",
+    "filter",
+    "flood-color",
+    "flood-opacity",
+    "font-family",
+    "font-feature-settings",
+    "font-kerning",
+    "font-size",
+    "font-stretch",
+    "font-style",
+    "font-variant",
+    "font-variant-caps",
+    "font-variant-ligatures",
+    "font-variant-numeric",
+    "font-variant-position",
+    "font-weight",
+    "glyph-orientation-vertical",
+    "inline-size",
+    "letter-spacing",
+    "mask",
+    "opacity",
+    "overflow",
+    "shape-inside",
+    "shape-rendering",
+    "stop-color",
+    "stop-opacity",
+    "stroke",
+    "stroke-dasharray",
+    "stroke-dashoffset",
+    "stroke-linecap",
+    "stroke-linejoin",
+    "stroke-miterlimit",
+    "stroke-opacity",
+    "stroke-width",
+    "text-anchor",
+    "text-decoration",
+    "text-decoration-color",
+    "text-decoration-style",
+    "text-orientation",
+    "text-rendering",
+    "unicode-bidi",
+    "visibility",
+    "word-spacing",
+    "writing-mode",
+    "xml:lang",
+    "xml:space",
+];
+
+// The properties that children don't inherit, so they have to be written
+// even if a child has the same value as its parent.
+const NOT_INHERITED: &[&str] = &[
+    "baseline-shift",
+    "clip-path",
+    "display",
+    "filter",
+    "flood-color",
+    "flood-opacity",
+    "inline-size",
+    "mask",
+    "opacity",
+    "overflow",
+    "shape-inside",
+    "stop-color",
+    "stop-opacity",
+    "text-decoration",
+    "unicode-bidi",
+];
+
+// The properties that reference other elements, which get copied into the
+// `<defs>`.
+const REFERENCE_PROPERTIES: &[&str] = &["clip-path", "fill", "filter", "mask", "stroke"];
+
+const CLIP_PATH_ID_PREFIX: &str = "rsvg-normalize-clip-";
+
+/// Options for `normalize()`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct NormalizeOptions {
+    /// Put each element on its own line, indented by its depth in the tree.
+    pub pretty: bool,
+
+    /// Apply the transformation of each shape to the coordinates of its path,
+    /// instead of writing it in a `transform` attribute.  Shapes that are
+    /// painted with a gradient or a pattern, stroked shapes whose
+    /// transformation does not scale equally in all directions, and shapes and
+    /// groups with a clipping path, a mask or a filter keep their
+    /// `transform`, since what they are painted with would change otherwise.
+    /// So do text and images.
+    pub bake_transforms: bool,
+}
+
+/// Writes a loaded document in a normalized form, for renderers that only
+/// understand a minimal subset of SVG.
+///
+/// The document is drawn in the same way as `rsvg_handle_render_cairo()`
+/// would do with an identity transformation, and each element is written as
+/// it gets drawn.  Groups are kept, with their opacity, clipping path, mask
+/// and filter, and the viewports of nested `<svg>` elements and markers that
+/// clip their contents become groups with a clipping path.  `<use>` elements
+/// are replaced by a group with the elements that they instantiate, markers
+/// are replaced by their contents, and basic shapes become paths.  Paths,
+/// text and images get the styles that come from classes, stylesheets and
+/// inheritance as explicit presentation attributes.  Percentages and
+/// font-relative units in the styles of paths are resolved to user units.
+///
+/// The clipping paths, masks, filters, gradients and patterns that the
+/// document uses are copied into a `<defs>`, with the styles of their
+/// children made explicit in the same way, along with the elements that
+/// those refer to.
+///
+/// Returns `None` if the handle has not finished loading.  Otherwise, returns
+/// the document and a `Diagnostic` for each part of it that the result can't
+/// express: compositing operators, references to elements that are not in
+/// the document, and `<use>` elements and basic shapes in the elements that
+/// are copied into the `<defs>`, which are only drawn as part of the element
+/// that uses them.
+pub fn normalize(
+    handle: *const RsvgHandle,
+    options: &NormalizeOptions,
+) -> Option<(String, Vec<Diagnostic>)> {
+    let mut normalizer = Normalizer::new(handle, options);
+
+    let ok: bool = unsafe {
+        from_glib(rsvg_handle_draw_for_normalizer(
+            handle,
+            &mut normalizer as *mut Normalizer as *mut libc::c_void,
+        ))
+    };
+
+    if !ok {
+        return None;
+    }
+
+    let mut dimensions = RsvgDimensionData {
+        width: 0,
+        height: 0,
+        em: 0.0,
+        ex: 0.0,
+    };
+
+    unsafe {
+        rsvg_handle_get_dimensions(handle, &mut dimensions);
+    }
+
+    let svg = normalizer.to_svg(dimensions.width, dimensions.height);
+
+    Some((svg, normalizer.diagnostics))
+}
+
+fn get_normalizer<'a>(draw_ctx: *const RsvgDrawingCtx) -> Option<&'a mut Normalizer> {
+    let normalizer = unsafe { rsvg_drawing_ctx_get_normalizer(draw_ctx) as *mut Normalizer };
+
+    if normalizer.is_null() {
+        None
+    } else {
+        Some(unsafe { &mut *normalizer })
+    }
+}
+
+/// Records the shape `node` that is being drawn with `builder`, if the caller
+/// of the drawing is normalizing the document.
+pub fn add_path(draw_ctx: *const RsvgDrawingCtx, node: &Node, builder: &PathBuilder) {
+    if let Some(normalizer) = get_normalizer(draw_ctx) {
+        let state = drawing_ctx::get_current_state(draw_ctx);
+        normalizer.add_path(draw_ctx, node, state, builder);
+    }
+}
+
+/// Starts a group for the element `node`, whose contents are about to be
+/// drawn with the current state.  Call `end_group()` when done.
+pub fn begin_group(draw_ctx: *const RsvgDrawingCtx, node: &Node) {
+    if let Some(normalizer) = get_normalizer(draw_ctx) {
+        let state = drawing_ctx::get_current_state(draw_ctx);
+        normalizer.begin_group(node, state);
+    }
+}
+
+pub fn end_group(draw_ctx: *const RsvgDrawingCtx) {
+    if let Some(normalizer) = get_normalizer(draw_ctx) {
+        normalizer.end_group();
+    }
+}
+
+/// Clips the rest of the current group to a rectangle in the current user
+/// space, for viewports that clip their contents.
+pub fn add_clipping_rect(draw_ctx: *const RsvgDrawingCtx, x: f64, y: f64, w: f64, h: f64) {
+    if let Some(normalizer) = get_normalizer(draw_ctx) {
+        let state = drawing_ctx::get_current_state(draw_ctx);
+        let affine = state::get_state_rust(state).affine;
+        normalizer.add_clipping_rect(&affine, x, y, w, h);
+    }
+}
+
+/// Records the text element `node`, with its children, which is about to be
+/// drawn with the current state.
+pub fn add_text(draw_ctx: *const RsvgDrawingCtx, node: &RsvgNode) {
+    if let Some(normalizer) = get_normalizer(draw_ctx) {
+        let state = drawing_ctx::get_current_state(draw_ctx);
+        let font_size = RsvgLength::new(
+            drawing_ctx::get_normalized_font_size(draw_ctx),
+            LengthUnit::Default,
+            LengthDir::Both,
+        );
+
+        normalizer.add_text(node, state, &font_size);
+    }
+}
+
+/// Records the image element `node`, which is about to be drawn with the
+/// current state in the given viewport.
+pub fn add_image(draw_ctx: *const RsvgDrawingCtx, node: &Node, x: f64, y: f64, w: f64, h: f64) {
+    if let Some(normalizer) = get_normalizer(draw_ctx) {
+        let state = drawing_ctx::get_current_state(draw_ctx);
+        normalizer.add_image(node, state, x, y, w, h);
+    }
+}
+
+/// Stops recording elements while drawing something that is not part of the
+/// canvas, like the contents of a pattern.  Pass the return value to
+/// `resume()` when done.
+pub fn pause(draw_ctx: *const RsvgDrawingCtx) -> *mut libc::c_void {
+    unsafe {
+        let normalizer = rsvg_drawing_ctx_get_normalizer(draw_ctx);
+        rsvg_drawing_ctx_set_normalizer(draw_ctx, ::std::ptr::null_mut());
+        normalizer
+    }
+}
+
+pub fn resume(draw_ctx: *const RsvgDrawingCtx, paused: *mut libc::c_void) {
+    unsafe {
+        rsvg_drawing_ctx_set_normalizer(draw_ctx, paused);
+    }
+}
+
+struct Group {
+    /// The transformation from the user space of the group's contents to the
+    /// canvas.
+    space: cairo::Matrix,
+
+    /// Whether the group only clips the rest of its parent's contents to a
+    /// rectangle, and gets closed together with its parent.
+    for_clipping_rect: bool,
+}
+
+struct Normalizer {
+    handle: *const RsvgHandle,
+    bake_transforms: bool,
+    pretty: bool,
+
+    /// The contents of the root element, as they get drawn.
+    out: String,
+
+    /// The groups that are open in `out`, innermost last.
+    groups: Vec<Group>,
+
+    /// The path data of the clipping rectangles of viewports, in the user
+    /// space of the groups that they clip.
+    clipping_rects: Vec<String>,
+
+    /// The IRIs of the elements that get copied into the `<defs>`, in order
+    /// of first use.
+    references: Vec<String>,
+
+    initial_values: Vec<(&'static str, String)>,
+
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Normalizer {
+    fn new(handle: *const RsvgHandle, options: &NormalizeOptions) -> Normalizer {
+        let initial_state = state::new();
+        let initial_values = computed_values(initial_state, &FontSize::default().0);
+        state::free(initial_state);
+
+        Normalizer {
+            handle,
+            bake_transforms: options.bake_transforms,
+            pretty: options.pretty,
+            out: String::new(),
+            groups: Vec::new(),
+            clipping_rects: Vec::new(),
+            references: Vec::new(),
+            initial_values,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    // The user space in which the contents of the current group get written.
+    fn space(&self) -> cairo::Matrix {
+        self.groups
+            .last()
+            .map_or_else(cairo::Matrix::identity, |g| g.space)
+    }
+
+    // Returns the transformation from a user space to the one of the current
+    // group, given the transformation from that user space to the canvas.
+    fn relative_transform(&self, affine: &cairo::Matrix) -> cairo::Matrix {
+        let inverse = self
+            .space()
+            .try_invert()
+            .unwrap_or_else(|_| cairo::Matrix::identity());
+
+        cairo::Matrix::multiply(affine, &inverse)
+    }
+
+    // The depth in the output of the elements in the current group.
+    fn depth(&self) -> usize {
+        self.groups.len() + 1
+    }
+
+    fn add_diagnostic(&mut self, node: &Node, attr_name: Option<&str>, message: &str) {
+        let diagnostic = Diagnostic::new(node, DiagnosticSeverity::Warning, attr_name, message);
+
+        // Elements get drawn once for each time that they are used
+        if !self.diagnostics.contains(&diagnostic) {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    fn add_reference(&mut self, node: &Node, attr_name: &str, iri: &str) {
+        if self.references.iter().any(|r| r == iri) {
+            return;
+        }
+
+        if iri.starts_with('#') && dom::get_element_by_id(self.handle, &iri[1..]).is_some() {
+            self.references.push(iri.to_string());
+        } else {
+            self.add_diagnostic(
+                node,
+                Some(attr_name),
+                &format!(
+                    "\"{}\" is not an element of the document, so it can't be copied",
+                    iri
+                ),
+            );
+        }
+    }
+
+    // Adds the opacity, clipping path, mask and filter that an element is
+    // drawn with to its attributes.
+    fn add_effects(
+        &mut self,
+        node: &Node,
+        state: *const RsvgState,
+        attributes: &mut Vec<(&'static str, String)>,
+    ) {
+        let opacity = state::get_opacity(state);
+        if opacity != 0xff {
+            attributes.push(("opacity", dom::opacity_to_string(opacity)));
+        }
+
+        let effects = [
+            ("clip-path", state::get_clip_path(state)),
+            ("mask", state::get_mask(state)),
+            ("filter", state::get_filter(state)),
+        ];
+
+        for &(attr_name, iri) in &effects {
+            if let Some(iri) = iri {
+                self.add_reference(node, attr_name, iri);
+                attributes.push((attr_name, format!("url({})", iri)));
+            }
+        }
+
+        if state::get_state_rust(state).comp_op.unwrap_or_default() != CompOp::default() {
+            self.add_diagnostic(
+                node,
+                Some("comp-op"),
+                "compositing operators can't be written in a normalized document",
+            );
+        }
+    }
+
+    fn write_element(&mut self, name: &str, attributes: &[(&str, String)], empty: bool) {
+        if self.pretty {
+            let depth = self.depth();
+            indent(&mut self.out, depth);
+        }
+
+        write_start_tag(&mut self.out, name, attributes, empty);
+
+        if self.pretty {
+            self.out.push('\n');
+        }
+    }
+
+    fn write_end_tag(&mut self, name: &str) {
+        if self.pretty {
+            let depth = self.depth();
+            indent(&mut self.out, depth);
+        }
+
+        self.out.push_str("</");
+        self.out.push_str(name);
+        self.out.push('>');
+
+        if self.pretty {
+            self.out.push('\n');
+        }
+    }
+
+    fn begin_group(&mut self, node: &Node, state: *const RsvgState) {
+        let affine = state::get_state_rust(state).affine;
+
+        let has_effects = state::get_clip_path(state).is_some()
+            || state::get_mask(state).is_some()
+            || state::get_filter(state).is_some();
+
+        let mut attributes = Vec::new();
+
+        // Clipping paths, masks and filters are in the user space of the group
+        let space = if self.bake_transforms && !has_effects {
+            self.space()
+        } else {
+            let transform = self.relative_transform(&affine);
+            if !is_identity(&transform) {
+                attributes.push(("transform", matrix_to_string(&transform)));
+            }
+
+            affine
+        };
+
+        self.add_effects(node, state, &mut attributes);
+
+        self.write_element("g", &attributes, false);
+
+        self.groups.push(Group {
+            space,
+            for_clipping_rect: false,
+        });
+    }
+
+    fn end_group(&mut self) {
+        while let Some(group) = self.groups.pop() {
+            self.write_end_tag("g");
+
+            if !group.for_clipping_rect {
+                break;
+            }
+        }
+    }
+
+    fn add_clipping_rect(&mut self, affine: &cairo::Matrix, x: f64, y: f64, w: f64, h: f64) {
+        let mut builder = PathBuilder::new();
+        builder.move_to(x, y);
+        builder.line_to(x + w, y);
+        builder.line_to(x + w, y + h);
+        builder.line_to(x, y + h);
+        builder.close_path();
+
+        let transform = self.relative_transform(affine);
+        self.clipping_rects.push(path_data(&builder, &transform));
+
+        let id = format!("{}{}", CLIP_PATH_ID_PREFIX, self.clipping_rects.len());
+        self.write_element("g", &[("clip-path", format!("url(#{})", id))], false);
+
+        let space = self.space();
+        self.groups.push(Group {
+            space,
+            for_clipping_rect: true,
+        });
+    }
+
+    fn add_path(
+        &mut self,
+        draw_ctx: *const RsvgDrawingCtx,
+        node: &Node,
+        state: *const RsvgState,
+        builder: &PathBuilder,
+    ) {
+        let rstate = state::get_state_rust(state);
+        let affine = self.relative_transform(&rstate.affine);
+
+        let fill = state::get_fill(state);
+        let stroke = state::get_stroke(state);
+
+        let has_effects = state::get_clip_path(state).is_some()
+            || state::get_mask(state).is_some()
+            || state::get_filter(state).is_some();
+
+        let scale = if self.bake_transforms && !is_iri(fill) && !is_iri(stroke) && !has_effects {
+            match (stroke, uniform_scale(&affine)) {
+                (_, Some(scale)) => Some(scale),
+                (None, None) => Some(1.0),
+                (Some(_), None) => None,
+            }
+        } else {
+            None
+        };
+
+        let mut attributes = Vec::new();
+
+        if scale.is_some() {
+            attributes.push(("d", path_data(builder, &affine)));
+        } else {
+            attributes.push(("d", path_data(builder, &cairo::Matrix::identity())));
+
+            if !is_identity(&affine) {
+                attributes.push(("transform", matrix_to_string(&affine)));
+            }
+        }
+
+        // Lengths in user units, scaled to the group's units if the path is baked
+        let length = |l: f64| l * scale.unwrap_or(1.0);
+
+        let fill_string = self.paint_to_string(node, "fill", fill, state);
+        attributes.push(("fill", fill_string));
+
+        let fill_opacity = state::get_fill_opacity(state);
+        if fill_opacity != 0xff {
+            attributes.push(("fill-opacity", dom::opacity_to_string(fill_opacity)));
+        }
+
+        let fill_rule = rstate.fill_rule.unwrap_or_default();
+        if fill_rule != FillRule::default() {
+            attributes.push(("fill-rule", fill_rule.to_string()));
+        }
+
+        if stroke.is_some() {
+            let stroke_string = self.paint_to_string(node, "stroke", stroke, state);
+            attributes.push(("stroke", stroke_string));
+
+            let stroke_opacity = state::get_stroke_opacity(state);
+            if stroke_opacity != 0xff {
+                attributes.push(("stroke-opacity", dom::opacity_to_string(stroke_opacity)));
+            }
+
+            let width = rstate
+                .stroke_width
+                .as_ref()
+                .map_or_else(|| StrokeWidth::default().0, |w| w.0)
+                .normalize(draw_ctx);
+            attributes.push(("stroke-width", length(width).to_string()));
+
+            let line_cap = rstate.stroke_line_cap.unwrap_or_default();
+            if line_cap != StrokeLinecap::default() {
+                attributes.push(("stroke-linecap", line_cap.to_string()));
+            }
+
+            let line_join = rstate.stroke_line_join.unwrap_or_default();
+            if line_join != StrokeLinejoin::default() {
+                attributes.push(("stroke-linejoin", line_join.to_string()));
+            }
+
+            let miter_limit = rstate.stroke_miterlimit.clone().unwrap_or_default();
+            if miter_limit != StrokeMiterlimit::default() {
+                attributes.push(("stroke-miterlimit", miter_limit.to_string()));
+            }
+
+            if let Some(&StrokeDasharray::Dasharray(ref dashes)) =
+                state::get_stroke_dasharray(state)
+            {
+                let dashes: Vec<f64> = dashes
+                    .iter()
+                    .map(|d| length(d.normalize(draw_ctx)))
+                    .collect();
+
+                // cairo does not dash the stroke if the dashes add up to zero
+                if dashes.iter().fold(0.0, |acc, &d| acc + d) > 0.0 {
+                    let strings: Vec<String> = dashes.iter().map(f64::to_string).collect();
+                    attributes.push(("stroke-dasharray", strings.join(",")));
+
+                    let offset = length(state::get_dash_offset(state).normalize(draw_ctx));
+                    if offset != 0.0 {
+                        attributes.push(("stroke-dashoffset", offset.to_string()));
+                    }
+                }
+            }
+        }
+
+        self.add_effects(node, state, &mut attributes);
+
+        self.write_element("path", &attributes, true);
+    }
+
+    fn paint_to_string(
+        &mut self,
+        node: &Node,
+        attr_name: &str,
+        paint: Option<&PaintServer>,
+        state: *const RsvgState,
+    ) -> String {
+        match paint {
+            Some(&PaintServer::SolidColor(Color::CurrentColor)) => {
+                dom::color_to_string(&state::get_current_color(state))
+            }
+
+            Some(&PaintServer::Iri { ref iri, .. }) => {
+                self.add_reference(node, attr_name, iri);
+                dom::paint_to_string(paint)
+            }
+
+            _ => dom::paint_to_string(paint),
+        }
+    }
+
+    fn add_text(&mut self, node: &RsvgNode, state: *const RsvgState, font_size: &RsvgLength) {
+        let affine = state::get_state_rust(state).affine;
+        let transform = self.relative_transform(&affine);
+
+        let mut root_attributes = Vec::new();
+        if !is_identity(&transform) {
+            root_attributes.push(("transform", matrix_to_string(&transform)));
+        }
+
+        let depth = self.depth();
+
+        let (text, references, diagnostics) = {
+            let mut inliner = StyleInliner {
+                root: node.clone(),
+                root_state: state,
+                root_font_size: *font_size,
+                root_attributes,
+                drawn: true,
+                initial_values: &self.initial_values,
+                references: Vec::new(),
+                diagnostics: Vec::new(),
+            };
+
+            let text =
+                serialize::serialize_element(node, depth, &self.serialize_options(), &mut inliner);
+
+            (text, inliner.references, inliner.diagnostics)
+        };
+
+        self.out.push_str(&text);
+
+        self.add_inliner_results(references, diagnostics);
+    }
+
+    fn add_image(&mut self, node: &Node, state: *const RsvgState, x: f64, y: f64, w: f64, h: f64) {
+        let affine = state::get_state_rust(state).affine;
+        let transform = self.relative_transform(&affine);
+
+        let mut attributes = vec![
+            ("x", x.to_string()),
+            ("y", y.to_string()),
+            ("width", w.to_string()),
+            ("height", h.to_string()),
+        ];
+
+        if let Some(aspect) = node.get_attribute("preserveAspectRatio") {
+            attributes.push(("preserveAspectRatio", aspect));
+        }
+
+        if let Some(href) = node.get_attribute("xlink:href") {
+            attributes.push(("xlink:href", href));
+        }
+
+        if !is_identity(&transform) {
+            attributes.push(("transform", matrix_to_string(&transform)));
+        }
+
+        if state::is_overflow(state) {
+            attributes.push(("overflow", "visible".to_string()));
+        }
+
+        self.add_effects(node, state, &mut attributes);
+
+        self.write_element("image", &attributes, true);
+    }
+
+    fn add_inliner_results(
+        &mut self,
+        references: Vec<(RsvgNode, &'static str, String)>,
+        diagnostics: Vec<Diagnostic>,
+    ) {
+        for (node, attr_name, iri) in references {
+            self.add_reference(&node, attr_name, &iri);
+        }
+
+        for diagnostic in diagnostics {
+            if !self.diagnostics.contains(&diagnostic) {
+                self.diagnostics.push(diagnostic);
+            }
+        }
+    }
+
+    fn serialize_options(&self) -> SerializeOptions {
+        SerializeOptions {
+            pretty: self.pretty,
+            drop_nodes_in_error: false,
+        }
+    }
+
+    // Writes the elements that the document references, and the ones that
+    // those refer to in turn.
+    fn write_references(&mut self, out: &mut String) {
+        let mut i = 0;
+
+        while i < self.references.len() {
+            let iri = self.references[i].clone();
+            i += 1;
+
+            let node = match dom::get_element_by_id(self.handle, &iri[1..]) {
+                Some(node) => node,
+                None => continue,
+            };
+
+            let state = dom::compute_state(&node);
+
+            let (text, references, diagnostics) = {
+                let mut inliner = StyleInliner {
+                    root: node.clone(),
+                    root_state: state,
+                    root_font_size: dom::compute_font_size(&node),
+                    root_attributes: Vec::new(),
+                    drawn: false,
+                    initial_values: &self.initial_values,
+                    references: Vec::new(),
+                    diagnostics: Vec::new(),
+                };
+
+                let text =
+                    serialize::serialize_element(&node, 2, &self.serialize_options(), &mut inliner);
+
+                (text, inliner.references, inliner.diagnostics)
+            };
+
+            state::free(state);
+
+            out.push_str(&text);
+
+            self.add_inliner_results(references, diagnostics);
+        }
+    }
+
+    fn to_svg(&mut self, width: i32, height: i32) -> String {
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let newline = if self.pretty { "\n" } else { "" };
+
+        out.push_str(&format!(
+            "<svg xmlns=\"{}\" xmlns:xlink=\"{}\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">{}",
+            serialize::SVG_NAMESPACE,
+            serialize::XLINK_NAMESPACE,
+            width,
+            height,
+            width,
+            height,
+            newline
+        ));
+
+        let mut defs = String::new();
+
+        for (i, data) in self.clipping_rects.iter().enumerate() {
+            if self.pretty {
+                indent(&mut defs, 2);
+            }
+
+            let id = format!("{}{}", CLIP_PATH_ID_PREFIX, i + 1);
+            write_start_tag(&mut defs, "clipPath", &[("id", id)], false);
+            write_start_tag(&mut defs, "path", &[("d", data.clone())], true);
+            defs.push_str("</clipPath>");
+            defs.push_str(newline);
+        }
+
+        self.write_references(&mut defs);
+
+        if !defs.is_empty() {
+            if self.pretty {
+                indent(&mut out, 1);
+            }
+
+            out.push_str("<defs>");
+            out.push_str(newline);
+            out.push_str(&defs);
+
+            if self.pretty {
+                indent(&mut out, 1);
+            }
+
+            out.push_str("</defs>");
+            out.push_str(newline);
+        }
+
+        out.push_str(&self.out);
+        out.push_str("</svg>\n");
+
+        out
+    }
+}
+
+/// The filter with which the normalizer writes text and the elements that it
+/// copies into the `<defs>`.  It replaces the styles of the elements with
+/// presentation attributes for their computed values.
+struct StyleInliner<'a> {
+    /// The element that is being written.
+    root: RsvgNode,
+
+    /// The computed state and font size of `root`.
+    root_state: *const RsvgState,
+    root_font_size: RsvgLength,
+
+    /// The attributes that replace the transformation of `root`.
+    root_attributes: Vec<(&'static str, String)>,
+
+    /// Whether `root` is drawn as part of the canvas, rather than copied into
+    /// the `<defs>`.  Drawn elements lose their ids, since `<use>` elements may
+    /// draw them many times.
+    drawn: bool,
+
+    initial_values: &'a [(&'static str, String)],
+
+    /// The references to other elements in what was written, as the element,
+    /// the attribute and the IRI.
+    references: Vec<(RsvgNode, &'static str, String)>,
+
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> StyleInliner<'a> {
+    fn is_root(&self, node: &RsvgNode) -> bool {
+        Rc::ptr_eq(node, &self.root)
+    }
+
+    // Computes the state and font size of `node`, which is `root` or one of
+    // its descendants.  Free the state with `state::free()`.
+    fn compute_state(&self, node: &RsvgNode) -> (*mut RsvgState, RsvgLength) {
+        match node.get_parent() {
+            Some(ref parent) if !self.is_root(node) => {
+                let (parent_state, parent_font_size) = self.compute_state(parent);
+                let state = dom::compute_child_state(node, parent_state);
+                state::free(parent_state);
+
+                (state, dom::compute_child_font_size(node, &parent_font_size))
+            }
+
+            _ => {
+                let state = state::new();
+                state::clone_from(state, self.root_state);
+
+                (state, self.root_font_size)
+            }
+        }
+    }
+
+    fn computed_values(&self, node: &RsvgNode) -> Vec<(&'static str, String)> {
+        let (state, font_size) = self.compute_state(node);
+        let values = computed_values(state, &font_size);
+        state::free(state);
+
+        values
+    }
+}
+
+impl<'a> SerializeFilter for StyleInliner<'a> {
+    fn keep_element(&mut self, node: &RsvgNode, name: &str) -> bool {
+        match node.get_type() {
+            NodeType::Use
+            | NodeType::Rect
+            | NodeType::Circle
+            | NodeType::Ellipse
+            | NodeType::Line
+            | NodeType::Polyline
+            | NodeType::Polygon => {
+                self.diagnostics.push(Diagnostic::new(
+                    node,
+                    DiagnosticSeverity::Warning,
+                    None,
+                    &format!(
+                        "<{}> is copied as it was written, since it is only drawn as part of \
+                         another element",
+                        name
+                    ),
+                ));
+            }
+
+            _ => (),
+        }
+
+        true
+    }
+
+    fn filter_attribute(
+        &mut self,
+        node: &RsvgNode,
+        _: &str,
+        attr: Attribute,
+        name: &str,
+        value: &str,
+    ) -> Option<String> {
+        if name == "class" || name == "style" || PROPERTIES.contains(&name) {
+            return None;
+        }
+
+        if self.drawn && (name == "id" || (name == "transform" && self.is_root(node))) {
+            return None;
+        }
+
+        if attr == Attribute::XlinkHref && value.starts_with('#') {
+            self.references
+                .push((node.clone(), "xlink:href", value.to_string()));
+        }
+
+        Some(value.to_string())
+    }
+
+    fn keep_css_declaration(&mut self, _: &CssDeclaration) -> bool {
+        false
+    }
+
+    fn extra_attributes(&mut self, node: &RsvgNode) -> Vec<(String, String)> {
+        let values = self.computed_values(node);
+
+        let (parent_values, mut attributes) = if self.is_root(node) {
+            (None, self.root_attributes.clone())
+        } else {
+            let parent = node.get_parent().unwrap();
+            (Some(self.computed_values(&parent)), Vec::new())
+        };
+
+        let changed = changed_values(
+            &values,
+            parent_values.as_ref().map(Vec::as_slice),
+            self.initial_values,
+        );
+
+        for &(name, ref value) in &changed {
+            if REFERENCE_PROPERTIES.contains(&name) {
+                if let Some(iri) = url_iri(value) {
+                    self.references.push((node.clone(), name, iri.to_string()));
+                }
+            }
+        }
+
+        attributes.extend(changed);
+
+        attributes
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect()
+    }
+}
+
+fn computed_values(state: *const RsvgState, font_size: &RsvgLength) -> Vec<(&'static str, String)> {
+    PROPERTIES
+        .iter()
+        .filter_map(|&name| dom::state_get_value(state, font_size, name).map(|v| (name, v)))
+        .collect()
+}
+
+fn value_of<'a>(values: &'a [(&'static str, String)], name: &str) -> Option<&'a str> {
+    values
+        .iter()
+        .find(|&&(n, _)| n == name)
+        .map(|&(_, ref v)| v.as_str())
+}
+
+// Picks the computed values that an element has to specify: the ones that are
+// not the same as its parent's, or as the initial values for the first element
+// that gets written and for properties that are not inherited.
+fn changed_values(
+    values: &[(&'static str, String)],
+    parent_values: Option<&[(&'static str, String)]>,
+    initial_values: &[(&'static str, String)],
+) -> Vec<(&'static str, String)> {
+    values
+        .iter()
+        .filter(|&&(name, ref value)| {
+            let base = match parent_values {
+                Some(parent_values) if !NOT_INHERITED.contains(&name) => parent_values,
+                _ => initial_values,
+            };
+
+            value_of(base, name) != Some(value.as_str())
+        })
+        .cloned()
+        .collect()
+}
+
+// Returns the IRI in a value like `url(#foo)` or `url(#foo) red`.
+fn url_iri(value: &str) -> Option<&str> {
+    if value.starts_with("url(") {
+        value[4..].find(')').map(|end| &value[4..4 + end])
+    } else {
+        None
+    }
+}
+
+fn indent(out: &mut String, depth: usize) {
+    for _ in 0..depth {
+        out.push_str("  ");
+    }
+}
+
+fn write_start_tag(out: &mut String, name: &str, attributes: &[(&str, String)], empty: bool) {
+    out.push('<');
+    out.push_str(name);
+
+    for &(name, ref value) in attributes {
+        out.push(' ');
+        out.push_str(name);
+        out.push_str("=\"");
+        serialize::escape_attribute_value(value, out);
+        out.push('"');
+    }
+
+    out.push_str(if empty { "/>" } else { ">" });
+}
+
+fn is_iri(paint: Option<&PaintServer>) -> bool {
+    match paint {
+        Some(&PaintServer::Iri { .. }) => true,
+        _ => false,
+    }
+}
+
+fn is_identity(m: &cairo::Matrix) -> bool {
+    m.xx.approx_eq_cairo(&1.0)
+        && m.yx.approx_eq_cairo(&0.0)
+        && m.xy.approx_eq_cairo(&0.0)
+        && m.yy.approx_eq_cairo(&1.0)
+        && m.x0.approx_eq_cairo(&0.0)
+        && m.y0.approx_eq_cairo(&0.0)
+}
+
+// Returns the scale factor of a transformation that scales equally in all
+// directions, or `None` if it stretches or skews shapes.
+fn uniform_scale(m: &cairo::Matrix) -> Option<f64> {
+    let sx = m.xx.hypot(m.yx);
+    let sy = m.xy.hypot(m.yy);
+    let dot = m.xx * m.xy + m.yx * m.yy;
+
+    if sx.approx_eq_cairo(&sy) && dot.approx_eq_cairo(&0.0) {
+        Some(sx)
+    } else {
+        None
+    }
+}
+
+fn path_data(builder: &PathBuilder, affine: &cairo::Matrix) -> String {
+    let mut data = Vec::new();
+
+    for command in builder.get_path_commands() {
+        match *command {
+            PathCommand::MoveTo(x, y) => {
+                let (x, y) = affine.transform_point(x, y);
+                data.push(format!("M {} {}", x, y));
+            }
+
+            PathCommand::LineTo(x, y) => {
+                let (x, y) = affine.transform_point(x, y);
+                data.push(format!("L {} {}", x, y));
+            }
+
+            PathCommand::CurveTo((x2, y2), (x3, y3), (x4, y4)) => {
+                let (x2, y2) = affine.transform_point(x2, y2);
+                let (x3, y3) = affine.transform_point(x3, y3);
+                let (x4, y4) = affine.transform_point(x4, y4);
+                data.push(format!("C {} {} {} {} {} {}", x2, y2, x3, y3, x4, y4));
+            }
+
+            PathCommand::ClosePath => data.push("Z".to_string()),
+        }
+    }
+
+    data.join(" ")
+}
+
+fn matrix_to_string(m: &cairo::Matrix) -> String {
+    format!(
+        "matrix({} {} {} {} {} {})",
+        m.xx, m.yx, m.xy, m.yy, m.x0, m.y0
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_dom_normalize(
+    handle: *const RsvgHandle,
+    pretty: glib_sys::gboolean,
+    bake_transforms: glib_sys::gboolean,
+    diagnostics: *mut glib_sys::GPtrArray,
+) -> *mut libc::c_char {
+    let options = NormalizeOptions {
+        pretty: from_glib(pretty),
+        bake_transforms: from_glib(bake_transforms),
+    };
+
+    match normalize(handle, &options) {
+        Some((svg, problems)) => {
+            diagnostics::append_to_array(diagnostics, &problems);
+            svg.to_glib_full()
+        }
+
+        None => ::std::ptr::null_mut(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_builder() -> PathBuilder {
+        let mut builder = PathBuilder::new();
+
+        builder.move_to(10.0, 20.0);
+        builder.line_to(30.0, 20.0);
+        builder.curve_to(30.0, 30.0, 20.0, 40.0, 10.0, 40.0);
+        builder.close_path();
+
+        builder
+    }
+
+    #[test]
+    fn writes_path_data() {
+        assert_eq!(
+            path_data(&test_builder(), &cairo::Matrix::identity()),
+            "M 10 20 L 30 20 C 30 30 20 40 10 40 Z"
+        );
+    }
+
+    #[test]
+    fn bakes_transforms_into_path_data() {
+        let m = cairo::Matrix::new(2.0, 0.0, 0.0, 2.0, 5.0, -5.0);
+
+        assert_eq!(
+            path_data(&test_builder(), &m),
+            "M 25 35 L 65 35 C 65 55 45 75 25 75 Z"
+        );
+    }
+
+    #[test]
+    fn writes_matrices() {
+        let m = cairo::Matrix::new(1.0, 2.0, 3.0, 4.0, 5.5, -6.0);
+
+        assert_eq!(matrix_to_string(&m), "matrix(1 2 3 4 5.5 -6)");
+    }
+
+    #[test]
+    fn detects_uniform_scales() {
+        assert_eq!(uniform_scale(&cairo::Matrix::identity()), Some(1.0));
+        assert_eq!(
+            uniform_scale(&cairo::Matrix::new(2.0, 0.0, 0.0, 2.0, 10.0, 10.0)),
+            Some(2.0)
+        );
+
+        // rotation by 90 degrees and a reflection
+        assert_eq!(
+            uniform_scale(&cairo::Matrix::new(0.0, 3.0, -3.0, 0.0, 0.0, 0.0)),
+            Some(3.0)
+        );
+        assert_eq!(
+            uniform_scale(&cairo::Matrix::new(-1.0, 0.0, 0.0, 1.0, 0.0, 0.0)),
+            Some(1.0)
+        );
+
+        assert_eq!(
+            uniform_scale(&cairo::Matrix::new(2.0, 0.0, 0.0, 1.0, 0.0, 0.0)),
+            None
+        );
+        assert_eq!(
+            uniform_scale(&cairo::Matrix::new(1.0, 0.0, 1.0, 1.0, 0.0, 0.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn finds_url_iris() {
+        assert_eq!(url_iri("url(#grad)"), Some("#grad"));
+        assert_eq!(url_iri("url(#grad) rgb(255, 0, 0)"), Some("#grad"));
+        assert_eq!(url_iri("rgb(255, 0, 0)"), None);
+        assert_eq!(url_iri("none"), None);
+    }
+
+    #[test]
+    fn writes_values_that_are_not_inherited() {
+        let initial = vec![
+            ("fill", "rgb(0, 0, 0)".to_string()),
+            ("opacity", "1".to_string()),
+            ("stop-color", "rgb(0, 0, 0)".to_string()),
+        ];
+
+        let parent = vec![
+            ("fill", "rgb(0, 0, 255)".to_string()),
+            ("opacity", "0.5".to_string()),
+            ("stop-color", "rgb(0, 0, 0)".to_string()),
+        ];
+
+        let child = vec![
+            ("fill", "rgb(0, 0, 255)".to_string()),
+            ("opacity", "0.5".to_string()),
+            ("stop-color", "rgb(255, 0, 0)".to_string()),
+        ];
+
+        assert_eq!(
+            changed_values(&parent, None, &initial),
+            vec![
+                ("fill", "rgb(0, 0, 255)".to_string()),
+                ("opacity", "0.5".to_string()),
+            ]
+        );
+
+        assert_eq!(
+            changed_values(&child, Some(&parent), &initial),
+            vec![
+                ("opacity", "0.5".to_string()),
+                ("stop-color", "rgb(255, 0, 0)".to_string()),
+            ]
+        );
+    }
+}
@@ -112,6 +112,21 @@ pub fn optional_comma(parser: &mut Parser) {
     let _ = parser.try(|p| p.expect_comma());
 }
 
+// number
+//
+// https://www.w3.org/TR/SVG/types.html#DataTypeNumber
+
+pub fn number(s: &str) -> Result<f64, ParseError> {
+    let mut input = ParserInput::new(s);
+    let mut parser = Parser::new(&mut input);
+
+    let n = f64::from(parser.expect_number()?);
+
+    parser.expect_exhausted()?;
+
+    Ok(n)
+}
+
 // number-optional-number
 //
 // https://www.w3.org/TR/SVG/types.html#DataTypeNumberOptionalNumber
@@ -347,6 +362,17 @@ pub extern "C" fn rsvg_css_parse_url(str: *const libc::c_char) -> *mut libc::c_c
 mod tests {
     use super::*;
 
+    #[test]
+    fn parses_number() {
+        assert_eq!(number("1"), Ok(1.0));
+        assert_eq!(number(" -2.5 "), Ok(-2.5));
+        assert_eq!(number("1e2"), Ok(100.0));
+
+        assert!(number("").is_err());
+        assert!(number("1 2").is_err());
+        assert!(number("1px").is_err());
+    }
+
     #[test]
     fn parses_number_optional_number() {
         assert_eq!(number_optional_number("1, 2"), Ok((1.0, 2.0)));
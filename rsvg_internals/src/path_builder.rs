@@ -280,6 +280,183 @@ impl PathBuilder {
             s.to_cairo(cr);
         }
     }
+
+    /// Returns the bounding box of the geometry of the path, computed from
+    /// the extrema of its curves rather than from their control points.
+    ///
+    /// Returns `None` if the path has no segments, for example if it just
+    /// has moves.
+    pub fn get_bbox(&self) -> Option<cairo::Rectangle> {
+        let mut extents = Extents::default();
+
+        for subpath in self.get_subpaths() {
+            extents.insert(subpath.start);
+
+            for segment in &subpath.segments {
+                extents.insert(segment.p3);
+
+                for t in segment.get_extrema() {
+                    extents.insert(segment.point(t));
+                }
+            }
+        }
+
+        extents.to_rectangle()
+    }
+
+    /// Returns the bounding box of the area that a stroke of the path covers,
+    /// with the specified line width, caps, joins and miter limit, and no
+    /// dashes.  This works like `cairo_stroke_extents()`, but without
+    /// flattening the curves.
+    ///
+    /// The sides of the stroke reach furthest where the curves are parallel
+    /// to the axes, and, on the inside of curves that turn more tightly than
+    /// half the line width, where the side folds back on itself.
+    ///
+    /// Returns `None` if the stroke covers nothing.
+    pub fn get_stroke_bbox(
+        &self,
+        line_width: f64,
+        line_cap: cairo::LineCap,
+        line_join: cairo::LineJoin,
+        miter_limit: f64,
+    ) -> Option<cairo::Rectangle> {
+        let mut extents = Extents::default();
+        let half = line_width / 2.0;
+
+        if half <= 0.0 {
+            return None;
+        }
+
+        for subpath in self.get_subpaths() {
+            // Zero-length segments have no direction, so they do not get joins
+            let segments: Vec<&Segment> = subpath
+                .segments
+                .iter()
+                .filter(|s| s.start_tangent().is_some())
+                .collect();
+
+            if segments.is_empty() {
+                // A zero-length subpath is stroked as a dot if it has caps;
+                // cairo aligns square dots to the axes
+                match line_cap {
+                    cairo::LineCap::Round | cairo::LineCap::Square => {
+                        insert_disc(&mut extents, subpath.start, half)
+                    }
+
+                    _ => (),
+                }
+
+                continue;
+            }
+
+            for segment in &segments {
+                let ts = [0.0, 1.0]
+                    .iter()
+                    .cloned()
+                    .chain(segment.get_extrema())
+                    .chain(segment.get_offset_cusps(half));
+
+                for t in ts {
+                    let p = segment.point(t);
+
+                    match segment.normal(t) {
+                        Some(n) => {
+                            extents.insert((p.0 + half * n.0, p.1 + half * n.1));
+                            extents.insert((p.0 - half * n.0, p.1 - half * n.1));
+                        }
+
+                        // A cusp; the stroke is within a disc around it
+                        None => insert_disc(&mut extents, p, half),
+                    }
+                }
+            }
+
+            for pair in segments.windows(2) {
+                insert_join(&mut extents, pair[0], pair[1], half, line_join, miter_limit);
+            }
+
+            let first = segments[0];
+            let last = segments[segments.len() - 1];
+
+            if subpath.closed {
+                insert_join(&mut extents, last, first, half, line_join, miter_limit);
+            } else {
+                let (dx, dy) = first.start_tangent().unwrap();
+                insert_cap(&mut extents, first.p0, (-dx, -dy), half, line_cap);
+                insert_cap(
+                    &mut extents,
+                    last.p3,
+                    last.end_tangent().unwrap(),
+                    half,
+                    line_cap,
+                );
+            }
+        }
+
+        extents.to_rectangle()
+    }
+
+    // Splits the path into subpaths, with every segment as a cubic Bézier.
+    // Moves do not start a subpath by themselves, so subpaths that only have
+    // a move are not included.
+    fn get_subpaths(&self) -> Vec<Subpath> {
+        let mut subpaths = Vec::new();
+        let mut subpath: Option<Subpath> = None;
+        let mut current = (0.0, 0.0);
+
+        for command in &self.path_commands {
+            let segment = match *command {
+                PathCommand::MoveTo(x, y) => {
+                    subpaths.extend(subpath.take());
+                    current = (x, y);
+                    continue;
+                }
+
+                PathCommand::LineTo(x, y) => Segment::line(current, (x, y)),
+
+                PathCommand::CurveTo(p1, p2, p3) => Segment {
+                    p0: current,
+                    p1,
+                    p2,
+                    p3,
+                },
+
+                PathCommand::ClosePath => {
+                    let start = subpath.as_ref().map_or(current, |s| s.start);
+                    let mut closed = subpath.take().unwrap_or_else(|| Subpath::new(start));
+
+                    // Paths that come back to their start before closing
+                    // often miss it by a rounding error; the closing line
+                    // must not get joins of its own in that case.
+                    let end = if approx_eq_point(current, start) {
+                        start
+                    } else {
+                        current
+                    };
+
+                    closed.segments.push(Segment::line(end, start));
+                    closed.closed = true;
+                    subpaths.push(closed);
+
+                    // Drawing after a close_path starts a new subpath there
+                    current = start;
+                    continue;
+                }
+            };
+
+            current = segment.p3;
+
+            subpath
+                .get_or_insert_with(|| Subpath::new(segment.p0))
+                .segments
+                .push(segment);
+        }
+
+        subpaths.extend(subpath);
+
+        subpaths
+    }
 }
 
 fn clamp(val: f64, low: f64, high: f64) -> f64 {
@@ -292,6 +469,360 @@ fn clamp(val: f64, low: f64, high: f64) -> f64 {
     }
 }
 
+// Number of intervals in which the roots of the curvature equation are
+// looked for, in each segment of a stroke
+const OFFSET_CUSP_SAMPLES: u32 = 32;
+
+#[derive(Debug, Default)]
+struct Extents(Option<(f64, f64, f64, f64)>);
+
+impl Extents {
+    fn insert(&mut self, (x, y): (f64, f64)) {
+        self.0 = Some(match self.0 {
+            Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+            None => (x, y, x, y),
+        });
+    }
+
+    fn to_rectangle(&self) -> Option<cairo::Rectangle> {
+        self.0.map(|(x0, y0, x1, y1)| cairo::Rectangle {
+            x: x0,
+            y: y0,
+            width: x1 - x0,
+            height: y1 - y0,
+        })
+    }
+}
+
+struct Subpath {
+    start: (f64, f64),
+    segments: Vec<Segment>,
+    closed: bool,
+}
+
+impl Subpath {
+    fn new(start: (f64, f64)) -> Subpath {
+        Subpath {
+            start,
+            segments: Vec::new(),
+            closed: false,
+        }
+    }
+}
+
+// A segment of a path as a cubic Bézier; lines have their control points at
+// their ends.
+#[derive(Debug, Copy, Clone)]
+struct Segment {
+    p0: (f64, f64),
+    p1: (f64, f64),
+    p2: (f64, f64),
+    p3: (f64, f64),
+}
+
+impl Segment {
+    fn line(from: (f64, f64), to: (f64, f64)) -> Segment {
+        Segment {
+            p0: from,
+            p1: from,
+            p2: to,
+            p3: to,
+        }
+    }
+
+    fn point(&self, t: f64) -> (f64, f64) {
+        let u = 1.0 - t;
+        let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+
+        (
+            a * self.p0.0 + b * self.p1.0 + c * self.p2.0 + d * self.p3.0,
+            a * self.p0.1 + b * self.p1.1 + c * self.p2.1 + d * self.p3.1,
+        )
+    }
+
+    fn derivative(&self, t: f64) -> (f64, f64) {
+        let u = 1.0 - t;
+        let (a, b, c) = (3.0 * u * u, 6.0 * u * t, 3.0 * t * t);
+
+        (
+            a * (self.p1.0 - self.p0.0) + b * (self.p2.0 - self.p1.0) + c * (self.p3.0 - self.p2.0),
+            a * (self.p1.1 - self.p0.1) + b * (self.p2.1 - self.p1.1) + c * (self.p3.1 - self.p2.1),
+        )
+    }
+
+    fn second_derivative(&self, t: f64) -> (f64, f64) {
+        let u = 1.0 - t;
+
+        (
+            6.0 * (u * (self.p2.0 - 2.0 * self.p1.0 + self.p0.0)
+                + t * (self.p3.0 - 2.0 * self.p2.0 + self.p1.0)),
+            6.0 * (u * (self.p2.1 - 2.0 * self.p1.1 + self.p0.1)
+                + t * (self.p3.1 - 2.0 * self.p2.1 + self.p1.1)),
+        )
+    }
+
+    // Parameters where the radius of curvature of the segment is `radius`.
+    // Where the segment turns more tightly than half the line width, the
+    // inner side of its stroke folds back on itself, and its coordinates can
+    // have extrema at those parameters.
+    fn get_offset_cusps(&self, radius: f64) -> Vec<f64> {
+        // Zero when |B'|³ / |B' × B''| is the radius; found by bisecting
+        // the changes of sign between samples
+        let f = |t: f64| {
+            let d = self.derivative(t);
+            let speed = d.0.hypot(d.1);
+
+            speed * speed * speed - radius * cross(d, self.second_derivative(t)).abs()
+        };
+
+        let mut roots = Vec::new();
+
+        for i in 0..OFFSET_CUSP_SAMPLES {
+            let mut low = f64::from(i) / f64::from(OFFSET_CUSP_SAMPLES);
+            let mut high = f64::from(i + 1) / f64::from(OFFSET_CUSP_SAMPLES);
+            let f_low = f(low);
+
+            if f_low * f(high) >= 0.0 {
+                continue;
+            }
+
+            for _ in 0..50 {
+                let mid = (low + high) / 2.0;
+
+                if f(mid) * f_low > 0.0 {
+                    low = mid;
+                } else {
+                    high = mid;
+                }
+            }
+
+            roots.push((low + high) / 2.0);
+        }
+
+        roots
+    }
+
+    // Parameters inside the segment where its x or y coordinate has an extremum
+    fn get_extrema(&self) -> Vec<f64> {
+        let mut roots = Vec::new();
+
+        for &(v0, v1, v2, v3) in &[
+            (self.p0.0, self.p1.0, self.p2.0, self.p3.0),
+            (self.p0.1, self.p1.1, self.p2.1, self.p3.1),
+        ] {
+            // The derivative is 3 (a t² + b t + c)
+            let a = -v0 + 3.0 * v1 - 3.0 * v2 + v3;
+            let b = 2.0 * (v0 - 2.0 * v1 + v2);
+            let c = v1 - v0;
+
+            solve_quadratic(a, b, c, &mut roots);
+        }
+
+        roots.retain(|&t| t > 0.0 && t < 1.0);
+        roots
+    }
+
+    // Unit tangent at the start of the segment, or `None` if the segment has
+    // no length.  Where the derivative vanishes because control points
+    // coincide, the direction is towards the next distinct point.
+    fn start_tangent(&self) -> Option<(f64, f64)> {
+        [self.p1, self.p2, self.p3]
+            .iter()
+            .filter_map(|p| normalize((p.0 - self.p0.0, p.1 - self.p0.1)))
+            .next()
+    }
+
+    fn end_tangent(&self) -> Option<(f64, f64)> {
+        [self.p2, self.p1, self.p0]
+            .iter()
+            .filter_map(|p| normalize((self.p3.0 - p.0, self.p3.1 - p.1)))
+            .next()
+    }
+
+    // Unit normal at `t`, or `None` at a cusp
+    fn normal(&self, t: f64) -> Option<(f64, f64)> {
+        let tangent = if t <= 0.0 {
+            self.start_tangent()
+        } else if t >= 1.0 {
+            self.end_tangent()
+        } else {
+            normalize(self.derivative(t))
+        };
+
+        tangent.map(|(dx, dy)| (-dy, dx))
+    }
+}
+
+fn normalize((x, y): (f64, f64)) -> Option<(f64, f64)> {
+    let length = x.hypot(y);
+
+    if length > 0.0 {
+        Some((x / length, y / length))
+    } else {
+        None
+    }
+}
+
+fn approx_eq_point(a: (f64, f64), b: (f64, f64)) -> bool {
+    let scale =
+        a.0.abs()
+            .max(a.1.abs())
+            .max(b.0.abs())
+            .max(b.1.abs())
+            .max(1.0);
+
+    (a.0 - b.0).abs() <= scale * 1e-12 && (a.1 - b.1).abs() <= scale * 1e-12
+}
+
+fn cross(a: (f64, f64), b: (f64, f64)) -> f64 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+fn dot(a: (f64, f64), b: (f64, f64)) -> f64 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+// Appends the real roots of a t² + b t + c = 0 to `roots`
+fn solve_quadratic(a: f64, b: f64, c: f64, roots: &mut Vec<f64>) {
+    let scale = a.abs().max(b.abs()).max(c.abs());
+
+    if scale == 0.0 {
+        return;
+    }
+
+    if a.abs() <= scale * 1e-12 {
+        if b != 0.0 {
+            roots.push(-c / b);
+        }
+
+        return;
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+
+    if discriminant < 0.0 {
+        return;
+    }
+
+    // Avoids the cancellation in -b + sqrt(discriminant) when b is large
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+
+    if q != 0.0 {
+        roots.push(q / a);
+        roots.push(c / q);
+    } else {
+        roots.push(0.0);
+    }
+}
+
+// Inserts the points of the arc of a circle that goes from the direction `a`
+// to the direction `b`, which must be at most half a turn apart.  Besides
+// the ends, the arc reaches further along the axes that it crosses.
+fn insert_arc(
+    extents: &mut Extents,
+    center: (f64, f64),
+    radius: f64,
+    a: (f64, f64),
+    b: (f64, f64),
+) {
+    let turn = cross(a, b);
+
+    for &d in &[a, b, (1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0)] {
+        let within = d == a
+            || d == b
+            || (cross(a, d) * turn >= 0.0
+                && cross(d, b) * turn >= 0.0
+                && dot(d, (a.0 + b.0, a.1 + b.1)) > 0.0);
+
+        if within {
+            extents.insert((center.0 + radius * d.0, center.1 + radius * d.1));
+        }
+    }
+}
+
+fn insert_disc(extents: &mut Extents, center: (f64, f64), radius: f64) {
+    extents.insert((center.0 - radius, center.1 - radius));
+    extents.insert((center.0 + radius, center.1 + radius));
+}
+
+// Inserts the cap at the end `p` of a subpath, whose direction going out of
+// the subpath is `d`.  The sides of the line at `p` are already inserted.
+fn insert_cap(
+    extents: &mut Extents,
+    p: (f64, f64),
+    d: (f64, f64),
+    half: f64,
+    line_cap: cairo::LineCap,
+) {
+    let n = (-d.1, d.0);
+
+    match line_cap {
+        cairo::LineCap::Round => {
+            insert_arc(extents, p, half, n, d);
+            insert_arc(extents, p, half, d, (-n.0, -n.1));
+        }
+
+        cairo::LineCap::Square => {
+            let (x, y) = (p.0 + half * d.0, p.1 + half * d.1);
+
+            extents.insert((x + half * n.0, y + half * n.1));
+            extents.insert((x - half * n.0, y - half * n.1));
+        }
+
+        _ => (),
+    }
+}
+
+// Inserts the join between the end of `from` and the start of `to`, with the
+// same logic as cairo to choose between a miter and a bevel.  The sides of
+// the lines at the join, and so the bevel, are already inserted.
+fn insert_join(
+    extents: &mut Extents,
+    from: &Segment,
+    to: &Segment,
+    half: f64,
+    line_join: cairo::LineJoin,
+    miter_limit: f64,
+) {
+    let p = to.p0;
+    let t_in = from.end_tangent().unwrap();
+    let t_out = to.start_tangent().unwrap();
+    let turn = cross(t_in, t_out);
+    let cos = dot(t_in, t_out);
+
+    match line_join {
+        cairo::LineJoin::Round => {
+            // The arc is on the outer side of the turn
+            let side = if turn < 0.0 { -1.0 } else { 1.0 };
+            let a = (side * t_in.1, -side * t_in.0);
+            let b = (side * t_out.1, -side * t_out.0);
+
+            if cos < 0.0 && turn.abs() < 1e-12 {
+                // The path turns back on itself; the join is a half circle
+                insert_arc(extents, p, half, a, t_in);
+                insert_arc(extents, p, half, t_in, b);
+            } else {
+                insert_arc(extents, p, half, a, b);
+            }
+        }
+
+        cairo::LineJoin::Miter => {
+            // The ratio of the miter length to the line width is
+            // 1 / sin(θ / 2), where θ is the angle between the segments
+            if turn != 0.0 && 2.0 <= miter_limit * miter_limit * (1.0 + cos) {
+                let sin_half_theta = ((1.0 + cos) / 2.0).sqrt();
+
+                if let Some(d) = normalize((t_in.0 - t_out.0, t_in.1 - t_out.1)) {
+                    let length = half / sin_half_theta;
+                    extents.insert((p.0 + length * d.0, p.1 + length * d.1));
+                }
+            }
+        }
+
+        _ => (),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -312,4 +843,196 @@ mod tests {
             1.0,
         );
     }
+
+    fn assert_rect(r: Option<cairo::Rectangle>, x: f64, y: f64, width: f64, height: f64) {
+        let r = r.unwrap();
+
+        assert!(
+            r.x.approx_eq_cairo(&x)
+                && r.y.approx_eq_cairo(&y)
+                && r.width.approx_eq_cairo(&width)
+                && r.height.approx_eq_cairo(&height),
+            "{:?} != ({}, {}, {}, {})",
+            (r.x, r.y, r.width, r.height),
+            x,
+            y,
+            width,
+            height
+        );
+    }
+
+    fn circle(cx: f64, cy: f64, r: f64) -> PathBuilder {
+        let mut builder = PathBuilder::new();
+        builder.move_to(cx + r, cy);
+        builder.arc(
+            cx + r,
+            cy,
+            r,
+            r,
+            0.0,
+            LargeArc(false),
+            Sweep::Positive,
+            cx - r,
+            cy,
+        );
+        builder.arc(
+            cx - r,
+            cy,
+            r,
+            r,
+            0.0,
+            LargeArc(false),
+            Sweep::Positive,
+            cx + r,
+            cy,
+        );
+        builder.close_path();
+        builder
+    }
+
+    fn stroke_bbox(
+        builder: &PathBuilder,
+        cap: cairo::LineCap,
+        join: cairo::LineJoin,
+        miter_limit: f64,
+    ) -> Option<cairo::Rectangle> {
+        builder.get_stroke_bbox(2.0, cap, join, miter_limit)
+    }
+
+    #[test]
+    fn empty_path_has_no_bbox() {
+        let mut builder = PathBuilder::new();
+        assert!(builder.get_bbox().is_none());
+
+        builder.move_to(10.0, 10.0);
+        assert!(builder.get_bbox().is_none());
+        assert!(
+            stroke_bbox(&builder, cairo::LineCap::Round, cairo::LineJoin::Round, 4.0).is_none()
+        );
+    }
+
+    #[test]
+    fn bbox_of_curves_comes_from_extrema() {
+        let mut builder = PathBuilder::new();
+        builder.move_to(0.0, 0.0);
+        builder.curve_to(0.0, 10.0, 10.0, 10.0, 10.0, 0.0);
+        assert_rect(builder.get_bbox(), 0.0, 0.0, 10.0, 7.5);
+
+        assert_rect(circle(50.0, 50.0, 10.0).get_bbox(), 40.0, 40.0, 20.0, 20.0);
+    }
+
+    #[test]
+    fn bbox_of_lines_has_no_area() {
+        let mut builder = PathBuilder::new();
+        builder.move_to(0.0, 5.0);
+        builder.line_to(10.0, 5.0);
+        assert_rect(builder.get_bbox(), 0.0, 5.0, 10.0, 0.0);
+    }
+
+    #[test]
+    fn stroke_bbox_has_caps() {
+        let mut builder = PathBuilder::new();
+        builder.move_to(0.0, 0.0);
+        builder.line_to(10.0, 0.0);
+
+        let join = cairo::LineJoin::Miter;
+
+        assert_rect(
+            stroke_bbox(&builder, cairo::LineCap::Butt, join, 4.0),
+            0.0,
+            -1.0,
+            10.0,
+            2.0,
+        );
+        assert_rect(
+            stroke_bbox(&builder, cairo::LineCap::Square, join, 4.0),
+            -1.0,
+            -1.0,
+            12.0,
+            2.0,
+        );
+        assert_rect(
+            stroke_bbox(&builder, cairo::LineCap::Round, join, 4.0),
+            -1.0,
+            -1.0,
+            12.0,
+            2.0,
+        );
+    }
+
+    #[test]
+    fn stroke_bbox_has_miters_within_limit() {
+        let mut builder = PathBuilder::new();
+        builder.move_to(0.0, 0.0);
+        builder.line_to(10.0, 0.0);
+        builder.line_to(0.0, 10.0);
+
+        let cap = cairo::LineCap::Butt;
+
+        // The segments meet at 45 degrees, so the miter is 1 / sin(22.5°)
+        // times the line width, and its tip is at 1 / tan(22.5°) from the corner
+        let r = stroke_bbox(&builder, cap, cairo::LineJoin::Miter, 4.0).unwrap();
+        assert!(r.x.approx_eq_cairo(&-(FRAC_1_SQRT_2)));
+        assert!(r.y.approx_eq_cairo(&-1.0));
+        assert!((r.x + r.width).approx_eq_cairo(&(10.0 + 1.0 + SQRT_2)));
+
+        let r = stroke_bbox(&builder, cap, cairo::LineJoin::Miter, 2.0).unwrap();
+        assert!((r.x + r.width).approx_eq_cairo(&(10.0 + FRAC_1_SQRT_2)));
+
+        let r = stroke_bbox(&builder, cap, cairo::LineJoin::Bevel, 4.0).unwrap();
+        assert!((r.x + r.width).approx_eq_cairo(&(10.0 + FRAC_1_SQRT_2)));
+
+        let r = stroke_bbox(&builder, cap, cairo::LineJoin::Round, 4.0).unwrap();
+        assert!((r.x + r.width).approx_eq_cairo(&11.0));
+    }
+
+    #[test]
+    fn stroke_bbox_has_join_at_start_of_closed_subpath() {
+        // The relative moves do not add up exactly to the start point
+        let mut builder = PathBuilder::new();
+        builder.move_to(62.0, 190.0);
+        builder.line_to(62.0 + 51.96, 280.0);
+        builder.line_to(62.0 + 51.96 - 103.92, 280.0);
+        builder.line_to(62.0 + 51.96 - 103.92 + 51.96, 190.0);
+        builder.close_path();
+
+        // The corner is close to 60 degrees, so the miter reaches one line
+        // width above it
+        let r = stroke_bbox(&builder, cairo::LineCap::Butt, cairo::LineJoin::Miter, 4.0).unwrap();
+        assert!((r.y - 188.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn stroke_bbox_of_curves_is_tight() {
+        let r = stroke_bbox(
+            &circle(50.0, 50.0, 10.0),
+            cairo::LineCap::Butt,
+            cairo::LineJoin::Miter,
+            4.0,
+        );
+        assert_rect(r, 39.0, 39.0, 22.0, 22.0);
+
+        let mut builder = PathBuilder::new();
+        builder.move_to(0.0, 0.0);
+        builder.curve_to(0.0, 10.0, 10.0, 10.0, 10.0, 0.0);
+        let r = stroke_bbox(&builder, cairo::LineCap::Butt, cairo::LineJoin::Miter, 4.0);
+        assert_rect(r, -1.0, 0.0, 12.0, 8.5);
+    }
+
+    #[test]
+    fn zero_length_subpaths_are_dots() {
+        let mut builder = PathBuilder::new();
+        builder.move_to(5.0, 5.0);
+        builder.close_path();
+
+        assert_rect(builder.get_bbox(), 5.0, 5.0, 0.0, 0.0);
+        assert!(stroke_bbox(&builder, cairo::LineCap::Butt, cairo::LineJoin::Miter, 4.0).is_none());
+        assert_rect(
+            stroke_bbox(&builder, cairo::LineCap::Round, cairo::LineJoin::Miter, 4.0),
+            4.0,
+            4.0,
+            2.0,
+            2.0,
+        );
+    }
 }
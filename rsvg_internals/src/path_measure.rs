@@ -0,0 +1,592 @@
+use path_builder::{PathBuilder, PathCommand};
+
+// Nodes and weights of the 5-point Gauss-Legendre quadrature on [-1, 1]
+const GAUSS_LEGENDRE: [(f64, f64); 5] = [
+    (0.0, 0.568_888_888_888_888_9),
+    (-0.538_469_310_105_683_1, 0.478_628_670_499_366_5),
+    (0.538_469_310_105_683_1, 0.478_628_670_499_366_5),
+    (-0.906_179_845_938_664_0, 0.236_926_885_056_189_1),
+    (0.906_179_845_938_664_0, 0.236_926_885_056_189_1),
+];
+
+// Number of parameter intervals in which the length of each curve is
+// integrated.  Curves from arcs span at most 90 degrees, so this is plenty.
+const CURVE_PIECES: usize = 16;
+
+#[derive(Debug, Copy, Clone)]
+struct Cubic {
+    p0: (f64, f64),
+    p1: (f64, f64),
+    p2: (f64, f64),
+    p3: (f64, f64),
+}
+
+impl Cubic {
+    fn point(&self, t: f64) -> (f64, f64) {
+        let u = 1.0 - t;
+        let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+
+        (
+            a * self.p0.0 + b * self.p1.0 + c * self.p2.0 + d * self.p3.0,
+            a * self.p0.1 + b * self.p1.1 + c * self.p2.1 + d * self.p3.1,
+        )
+    }
+
+    fn derivative(&self, t: f64) -> (f64, f64) {
+        let u = 1.0 - t;
+        let (a, b, c) = (3.0 * u * u, 6.0 * u * t, 3.0 * t * t);
+
+        (
+            a * (self.p1.0 - self.p0.0) + b * (self.p2.0 - self.p1.0) + c * (self.p3.0 - self.p2.0),
+            a * (self.p1.1 - self.p0.1) + b * (self.p2.1 - self.p1.1) + c * (self.p3.1 - self.p2.1),
+        )
+    }
+
+    fn speed(&self, t: f64) -> f64 {
+        let (dx, dy) = self.derivative(t);
+        dx.hypot(dy)
+    }
+
+    fn length_between(&self, t0: f64, t1: f64) -> f64 {
+        let half = (t1 - t0) / 2.0;
+        let mid = (t0 + t1) / 2.0;
+
+        GAUSS_LEGENDRE
+            .iter()
+            .map(|&(x, w)| w * self.speed(mid + half * x))
+            .sum::<f64>()
+            * half
+    }
+
+    // Where the derivative vanishes because control points coincide with an
+    // end point, the direction is that of the next distinct control point.
+    fn direction(&self, t: f64) -> (f64, f64) {
+        let (dx, dy) = self.derivative(t);
+
+        if dx.hypot(dy) > 1e-12 {
+            return (dx, dy);
+        }
+
+        let (from, candidates) = if t < 0.5 {
+            (self.p0, [self.p1, self.p2, self.p3])
+        } else {
+            (self.p3, [self.p2, self.p1, self.p0])
+        };
+
+        for p in &candidates {
+            let (dx, dy) = (p.0 - from.0, p.1 - from.1);
+
+            if dx.hypot(dy) > 1e-12 {
+                return if t < 0.5 { (dx, dy) } else { (-dx, -dy) };
+            }
+        }
+
+        (0.0, 0.0)
+    }
+}
+
+#[derive(Debug)]
+enum SegmentKind {
+    Line,
+
+    // Arc length from the start of the curve at each of the
+    // CURVE_PIECES + 1 equally spaced values of the parameter
+    Curve(Cubic, Vec<f64>),
+}
+
+#[derive(Debug)]
+struct Segment {
+    kind: SegmentKind,
+    start: (f64, f64),
+    end: (f64, f64),
+
+    /// Distance along the path at which the segment starts.
+    offset: f64,
+
+    length: f64,
+}
+
+impl Segment {
+    fn line(start: (f64, f64), end: (f64, f64), offset: f64) -> Segment {
+        Segment {
+            kind: SegmentKind::Line,
+            start,
+            end,
+            offset,
+            length: (end.0 - start.0).hypot(end.1 - start.1),
+        }
+    }
+
+    fn curve(cubic: Cubic, offset: f64) -> Segment {
+        let mut lengths = Vec::with_capacity(CURVE_PIECES + 1);
+        let mut length = 0.0;
+
+        lengths.push(0.0);
+
+        for i in 0..CURVE_PIECES {
+            let t0 = i as f64 / CURVE_PIECES as f64;
+            let t1 = (i + 1) as f64 / CURVE_PIECES as f64;
+
+            length += cubic.length_between(t0, t1);
+            lengths.push(length);
+        }
+
+        Segment {
+            kind: SegmentKind::Curve(cubic, lengths),
+            start: cubic.p0,
+            end: cubic.p3,
+            offset,
+            length,
+        }
+    }
+
+    // Parameter of the point at `distance` from the start of the segment.
+    fn parameter_at(&self, distance: f64) -> f64 {
+        if self.length <= 0.0 {
+            return 0.0;
+        }
+
+        let distance = distance.max(0.0).min(self.length);
+
+        match self.kind {
+            SegmentKind::Line => distance / self.length,
+
+            SegmentKind::Curve(ref cubic, ref lengths) => {
+                let i = match lengths.iter().position(|&l| l >= distance) {
+                    Some(0) | None => 0,
+                    Some(i) => i - 1,
+                };
+
+                let piece_start = i as f64 / CURVE_PIECES as f64;
+                let piece_end = (i + 1) as f64 / CURVE_PIECES as f64;
+                let start = lengths[i];
+
+                // Newton's method on the arc length, falling back to
+                // bisection when a step leaves the bracket
+                let (mut low, mut high) = (piece_start, piece_end);
+                let mut t = low + (high - low) * (distance - start) / (lengths[i + 1] - start);
+
+                for _ in 0..16 {
+                    let error = start + cubic.length_between(piece_start, t) - distance;
+
+                    if error.abs() < 1e-10 {
+                        break;
+                    }
+
+                    if error > 0.0 {
+                        high = t;
+                    } else {
+                        low = t;
+                    }
+
+                    let speed = cubic.speed(t);
+                    let next = if speed > 0.0 { t - error / speed } else { low };
+
+                    t = if next > low && next < high {
+                        next
+                    } else {
+                        (low + high) / 2.0
+                    };
+                }
+
+                t
+            }
+        }
+    }
+
+    fn point(&self, t: f64) -> (f64, f64) {
+        match self.kind {
+            SegmentKind::Line => (
+                self.start.0 + (self.end.0 - self.start.0) * t,
+                self.start.1 + (self.end.1 - self.start.1) * t,
+            ),
+
+            SegmentKind::Curve(ref cubic, _) => cubic.point(t),
+        }
+    }
+
+    fn direction(&self, t: f64) -> (f64, f64) {
+        match self.kind {
+            SegmentKind::Line => (self.end.0 - self.start.0, self.end.1 - self.start.1),
+            SegmentKind::Curve(ref cubic, _) => cubic.direction(t),
+        }
+    }
+}
+
+/// Measures distances along a path, for example to place objects along it.
+///
+/// Lines are measured exactly, and the length of curves is integrated
+/// numerically; the error is far below what is visible at any practical
+/// scale.  Moves between subpaths do not count towards the length.
+pub struct PathMeasure {
+    segments: Vec<Segment>,
+    total_length: f64,
+
+    /// The author's length of the path, from the `pathLength` attribute.
+    path_length: Option<f64>,
+}
+
+impl PathMeasure {
+    /// Creates a measure for the path in `builder`.  If `path_length` is
+    /// given, as from the `pathLength` attribute, the distances that are
+    /// passed to the methods of the measure are relative to it instead of
+    /// being in user units.
+    pub fn new(builder: &PathBuilder, path_length: Option<f64>) -> PathMeasure {
+        let mut segments = Vec::new();
+        let mut current = (0.0, 0.0);
+        let mut subpath_start = (0.0, 0.0);
+        let mut offset = 0.0;
+
+        for command in builder.get_path_commands() {
+            let segment = match *command {
+                PathCommand::MoveTo(x, y) => {
+                    current = (x, y);
+                    subpath_start = current;
+                    continue;
+                }
+
+                PathCommand::LineTo(x, y) => Segment::line(current, (x, y), offset),
+
+                PathCommand::CurveTo(p1, p2, p3) => Segment::curve(
+                    Cubic {
+                        p0: current,
+                        p1,
+                        p2,
+                        p3,
+                    },
+                    offset,
+                ),
+
+                PathCommand::ClosePath => Segment::line(current, subpath_start, offset),
+            };
+
+            current = segment.end;
+            offset += segment.length;
+            segments.push(segment);
+        }
+
+        PathMeasure {
+            segments,
+            total_length: offset,
+            path_length,
+        }
+    }
+
+    /// Returns the length of the path in user units, regardless of
+    /// `pathLength`, like `getTotalLength()` in the SVG DOM.
+    pub fn get_total_length(&self) -> f64 {
+        self.total_length
+    }
+
+    /// Converts a distance along the path to user units.
+    fn to_user_distance(&self, distance: f64) -> f64 {
+        match self.path_length {
+            // A pathLength of zero scales every distance to infinity
+            Some(path_length) if path_length == 0.0 => {
+                if distance > 0.0 {
+                    self.total_length
+                } else {
+                    0.0
+                }
+            }
+
+            Some(path_length) => distance * self.total_length / path_length,
+
+            None => distance,
+        }
+    }
+
+    // Finds the segment at `distance`, in user units, and the parameter of
+    // the point there.  Distances outside of the path are clamped to its ends.
+    // Zero-length segments are skipped, so that they do not affect directions.
+    fn locate(&self, distance: f64) -> Option<(&Segment, f64)> {
+        let distance = distance.max(0.0).min(self.total_length);
+
+        let segment = self
+            .segments
+            .iter()
+            .find(|s| s.length > 0.0 && distance <= s.offset + s.length)
+            .or_else(|| self.segments.iter().rev().find(|s| s.length > 0.0))
+            .or_else(|| self.segments.last())?;
+
+        Some((segment, segment.parameter_at(distance - segment.offset)))
+    }
+
+    /// Returns the point at `distance` along the path.  Returns `None` if the
+    /// path has no segments.
+    pub fn get_point_at_length(&self, distance: f64) -> Option<(f64, f64)> {
+        self.locate(self.to_user_distance(distance))
+            .map(|(segment, t)| segment.point(t))
+    }
+
+    /// Returns the direction of the path at `distance` along it, as an angle
+    /// in radians from the positive x axis towards the positive y axis.
+    /// At the point where two segments meet, this is the direction at the
+    /// end of the first one.  Returns `None` if the path has no segments.
+    pub fn get_angle_at_length(&self, distance: f64) -> Option<f64> {
+        self.locate(self.to_user_distance(distance))
+            .map(|(segment, t)| {
+                let (dx, dy) = segment.direction(t);
+                dy.atan2(dx)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use path_builder::{LargeArc, Sweep};
+    use std::f64::consts::*;
+
+    fn assert_near(a: f64, b: f64, tolerance: f64) {
+        assert!((a - b).abs() < tolerance, "{} != {}", a, b);
+    }
+
+    fn assert_point_near(p: (f64, f64), x: f64, y: f64) {
+        assert_near(p.0, x, 1e-3);
+        assert_near(p.1, y, 1e-3);
+    }
+
+    // Curves that approximate circular arcs, as in shapes.rs and
+    // PathBuilder::arc(), are slightly longer than the arcs themselves;
+    // these are the tolerances for the analytic lengths and points.
+    const ARC_LENGTH_ERROR: f64 = 3e-4;
+    const ARC_POINT_ERROR: f64 = 1e-3;
+    const ARC_ANGLE_ERROR: f64 = 5e-3;
+
+    // The same approximation of a circle that shapes.rs uses
+    fn circle(cx: f64, cy: f64, r: f64) -> PathBuilder {
+        let arc_magic: f64 = 0.5522847498;
+
+        let mut builder = PathBuilder::new();
+        builder.move_to(cx + r, cy);
+        builder.curve_to(
+            cx + r,
+            cy + arc_magic * r,
+            cx + arc_magic * r,
+            cy + r,
+            cx,
+            cy + r,
+        );
+        builder.curve_to(
+            cx - arc_magic * r,
+            cy + r,
+            cx - r,
+            cy + arc_magic * r,
+            cx - r,
+            cy,
+        );
+        builder.curve_to(
+            cx - r,
+            cy - arc_magic * r,
+            cx - arc_magic * r,
+            cy - r,
+            cx,
+            cy - r,
+        );
+        builder.curve_to(
+            cx + arc_magic * r,
+            cy - r,
+            cx + r,
+            cy - arc_magic * r,
+            cx + r,
+            cy,
+        );
+        builder.close_path();
+        builder
+    }
+
+    #[test]
+    fn measures_lines() {
+        let mut builder = PathBuilder::new();
+        builder.move_to(10.0, 10.0);
+        builder.line_to(40.0, 50.0);
+        builder.line_to(40.0, 50.0);
+        builder.line_to(40.0, 80.0);
+
+        let measure = PathMeasure::new(&builder, None);
+        assert_near(measure.get_total_length(), 80.0, 1e-12);
+
+        assert_point_near(measure.get_point_at_length(25.0).unwrap(), 25.0, 30.0);
+        assert_point_near(measure.get_point_at_length(65.0).unwrap(), 40.0, 65.0);
+
+        assert_near(
+            measure.get_angle_at_length(25.0).unwrap(),
+            (4.0f64).atan2(3.0),
+            1e-12,
+        );
+        assert_near(
+            measure.get_angle_at_length(50.0).unwrap(),
+            (4.0f64).atan2(3.0),
+            1e-12,
+        );
+        assert_near(measure.get_angle_at_length(65.0).unwrap(), FRAC_PI_2, 1e-12);
+    }
+
+    #[test]
+    fn clamps_distances_to_the_path() {
+        let mut builder = PathBuilder::new();
+        builder.move_to(0.0, 0.0);
+        builder.line_to(10.0, 0.0);
+
+        let measure = PathMeasure::new(&builder, None);
+        assert_point_near(measure.get_point_at_length(-5.0).unwrap(), 0.0, 0.0);
+        assert_point_near(measure.get_point_at_length(15.0).unwrap(), 10.0, 0.0);
+    }
+
+    #[test]
+    fn moves_between_subpaths_have_no_length() {
+        let mut builder = PathBuilder::new();
+        builder.move_to(0.0, 0.0);
+        builder.line_to(10.0, 0.0);
+        builder.move_to(100.0, 100.0);
+        builder.line_to(100.0, 110.0);
+        builder.close_path();
+
+        let measure = PathMeasure::new(&builder, None);
+        assert_near(measure.get_total_length(), 30.0, 1e-12);
+        assert_point_near(measure.get_point_at_length(15.0).unwrap(), 100.0, 105.0);
+        assert_point_near(measure.get_point_at_length(25.0).unwrap(), 100.0, 105.0);
+        assert_near(
+            measure.get_angle_at_length(25.0).unwrap(),
+            -FRAC_PI_2,
+            1e-12,
+        );
+    }
+
+    #[test]
+    fn measures_circles() {
+        let measure = PathMeasure::new(&circle(50.0, 50.0, 20.0), None);
+        let circumference = 2.0 * PI * 20.0;
+
+        assert_near(
+            measure.get_total_length(),
+            circumference,
+            circumference * ARC_LENGTH_ERROR,
+        );
+
+        for i in 0..16 {
+            let angle = f64::from(i) * 2.0 * PI / 16.0;
+            let p = measure
+                .get_point_at_length(circumference * f64::from(i) / 16.0)
+                .unwrap();
+
+            assert_near(p.0, 50.0 + 20.0 * angle.cos(), 20.0 * ARC_POINT_ERROR);
+            assert_near(p.1, 50.0 + 20.0 * angle.sin(), 20.0 * ARC_POINT_ERROR);
+
+            let tangent = measure
+                .get_angle_at_length(circumference * f64::from(i) / 16.0)
+                .unwrap();
+            let expected = angle + FRAC_PI_2;
+            assert_near((tangent - expected).sin(), 0.0, ARC_ANGLE_ERROR);
+            assert!((tangent - expected).cos() > 0.0);
+        }
+    }
+
+    #[test]
+    fn integrates_curves() {
+        let cubic = Cubic {
+            p0: (0.0, 0.0),
+            p1: (10.0, 40.0),
+            p2: (60.0, -30.0),
+            p3: (50.0, 20.0),
+        };
+
+        let mut builder = PathBuilder::new();
+        builder.move_to(0.0, 0.0);
+        builder.curve_to(10.0, 40.0, 60.0, -30.0, 50.0, 20.0);
+
+        let measure = PathMeasure::new(&builder, None);
+
+        // Length of a very fine polyline on the curve
+        let n = 100_000;
+        let mut flattened = 0.0;
+        let mut previous = cubic.p0;
+        let mut halfway = None;
+
+        for i in 1..n + 1 {
+            let p = cubic.point(f64::from(i) / f64::from(n));
+            flattened += (p.0 - previous.0).hypot(p.1 - previous.1);
+            previous = p;
+
+            if i == n / 2 {
+                halfway = Some((flattened, p));
+            }
+        }
+
+        assert_near(measure.get_total_length(), flattened, 1e-6);
+
+        let (distance, p) = halfway.unwrap();
+        let q = measure.get_point_at_length(distance).unwrap();
+        assert_near(q.0, p.0, 1e-6);
+        assert_near(q.1, p.1, 1e-6);
+    }
+
+    #[test]
+    fn measures_arcs() {
+        let mut builder = PathBuilder::new();
+        builder.move_to(30.0, 0.0);
+        builder.arc(
+            30.0,
+            0.0,
+            30.0,
+            30.0,
+            0.0,
+            LargeArc(false),
+            Sweep::Positive,
+            -30.0,
+            0.0,
+        );
+
+        let measure = PathMeasure::new(&builder, None);
+        assert_near(
+            measure.get_total_length(),
+            PI * 30.0,
+            PI * 30.0 * ARC_LENGTH_ERROR,
+        );
+
+        assert_point_near(measure.get_point_at_length(0.0).unwrap(), 30.0, 0.0);
+        let p = measure.get_point_at_length(PI * 15.0).unwrap();
+        assert_near(p.0, 0.0, 30.0 * ARC_POINT_ERROR);
+        assert_near(p.1, 30.0, 30.0 * ARC_POINT_ERROR);
+
+        let p = measure.get_point_at_length(PI * 10.0).unwrap();
+        assert_near(p.0, 30.0 * (PI / 3.0).cos(), 30.0 * ARC_POINT_ERROR);
+        assert_near(p.1, 30.0 * (PI / 3.0).sin(), 30.0 * ARC_POINT_ERROR);
+
+        assert_near(measure.get_angle_at_length(0.0).unwrap(), FRAC_PI_2, 1e-6);
+        assert_near(
+            measure.get_angle_at_length(PI * 15.0).unwrap(),
+            PI,
+            ARC_ANGLE_ERROR,
+        );
+    }
+
+    #[test]
+    fn honors_path_length() {
+        let mut builder = PathBuilder::new();
+        builder.move_to(0.0, 0.0);
+        builder.line_to(200.0, 0.0);
+
+        let measure = PathMeasure::new(&builder, Some(10.0));
+        assert_near(measure.get_total_length(), 200.0, 1e-12);
+        assert_point_near(measure.get_point_at_length(2.5).unwrap(), 50.0, 0.0);
+        assert_point_near(measure.get_point_at_length(10.0).unwrap(), 200.0, 0.0);
+
+        let measure = PathMeasure::new(&builder, Some(0.0));
+        assert_point_near(measure.get_point_at_length(0.0).unwrap(), 0.0, 0.0);
+        assert_point_near(measure.get_point_at_length(1.0).unwrap(), 200.0, 0.0);
+    }
+
+    #[test]
+    fn empty_path_has_no_points() {
+        let mut builder = PathBuilder::new();
+        builder.move_to(10.0, 10.0);
+
+        let measure = PathMeasure::new(&builder, None);
+        assert_eq!(measure.get_total_length(), 0.0);
+        assert!(measure.get_point_at_length(0.0).is_none());
+        assert!(measure.get_angle_at_length(0.0).is_none());
+    }
+}
@@ -1,14 +1,14 @@
 use path_builder::*;
 use std::error::Error;
 use std::fmt::{self, Display, Formatter};
-use std::iter::Enumerate;
 use std::str;
-use std::str::Chars;
+use std::str::CharIndices;
 
 struct PathParser<'b> {
-    chars_enumerator: Enumerate<Chars<'b>>,
+    chars_enumerator: CharIndices<'b>,
     lookahead: Option<char>,    // None if we are in EOF
-    current_pos: Option<usize>, // None if the string hasn't been scanned
+    current_pos: Option<usize>, // byte offset of the lookahead; None if the string hasn't been scanned
+    path_len: usize,
 
     builder: &'b mut PathBuilder,
 
@@ -58,9 +58,10 @@ struct PathParser<'b> {
 impl<'b> PathParser<'b> {
     fn new(builder: &'b mut PathBuilder, path_str: &'b str) -> PathParser<'b> {
         PathParser {
-            chars_enumerator: path_str.chars().enumerate(),
+            chars_enumerator: path_str.char_indices(),
             lookahead: None,
             current_pos: None,
+            path_len: path_str.len(),
 
             builder,
 
@@ -93,18 +94,25 @@ impl<'b> PathParser<'b> {
             // We got to EOF; make current_pos point to the position after the last char in the
             // string
             self.lookahead = None;
-            if self.current_pos.is_none() {
-                self.current_pos = Some(0);
-            } else {
-                self.current_pos = Some(self.current_pos.unwrap() + 1);
-            }
+            self.current_pos = Some(self.path_len);
         }
     }
 
-    fn error(&self, kind: ErrorKind) -> ParseError {
+    fn error(&self, kind: ErrorKind, expected: Expected) -> ParseError {
         ParseError {
             position: self.current_pos.unwrap(),
             kind,
+            expected,
+        }
+    }
+
+    // Returns an error about the lookahead, or about the end of the data if
+    // there is no lookahead.
+    fn unexpected(&self, expected: Expected) -> ParseError {
+        if self.lookahead.is_some() {
+            self.error(ErrorKind::UnexpectedToken, expected)
+        } else {
+            self.error(ErrorKind::UnexpectedEof, expected)
         }
     }
 
@@ -243,10 +251,8 @@ impl<'b> PathParser<'b> {
                     }
 
                     exponent = Some(exp);
-                } else if self.lookahead.is_some() {
-                    return Err(self.error(ErrorKind::UnexpectedToken));
                 } else {
-                    return Err(self.error(ErrorKind::UnexpectedEof));
+                    return Err(self.unexpected(Expected::Number));
                 }
             }
 
@@ -255,10 +261,8 @@ impl<'b> PathParser<'b> {
             } else {
                 Ok(sign * value)
             }
-        } else if self.lookahead.is_some() {
-            Err(self.error(ErrorKind::UnexpectedToken))
         } else {
-            Err(self.error(ErrorKind::UnexpectedEof))
+            Err(self.unexpected(Expected::Number))
         }
     }
 
@@ -267,10 +271,8 @@ impl<'b> PathParser<'b> {
             Ok(false)
         } else if self.match_char('1') {
             Ok(true)
-        } else if self.lookahead.is_some() {
-            Err(self.error(ErrorKind::UnexpectedToken))
         } else {
-            Err(self.error(ErrorKind::UnexpectedEof))
+            Err(self.unexpected(Expected::Flag))
         }
     }
 
@@ -450,10 +452,11 @@ impl<'b> PathParser<'b> {
 
             self.optional_whitespace()?;
             self.moveto_argument_sequence(absolute, is_initial_moveto)
-        } else if self.lookahead.is_some() {
-            Err(self.error(ErrorKind::UnexpectedToken))
+        } else if is_initial_moveto {
+            Err(self.unexpected(Expected::MoveTo))
         } else {
-            Err(self.error(ErrorKind::UnexpectedEof))
+            // after the first subpath, any command could have come here
+            Err(self.unexpected(Expected::Command))
         }
     }
 
@@ -892,10 +895,36 @@ pub enum ErrorKind {
     UnexpectedEof,
 }
 
+/// What the parser was looking for when it found an error.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Expected {
+    Number,
+    Flag,
+    MoveTo,
+    Command,
+}
+
+impl Display for Expected {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let s = match *self {
+            Expected::Number => "a number",
+            Expected::Flag => "a flag (0 or 1)",
+            Expected::MoveTo => "a moveto command",
+            Expected::Command => "a path command",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+/// The first error in a path's data.  The commands before the error are
+/// still valid, and per the SVG spec they get rendered.
 #[derive(Debug, PartialEq)]
 pub struct ParseError {
+    /// Byte offset in the path data
     pub position: usize,
     pub kind: ErrorKind,
+    pub expected: Expected,
 }
 
 impl Error for ParseError {
@@ -911,13 +940,16 @@ impl Display for ParseError {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(
             f,
-            "error at position {}: {}",
+            "error at byte {}: {}, expected {}",
             self.position,
-            self.description()
+            self.description(),
+            self.expected
         )
     }
 }
 
+/// Parses path data into `builder`.  If there is an error, the builder keeps
+/// the commands up to the error.
 pub fn parse_path_into_builder(
     path_str: &str,
     builder: &mut PathBuilder,
@@ -938,12 +970,9 @@ mod tests {
     fn make_parse_result(
         error_pos_str: &str,
         error_kind: Option<ErrorKind>,
-    ) -> Result<(), ParseError> {
+    ) -> Result<(), (usize, ErrorKind)> {
         if let Some(pos) = find_error_pos(error_pos_str) {
-            Err(ParseError {
-                position: pos,
-                kind: error_kind.unwrap(),
-            })
+            Err((pos, error_kind.unwrap()))
         } else {
             assert!(error_kind.is_none());
             Ok(())
@@ -959,7 +988,8 @@ mod tests {
         let expected_result = make_parse_result(error_pos_str, expected_error_kind);
 
         let mut builder = PathBuilder::new();
-        let result = parse_path_into_builder(path_str, &mut builder);
+        let result =
+            parse_path_into_builder(path_str, &mut builder).map_err(|e| (e.position, e.kind));
 
         let commands = builder.get_path_commands();
 
@@ -967,6 +997,11 @@ mod tests {
         assert_eq!(expected_result, result);
     }
 
+    fn parse_error(path_str: &str) -> ParseError {
+        let mut builder = PathBuilder::new();
+        parse_path_into_builder(path_str, &mut builder).unwrap_err()
+    }
+
     fn moveto(x: f64, y: f64) -> PathCommand {
         PathCommand::MoveTo(x, y)
     }
@@ -1994,4 +2029,44 @@ mod tests {
         //                   arc(...)],
         //             Some(ErrorKind::UnexpectedEof));
     }
+
+    #[test]
+    fn reports_byte_offsets() {
+        // "é" takes two bytes in UTF-8
+        let e = parse_error("M10 20 L é");
+        assert_eq!(e.position, 9);
+        assert_eq!(e.kind, ErrorKind::UnexpectedToken);
+
+        let e = parse_error("M10 20 L é 5");
+        assert_eq!(e.position, 9);
+
+        let e = parse_error("M\u{a0}10");
+        assert_eq!(e.position, 5);
+        assert_eq!(e.kind, ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn reports_expected_token() {
+        assert_eq!(parse_error("").expected, Expected::MoveTo);
+        assert_eq!(parse_error("L 10 20").expected, Expected::MoveTo);
+        assert_eq!(parse_error("M 10").expected, Expected::Number);
+        assert_eq!(parse_error("M 10 20 L 30").expected, Expected::Number);
+        assert_eq!(parse_error("M 10 20 A 1 2 3 5").expected, Expected::Flag);
+        assert_eq!(parse_error("M 10 20 x").expected, Expected::Command);
+
+        assert_eq!(
+            parse_error("M 10 20 L 30").to_string(),
+            "error at byte 12: unexpected end of data, expected a number"
+        );
+    }
+
+    #[test]
+    fn keeps_commands_before_truncation() {
+        test_parser(
+            "M10 20 L30 40 L50 60 C",
+            "                      ^",
+            &vec![moveto(10.0, 20.0), lineto(30.0, 40.0), lineto(50.0, 60.0)],
+            Some(ErrorKind::UnexpectedEof),
+        );
+    }
 }
@@ -14,9 +14,12 @@ use bbox::*;
 use coord_units::CoordUnits;
 use drawing_ctx::{self, RsvgDrawingCtx};
 use float_eq_cairo::ApproxEqCairo;
+use geometry;
 use handle::RsvgHandle;
+use hit_test;
 use length::*;
 use node::*;
+use normalize;
 use parsers::parse;
 use property_bag::PropertyBag;
 use state;
@@ -407,9 +410,17 @@ fn set_pattern_on_draw_context(
     let rstate = state::get_state_rust(state);
     rstate.affine = caffine;
 
-    // Draw everything
+    // Draw everything; the contents of the pattern are not shapes of their own
+    // in a normalized document, they can't be hit, and they are not part of
+    // the geometry of the shape that is being painted
+    let normalizer = normalize::pause(draw_ctx);
+    let hit_test = hit_test::pause(draw_ctx);
+    let geometry = geometry::pause(draw_ctx);
     let pattern_node = pattern.node.clone().unwrap().upgrade().unwrap();
     pattern_node.draw_children(draw_ctx, 2, false);
+    geometry::resume(draw_ctx, geometry);
+    hit_test::resume(draw_ctx, hit_test);
+    normalize::resume(draw_ctx, normalizer);
 
     // Return to the original coordinate system and rendering context
 
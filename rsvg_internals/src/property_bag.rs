@@ -11,14 +11,18 @@ use std::str::{self, FromStr};
 
 use attributes::Attribute;
 
-// We store (key, attribute, value)
-pub struct PropertyBag<'a>(Vec<(&'a CStr, Attribute, &'a CStr)>);
+// We store (key, attribute, value); the attribute is None for the ones that
+// librsvg does not know about.
+pub struct PropertyBag<'a>(Vec<(&'a CStr, Option<Attribute>, &'a CStr)>);
 
-pub struct OwnedPropertyBag(Vec<(CString, Attribute, CString)>);
+#[derive(Clone, Default)]
+pub struct OwnedPropertyBag(Vec<(CString, Option<Attribute>, CString)>);
 
 pub struct PropertyBagIter<'a>(PropertyBagCStrIter<'a>);
 
-pub struct PropertyBagCStrIter<'a>(slice::Iter<'a, (&'a CStr, Attribute, &'a CStr)>);
+pub struct PropertyBagCStrIter<'a>(slice::Iter<'a, (&'a CStr, Option<Attribute>, &'a CStr)>);
+
+pub struct PropertyBagAllIter<'a>(slice::Iter<'a, (&'a CStr, Option<Attribute>, &'a CStr)>);
 
 trait Utf8CStrToStr {
     fn to_str_utf8(&self) -> &str;
@@ -67,11 +71,10 @@ impl<'a> PropertyBag<'a> {
                     let key_str = CStr::from_ptr(key);
                     let val_str = CStr::from_ptr(val);
 
-                    // We silently drop unknown attributes.  New attributes should be added in
-                    // build.rs.
-                    if let Ok(attr) = Attribute::from_str(key_str.to_str_utf8()) {
-                        array.push((key_str, attr, val_str));
-                    }
+                    // Unknown attributes are kept so that they can be read back, but
+                    // iter() skips them.  New attributes should be added in build.rs.
+                    let attr = Attribute::from_str(key_str.to_str_utf8()).ok();
+                    array.push((key_str, attr, val_str));
                 } else {
                     break;
                 }
@@ -94,7 +97,7 @@ impl<'a> PropertyBag<'a> {
     }
 
     pub fn to_owned(&self) -> OwnedPropertyBag {
-        let mut array = Vec::<(CString, Attribute, CString)>::new();
+        let mut array = Vec::<(CString, Option<Attribute>, CString)>::new();
 
         for &(k, a, v) in &self.0 {
             array.push(((*k).to_owned(), a, (*v).to_owned()));
@@ -108,7 +111,7 @@ impl<'a> PropertyBag<'a> {
     }
 
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.iter().count()
     }
 
     pub fn iter(&self) -> PropertyBagIter {
@@ -118,6 +121,26 @@ impl<'a> PropertyBag<'a> {
     pub fn cstr_iter(&self) -> PropertyBagCStrIter {
         PropertyBagCStrIter(self.0.iter())
     }
+
+    /// Iterates over all the attributes as (key, value) pairs in document
+    /// order, including the ones that `iter()` skips because librsvg does not
+    /// know about them.
+    pub fn all_iter(&self) -> PropertyBagAllIter {
+        PropertyBagAllIter(self.0.iter())
+    }
+}
+
+impl OwnedPropertyBag {
+    /// Replaces the value of an attribute, or adds the attribute at the end
+    /// if the bag does not have it yet.
+    pub fn set(&mut self, key: &CStr, attr: Attribute, value: &CStr) {
+        if let Some(entry) = self.0.iter_mut().find(|entry| entry.1 == Some(attr)) {
+            entry.2 = value.to_owned();
+            return;
+        }
+
+        self.0.push((key.to_owned(), Some(attr), value.to_owned()));
+    }
 }
 
 impl<'a> Iterator for PropertyBagIter<'a> {
@@ -134,7 +157,20 @@ impl<'a> Iterator for PropertyBagCStrIter<'a> {
     type Item = (&'a CStr, Attribute, &'a CStr);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next().map(|&(k, a, v)| (k, a, v))
+        self.0
+            .by_ref()
+            .filter_map(|&(k, a, v)| a.map(|a| (k, a, v)))
+            .next()
+    }
+}
+
+impl<'a> Iterator for PropertyBagAllIter<'a> {
+    type Item = (&'a str, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0
+            .next()
+            .map(|&(k, _, v)| (k.to_str_utf8(), v.to_str_utf8()))
     }
 }
 
@@ -248,6 +284,53 @@ mod tests {
         assert!(had_ry);
     }
 
+    #[test]
+    fn property_bag_keeps_unknown_attributes() {
+        let pairs = [
+            CString::new("rx").unwrap(),
+            CString::new("1").unwrap(),
+            CString::new("frobnicate").unwrap(),
+            CString::new("yes").unwrap(),
+        ];
+
+        let mut v: Vec<*const libc::c_char> = pairs.iter().map(|s| s.as_ptr()).collect();
+        v.push(ptr::null());
+
+        let pbag = unsafe { PropertyBag::new_from_key_value_pairs(v.as_ptr()) };
+
+        assert_eq!(pbag.len(), 1);
+        assert_eq!(
+            pbag.iter().map(|(k, _, _)| k).collect::<Vec<_>>(),
+            vec!["rx"]
+        );
+
+        let owned = pbag.to_owned();
+        assert_eq!(
+            PropertyBag::from_owned(&owned)
+                .all_iter()
+                .collect::<Vec<_>>(),
+            vec![("rx", "1"), ("frobnicate", "yes")]
+        );
+    }
+
+    #[test]
+    fn owned_property_bag_sets_values() {
+        let rx = CString::new("rx").unwrap();
+        let ry = CString::new("ry").unwrap();
+        let one = CString::new("1").unwrap();
+        let two = CString::new("2").unwrap();
+
+        let mut owned = OwnedPropertyBag::default();
+        owned.set(&rx, Attribute::Rx, &one);
+        owned.set(&ry, Attribute::Ry, &one);
+        owned.set(&rx, Attribute::Rx, &two);
+
+        let pbag = PropertyBag::from_owned(&owned);
+        let values: Vec<(&str, &str)> = pbag.iter().map(|(k, _, v)| (k, v)).collect();
+
+        assert_eq!(values, vec![("rx", "2"), ("ry", "1")]);
+    }
+
     #[test]
     fn property_bag_can_iterate_from_c() {
         let pairs = [
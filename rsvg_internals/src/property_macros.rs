@@ -24,7 +24,9 @@ pub trait Property {
 /// variants.  It will generate an `impl Default for StrokeLinejoin`
 /// with the provided `default:` value.  Finally, it will generate an
 /// `impl Parse for StrokeLinejoin`, from `parsers::Parse`, where
-/// `type Data = ()` and `type Err = AttributeError`.
+/// `type Data = ()` and `type Err = AttributeError`, and an
+/// `impl Display for StrokeLinejoin` that writes the variant's
+/// identifier back.
 #[macro_export]
 macro_rules! make_property {
     ($name: ident,
@@ -53,6 +55,14 @@ macro_rules! make_property {
                 }
             }
         }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                match *self {
+                    $($name::$variant => write!(f, "{}", $str_prop),)+
+                }
+            }
+        }
     };
 
     ($name: ident,
@@ -79,6 +89,12 @@ macro_rules! make_property {
                     ))
             }
         }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
     };
 
     ($name: ident,
@@ -151,6 +167,7 @@ mod tests {
         assert_eq!(<Foo as Property>::inherits_automatically(), true);
         assert!(<Foo as Parse>::parse("blargh", ()).is_err());
         assert_eq!(<Foo as Parse>::parse("bar", ()), Ok(Foo::Bar));
+        assert_eq!(Foo::Baz.to_string(), "baz");
 
         make_property! {
             Bar,
@@ -165,6 +182,7 @@ mod tests {
             <Bar as Parse>::parse("test", ()),
             Ok(Bar("test".to_string()))
         );
+        assert_eq!(Bar("test".to_string()).to_string(), "test");
 
         make_property! {
             Baz,
@@ -0,0 +1,573 @@
+use cssparser::{self, Parser, ParserInput, Token};
+use glib::translate::*;
+use glib_sys;
+use libc;
+
+use std::fmt;
+
+use attributes::Attribute;
+use handle::RsvgHandle;
+use node::RsvgNode;
+use serialize::{self, CssDeclaration, SerializeFilter, SerializeOptions};
+
+extern "C" {
+    fn rsvg_handle_allow_load_url(
+        handle: *const RsvgHandle,
+        url: *const libc::c_char,
+    ) -> glib_sys::gboolean;
+
+    fn rsvg_sanitize_removals_add(
+        removals: *mut glib_sys::GPtrArray,
+        removal: *const RsvgSanitizeRemoval,
+    );
+}
+
+// Keep this in sync with ../../librsvg/rsvg.h:RsvgSanitizeRemovalKind
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RemovalKind {
+    Element,
+    Attribute,
+    Property,
+}
+
+// Keep this in sync with ../../librsvg/rsvg.h:RsvgSanitizeRemoval
+#[repr(C)]
+struct RsvgSanitizeRemoval {
+    kind: RemovalKind,
+    name: *mut libc::c_char,
+    element: *mut libc::c_char,
+    id: *mut libc::c_char,
+    value: *mut libc::c_char,
+    reason: *mut libc::c_char,
+}
+
+/// Why `sanitize()` removed part of a document.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RemovalReason {
+    Script,
+    ForeignObject,
+    UnknownNamespace,
+    ExternalReference,
+    EventHandler,
+}
+
+impl fmt::Display for RemovalReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RemovalReason::Script => write!(f, "script"),
+            RemovalReason::ForeignObject => write!(f, "foreign object"),
+            RemovalReason::UnknownNamespace => write!(f, "element from an unknown namespace"),
+            RemovalReason::ExternalReference => {
+                write!(f, "reference to a resource that librsvg may not load")
+            }
+            RemovalReason::EventHandler => write!(f, "event handler"),
+        }
+    }
+}
+
+/// A part of the document that `sanitize()` left out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Removal {
+    pub kind: RemovalKind,
+
+    /// Name of the element, attribute or property.
+    pub name: String,
+
+    /// Name of the element that had the attribute or property, or the
+    /// selector of a stylesheet rule; `None` for elements.
+    pub element: Option<String>,
+
+    /// Value of the `id` attribute of the element.
+    pub id: Option<String>,
+
+    /// The removed value of an attribute or property.
+    pub value: Option<String>,
+
+    pub reason: RemovalReason,
+}
+
+/// Writes a loaded document as SVG XML, like `serialize::serialize()`, but
+/// leaves out the parts that are unsafe to serve to a web browser: scripts,
+/// `<foreignObject>`, elements from other namespaces, and references to
+/// files that librsvg itself would refuse to load.  References within the
+/// document and `data:` URLs are kept.
+///
+/// Event handler attributes like `onclick` are left out as well; librsvg does
+/// not know about them, so the serializer never writes them, but they are
+/// still reported.
+///
+/// Returns the document and what was removed from it, or `None` if the
+/// handle has not finished loading.
+pub fn sanitize(
+    handle: *const RsvgHandle,
+    options: &SerializeOptions,
+) -> Option<(String, Vec<Removal>)> {
+    let mut sanitizer = Sanitizer::new(|url: &str| unsafe {
+        from_glib(rsvg_handle_allow_load_url(handle, url.to_glib_none().0))
+    });
+
+    let svg = serialize::serialize_filtered(handle, options, &mut sanitizer)?;
+
+    Some((svg, sanitizer.removals))
+}
+
+struct Sanitizer<F: Fn(&str) -> bool> {
+    allow_load: F,
+    removals: Vec<Removal>,
+}
+
+impl<F: Fn(&str) -> bool> Sanitizer<F> {
+    fn new(allow_load: F) -> Sanitizer<F> {
+        Sanitizer {
+            allow_load,
+            removals: Vec::new(),
+        }
+    }
+
+    // Whether librsvg would refuse to load the file that `url` refers to.
+    fn is_refused(&self, url: &str) -> bool {
+        let file = match url.find('#') {
+            Some(pos) => &url[..pos],
+            None => url,
+        };
+
+        !file.is_empty() && !(self.allow_load)(file)
+    }
+
+    fn has_refused_url(&self, value: &str) -> bool {
+        css_urls(value).iter().any(|url| self.is_refused(url))
+    }
+
+    fn remove(
+        &mut self,
+        kind: RemovalKind,
+        name: &str,
+        element: Option<&str>,
+        node: Option<&RsvgNode>,
+        value: Option<&str>,
+        reason: RemovalReason,
+    ) {
+        self.removals.push(Removal {
+            kind,
+            name: name.to_string(),
+            element: element.map(str::to_string),
+            id: node.and_then(|n| n.get_attribute("id")),
+            value: value.map(str::to_string),
+            reason,
+        });
+    }
+
+    fn filter_style_attribute(&mut self, node: &RsvgNode, element: &str, style: &str) -> String {
+        let mut kept = Vec::new();
+
+        for decl in style.split(';').filter(|decl| !decl.trim().is_empty()) {
+            let (name, value) = match decl.find(':') {
+                Some(colon) => (decl[..colon].trim(), decl[colon + 1..].trim()),
+                None => (decl.trim(), ""),
+            };
+
+            if self.has_refused_url(value) {
+                self.remove(
+                    RemovalKind::Property,
+                    name,
+                    Some(element),
+                    Some(node),
+                    Some(value),
+                    RemovalReason::ExternalReference,
+                );
+            } else {
+                kept.push(decl);
+            }
+        }
+
+        kept.join(";")
+    }
+}
+
+impl<F: Fn(&str) -> bool> SerializeFilter for Sanitizer<F> {
+    fn keep_element(&mut self, node: &RsvgNode, name: &str) -> bool {
+        let reason = if name == "script" {
+            RemovalReason::Script
+        } else if name == "foreignObject" {
+            RemovalReason::ForeignObject
+        } else if node.is_in_foreign_namespace() {
+            RemovalReason::UnknownNamespace
+        } else {
+            return true;
+        };
+
+        self.remove(RemovalKind::Element, name, None, Some(node), None, reason);
+        false
+    }
+
+    fn filter_attribute(
+        &mut self,
+        node: &RsvgNode,
+        element: &str,
+        attr: Attribute,
+        name: &str,
+        value: &str,
+    ) -> Option<String> {
+        let refused = match attr {
+            Attribute::XlinkHref | Attribute::Href | Attribute::Path => {
+                self.is_refused(value.trim())
+            }
+
+            // <set> and <animate> can change a reference after loading
+            Attribute::To | Attribute::From | Attribute::By | Attribute::Values
+                if animates_href(node) =>
+            {
+                value.split(';').any(|v| self.is_refused(v.trim()))
+            }
+
+            Attribute::Style => {
+                let style = self.filter_style_attribute(node, element, value);
+                return if style.is_empty() { None } else { Some(style) };
+            }
+
+            _ => self.has_refused_url(value),
+        };
+
+        if refused {
+            self.remove(
+                RemovalKind::Attribute,
+                name,
+                Some(element),
+                Some(node),
+                Some(value),
+                RemovalReason::ExternalReference,
+            );
+            None
+        } else {
+            Some(value.to_string())
+        }
+    }
+
+    fn skip_unknown_attribute(&mut self, node: &RsvgNode, element: &str, name: &str, value: &str) {
+        if name.to_lowercase().starts_with("on") {
+            self.remove(
+                RemovalKind::Attribute,
+                name,
+                Some(element),
+                Some(node),
+                Some(value),
+                RemovalReason::EventHandler,
+            );
+        }
+    }
+
+    fn keep_css_declaration(&mut self, decl: &CssDeclaration) -> bool {
+        if self.has_refused_url(&decl.value) {
+            self.remove(
+                RemovalKind::Property,
+                &decl.name,
+                Some(&decl.selector),
+                None,
+                Some(&decl.value),
+                RemovalReason::ExternalReference,
+            );
+            false
+        } else {
+            true
+        }
+    }
+}
+
+fn animates_href(node: &RsvgNode) -> bool {
+    node.get_attribute("attributeName")
+        .map_or(false, |name| match name.trim() {
+            "href" | "xlink:href" => true,
+            _ => false,
+        })
+}
+
+/// Returns the URLs in the `url()` functions of a property value.
+///
+/// The value is tokenized like a CSS parser would do it, so that `URL(...)`
+/// or escapes like `u\72l(...)` are found as well.
+fn css_urls(value: &str) -> Vec<String> {
+    let mut input = ParserInput::new(value);
+    let mut parser = Parser::new(&mut input);
+    let mut urls = Vec::new();
+
+    collect_css_urls(&mut parser, &mut urls);
+
+    urls
+}
+
+fn collect_css_urls(parser: &mut Parser, urls: &mut Vec<String>) {
+    while let Ok(token) = parser.next().map(Token::clone) {
+        match token {
+            Token::UnquotedUrl(ref url) => urls.push(url.to_string()),
+
+            Token::Function(ref name) if name.eq_ignore_ascii_case("url") => {
+                let _: Result<(), cssparser::ParseError<()>> = parser.parse_nested_block(|p| {
+                    if let Ok(url) = p.expect_string() {
+                        urls.push(url.to_string());
+                    }
+
+                    collect_css_urls(p, urls);
+                    Ok(())
+                });
+            }
+
+            Token::Function(_)
+            | Token::ParenthesisBlock
+            | Token::SquareBracketBlock
+            | Token::CurlyBracketBlock => {
+                let _: Result<(), cssparser::ParseError<()>> = parser.parse_nested_block(|p| {
+                    collect_css_urls(p, urls);
+                    Ok(())
+                });
+            }
+
+            _ => (),
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_dom_sanitize(
+    handle: *const RsvgHandle,
+    pretty: glib_sys::gboolean,
+    drop_nodes_in_error: glib_sys::gboolean,
+    removals: *mut glib_sys::GPtrArray,
+) -> *mut libc::c_char {
+    assert!(!removals.is_null());
+
+    let options = SerializeOptions {
+        pretty: from_glib(pretty),
+        drop_nodes_in_error: from_glib(drop_nodes_in_error),
+    };
+
+    match sanitize(handle, &options) {
+        Some((svg, list)) => {
+            for removal in list {
+                let r = RsvgSanitizeRemoval {
+                    kind: removal.kind,
+                    name: removal.name.to_glib_full(),
+                    element: removal.element.to_glib_full(),
+                    id: removal.id.to_glib_full(),
+                    value: removal.value.to_glib_full(),
+                    reason: removal.reason.to_string().to_glib_full(),
+                };
+
+                // The C side takes ownership of the strings
+                unsafe {
+                    rsvg_sanitize_removals_add(removals, &r);
+                }
+            }
+
+            svg.to_glib_full()
+        }
+
+        None => ::std::ptr::null_mut(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drawing_ctx::RsvgDrawingCtx;
+    use node::{Node, NodeResult, NodeTrait, NodeType, RsvgCNodeImpl};
+    use property_bag::PropertyBag;
+    use std::ffi::CString;
+    use std::ptr;
+    use std::rc::Rc;
+
+    struct TestNodeImpl {}
+
+    impl NodeTrait for TestNodeImpl {
+        fn set_atts(&self, _: &RsvgNode, _: *const RsvgHandle, _: &PropertyBag) -> NodeResult {
+            Ok(())
+        }
+
+        fn draw(&self, _: &RsvgNode, _: *mut RsvgDrawingCtx, _: i32, _: bool) {}
+
+        fn get_c_impl(&self) -> *const RsvgCNodeImpl {
+            unreachable!();
+        }
+    }
+
+    fn new_element(node_type: NodeType, name: &str, atts: &[(&str, &str)]) -> RsvgNode {
+        let node = Rc::new(Node::new(
+            node_type,
+            None,
+            ptr::null_mut(),
+            Box::new(TestNodeImpl {}),
+        ));
+
+        node.set_element_name(name);
+
+        let strings: Vec<CString> = atts
+            .iter()
+            .flat_map(|&(k, v)| vec![CString::new(k).unwrap(), CString::new(v).unwrap()])
+            .collect();
+        let mut v: Vec<*const libc::c_char> = strings.iter().map(|s| s.as_ptr()).collect();
+        v.push(ptr::null());
+
+        let pbag = unsafe { PropertyBag::new_from_key_value_pairs(v.as_ptr()) };
+        node.set_atts(&node, ptr::null(), &pbag);
+
+        node
+    }
+
+    fn allow_relative(url: &str) -> bool {
+        url.starts_with("data:") || !url.contains(':')
+    }
+
+    #[test]
+    fn finds_css_urls() {
+        assert_eq!(css_urls("red"), Vec::<&str>::new());
+        assert_eq!(css_urls("url(#a) red"), vec!["#a"]);
+        assert_eq!(
+            css_urls("url( \"b.svg#c\" ), url('d.png')"),
+            vec!["b.svg#c", "d.png"]
+        );
+        assert_eq!(css_urls("url(#unterminated"), vec!["#unterminated"]);
+    }
+
+    #[test]
+    fn finds_css_urls_like_a_css_parser() {
+        assert_eq!(css_urls("URL(http://a/b.png)"), vec!["http://a/b.png"]);
+        assert_eq!(css_urls("Url(\"http://a/c.png\")"), vec!["http://a/c.png"]);
+        assert_eq!(css_urls("u\\72l(http://a/d.png)"), vec!["http://a/d.png"]);
+        assert_eq!(
+            css_urls("drop-shadow(url(http://a/e.svg))"),
+            vec!["http://a/e.svg"]
+        );
+        assert_eq!(css_urls("curl(#a)"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn refuses_only_disallowed_files() {
+        let sanitizer = Sanitizer::new(allow_relative);
+
+        assert!(!sanitizer.is_refused("#foo"));
+        assert!(!sanitizer.is_refused("images/a.png"));
+        assert!(!sanitizer.is_refused("data:image/png;base64,AAAA"));
+        assert!(sanitizer.is_refused("http://example.com/a.svg#foo"));
+        assert!(sanitizer.is_refused("javascript:alert(1)"));
+
+        assert!(!sanitizer.has_refused_url("url(#grad) none"));
+        assert!(sanitizer.has_refused_url("url(http://example.com/a.svg#grad) none"));
+    }
+
+    #[test]
+    fn removes_stylesheet_declarations_with_refused_urls() {
+        let mut sanitizer = Sanitizer::new(allow_relative);
+
+        let local = CssDeclaration {
+            selector: "rect".to_string(),
+            name: "fill".to_string(),
+            value: "url(#grad)".to_string(),
+            important: false,
+        };
+
+        let remote = CssDeclaration {
+            selector: ".a".to_string(),
+            name: "filter".to_string(),
+            value: "url(http://example.com/f.svg#blur)".to_string(),
+            important: false,
+        };
+
+        assert!(sanitizer.keep_css_declaration(&local));
+        assert!(!sanitizer.keep_css_declaration(&remote));
+
+        assert_eq!(
+            sanitizer.removals,
+            vec![Removal {
+                kind: RemovalKind::Property,
+                name: "filter".to_string(),
+                element: Some(".a".to_string()),
+                id: None,
+                value: Some("url(http://example.com/f.svg#blur)".to_string()),
+                reason: RemovalReason::ExternalReference,
+            }]
+        );
+    }
+
+    #[test]
+    fn removes_plain_hrefs_to_refused_files() {
+        let mut sanitizer = Sanitizer::new(allow_relative);
+
+        let a = new_element(
+            NodeType::Link,
+            "a",
+            &[("id", "link"), ("href", "javascript:alert(1)")],
+        );
+        let image = new_element(NodeType::Image, "image", &[("href", "http://evil/x.png")]);
+        let local = new_element(NodeType::Use, "use", &[("href", "#shape")]);
+
+        assert_eq!(
+            sanitizer.filter_attribute(&a, "a", Attribute::Href, "href", "javascript:alert(1)"),
+            None
+        );
+        assert_eq!(
+            sanitizer.filter_attribute(
+                &image,
+                "image",
+                Attribute::Href,
+                "href",
+                "http://evil/x.png"
+            ),
+            None
+        );
+        assert_eq!(
+            sanitizer.filter_attribute(&local, "use", Attribute::Href, "href", "#shape"),
+            Some("#shape".to_string())
+        );
+
+        assert_eq!(
+            sanitizer.removals,
+            vec![
+                Removal {
+                    kind: RemovalKind::Attribute,
+                    name: "href".to_string(),
+                    element: Some("a".to_string()),
+                    id: Some("link".to_string()),
+                    value: Some("javascript:alert(1)".to_string()),
+                    reason: RemovalReason::ExternalReference,
+                },
+                Removal {
+                    kind: RemovalKind::Attribute,
+                    name: "href".to_string(),
+                    element: Some("image".to_string()),
+                    id: None,
+                    value: Some("http://evil/x.png".to_string()),
+                    reason: RemovalReason::ExternalReference,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_event_handlers() {
+        let mut sanitizer = Sanitizer::new(allow_relative);
+
+        let rect = new_element(
+            NodeType::Rect,
+            "rect",
+            &[("id", "button"), ("width", "10"), ("onclick", "alert(1)")],
+        );
+
+        let options = SerializeOptions::default();
+        let svg = serialize::serialize_tree(&rect, &[], &options, &mut sanitizer);
+
+        assert!(!svg.contains("onclick"));
+        assert!(svg.contains("width=\"10\""));
+
+        assert_eq!(
+            sanitizer.removals,
+            vec![Removal {
+                kind: RemovalKind::Attribute,
+                name: "onclick".to_string(),
+                element: Some("rect".to_string()),
+                id: Some("button".to_string()),
+                value: Some("alert(1)".to_string()),
+                reason: RemovalReason::EventHandler,
+            }]
+        );
+    }
+}
@@ -0,0 +1,561 @@
+use glib::translate::*;
+use glib_sys;
+use libc;
+
+use attributes::Attribute;
+use dom;
+use handle::RsvgHandle;
+use node::{NodeType, RsvgNode};
+use property_bag::PropertyBag;
+use text;
+
+extern "C" {
+    fn rsvg_handle_foreach_css_declaration(
+        handle: *const RsvgHandle,
+        func: extern "C" fn(
+            *const libc::c_char,
+            *const libc::c_char,
+            *const libc::c_char,
+            glib_sys::gboolean,
+            glib_sys::gpointer,
+        ),
+        user_data: glib_sys::gpointer,
+    );
+}
+
+pub const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+pub const XLINK_NAMESPACE: &str = "http://www.w3.org/1999/xlink";
+
+/// Options for `serialize()`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SerializeOptions {
+    /// Put each element on its own line, indented by its depth in the tree.
+    /// The contents of text elements are written on a single line, since
+    /// adding white space to them would change their text.
+    pub pretty: bool,
+
+    /// Leave out the elements that librsvg does not render because of an
+    /// invalid attribute, together with their children.
+    pub drop_nodes_in_error: bool,
+}
+
+/// A declaration from one of the document's stylesheets, like
+/// `fill: red` for the selector `rect.warning`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CssDeclaration {
+    pub selector: String,
+    pub name: String,
+    pub value: String,
+    pub important: bool,
+}
+
+/// Decides which parts of the document `serialize_filtered()` writes.
+pub trait SerializeFilter {
+    /// Returns whether to write an element other than the root, together
+    /// with its children.
+    fn keep_element(&mut self, node: &RsvgNode, name: &str) -> bool;
+
+    /// Returns the value to write for an attribute, or `None` to leave the
+    /// attribute out.
+    fn filter_attribute(
+        &mut self,
+        node: &RsvgNode,
+        element: &str,
+        attr: Attribute,
+        name: &str,
+        value: &str,
+    ) -> Option<String>;
+
+    /// Called for each attribute that librsvg does not know about; those
+    /// are never written.
+    fn skip_unknown_attribute(
+        &mut self,
+        _node: &RsvgNode,
+        _element: &str,
+        _name: &str,
+        _value: &str,
+    ) {
+    }
+
+    /// Returns whether to write a declaration from the stylesheets.
+    fn keep_css_declaration(&mut self, declaration: &CssDeclaration) -> bool;
+
+    /// Returns the attributes to write after the ones that an element has.
+    fn extra_attributes(&mut self, _node: &RsvgNode) -> Vec<(String, String)> {
+        Vec::new()
+    }
+}
+
+/// The filter for `serialize()`, which keeps everything.
+struct KeepAll;
+
+impl SerializeFilter for KeepAll {
+    fn keep_element(&mut self, _: &RsvgNode, _: &str) -> bool {
+        true
+    }
+
+    fn filter_attribute(
+        &mut self,
+        _: &RsvgNode,
+        _: &str,
+        _: Attribute,
+        _: &str,
+        value: &str,
+    ) -> Option<String> {
+        Some(value.to_string())
+    }
+
+    fn keep_css_declaration(&mut self, _: &CssDeclaration) -> bool {
+        true
+    }
+}
+
+/// Writes the tree of a loaded document as SVG XML.
+///
+/// Elements are written with the attributes that librsvg knows about, in
+/// document order.  Elements from other namespaces, like Inkscape's, are left
+/// out, since librsvg does not render them and only the SVG and XLink
+/// namespaces get declared.  The rules from the document's stylesheets go
+/// into a single `<style>` element at the start of the root element.
+///
+/// Returns `None` if the handle has not finished loading.
+pub fn serialize(handle: *const RsvgHandle, options: &SerializeOptions) -> Option<String> {
+    serialize_filtered(handle, options, &mut KeepAll)
+}
+
+/// Like `serialize()`, but only writes what `filter` keeps.
+pub fn serialize_filtered<F: SerializeFilter>(
+    handle: *const RsvgHandle,
+    options: &SerializeOptions,
+    filter: &mut F,
+) -> Option<String> {
+    let root = dom::get_root_element(handle)?;
+    let declarations = get_css_declarations(handle);
+
+    Some(serialize_tree(&root, &declarations, options, filter))
+}
+
+/// Writes `node` and its children as they would appear at `depth` in the
+/// output of `serialize_filtered()`, for copying an element into another
+/// document.
+pub fn serialize_element<F: SerializeFilter>(
+    node: &RsvgNode,
+    depth: usize,
+    options: &SerializeOptions,
+    filter: &mut F,
+) -> String {
+    let mut serializer = Serializer {
+        out: String::new(),
+        options: *options,
+        filter,
+    };
+
+    serializer.write_node(node, depth, false);
+
+    serializer.out
+}
+
+/// Returns the declarations from the document's stylesheets, sorted by
+/// selector and then by property name.
+pub fn get_css_declarations(handle: *const RsvgHandle) -> Vec<CssDeclaration> {
+    let mut declarations: Vec<CssDeclaration> = Vec::new();
+
+    unsafe {
+        rsvg_handle_foreach_css_declaration(
+            handle,
+            collect_css_declaration,
+            &mut declarations as *mut Vec<CssDeclaration> as glib_sys::gpointer,
+        );
+    }
+
+    declarations
+}
+
+extern "C" fn collect_css_declaration(
+    selector: *const libc::c_char,
+    name: *const libc::c_char,
+    value: *const libc::c_char,
+    important: glib_sys::gboolean,
+    user_data: glib_sys::gpointer,
+) {
+    let declarations = unsafe { &mut *(user_data as *mut Vec<CssDeclaration>) };
+
+    unsafe {
+        declarations.push(CssDeclaration {
+            selector: from_glib_none(selector),
+            name: from_glib_none(name),
+            value: from_glib_none(value),
+            important: from_glib(important),
+        });
+    }
+}
+
+/// Writes sorted declarations as CSS, with one rule per selector.
+fn stylesheet_text(declarations: &[&CssDeclaration]) -> String {
+    let mut text = String::new();
+    let mut selector: Option<&str> = None;
+
+    for decl in declarations {
+        if selector != Some(decl.selector.as_str()) {
+            if selector.is_some() {
+                text.push_str(" }\n");
+            }
+
+            text.push_str(&decl.selector);
+            text.push_str(" {");
+            selector = Some(decl.selector.as_str());
+        }
+
+        text.push_str(&format!(
+            " {}: {}{};",
+            decl.name,
+            decl.value,
+            if decl.important { " !important" } else { "" }
+        ));
+    }
+
+    if selector.is_some() {
+        text.push_str(" }\n");
+    }
+
+    text
+}
+
+/// Writes the tree under `root`, with `declarations` as its stylesheet.
+pub fn serialize_tree<F: SerializeFilter>(
+    root: &RsvgNode,
+    declarations: &[CssDeclaration],
+    options: &SerializeOptions,
+    filter: &mut F,
+) -> String {
+    let kept: Vec<&CssDeclaration> = declarations
+        .iter()
+        .filter(|decl| filter.keep_css_declaration(decl))
+        .collect();
+    let stylesheet = stylesheet_text(&kept);
+
+    let mut serializer = Serializer {
+        out: String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"),
+        options: *options,
+        filter,
+    };
+
+    if let Some(name) = root.get_element_name() {
+        serializer.write_element(root, &name, 0, false, &stylesheet);
+    }
+
+    if !options.pretty {
+        serializer.out.push('\n');
+    }
+
+    serializer.out
+}
+
+struct Serializer<'a, F: 'a + SerializeFilter> {
+    out: String,
+    options: SerializeOptions,
+    filter: &'a mut F,
+}
+
+impl<'a, F: SerializeFilter> Serializer<'a, F> {
+    fn write_node(&mut self, node: &RsvgNode, depth: usize, inline: bool) {
+        if node.get_type() == NodeType::Chars {
+            escape_text(&text::get_chars_string(node), &mut self.out);
+            return;
+        }
+
+        let name = match node.get_element_name() {
+            Some(name) => name,
+            None => return,
+        };
+
+        if !self.filter.keep_element(node, &name)
+            || node.is_in_foreign_namespace()
+            || (self.options.drop_nodes_in_error && node.get_result().is_err())
+        {
+            return;
+        }
+
+        self.write_element(node, &name, depth, inline, "");
+    }
+
+    fn write_element(
+        &mut self,
+        node: &RsvgNode,
+        name: &str,
+        depth: usize,
+        inline: bool,
+        stylesheet: &str,
+    ) {
+        let separate_lines = self.options.pretty && !inline;
+
+        if separate_lines {
+            self.indent(depth);
+        }
+
+        self.out.push('<');
+        self.out.push_str(name);
+
+        if depth == 0 {
+            self.write_attribute("xmlns", SVG_NAMESPACE);
+            self.write_attribute("xmlns:xlink", XLINK_NAMESPACE);
+        }
+
+        let attributes = node.get_attributes();
+        let pbag = PropertyBag::from_owned(&attributes);
+
+        for (key, attr, value) in pbag.iter() {
+            if let Some(value) = self.filter.filter_attribute(node, name, attr, key, value) {
+                self.write_attribute(key, &value);
+            }
+        }
+
+        for (key, value) in pbag.all_iter() {
+            if key.parse::<Attribute>().is_err() {
+                self.filter.skip_unknown_attribute(node, name, key, value);
+            }
+        }
+
+        for (key, value) in self.filter.extra_attributes(node) {
+            self.write_attribute(&key, &value);
+        }
+
+        if !node.has_children() && stylesheet.is_empty() {
+            self.out.push_str("/>");
+        } else {
+            self.out.push('>');
+
+            let children_inline = inline || keeps_character_data(node);
+            let children_on_lines = self.options.pretty && !children_inline;
+
+            if children_on_lines {
+                self.out.push('\n');
+            }
+
+            if !stylesheet.is_empty() {
+                if children_on_lines {
+                    self.indent(depth + 1);
+                }
+
+                self.out.push_str("<style type=\"text/css\">");
+                escape_text(stylesheet, &mut self.out);
+                self.out.push_str("</style>");
+
+                if children_on_lines {
+                    self.out.push('\n');
+                }
+            }
+
+            for child in node.children() {
+                self.write_node(&child, depth + 1, children_inline);
+            }
+
+            if children_on_lines {
+                self.indent(depth);
+            }
+
+            self.out.push_str("</");
+            self.out.push_str(name);
+            self.out.push('>');
+        }
+
+        if separate_lines {
+            self.out.push('\n');
+        }
+    }
+
+    fn write_attribute(&mut self, name: &str, value: &str) {
+        self.out.push(' ');
+        self.out.push_str(name);
+        self.out.push_str("=\"");
+        escape_attribute_value(value, &mut self.out);
+        self.out.push('"');
+    }
+
+    fn indent(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.out.push_str("  ");
+        }
+    }
+}
+
+/// Whether the loader stores the character data inside the element, in which
+/// case white space added by pretty-printing would become part of its text.
+fn keeps_character_data(node: &RsvgNode) -> bool {
+    match node.get_type() {
+        NodeType::Text | NodeType::TSpan | NodeType::FlowPara | NodeType::FlowSpan => true,
+        _ => false,
+    }
+}
+
+fn escape_text(s: &str, out: &mut String) {
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+pub fn escape_attribute_value(s: &str, out: &mut String) {
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\t' => out.push_str("&#9;"),
+            '\n' => out.push_str("&#10;"),
+            '\r' => out.push_str("&#13;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_dom_serialize(
+    handle: *const RsvgHandle,
+    pretty: glib_sys::gboolean,
+    drop_nodes_in_error: glib_sys::gboolean,
+) -> *mut libc::c_char {
+    let options = SerializeOptions {
+        pretty: from_glib(pretty),
+        drop_nodes_in_error: from_glib(drop_nodes_in_error),
+    };
+
+    serialize(handle, &options).to_glib_full()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drawing_ctx::RsvgDrawingCtx;
+    use error::{AttributeError, NodeError};
+    use node::{Node, NodeResult, NodeTrait, RsvgCNodeImpl};
+    use std::ffi::CString;
+    use std::ptr;
+    use std::rc::Rc;
+
+    struct TestNodeImpl {}
+
+    impl NodeTrait for TestNodeImpl {
+        fn set_atts(&self, _: &RsvgNode, _: *const RsvgHandle, _: &PropertyBag) -> NodeResult {
+            Ok(())
+        }
+
+        fn draw(&self, _: &RsvgNode, _: *mut RsvgDrawingCtx, _: i32, _: bool) {}
+
+        fn get_c_impl(&self) -> *const RsvgCNodeImpl {
+            unreachable!();
+        }
+    }
+
+    fn new_element(
+        node_type: NodeType,
+        name: &str,
+        parent: Option<&RsvgNode>,
+        atts: &[(&str, &str)],
+    ) -> RsvgNode {
+        let node = Rc::new(Node::new(
+            node_type,
+            parent.map(Rc::downgrade),
+            ptr::null_mut(),
+            Box::new(TestNodeImpl {}),
+        ));
+
+        node.set_element_name(name);
+
+        let strings: Vec<CString> = atts
+            .iter()
+            .flat_map(|&(k, v)| vec![CString::new(k).unwrap(), CString::new(v).unwrap()])
+            .collect();
+        let mut v: Vec<*const libc::c_char> = strings.iter().map(|s| s.as_ptr()).collect();
+        v.push(ptr::null());
+
+        let pbag = unsafe { PropertyBag::new_from_key_value_pairs(v.as_ptr()) };
+        node.set_atts(&node, ptr::null(), &pbag);
+
+        if let Some(parent) = parent {
+            parent.add_child(&node);
+        }
+
+        node
+    }
+
+    fn test_tree() -> RsvgNode {
+        let svg = new_element(NodeType::Svg, "svg", None, &[("width", "10")]);
+        let g = new_element(NodeType::Group, "g", Some(&svg), &[("fill", "url(#a&b)")]);
+        new_element(NodeType::Rect, "rect", Some(&g), &[("x", "1"), ("y", "2")]);
+        let namedview = new_element(NodeType::Defs, "namedview", Some(&svg), &[]);
+        namedview.set_foreign_namespace();
+
+        let text = new_element(NodeType::Text, "text", Some(&svg), &[("x", "5")]);
+        new_element(NodeType::TSpan, "tspan", Some(&text), &[]);
+        new_element(NodeType::TSpan, "tspan", Some(&text), &[("dy", "1")]);
+
+        let circle = new_element(NodeType::Circle, "circle", Some(&svg), &[("r", "-1")]);
+        circle.set_error(NodeError::attribute_error(
+            "r",
+            AttributeError::Value("must be non-negative".to_string()),
+        ));
+
+        svg
+    }
+
+    #[test]
+    fn escapes_text_and_attributes() {
+        let mut s = String::new();
+        escape_text("a < b && \"c\" > d", &mut s);
+        assert_eq!(s, "a &lt; b &amp;&amp; \"c\" &gt; d");
+
+        let mut s = String::new();
+        escape_attribute_value("<\"a\" & b>\n", &mut s);
+        assert_eq!(s, "&lt;&quot;a&quot; &amp; b&gt;&#10;");
+    }
+
+    #[test]
+    fn serializes_compact_tree() {
+        let options = SerializeOptions::default();
+
+        assert_eq!(
+            serialize_tree(&test_tree(), &[], &options, &mut KeepAll),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <svg xmlns=\"http://www.w3.org/2000/svg\" \
+             xmlns:xlink=\"http://www.w3.org/1999/xlink\" width=\"10\">\
+             <g fill=\"url(#a&amp;b)\"><rect x=\"1\" y=\"2\"/></g>\
+             <text x=\"5\"><tspan/><tspan dy=\"1\"/></text>\
+             <circle r=\"-1\"/>\
+             </svg>\n"
+        );
+    }
+
+    #[test]
+    fn serializes_pretty_tree_without_nodes_in_error() {
+        let options = SerializeOptions {
+            pretty: true,
+            drop_nodes_in_error: true,
+        };
+
+        let declarations = vec![CssDeclaration {
+            selector: "rect".to_string(),
+            name: "fill".to_string(),
+            value: "red".to_string(),
+            important: false,
+        }];
+
+        assert_eq!(
+            serialize_tree(&test_tree(), &declarations, &options, &mut KeepAll),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <svg xmlns=\"http://www.w3.org/2000/svg\" \
+             xmlns:xlink=\"http://www.w3.org/1999/xlink\" width=\"10\">\n\
+             \x20\x20<style type=\"text/css\">rect { fill: red; }\n</style>\n\
+             \x20\x20<g fill=\"url(#a&amp;b)\">\n\
+             \x20\x20\x20\x20<rect x=\"1\" y=\"2\"/>\n\
+             \x20\x20</g>\n\
+             \x20\x20<text x=\"5\"><tspan/><tspan dy=\"1\"/></text>\n\
+             </svg>\n"
+        );
+    }
+}
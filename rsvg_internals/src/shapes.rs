@@ -1,3 +1,4 @@
+use cairo;
 use libc;
 
 use std::cell::Cell;
@@ -12,22 +13,27 @@ use handle::RsvgHandle;
 use length::*;
 use marker;
 use node::*;
+use normalize;
 use parsers::{self, parse};
 use path_builder::*;
 use path_parser;
 use property_bag::PropertyBag;
-use state::RsvgState;
 
 fn render_path_builder(
     builder: &PathBuilder,
     draw_ctx: *mut RsvgDrawingCtx,
-    state: *mut RsvgState,
+    node: &RsvgNode,
     dominate: i32,
     render_markers: bool,
     clipping: bool,
 ) {
-    drawing_ctx::state_reinherit_top(draw_ctx, state, dominate);
-    draw_path_builder(draw_ctx, builder, clipping);
+    drawing_ctx::state_reinherit_top(draw_ctx, node.get_state(), dominate);
+
+    if !clipping {
+        normalize::add_path(draw_ctx, node, builder);
+    }
+
+    draw_path_builder(draw_ctx, builder, Some(node), clipping);
 
     if render_markers {
         marker::render_markers_for_path_builder(builder, draw_ctx, clipping);
@@ -95,41 +101,65 @@ fn render_ellipse(
 
     builder.close_path();
 
-    render_path_builder(
-        &builder,
-        draw_ctx,
-        node.get_state(),
-        dominate,
-        false,
-        clipping,
-    );
+    render_path_builder(&builder, draw_ctx, node, dominate, false, clipping);
 }
 
 // ************ NodePath ************
-struct NodePath {
+pub struct NodePath {
     builder: RefCell<Option<PathBuilder>>,
+    path_length: Cell<Option<f64>>,
 }
 
 impl NodePath {
     fn new() -> NodePath {
         NodePath {
             builder: RefCell::new(None),
+            path_length: Cell::new(None),
         }
     }
+
+    /// Returns the author's length of the path, from its `pathLength`
+    /// attribute, to which distances along the path are relative.
+    pub fn get_path_length(&self) -> Option<f64> {
+        self.path_length.get()
+    }
 }
 
 impl NodeTrait for NodePath {
-    fn set_atts(&self, _: &RsvgNode, _: *const RsvgHandle, pbag: &PropertyBag) -> NodeResult {
+    fn set_atts(&self, node: &RsvgNode, _: *const RsvgHandle, pbag: &PropertyBag) -> NodeResult {
         for (_key, attr, value) in pbag.iter() {
-            if attr == Attribute::D {
-                let mut builder = PathBuilder::new();
+            match attr {
+                Attribute::D => {
+                    let mut builder = PathBuilder::new();
+
+                    // Per the spec, the path gets rendered up to the first error
+                    // in its data, so this does not put the node in error.  An
+                    // empty path just disables rendering.
+                    if let Err(e) = path_parser::parse_path_into_builder(value, &mut builder) {
+                        if !value.trim().is_empty() {
+                            node.add_warning(NodeError::parse_error(
+                                "d",
+                                parsers::ParseError::new(e.to_string()),
+                            ));
+                        }
+                    }
 
-                if path_parser::parse_path_into_builder(value, &mut builder).is_err() {
-                    // FIXME: we don't propagate errors upstream, but creating a partial
-                    // path is OK per the spec
+                    *self.builder.borrow_mut() = Some(builder);
                 }
 
-                *self.builder.borrow_mut() = Some(builder);
+                // An invalid pathLength is ignored, and the path is still drawn
+                Attribute::PathLength => match parsers::number(value) {
+                    Ok(length) if length >= 0.0 => self.path_length.set(Some(length)),
+
+                    Ok(_) => node.add_warning(NodeError::value_error(
+                        "pathLength",
+                        "value must be non-negative",
+                    )),
+
+                    Err(e) => node.add_warning(NodeError::parse_error("pathLength", e)),
+                },
+
+                _ => (),
             }
         }
 
@@ -138,14 +168,7 @@ impl NodeTrait for NodePath {
 
     fn draw(&self, node: &RsvgNode, draw_ctx: *mut RsvgDrawingCtx, dominate: i32, clipping: bool) {
         if let Some(ref builder) = *self.builder.borrow() {
-            render_path_builder(
-                builder,
-                draw_ctx,
-                node.get_state(),
-                dominate,
-                true,
-                clipping,
-            );
+            render_path_builder(builder, draw_ctx, node, dominate, true, clipping);
         }
     }
 
@@ -214,14 +237,7 @@ impl NodeTrait for NodePoly {
                 builder.close_path();
             }
 
-            render_path_builder(
-                &builder,
-                draw_ctx,
-                node.get_state(),
-                dominate,
-                true,
-                clipping,
-            );
+            render_path_builder(&builder, draw_ctx, node, dominate, true, clipping);
         }
     }
 
@@ -277,14 +293,7 @@ impl NodeTrait for NodeLine {
         builder.move_to(x1, y1);
         builder.line_to(x2, y2);
 
-        render_path_builder(
-            &builder,
-            draw_ctx,
-            node.get_state(),
-            dominate,
-            true,
-            clipping,
-        );
+        render_path_builder(&builder, draw_ctx, node, dominate, true, clipping);
     }
 
     fn get_c_impl(&self) -> *const RsvgCNodeImpl {
@@ -293,7 +302,7 @@ impl NodeTrait for NodeLine {
 }
 
 // ************ NodeRect ************
-struct NodeRect {
+pub struct NodeRect {
     // x, y, width, height
     x: Cell<RsvgLength>,
     y: Cell<RsvgLength>,
@@ -317,6 +326,17 @@ impl NodeRect {
             ry: Cell::new(None),
         }
     }
+
+    /// Returns the position and size of the rectangle, ignoring its
+    /// rounded corners.  This is used for text that flows inside a rectangle.
+    pub fn get_rect(&self, draw_ctx: *const RsvgDrawingCtx) -> cairo::Rectangle {
+        cairo::Rectangle {
+            x: self.x.get().normalize(draw_ctx),
+            y: self.y.get().normalize(draw_ctx),
+            width: self.w.get().normalize(draw_ctx),
+            height: self.h.get().normalize(draw_ctx),
+        }
+    }
 }
 
 impl NodeTrait for NodeRect {
@@ -493,14 +513,7 @@ impl NodeTrait for NodeRect {
             builder.close_path ();
         }
 
-        render_path_builder(
-            &builder,
-            draw_ctx,
-            node.get_state(),
-            dominate,
-            false,
-            clipping,
-        );
+        render_path_builder(&builder, draw_ctx, node, dominate, false, clipping);
     }
 
     fn get_c_impl(&self) -> *const RsvgCNodeImpl {
@@ -13,6 +13,54 @@ pub fn xml_space_normalize(mode: XmlSpace, s: &str) -> String {
     }
 }
 
+/// Normalizes one of several strings of character data that are laid out
+/// together as a single paragraph, like the text of a wrapped `<text>`.
+///
+/// Unlike `xml_space_normalize()`, contiguous spaces in `XmlSpace::Default`
+/// mode are consolidated across the boundaries between the strings, and
+/// leading spaces are only stripped at the start of the paragraph.
+/// `after_space` must be true for the first string of the paragraph; it gets
+/// updated to tell whether the text so far ends in a space that absorbs the
+/// spaces after it.  In that case the caller should strip that space if it
+/// turns out to be at the end of the paragraph.
+pub fn xml_space_normalize_run(mode: XmlSpace, s: &str, after_space: &mut bool) -> String {
+    match mode {
+        XmlSpace::Default => {
+            let mut normalized = String::new();
+
+            for ch in s.chars() {
+                match ch {
+                    '\n' => (),
+
+                    ' ' | '\t' => {
+                        if !*after_space {
+                            normalized.push(' ');
+                            *after_space = true;
+                        }
+                    }
+
+                    c => {
+                        normalized.push(c);
+                        *after_space = false;
+                    }
+                }
+            }
+
+            normalized
+        }
+
+        XmlSpace::Preserve => {
+            let normalized = normalize_preserve(s);
+
+            if !normalized.is_empty() {
+                *after_space = false;
+            }
+
+            normalized
+        }
+    }
+}
+
 // From https://www.w3.org/TR/SVG/text.html#WhiteSpace
 //
 // When xml:space="default", the SVG user agent will do the following
@@ -117,4 +165,35 @@ mod tests {
             "       WS    example      duplicate letters       "
         );
     }
+
+    #[test]
+    fn xml_space_run_consolidates_across_strings() {
+        let mut after_space = true;
+
+        assert_eq!(
+            xml_space_normalize_run(XmlSpace::Default, "\n  Hello ", &mut after_space),
+            "Hello "
+        );
+        assert_eq!(
+            xml_space_normalize_run(XmlSpace::Default, " \tbig", &mut after_space),
+            "big"
+        );
+        assert_eq!(
+            xml_space_normalize_run(XmlSpace::Preserve, "  ", &mut after_space),
+            "  "
+        );
+        assert_eq!(
+            xml_space_normalize_run(XmlSpace::Default, " world\n  ", &mut after_space),
+            " world "
+        );
+        assert!(after_space);
+
+        let mut after_space = true;
+
+        assert_eq!(
+            xml_space_normalize_run(XmlSpace::Default, "   ", &mut after_space),
+            ""
+        );
+        assert!(after_space);
+    }
 }
@@ -6,7 +6,8 @@ use pango;
 use pango_sys;
 
 use attributes::Attribute;
-use color::{Color, ColorSpec};
+use color::{AllowCurrentColor, AllowInherit, Color, ColorSpec};
+use css_animation::{self, Direction, FillMode, IterationCount, TimingFunction};
 use error::*;
 use length::{LengthDir, RsvgLength, StrokeDasharray};
 use node::RsvgNode;
@@ -35,20 +36,37 @@ pub enum RsvgState {}
 pub struct State {
     pub affine: cairo::Matrix,
 
+    pub animation_delay: Option<AnimationDelay>,
+    pub animation_direction: Option<AnimationDirection>,
+    pub animation_duration: Option<AnimationDuration>,
+    pub animation_fill_mode: Option<AnimationFillMode>,
+    pub animation_iteration_count: Option<AnimationIterationCount>,
+    pub animation_name: Option<AnimationName>,
+    pub animation_timing_function: Option<AnimationTimingFunction>,
     pub baseline_shift: Option<BaselineShift>,
     pub clip_rule: Option<ClipRule>,
     pub comp_op: Option<CompOp>,
     pub fill_rule: Option<FillRule>,
     pub font_family: Option<FontFamily>,
+    pub font_feature_settings: Option<FontFeatureSettings>,
+    pub font_kerning: Option<FontKerning>,
     pub font_size: Option<FontSize>,
     pub font_stretch: Option<FontStretch>,
     pub font_style: Option<FontStyle>,
     pub font_variant: Option<FontVariant>,
+    pub font_variant_caps: Option<FontVariantCaps>,
+    pub font_variant_ligatures: Option<FontVariantLigatures>,
+    pub font_variant_numeric: Option<FontVariantNumeric>,
+    pub font_variant_position: Option<FontVariantPosition>,
     pub font_weight: Option<FontWeight>,
     pub display: Option<Display>,
     pub enable_background: Option<EnableBackground>,
+    pub glyph_orientation_vertical: Option<GlyphOrientationVertical>,
+    pub inline_size: Option<InlineSize>,
     pub letter_spacing: Option<LetterSpacing>,
     pub overflow: Option<Overflow>,
+    pub pointer_events: Option<PointerEvents>,
+    pub shape_inside: Option<ShapeInside>,
     pub shape_rendering: Option<ShapeRendering>,
     pub stroke_line_cap: Option<StrokeLinecap>,
     pub stroke_line_join: Option<StrokeLinejoin>,
@@ -56,9 +74,18 @@ pub struct State {
     pub stroke_width: Option<StrokeWidth>,
     pub text_anchor: Option<TextAnchor>,
     pub text_decoration: Option<TextDecoration>,
+    pub text_decoration_color: Option<TextDecorationColor>,
+    pub text_decoration_style: Option<TextDecorationStyle>,
+    pub text_orientation: Option<TextOrientation>,
     pub text_rendering: Option<TextRendering>,
+    pub transition_delay: Option<TransitionDelay>,
+    pub transition_duration: Option<TransitionDuration>,
+    pub transition_property: Option<TransitionProperty>,
+    pub transition_timing_function: Option<TransitionTimingFunction>,
     pub unicode_bidi: Option<UnicodeBidi>,
     pub visibility: Option<Visibility>,
+    pub word_spacing: Option<WordSpacing>,
+    pub writing_mode: Option<WritingMode>,
     pub xml_lang: Option<XmlLang>,
     pub xml_space: Option<XmlSpace>,
 }
@@ -69,20 +96,37 @@ impl State {
             affine: cairo::Matrix::identity(),
 
             // please keep these sorted
+            animation_delay: Default::default(),
+            animation_direction: Default::default(),
+            animation_duration: Default::default(),
+            animation_fill_mode: Default::default(),
+            animation_iteration_count: Default::default(),
+            animation_name: Default::default(),
+            animation_timing_function: Default::default(),
             baseline_shift: Default::default(),
             clip_rule: Default::default(),
             comp_op: Default::default(),
             fill_rule: Default::default(),
             font_family: Default::default(),
+            font_feature_settings: Default::default(),
+            font_kerning: Default::default(),
             font_size: Default::default(),
             font_stretch: Default::default(),
             font_style: Default::default(),
             font_variant: Default::default(),
+            font_variant_caps: Default::default(),
+            font_variant_ligatures: Default::default(),
+            font_variant_numeric: Default::default(),
+            font_variant_position: Default::default(),
             font_weight: Default::default(),
             display: Default::default(),
             enable_background: Default::default(),
+            glyph_orientation_vertical: Default::default(),
+            inline_size: Default::default(),
             letter_spacing: Default::default(),
             overflow: Default::default(),
+            pointer_events: Default::default(),
+            shape_inside: Default::default(),
             shape_rendering: Default::default(),
             stroke_line_cap: Default::default(),
             stroke_line_join: Default::default(),
@@ -90,9 +134,18 @@ impl State {
             stroke_width: Default::default(),
             text_anchor: Default::default(),
             text_decoration: Default::default(),
+            text_decoration_color: Default::default(),
+            text_decoration_style: Default::default(),
+            text_orientation: Default::default(),
             text_rendering: Default::default(),
+            transition_delay: Default::default(),
+            transition_duration: Default::default(),
+            transition_property: Default::default(),
+            transition_timing_function: Default::default(),
             unicode_bidi: Default::default(),
             visibility: Default::default(),
+            word_spacing: Default::default(),
+            writing_mode: Default::default(),
             xml_lang: Default::default(),
             xml_space: Default::default(),
         }
@@ -101,6 +154,58 @@ impl State {
     fn parse_style_pair(&mut self, attr: Attribute, value: &str) -> Result<(), AttributeError> {
         // please keep these sorted
         match attr {
+            Attribute::Animation => {
+                if value.trim() == "inherit" {
+                    self.animation_delay = None;
+                    self.animation_direction = None;
+                    self.animation_duration = None;
+                    self.animation_fill_mode = None;
+                    self.animation_iteration_count = None;
+                    self.animation_name = None;
+                    self.animation_timing_function = None;
+                } else {
+                    let shorthand = css_animation::parse_shorthand(value)?;
+
+                    self.animation_delay = Some(AnimationDelay(shorthand.delays));
+                    self.animation_direction = Some(AnimationDirection(shorthand.directions));
+                    self.animation_duration = Some(AnimationDuration(shorthand.durations));
+                    self.animation_fill_mode = Some(AnimationFillMode(shorthand.fill_modes));
+                    self.animation_iteration_count =
+                        Some(AnimationIterationCount(shorthand.iteration_counts));
+                    self.animation_name = Some(AnimationName(shorthand.names));
+                    self.animation_timing_function =
+                        Some(AnimationTimingFunction(shorthand.timing_functions));
+                }
+            }
+
+            Attribute::AnimationDelay => {
+                self.animation_delay = parse_property(value, ())?;
+            }
+
+            Attribute::AnimationDirection => {
+                self.animation_direction = parse_property(value, ())?;
+            }
+
+            Attribute::AnimationDuration => {
+                self.animation_duration = parse_property(value, ())?;
+            }
+
+            Attribute::AnimationFillMode => {
+                self.animation_fill_mode = parse_property(value, ())?;
+            }
+
+            Attribute::AnimationIterationCount => {
+                self.animation_iteration_count = parse_property(value, ())?;
+            }
+
+            Attribute::AnimationName => {
+                self.animation_name = parse_property(value, ())?;
+            }
+
+            Attribute::AnimationTimingFunction => {
+                self.animation_timing_function = parse_property(value, ())?;
+            }
+
             Attribute::BaselineShift => {
                 self.baseline_shift = parse_property(value, ())?;
             }
@@ -121,6 +226,14 @@ impl State {
                 self.font_family = parse_property(value, ())?;
             }
 
+            Attribute::FontFeatureSettings => {
+                self.font_feature_settings = parse_property(value, ())?;
+            }
+
+            Attribute::FontKerning => {
+                self.font_kerning = parse_property(value, ())?;
+            }
+
             Attribute::FontSize => {
                 self.font_size = parse_property(value, LengthDir::Both)?;
             }
@@ -137,6 +250,22 @@ impl State {
                 self.font_variant = parse_property(value, ())?;
             }
 
+            Attribute::FontVariantCaps => {
+                self.font_variant_caps = parse_property(value, ())?;
+            }
+
+            Attribute::FontVariantLigatures => {
+                self.font_variant_ligatures = parse_property(value, ())?;
+            }
+
+            Attribute::FontVariantNumeric => {
+                self.font_variant_numeric = parse_property(value, ())?;
+            }
+
+            Attribute::FontVariantPosition => {
+                self.font_variant_position = parse_property(value, ())?;
+            }
+
             Attribute::FontWeight => {
                 self.font_weight = parse_property(value, ())?;
             }
@@ -149,6 +278,17 @@ impl State {
                 self.enable_background = parse_property(value, ())?;
             }
 
+            Attribute::GlyphOrientationVertical => {
+                // This is the deprecated SVG 1.1 version of text-orientation,
+                // which can also turn glyphs upside down or counterclockwise.
+                self.glyph_orientation_vertical = parse_property(value, ())?;
+                self.text_orientation = self.glyph_orientation_vertical.map(TextOrientation::from);
+            }
+
+            Attribute::InlineSize => {
+                self.inline_size = parse_property(value, LengthDir::Horizontal)?;
+            }
+
             Attribute::LetterSpacing => {
                 self.letter_spacing = parse_property(value, LengthDir::Horizontal)?;
             }
@@ -157,6 +297,14 @@ impl State {
                 self.overflow = parse_property(value, ())?;
             }
 
+            Attribute::PointerEvents => {
+                self.pointer_events = parse_property(value, ())?;
+            }
+
+            Attribute::ShapeInside => {
+                self.shape_inside = parse_property(value, ())?;
+            }
+
             Attribute::ShapeRendering => {
                 self.shape_rendering = parse_property(value, ())?;
             }
@@ -185,10 +333,57 @@ impl State {
                 self.text_decoration = parse_property(value, ())?;
             }
 
+            Attribute::TextDecorationColor => {
+                self.text_decoration_color = parse_property(value, ())?;
+            }
+
+            Attribute::TextDecorationStyle => {
+                self.text_decoration_style = parse_property(value, ())?;
+            }
+
+            Attribute::TextOrientation => {
+                self.text_orientation = parse_property(value, ())?;
+                self.glyph_orientation_vertical =
+                    self.text_orientation.map(GlyphOrientationVertical::from);
+            }
+
             Attribute::TextRendering => {
                 self.text_rendering = parse_property(value, ())?;
             }
 
+            Attribute::Transition => {
+                if value.trim() == "inherit" {
+                    self.transition_delay = None;
+                    self.transition_duration = None;
+                    self.transition_property = None;
+                    self.transition_timing_function = None;
+                } else {
+                    let shorthand = css_animation::parse_transition_shorthand(value)?;
+
+                    self.transition_delay = Some(TransitionDelay(shorthand.delays));
+                    self.transition_duration = Some(TransitionDuration(shorthand.durations));
+                    self.transition_property = Some(TransitionProperty(shorthand.properties));
+                    self.transition_timing_function =
+                        Some(TransitionTimingFunction(shorthand.timing_functions));
+                }
+            }
+
+            Attribute::TransitionDelay => {
+                self.transition_delay = parse_property(value, ())?;
+            }
+
+            Attribute::TransitionDuration => {
+                self.transition_duration = parse_property(value, ())?;
+            }
+
+            Attribute::TransitionProperty => {
+                self.transition_property = parse_property(value, ())?;
+            }
+
+            Attribute::TransitionTimingFunction => {
+                self.transition_timing_function = parse_property(value, ())?;
+            }
+
             Attribute::UnicodeBidi => {
                 self.unicode_bidi = parse_property(value, ())?;
             }
@@ -197,6 +392,14 @@ impl State {
                 self.visibility = parse_property(value, ())?;
             }
 
+            Attribute::WordSpacing => {
+                self.word_spacing = parse_property(value, LengthDir::Horizontal)?;
+            }
+
+            Attribute::WritingMode => {
+                self.writing_mode = parse_property(value, ())?;
+            }
+
             Attribute::XmlLang => {
                 // xml:lang is not a property; it is a non-presentation attribute and as such
                 // cannot have the "inherit" value.  So, we don't call parse_property() for it,
@@ -251,12 +454,18 @@ extern "C" {
     fn rsvg_state_get_stroke_dasharray(state: *const RsvgState) -> *const StrokeDasharray;
     fn rsvg_state_get_dash_offset(state: *const RsvgState) -> RsvgLength;
     fn rsvg_state_get_current_color(state: *const RsvgState) -> u32;
+    fn rsvg_state_get_flood_color(state: *const RsvgState) -> u32;
+    fn rsvg_state_get_flood_opacity(state: *const RsvgState) -> u8;
     fn rsvg_state_get_stroke(state: *const RsvgState) -> *const PaintServer;
     fn rsvg_state_get_stroke_opacity(state: *const RsvgState) -> u8;
     fn rsvg_state_get_text_dir(state: *const RsvgState) -> pango_sys::PangoDirection;
-    fn rsvg_state_get_text_gravity(state: *const RsvgState) -> pango_sys::PangoGravity;
     fn rsvg_state_get_fill(state: *const RsvgState) -> *const PaintServer;
     fn rsvg_state_get_fill_opacity(state: *const RsvgState) -> u8;
+    fn rsvg_state_get_opacity(state: *const RsvgState) -> u8;
+
+    fn rsvg_state_get_clip_path(state: *const RsvgState) -> *const libc::c_char;
+    fn rsvg_state_get_filter(state: *const RsvgState) -> *const libc::c_char;
+    fn rsvg_state_get_mask(state: *const RsvgState) -> *const libc::c_char;
 
     fn rsvg_state_get_start_marker(state: *const RsvgState) -> *const libc::c_char;
     fn rsvg_state_get_middle_marker(state: *const RsvgState) -> *const libc::c_char;
@@ -389,6 +598,16 @@ pub fn get_current_color(state: *const RsvgState) -> Color {
     Color::from(argb)
 }
 
+pub fn get_flood_color(state: *const RsvgState) -> Color {
+    let argb = unsafe { rsvg_state_get_flood_color(state) };
+
+    Color::from(argb)
+}
+
+pub fn get_flood_opacity(state: *const RsvgState) -> u8 {
+    unsafe { rsvg_state_get_flood_opacity(state) }
+}
+
 pub fn get_stroke<'a>(state: *const RsvgState) -> Option<&'a PaintServer> {
     unsafe {
         let ps = rsvg_state_get_stroke(state);
@@ -406,11 +625,28 @@ pub fn get_stroke_opacity(state: *const RsvgState) -> u8 {
 }
 
 pub fn get_text_dir(state: *const RsvgState) -> pango::Direction {
+    let rstate = get_state_rust(state);
+
+    match rstate.writing_mode {
+        Some(WritingMode::RlTb) | Some(WritingMode::Rl) => pango::Direction::Rtl,
+        _ => unsafe { from_glib(rsvg_state_get_text_dir(state)) },
+    }
+}
+
+/// Returns the value of the `direction` property, unlike `get_text_dir()`,
+/// which also takes the `writing-mode` into account.
+pub fn get_direction(state: *const RsvgState) -> pango::Direction {
     unsafe { from_glib(rsvg_state_get_text_dir(state)) }
 }
 
 pub fn get_text_gravity(state: *const RsvgState) -> pango::Gravity {
-    unsafe { from_glib(rsvg_state_get_text_gravity(state)) }
+    let rstate = get_state_rust(state);
+
+    if rstate.writing_mode.unwrap_or_default().is_vertical() {
+        pango::Gravity::East
+    } else {
+        pango::Gravity::South
+    }
 }
 
 pub fn get_fill<'a>(state: *const RsvgState) -> Option<&'a PaintServer> {
@@ -429,6 +665,43 @@ pub fn get_fill_opacity(state: *const RsvgState) -> u8 {
     unsafe { rsvg_state_get_fill_opacity(state) }
 }
 
+pub fn get_opacity(state: *const RsvgState) -> u8 {
+    unsafe { rsvg_state_get_opacity(state) }
+}
+
+pub fn get_clip_path<'a>(state: *const RsvgState) -> Option<&'a str> {
+    unsafe {
+        let clip_path = rsvg_state_get_clip_path(state);
+        if clip_path.is_null() {
+            None
+        } else {
+            Some(utf8_cstr(clip_path))
+        }
+    }
+}
+
+pub fn get_filter<'a>(state: *const RsvgState) -> Option<&'a str> {
+    unsafe {
+        let filter = rsvg_state_get_filter(state);
+        if filter.is_null() {
+            None
+        } else {
+            Some(utf8_cstr(filter))
+        }
+    }
+}
+
+pub fn get_mask<'a>(state: *const RsvgState) -> Option<&'a str> {
+    unsafe {
+        let mask = rsvg_state_get_mask(state);
+        if mask.is_null() {
+            None
+        } else {
+            Some(utf8_cstr(mask))
+        }
+    }
+}
+
 pub fn get_start_marker<'a>(state: *const RsvgState) -> Option<&'a str> {
     unsafe {
         let marker = rsvg_state_get_start_marker(state);
@@ -484,6 +757,120 @@ pub fn get_state_rust<'a>(state: *const RsvgState) -> &'a mut State {
     unsafe { &mut *rsvg_state_get_state_rust(state) }
 }
 
+make_property!(
+    AnimationDelay,
+    default: vec![0.0],
+    inherits_automatically: false,
+    newtype: Vec<f64>
+);
+
+impl Parse for AnimationDelay {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: Self::Data) -> Result<AnimationDelay, AttributeError> {
+        css_animation::parse_list(s, css_animation::parse_time).map(AnimationDelay)
+    }
+}
+
+make_property!(
+    AnimationDirection,
+    default: vec![Direction::Normal],
+    inherits_automatically: false,
+    newtype: Vec<Direction>
+);
+
+impl Parse for AnimationDirection {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: Self::Data) -> Result<AnimationDirection, AttributeError> {
+        css_animation::parse_list(s, |item| Direction::parse(item, ())).map(AnimationDirection)
+    }
+}
+
+make_property!(
+    AnimationDuration,
+    default: vec![0.0],
+    inherits_automatically: false,
+    newtype: Vec<f64>
+);
+
+impl Parse for AnimationDuration {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: Self::Data) -> Result<AnimationDuration, AttributeError> {
+        css_animation::parse_list(s, css_animation::parse_time).map(AnimationDuration)
+    }
+}
+
+make_property!(
+    AnimationFillMode,
+    default: vec![FillMode::None],
+    inherits_automatically: false,
+    newtype: Vec<FillMode>
+);
+
+impl Parse for AnimationFillMode {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: Self::Data) -> Result<AnimationFillMode, AttributeError> {
+        css_animation::parse_list(s, |item| FillMode::parse(item, ())).map(AnimationFillMode)
+    }
+}
+
+make_property!(
+    AnimationIterationCount,
+    default: vec![IterationCount::Count(1.0)],
+    inherits_automatically: false,
+    newtype: Vec<IterationCount>
+);
+
+impl Parse for AnimationIterationCount {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: Self::Data) -> Result<AnimationIterationCount, AttributeError> {
+        css_animation::parse_list(s, |item| IterationCount::parse(item, ()))
+            .map(AnimationIterationCount)
+    }
+}
+
+make_property!(
+    AnimationName,
+    default: vec!["none".to_string()],
+    inherits_automatically: false,
+    newtype: Vec<String>
+);
+
+impl Parse for AnimationName {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: Self::Data) -> Result<AnimationName, AttributeError> {
+        css_animation::parse_list(s, css_animation::parse_name).map(AnimationName)
+    }
+}
+
+make_property!(
+    AnimationTimingFunction,
+    default: vec![TimingFunction::default()],
+    inherits_automatically: false,
+    newtype: Vec<TimingFunction>
+);
+
+impl Parse for AnimationTimingFunction {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: Self::Data) -> Result<AnimationTimingFunction, AttributeError> {
+        css_animation::parse_list(s, |item| TimingFunction::parse(item, ()))
+            .map(AnimationTimingFunction)
+    }
+}
+
 make_property!(
     BaselineShift,
     default: 0f64,
@@ -569,6 +956,91 @@ make_property!(
     newtype_from_str: String
 );
 
+/// A single OpenType feature from `font-feature-settings`, like `"tnum" 1`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontFeature {
+    pub tag: String,
+    pub value: u32,
+}
+
+make_property!(
+    FontFeatureSettings,
+    default: Vec::new(),
+    inherits_automatically: true,
+    newtype: Vec<FontFeature>
+);
+
+impl Parse for FontFeatureSettings {
+    type Data = ();
+    type Err = AttributeError;
+
+    // normal | <feature-tag-value> [, <feature-tag-value>]*
+    //
+    // where <feature-tag-value> = <string> [ <integer> | on | off ]?
+    fn parse(s: &str, _: Self::Data) -> Result<FontFeatureSettings, AttributeError> {
+        let s = s.trim();
+
+        if s == "normal" {
+            return Ok(FontFeatureSettings(Vec::new()));
+        }
+
+        let mut features = Vec::new();
+
+        for item in s.split(',') {
+            let mut tokens = item.split_whitespace();
+
+            // The C code that splits style attributes strips single quotes, so
+            // we accept unquoted tags as well as double-quoted ones.
+            let tag = match tokens.next() {
+                Some(t) => t.trim_matches('"'),
+                None => {
+                    return Err(AttributeError::from(::parsers::ParseError::new(
+                        "expected feature tag",
+                    )))
+                }
+            };
+
+            if tag.len() != 4 || !tag.chars().all(|c| c >= ' ' && c <= '~') {
+                return Err(AttributeError::from(::parsers::ParseError::new(
+                    "feature tags must have 4 ASCII characters",
+                )));
+            }
+
+            let value = match tokens.next() {
+                None | Some("on") => 1,
+                Some("off") => 0,
+                Some(v) => v.parse::<u32>().map_err(|_| {
+                    AttributeError::from(::parsers::ParseError::new("invalid feature value"))
+                })?,
+            };
+
+            if tokens.next().is_some() {
+                return Err(AttributeError::from(::parsers::ParseError::new(
+                    "expected ',' after feature value",
+                )));
+            }
+
+            features.push(FontFeature {
+                tag: tag.to_string(),
+                value,
+            });
+        }
+
+        Ok(FontFeatureSettings(features))
+    }
+}
+
+make_property!(
+    FontKerning,
+    default: Auto,
+    inherits_automatically: true,
+
+    identifiers:
+    "auto" => Auto,
+    "normal" => Normal,
+    "none" => None,
+);
+
 make_property!(
     FontSize,
     default: RsvgLength::parse("12.0", LengthDir::Both).unwrap(),
@@ -625,6 +1097,155 @@ make_property!(
     "small-caps" => SmallCaps,
 );
 
+make_property!(
+    FontVariantCaps,
+    default: Normal,
+    inherits_automatically: true,
+
+    identifiers:
+    "normal" => Normal,
+    "small-caps" => SmallCaps,
+    "all-small-caps" => AllSmallCaps,
+    "petite-caps" => PetiteCaps,
+    "all-petite-caps" => AllPetiteCaps,
+    "unicase" => Unicase,
+    "titling-caps" => TitlingCaps,
+);
+
+// Each field is None if the value did not mention it, so that the
+// font's defaults are used.
+make_property!(
+    FontVariantLigatures,
+    inherits_automatically: true,
+
+    fields:
+    common: Option<bool>, default: None,
+    discretionary: Option<bool>, default: None,
+    historical: Option<bool>, default: None,
+    contextual: Option<bool>, default: None,
+);
+
+impl Parse for FontVariantLigatures {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: Self::Data) -> Result<FontVariantLigatures, AttributeError> {
+        let mut ligatures = FontVariantLigatures::default();
+
+        match s.trim() {
+            "normal" => return Ok(ligatures),
+
+            "none" => {
+                return Ok(FontVariantLigatures {
+                    common: Some(false),
+                    discretionary: Some(false),
+                    historical: Some(false),
+                    contextual: Some(false),
+                })
+            }
+
+            _ => (),
+        }
+
+        for token in s.split_whitespace() {
+            let (field, value) = match token {
+                "common-ligatures" => (&mut ligatures.common, true),
+                "no-common-ligatures" => (&mut ligatures.common, false),
+                "discretionary-ligatures" => (&mut ligatures.discretionary, true),
+                "no-discretionary-ligatures" => (&mut ligatures.discretionary, false),
+                "historical-ligatures" => (&mut ligatures.historical, true),
+                "no-historical-ligatures" => (&mut ligatures.historical, false),
+                "contextual" => (&mut ligatures.contextual, true),
+                "no-contextual" => (&mut ligatures.contextual, false),
+                _ => {
+                    return Err(AttributeError::from(::parsers::ParseError::new(
+                        "invalid value",
+                    )))
+                }
+            };
+
+            if field.is_some() {
+                return Err(AttributeError::from(::parsers::ParseError::new(
+                    "conflicting values",
+                )));
+            }
+
+            *field = Some(value);
+        }
+
+        Ok(ligatures)
+    }
+}
+
+make_property!(
+    FontVariantNumeric,
+    inherits_automatically: true,
+
+    fields:
+    lining: bool, default: false,
+    oldstyle: bool, default: false,
+    proportional: bool, default: false,
+    tabular: bool, default: false,
+    diagonal_fractions: bool, default: false,
+    stacked_fractions: bool, default: false,
+    ordinal: bool, default: false,
+    slashed_zero: bool, default: false,
+);
+
+impl Parse for FontVariantNumeric {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: Self::Data) -> Result<FontVariantNumeric, AttributeError> {
+        let mut numeric = FontVariantNumeric::default();
+
+        if s.trim() == "normal" {
+            return Ok(numeric);
+        }
+
+        for token in s.split_whitespace() {
+            let (field, exclusive) = match token {
+                "lining-nums" => (&mut numeric.lining, numeric.oldstyle),
+                "oldstyle-nums" => (&mut numeric.oldstyle, numeric.lining),
+                "proportional-nums" => (&mut numeric.proportional, numeric.tabular),
+                "tabular-nums" => (&mut numeric.tabular, numeric.proportional),
+                "diagonal-fractions" => {
+                    (&mut numeric.diagonal_fractions, numeric.stacked_fractions)
+                }
+                "stacked-fractions" => (&mut numeric.stacked_fractions, numeric.diagonal_fractions),
+                "ordinal" => (&mut numeric.ordinal, false),
+                "slashed-zero" => (&mut numeric.slashed_zero, false),
+                _ => {
+                    return Err(AttributeError::from(::parsers::ParseError::new(
+                        "invalid value",
+                    )))
+                }
+            };
+
+            if *field || exclusive {
+                return Err(AttributeError::from(::parsers::ParseError::new(
+                    "conflicting values",
+                )));
+            }
+
+            *field = true;
+        }
+
+        Ok(numeric)
+    }
+}
+
+make_property!(
+    FontVariantPosition,
+    default: Normal,
+    inherits_automatically: true,
+
+    identifiers:
+    "normal" => Normal,
+    "sub" => Sub,
+    "super" => Super,
+);
+
 make_property!(
     FontWeight,
     default: Normal,
@@ -681,6 +1302,55 @@ make_property!(
     "new" => New,
 );
 
+// This is not generated with make_property!() since the angles can be
+// specified with or without units.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GlyphOrientationVertical {
+    Auto,
+    Angle0,
+    Angle90,
+    Angle180,
+    Angle270,
+}
+
+impl_default!(GlyphOrientationVertical, GlyphOrientationVertical::Auto);
+impl_property!(GlyphOrientationVertical, true);
+
+impl Parse for GlyphOrientationVertical {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: Self::Data) -> Result<GlyphOrientationVertical, AttributeError> {
+        match s.trim() {
+            "auto" => Ok(GlyphOrientationVertical::Auto),
+            "0" | "0deg" => Ok(GlyphOrientationVertical::Angle0),
+            "90" | "90deg" => Ok(GlyphOrientationVertical::Angle90),
+            "180" | "180deg" => Ok(GlyphOrientationVertical::Angle180),
+            "270" | "270deg" => Ok(GlyphOrientationVertical::Angle270),
+
+            _ => Err(AttributeError::from(::parsers::ParseError::new(
+                "invalid value",
+            ))),
+        }
+    }
+}
+
+make_property!(
+    InlineSize,
+    default: RsvgLength::default(),
+    inherits_automatically: false,
+    newtype: RsvgLength
+);
+
+impl Parse for InlineSize {
+    type Data = LengthDir;
+    type Err = AttributeError;
+
+    fn parse(s: &str, dir: LengthDir) -> Result<InlineSize, AttributeError> {
+        Ok(InlineSize(RsvgLength::parse(s, dir)?))
+    }
+}
+
 make_property!(
     LetterSpacing,
     default: RsvgLength::default(),
@@ -709,6 +1379,43 @@ make_property!(
     "auto" => Auto,
 );
 
+make_property!(
+    PointerEvents,
+    default: VisiblePainted,
+    inherits_automatically: true,
+
+    identifiers:
+    "bounding-box" => BoundingBox,
+    "visiblePainted" => VisiblePainted,
+    "visibleFill" => VisibleFill,
+    "visibleStroke" => VisibleStroke,
+    "visible" => Visible,
+    "painted" => Painted,
+    "fill" => Fill,
+    "stroke" => Stroke,
+    "all" => All,
+    "none" => None,
+);
+
+make_property!(
+    ShapeInside,
+    default: None,
+    inherits_automatically: false,
+    newtype: Option<String>
+);
+
+impl Parse for ShapeInside {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: Self::Data) -> Result<ShapeInside, AttributeError> {
+        match s.trim() {
+            "none" => Ok(ShapeInside(None)),
+            _ => Ok(ShapeInside(Some(::parsers::url(s)?))),
+        }
+    }
+}
+
 make_property!(
     ShapeRendering,
     default: Auto,
@@ -800,6 +1507,75 @@ impl Parse for TextDecoration {
     }
 }
 
+// If this is not specified, decorations are painted with the fill and
+// stroke of the element that has the text-decoration property.
+make_property!(
+    TextDecorationColor,
+    default: Color::CurrentColor,
+    inherits_automatically: false,
+    newtype: Color
+);
+
+impl Parse for TextDecorationColor {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: Self::Data) -> Result<TextDecorationColor, AttributeError> {
+        Ok(TextDecorationColor(Color::parse(
+            s,
+            (AllowInherit::No, AllowCurrentColor::Yes),
+        )?))
+    }
+}
+
+make_property!(
+    TextDecorationStyle,
+    default: Solid,
+    inherits_automatically: false,
+
+    identifiers:
+    "solid" => Solid,
+    "double" => Double,
+    "dotted" => Dotted,
+    "dashed" => Dashed,
+    "wavy" => Wavy,
+);
+
+make_property!(
+    TextOrientation,
+    default: Mixed,
+    inherits_automatically: true,
+
+    identifiers:
+    "mixed" => Mixed,
+    "upright" => Upright,
+    "sideways" => Sideways,
+);
+
+// Upside-down glyphs are shaped like upright ones, and counterclockwise
+// glyphs like sideways ones; text.rs takes care of the extra rotation.
+impl From<GlyphOrientationVertical> for TextOrientation {
+    fn from(o: GlyphOrientationVertical) -> TextOrientation {
+        match o {
+            GlyphOrientationVertical::Auto => TextOrientation::Mixed,
+            GlyphOrientationVertical::Angle0 => TextOrientation::Upright,
+            GlyphOrientationVertical::Angle90 => TextOrientation::Sideways,
+            GlyphOrientationVertical::Angle180 => TextOrientation::Upright,
+            GlyphOrientationVertical::Angle270 => TextOrientation::Sideways,
+        }
+    }
+}
+
+impl From<TextOrientation> for GlyphOrientationVertical {
+    fn from(o: TextOrientation) -> GlyphOrientationVertical {
+        match o {
+            TextOrientation::Mixed => GlyphOrientationVertical::Auto,
+            TextOrientation::Upright => GlyphOrientationVertical::Angle0,
+            TextOrientation::Sideways => GlyphOrientationVertical::Angle90,
+        }
+    }
+}
+
 make_property!(
     TextRendering,
     default: Auto,
@@ -812,6 +1588,72 @@ make_property!(
     "geometricPrecision" => GeometricPrecision,
 );
 
+make_property!(
+    TransitionDelay,
+    default: vec![0.0],
+    inherits_automatically: false,
+    newtype: Vec<f64>
+);
+
+impl Parse for TransitionDelay {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: Self::Data) -> Result<TransitionDelay, AttributeError> {
+        css_animation::parse_list(s, css_animation::parse_time).map(TransitionDelay)
+    }
+}
+
+make_property!(
+    TransitionDuration,
+    default: vec![0.0],
+    inherits_automatically: false,
+    newtype: Vec<f64>
+);
+
+impl Parse for TransitionDuration {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: Self::Data) -> Result<TransitionDuration, AttributeError> {
+        css_animation::parse_list(s, css_animation::parse_time).map(TransitionDuration)
+    }
+}
+
+make_property!(
+    TransitionProperty,
+    default: vec!["all".to_string()],
+    inherits_automatically: false,
+    newtype: Vec<String>
+);
+
+impl Parse for TransitionProperty {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: Self::Data) -> Result<TransitionProperty, AttributeError> {
+        css_animation::parse_list(s, css_animation::parse_transition_property)
+            .map(TransitionProperty)
+    }
+}
+
+make_property!(
+    TransitionTimingFunction,
+    default: vec![TimingFunction::default()],
+    inherits_automatically: false,
+    newtype: Vec<TimingFunction>
+);
+
+impl Parse for TransitionTimingFunction {
+    type Data = ();
+    type Err = AttributeError;
+
+    fn parse(s: &str, _: Self::Data) -> Result<TransitionTimingFunction, AttributeError> {
+        css_animation::parse_list(s, |item| TimingFunction::parse(item, ()))
+            .map(TransitionTimingFunction)
+    }
+}
+
 make_property!(
     UnicodeBidi,
     default: Normal,
@@ -834,6 +1676,51 @@ make_property!(
     "collapse" => Collapse,
 );
 
+make_property!(
+    WordSpacing,
+    default: RsvgLength::default(),
+    inherits_automatically: true,
+    newtype: RsvgLength
+);
+
+impl Parse for WordSpacing {
+    type Data = LengthDir;
+    type Err = AttributeError;
+
+    fn parse(s: &str, dir: LengthDir) -> Result<WordSpacing, AttributeError> {
+        Ok(WordSpacing(RsvgLength::parse(s, dir)?))
+    }
+}
+
+make_property!(
+    WritingMode,
+    default: HorizontalTb,
+    inherits_automatically: true,
+
+    identifiers:
+    "horizontal-tb" => HorizontalTb,
+    "vertical-rl" => VerticalRl,
+    "vertical-lr" => VerticalLr,
+    "lr-tb" => LrTb,
+    "lr" => Lr,
+    "rl-tb" => RlTb,
+    "rl" => Rl,
+    "tb-rl" => TbRl,
+    "tb" => Tb,
+);
+
+impl WritingMode {
+    pub fn is_vertical(&self) -> bool {
+        match *self {
+            WritingMode::VerticalRl
+            | WritingMode::VerticalLr
+            | WritingMode::TbRl
+            | WritingMode::Tb => true,
+            _ => false,
+        }
+    }
+}
+
 make_property!(
     XmlLang,
     default: "C".to_string(),
@@ -945,14 +1832,41 @@ pub extern "C" fn rsvg_state_rust_inherit_run(
     inherit(inherit_fn, &mut dst.clip_rule, &src.clip_rule);
     inherit(inherit_fn, &mut dst.fill_rule, &src.fill_rule);
     inherit(inherit_fn, &mut dst.font_family, &src.font_family);
+    inherit(
+        inherit_fn,
+        &mut dst.font_feature_settings,
+        &src.font_feature_settings,
+    );
+    inherit(inherit_fn, &mut dst.font_kerning, &src.font_kerning);
     inherit(inherit_fn, &mut dst.font_size, &src.font_size);
     inherit(inherit_fn, &mut dst.font_stretch, &src.font_stretch);
     inherit(inherit_fn, &mut dst.font_style, &src.font_style);
     inherit(inherit_fn, &mut dst.font_variant, &src.font_variant);
+    inherit(
+        inherit_fn,
+        &mut dst.font_variant_caps,
+        &src.font_variant_caps,
+    );
+    inherit(
+        inherit_fn,
+        &mut dst.font_variant_ligatures,
+        &src.font_variant_ligatures,
+    );
+    inherit(
+        inherit_fn,
+        &mut dst.font_variant_numeric,
+        &src.font_variant_numeric,
+    );
+    inherit(
+        inherit_fn,
+        &mut dst.font_variant_position,
+        &src.font_variant_position,
+    );
     inherit(inherit_fn, &mut dst.font_weight, &src.font_weight);
     inherit(inherit_fn, &mut dst.display, &src.display);
     inherit(inherit_fn, &mut dst.letter_spacing, &src.letter_spacing);
     inherit(inherit_fn, &mut dst.overflow, &src.overflow);
+    inherit(inherit_fn, &mut dst.pointer_events, &src.pointer_events);
     inherit(inherit_fn, &mut dst.shape_rendering, &src.shape_rendering);
     inherit(inherit_fn, &mut dst.stroke_line_cap, &src.stroke_line_cap);
     inherit(inherit_fn, &mut dst.stroke_line_join, &src.stroke_line_join);
@@ -964,15 +1878,29 @@ pub extern "C" fn rsvg_state_rust_inherit_run(
     inherit(inherit_fn, &mut dst.stroke_width, &src.stroke_width);
     inherit(inherit_fn, &mut dst.text_anchor, &src.text_anchor);
     inherit(inherit_fn, &mut dst.text_decoration, &src.text_decoration);
+    inherit(inherit_fn, &mut dst.text_orientation, &src.text_orientation);
+    inherit(
+        inherit_fn,
+        &mut dst.glyph_orientation_vertical,
+        &src.glyph_orientation_vertical,
+    );
     inherit(inherit_fn, &mut dst.text_rendering, &src.text_rendering);
     inherit(inherit_fn, &mut dst.unicode_bidi, &src.unicode_bidi);
     inherit(inherit_fn, &mut dst.visibility, &src.visibility);
+    inherit(inherit_fn, &mut dst.word_spacing, &src.word_spacing);
+    inherit(inherit_fn, &mut dst.writing_mode, &src.writing_mode);
     inherit(inherit_fn, &mut dst.xml_lang, &src.xml_lang);
     inherit(inherit_fn, &mut dst.xml_space, &src.xml_space);
 
     if from_glib(inheritunheritables) {
         dst.comp_op.clone_from(&src.comp_op);
         dst.enable_background.clone_from(&src.enable_background);
+        dst.inline_size.clone_from(&src.inline_size);
+        dst.shape_inside.clone_from(&src.shape_inside);
+        dst.text_decoration_color
+            .clone_from(&src.text_decoration_color);
+        dst.text_decoration_style
+            .clone_from(&src.text_decoration_style);
     }
 }
 
@@ -1024,3 +1952,207 @@ pub extern "C" fn rsvg_state_rust_get_enable_background(state: *const State) ->
         EnableBackgroundC::from(state.enable_background.unwrap_or_default())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_font_feature_settings() {
+        assert_eq!(
+            FontFeatureSettings::parse("normal", ()),
+            Ok(FontFeatureSettings(Vec::new()))
+        );
+
+        assert_eq!(
+            FontFeatureSettings::parse("\"tnum\", \"liga\" off, \"salt\" 3", ()),
+            Ok(FontFeatureSettings(vec![
+                FontFeature {
+                    tag: "tnum".to_string(),
+                    value: 1,
+                },
+                FontFeature {
+                    tag: "liga".to_string(),
+                    value: 0,
+                },
+                FontFeature {
+                    tag: "salt".to_string(),
+                    value: 3,
+                },
+            ]))
+        );
+    }
+
+    #[test]
+    fn invalid_font_feature_settings_yield_error() {
+        assert!(FontFeatureSettings::parse("", ()).is_err());
+        assert!(FontFeatureSettings::parse("\"toolong\"", ()).is_err());
+        assert!(FontFeatureSettings::parse("\"tnum\" -1", ()).is_err());
+        assert!(FontFeatureSettings::parse("\"tnum\" 1 2", ()).is_err());
+    }
+
+    #[test]
+    fn parses_writing_mode() {
+        assert_eq!(
+            WritingMode::parse("vertical-rl", ()),
+            Ok(WritingMode::VerticalRl)
+        );
+        assert!(WritingMode::parse("tb", ()).unwrap().is_vertical());
+        assert!(!WritingMode::parse("rl-tb", ()).unwrap().is_vertical());
+        assert!(WritingMode::parse("sideways-rl", ()).is_err());
+    }
+
+    #[test]
+    fn glyph_orientation_vertical_maps_to_text_orientation() {
+        let mut state = State::new();
+
+        state
+            .parse_style_pair(Attribute::GlyphOrientationVertical, "0deg")
+            .unwrap();
+        assert_eq!(state.text_orientation, Some(TextOrientation::Upright));
+
+        state
+            .parse_style_pair(Attribute::GlyphOrientationVertical, "90")
+            .unwrap();
+        assert_eq!(state.text_orientation, Some(TextOrientation::Sideways));
+
+        state
+            .parse_style_pair(Attribute::GlyphOrientationVertical, "180deg")
+            .unwrap();
+        assert_eq!(state.text_orientation, Some(TextOrientation::Upright));
+        assert_eq!(
+            state.glyph_orientation_vertical,
+            Some(GlyphOrientationVertical::Angle180)
+        );
+
+        state
+            .parse_style_pair(Attribute::GlyphOrientationVertical, "270")
+            .unwrap();
+        assert_eq!(state.text_orientation, Some(TextOrientation::Sideways));
+        assert_eq!(
+            state.glyph_orientation_vertical,
+            Some(GlyphOrientationVertical::Angle270)
+        );
+
+        state
+            .parse_style_pair(Attribute::TextOrientation, "mixed")
+            .unwrap();
+        assert_eq!(state.text_orientation, Some(TextOrientation::Mixed));
+        assert_eq!(
+            state.glyph_orientation_vertical,
+            Some(GlyphOrientationVertical::Auto)
+        );
+
+        assert!(GlyphOrientationVertical::parse("45deg", ()).is_err());
+    }
+
+    #[test]
+    fn parses_font_variant_ligatures() {
+        assert_eq!(
+            FontVariantLigatures::parse("none", ()),
+            Ok(FontVariantLigatures {
+                common: Some(false),
+                discretionary: Some(false),
+                historical: Some(false),
+                contextual: Some(false),
+            })
+        );
+
+        assert_eq!(
+            FontVariantLigatures::parse("no-common-ligatures discretionary-ligatures", ()),
+            Ok(FontVariantLigatures {
+                common: Some(false),
+                discretionary: Some(true),
+                historical: None,
+                contextual: None,
+            })
+        );
+
+        assert!(FontVariantLigatures::parse("contextual no-contextual", ()).is_err());
+    }
+
+    #[test]
+    fn parses_font_variant_numeric() {
+        assert_eq!(
+            FontVariantNumeric::parse("tabular-nums slashed-zero", ()),
+            Ok(FontVariantNumeric {
+                tabular: true,
+                slashed_zero: true,
+                ..Default::default()
+            })
+        );
+
+        assert!(FontVariantNumeric::parse("tabular-nums proportional-nums", ()).is_err());
+        assert!(FontVariantNumeric::parse("ordinal ordinal", ()).is_err());
+        assert!(FontVariantNumeric::parse("foo", ()).is_err());
+    }
+    #[test]
+    fn parses_shape_inside() {
+        assert_eq!(ShapeInside::parse("none", ()), Ok(ShapeInside(None)));
+
+        assert_eq!(
+            ShapeInside::parse("url(#region)", ()),
+            Ok(ShapeInside(Some("#region".to_string())))
+        );
+
+        assert!(ShapeInside::parse("circle(50%)", ()).is_err());
+    }
+
+    #[test]
+    fn parses_animation_lists() {
+        assert_eq!(
+            AnimationDuration::parse("1s, 250ms", ()),
+            Ok(AnimationDuration(vec![1.0, 0.25]))
+        );
+
+        assert_eq!(
+            AnimationName::parse("spin, 'fade'", ()),
+            Ok(AnimationName(vec!["spin".to_string(), "fade".to_string()]))
+        );
+
+        assert_eq!(
+            AnimationTimingFunction::parse("steps(3, start), linear", ()),
+            Ok(AnimationTimingFunction(vec![
+                TimingFunction::Steps(3, ::css_animation::StepPosition::Start),
+                TimingFunction::CubicBezier(0.0, 0.0, 1.0, 1.0),
+            ]))
+        );
+
+        assert!(AnimationDuration::parse("1", ()).is_err());
+        assert!(AnimationIterationCount::parse("-1", ()).is_err());
+    }
+
+    #[test]
+    fn parses_transition_lists() {
+        assert_eq!(
+            TransitionProperty::parse("fill, Opacity", ()),
+            Ok(TransitionProperty(vec![
+                "fill".to_string(),
+                "opacity".to_string(),
+            ]))
+        );
+
+        assert_eq!(
+            TransitionDelay::parse("0s, -500ms", ()),
+            Ok(TransitionDelay(vec![0.0, -0.5]))
+        );
+
+        assert!(TransitionProperty::parse("fill stroke", ()).is_err());
+        assert!(TransitionDuration::parse("fast", ()).is_err());
+    }
+
+    #[test]
+    fn parses_pointer_events() {
+        assert_eq!(
+            PointerEvents::parse("visibleStroke", ()),
+            Ok(PointerEvents::VisibleStroke)
+        );
+        assert_eq!(
+            PointerEvents::parse("bounding-box", ()),
+            Ok(PointerEvents::BoundingBox)
+        );
+        assert_eq!(PointerEvents::parse("none", ()), Ok(PointerEvents::None));
+
+        assert!(PointerEvents::parse("visiblestroke", ()).is_err());
+    }
+}
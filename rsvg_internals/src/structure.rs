@@ -8,12 +8,14 @@ use cairo::MatrixTrait;
 
 use aspect_ratio::*;
 use attributes::Attribute;
+use diagnostics::{self, DiagnosticSeverity};
 use drawing_ctx;
 use drawing_ctx::RsvgDrawingCtx;
 use float_eq_cairo::ApproxEqCairo;
 use handle::RsvgHandle;
 use length::*;
 use node::*;
+use normalize;
 use parsers::{parse, Parse};
 use property_bag::{OwnedPropertyBag, PropertyBag};
 use state;
@@ -85,6 +87,10 @@ impl NodeTrait for NodeSwitch {
 
         drawing_ctx::push_discrete_layer(draw_ctx, clipping);
 
+        if !clipping {
+            normalize::begin_group(draw_ctx, node);
+        }
+
         if let Some(child) = node.children()
             .find(|c| state::get_cond_true(c.get_state()))
         {
@@ -95,6 +101,10 @@ impl NodeTrait for NodeSwitch {
             rsvg_node_unref(boxed_child);
         }
 
+        if !clipping {
+            normalize::end_group(draw_ctx);
+        }
+
         drawing_ctx::pop_discrete_layer(draw_ctx, clipping);
     }
 
@@ -202,6 +212,7 @@ impl NodeTrait for NodeSvg {
             self.vbox.get(),
             self.preserve_aspect_ratio.get(),
             affine,
+            node,
             draw_ctx,
             clipping,
             || {
@@ -279,11 +290,29 @@ impl NodeTrait for NodeUse {
         {
             acquired.get()
         } else {
+            if !clipping {
+                diagnostics::check_reference(
+                    draw_ctx,
+                    node,
+                    "xlink:href",
+                    link.as_ref().unwrap(),
+                    &[],
+                );
+            }
             return;
         };
 
         if Node::is_ancestor(node.clone(), child.clone()) {
             // or, if we're <use>'ing ourselves
+            if !clipping {
+                diagnostics::add_diagnostic(
+                    draw_ctx,
+                    node,
+                    DiagnosticSeverity::Warning,
+                    "xlink:href",
+                    "circular reference to an ancestor of the element",
+                );
+            }
             return;
         }
 
@@ -322,10 +351,18 @@ impl NodeTrait for NodeUse {
 
             drawing_ctx::push_discrete_layer(draw_ctx, clipping);
 
+            if !clipping {
+                normalize::begin_group(draw_ctx, node);
+            }
+
             let boxed_child = box_node(child.clone());
             drawing_ctx::draw_node_from_stack(draw_ctx, boxed_child, 1, clipping);
             rsvg_node_unref(boxed_child);
 
+            if !clipping {
+                normalize::end_group(draw_ctx);
+            }
+
             drawing_ctx::pop_discrete_layer(draw_ctx, clipping);
         } else {
             child.with_impl(|symbol: &NodeSymbol| {
@@ -342,6 +379,7 @@ impl NodeTrait for NodeUse {
                     symbol.vbox.get(),
                     symbol.preserve_aspect_ratio.get(),
                     rstate.affine,
+                    node,
                     draw_ctx,
                     clipping,
                     || {
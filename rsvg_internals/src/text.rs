@@ -1,45 +1,76 @@
+use cairo;
+use glib::translate::*;
+use glib_sys;
 use libc;
 use pango::{self, ContextExt, LayoutExt};
+use pango_sys;
+use pangocairo;
 use std;
 use std::cell::{Cell, RefCell};
+use std::ptr;
+use std::rc::Rc;
 use std::str;
 
 use attributes::Attribute;
-use draw::draw_pango_layout;
+use draw::{draw_pango_layout, draw_path_builder, draw_text_decoration};
 use drawing_ctx::{self, RsvgDrawingCtx};
+use font::{self, SvgFont};
 use handle::RsvgHandle;
 use length::*;
 use node::{
     boxed_node_new,
     rsvg_node_get_state,
+    Node,
     NodeResult,
     NodeTrait,
     NodeType,
     RsvgCNodeImpl,
     RsvgNode,
 };
+use normalize;
 use parsers::parse;
+use path_builder::PathBuilder;
 use property_bag::PropertyBag;
-use space::xml_space_normalize;
+use shapes::NodeRect;
+use space::{xml_space_normalize, xml_space_normalize_run};
 use state::{
     self,
     FontFamily,
+    FontFeatureSettings,
+    FontKerning,
     FontStretch,
     FontStyle,
     FontVariant,
+    FontVariantCaps,
+    FontVariantLigatures,
+    FontVariantNumeric,
+    FontVariantPosition,
     FontWeight,
+    GlyphOrientationVertical,
+    InlineSize,
     LetterSpacing,
     RsvgState,
+    ShapeInside,
     TextAnchor,
+    TextDecorationStyle,
+    TextOrientation,
     UnicodeBidi,
+    WordSpacing,
+    WritingMode,
     XmlLang,
 };
+use text_outline;
 
 extern "C" {
     fn _rsvg_css_normalize_font_size(
         state: *mut RsvgState,
         draw_ctx: *const RsvgDrawingCtx,
     ) -> libc::c_double;
+
+    // Not bound in the pango crate yet; available since Pango 1.38
+    fn pango_attr_font_features_new(
+        features: *const libc::c_char,
+    ) -> *mut pango_sys::PangoAttribute;
 }
 
 /// In SVG text elements, we use `NodeChars` to store character data.  For example,
@@ -80,32 +111,44 @@ impl NodeChars {
         self.string.borrow_mut().push_str(s);
     }
 
-    fn measure(&self, draw_ctx: *const RsvgDrawingCtx, length: &mut f64) {
-        let s = self.string.borrow();
+    fn set_string(&self, s: &str) {
+        *self.string.borrow_mut() = s.to_string();
+    }
+
+    fn measure(&self, node: &RsvgNode, draw_ctx: *const RsvgDrawingCtx, length: &mut f64) {
+        let state = drawing_ctx::get_current_state(draw_ctx);
+        let s = xml_space_normalize(
+            state::get_state_rust(state).xml_space.unwrap_or_default(),
+            &self.string.borrow(),
+        );
+
+        if let Some(font) = get_svg_font(node, draw_ctx) {
+            let (_, advance) = layout_svg_font_text(&font, draw_ctx, &s, 0.0, 0.0);
+            *length = advance;
+            return;
+        }
+
         let layout = create_pango_layout(draw_ctx, &s);
         let (width, _) = layout.get_size();
 
         *length = f64::from(width) / f64::from(pango::SCALE);
     }
 
-    fn render(&self, draw_ctx: *mut RsvgDrawingCtx, x: &mut f64, y: &mut f64, clipping: bool) {
-        let s = self.string.borrow();
-        let layout = create_pango_layout(draw_ctx, &s);
-        let (width, _) = layout.get_size();
-
+    fn render(
+        &self,
+        node: &RsvgNode,
+        draw_ctx: *mut RsvgDrawingCtx,
+        x: &mut f64,
+        y: &mut f64,
+        clipping: bool,
+    ) {
         let state = drawing_ctx::get_current_state(draw_ctx);
+        let s = xml_space_normalize(
+            state::get_state_rust(state).xml_space.unwrap_or_default(),
+            &self.string.borrow(),
+        );
 
-        let baseline = f64::from(layout.get_baseline()) / f64::from(pango::SCALE);
-        let offset = baseline + drawing_ctx::get_accumulated_baseline_shift(draw_ctx);
-
-        let gravity = state::get_text_gravity(state);
-        if gravity_is_vertical(gravity) {
-            draw_pango_layout(draw_ctx, &layout, *x + offset, *y, clipping);
-            *y += f64::from(width) / f64::from(pango::SCALE);
-        } else {
-            draw_pango_layout(draw_ctx, &layout, *x, *y - offset, clipping);
-            *x += f64::from(width) / f64::from(pango::SCALE);
-        }
+        render_text(node, draw_ctx, &s, x, y, clipping);
     }
 }
 
@@ -123,6 +166,97 @@ impl NodeTrait for NodeChars {
     }
 }
 
+/// Draws `text`, already normalized for `xml:space`, with the current state
+/// and the style of the `NodeChars` it comes from, starting at `(x, y)` on
+/// the baseline.  Advances `x` (or `y` for vertical text) past the text.
+fn render_text(
+    node: &RsvgNode,
+    draw_ctx: *mut RsvgDrawingCtx,
+    text: &str,
+    x: &mut f64,
+    y: &mut f64,
+    clipping: bool,
+) {
+    if let Some(font) = get_svg_font(node, draw_ctx) {
+        let (builder, advance) = layout_svg_font_text(&font, draw_ctx, text, *x, *y);
+        if !clipping {
+            let state = drawing_ctx::get_current_state(draw_ctx);
+            text_outline::add_path_outline(draw_ctx, state, &builder);
+        }
+        draw_path_builder(draw_ctx, &builder, None, clipping);
+        *x += advance;
+        return;
+    }
+
+    let layout = create_pango_layout(draw_ctx, text);
+    let (width, _) = layout.get_size();
+
+    let state = drawing_ctx::get_current_state(draw_ctx);
+
+    let baseline = f64::from(layout.get_baseline()) / f64::from(pango::SCALE);
+    let offset = baseline + drawing_ctx::get_accumulated_baseline_shift(draw_ctx);
+
+    let gravity = state::get_text_gravity(state);
+
+    let (layout_x, layout_y) = if gravity_is_vertical(gravity) {
+        (*x + offset, *y)
+    } else {
+        (*x, *y - offset)
+    };
+
+    // Decorations are painted with the fill and stroke of the element
+    // that specified text-decoration, so we need its computed state.
+    let decorating_node = get_decorating_ancestor(node);
+    let paint_state = match decorating_node {
+        Some(ref n) => {
+            let s = state::new();
+            state::reconstruct(s, n);
+            s
+        }
+
+        None => state,
+    };
+
+    let (over, through) = build_text_decorations(&layout, paint_state);
+
+    // Underlines and overlines go below the text; line-through goes above it.
+    if !over.get_path_commands().is_empty() {
+        draw_text_decoration(
+            draw_ctx,
+            &over,
+            layout_x,
+            layout_y,
+            gravity,
+            paint_state,
+            clipping,
+        );
+    }
+
+    draw_pango_layout(draw_ctx, &layout, layout_x, layout_y, gravity, clipping);
+
+    if !through.get_path_commands().is_empty() {
+        draw_text_decoration(
+            draw_ctx,
+            &through,
+            layout_x,
+            layout_y,
+            gravity,
+            paint_state,
+            clipping,
+        );
+    }
+
+    if decorating_node.is_some() {
+        state::free(paint_state);
+    }
+
+    if gravity_is_vertical(gravity) {
+        *y += f64::from(width) / f64::from(pango::SCALE);
+    } else {
+        *x += f64::from(width) / f64::from(pango::SCALE);
+    }
+}
+
 struct NodeText {
     x: Cell<RsvgLength>,
     y: Cell<RsvgLength>,
@@ -139,6 +273,68 @@ impl NodeText {
             dy: Cell::new(RsvgLength::default()),
         }
     }
+
+    /// Lays out the text as a wrapped paragraph if it has a `shape-inside`
+    /// that refers to a rectangle, or a positive `inline-size`.  Returns false
+    /// if the text does not wrap and must be laid out normally.
+    fn draw_wrapped(&self, node: &RsvgNode, draw_ctx: *mut RsvgDrawingCtx, clipping: bool) -> bool {
+        let state = drawing_ctx::get_current_state(draw_ctx);
+        let vertical = gravity_is_vertical(state::get_text_gravity(state));
+
+        // These properties are not inherited, so look at the element's own state
+        let rstate = state::get_state_rust(node.get_state());
+
+        let shape_inside = rstate
+            .shape_inside
+            .as_ref()
+            .and_then(|s| get_shape_inside_rect(s, draw_ctx));
+
+        let (x, y, paragraph) = if let Some(region) = shape_inside {
+            if vertical {
+                let paragraph = Paragraph::new(node, draw_ctx, region.height);
+
+                if paragraph.left_to_right {
+                    (region.x, region.y, paragraph)
+                } else {
+                    (region.x + region.width, region.y, paragraph)
+                }
+            } else {
+                let paragraph = Paragraph::new(node, draw_ctx, region.width);
+                (region.x, region.y, paragraph)
+            }
+        } else {
+            let inline_size = rstate
+                .inline_size
+                .as_ref()
+                .map_or(0.0, |&InlineSize(ref l)| l.normalize(draw_ctx));
+
+            if inline_size <= 0.0 {
+                return false;
+            }
+
+            let paragraph = Paragraph::new(node, draw_ctx, inline_size);
+            let anchor = state::get_state_rust(state).text_anchor.unwrap_or_default();
+            let inline_offset =
+                inline_anchor_offset(anchor, state::get_text_dir(state), inline_size);
+            let baseline_offset = paragraph.get_baseline_offset(&paragraph.get_lines()[0]);
+
+            // The text's position is the anchor point on the first line's baseline
+            let x = self.x.get().normalize(draw_ctx) + self.dx.get().normalize(draw_ctx);
+            let y = self.y.get().normalize(draw_ctx) + self.dy.get().normalize(draw_ctx);
+
+            if vertical {
+                (x - baseline_offset, y - inline_offset, paragraph)
+            } else {
+                (x - inline_offset, y - baseline_offset, paragraph)
+            }
+        };
+
+        drawing_ctx::push_discrete_layer(draw_ctx, clipping);
+        paragraph.draw(node, draw_ctx, x, y, clipping);
+        drawing_ctx::pop_discrete_layer(draw_ctx, clipping);
+
+        true
+    }
 }
 
 impl NodeTrait for NodeText {
@@ -160,6 +356,14 @@ impl NodeTrait for NodeText {
     fn draw(&self, node: &RsvgNode, draw_ctx: *mut RsvgDrawingCtx, dominate: i32, clipping: bool) {
         drawing_ctx::state_reinherit_top(draw_ctx, node.get_state(), dominate);
 
+        if !clipping {
+            normalize::add_text(draw_ctx, node);
+        }
+
+        if self.draw_wrapped(node, draw_ctx, clipping) {
+            return;
+        }
+
         let mut x = self.x.get().normalize(draw_ctx);
         let mut y = self.y.get().normalize(draw_ctx);
         let mut dx = self.dx.get().normalize(draw_ctx);
@@ -381,6 +585,448 @@ impl NodeTrait for NodeTSpan {
     }
 }
 
+/// Inkscape's `<flowRoot>` element, from the SVG 1.2 drafts.  It contains a
+/// `<flowRegion>` with the shape inside which the text flows, and a number
+/// of `<flowPara>` elements with the text of each paragraph.
+///
+/// We only support rectangular regions.  Each paragraph is wrapped with
+/// the style of its `<flowPara>`, and the text of the `<flowSpan>` elements
+/// inside it is drawn with their own style.
+struct NodeFlowRoot;
+
+impl NodeFlowRoot {
+    fn new() -> NodeFlowRoot {
+        NodeFlowRoot
+    }
+}
+
+impl NodeTrait for NodeFlowRoot {
+    fn set_atts(&self, _: &RsvgNode, _: *const RsvgHandle, _: &PropertyBag) -> NodeResult {
+        Ok(())
+    }
+
+    fn draw(&self, node: &RsvgNode, draw_ctx: *mut RsvgDrawingCtx, dominate: i32, clipping: bool) {
+        drawing_ctx::state_reinherit_top(draw_ctx, node.get_state(), dominate);
+
+        let region = match get_flow_region(node, draw_ctx) {
+            Some(region) => region,
+            None => return,
+        };
+
+        if !clipping {
+            normalize::add_text(draw_ctx, node);
+        }
+
+        let state = drawing_ctx::get_current_state(draw_ctx);
+        let vertical = gravity_is_vertical(state::get_text_gravity(state));
+        let left_to_right = lines_progress_left_to_right(state);
+
+        let (inline_size, block_size) = if vertical {
+            (region.height, region.width)
+        } else {
+            (region.width, region.height)
+        };
+
+        drawing_ctx::push_discrete_layer(draw_ctx, clipping);
+
+        // Distance from the edge of the region where the paragraphs start
+        let mut block = 0.0;
+
+        for para in node
+            .children()
+            .filter(|c| c.get_type() == NodeType::FlowPara)
+        {
+            // Like Inkscape, don't show paragraphs that start outside the region
+            if block >= block_size {
+                break;
+            }
+
+            drawing_ctx::state_push(draw_ctx);
+            drawing_ctx::state_reinherit_top(draw_ctx, para.get_state(), 0);
+
+            let paragraph = Paragraph::new(&para, draw_ctx, inline_size);
+
+            let (x, y) = if !vertical {
+                (region.x, region.y + block)
+            } else if left_to_right {
+                (region.x + block, region.y)
+            } else {
+                (region.x + region.width - block, region.y)
+            };
+
+            paragraph.draw(&para, draw_ctx, x, y, clipping);
+
+            let (_, height) = paragraph.layout.get_size();
+            block += f64::from(height) / f64::from(pango::SCALE);
+
+            drawing_ctx::state_pop(draw_ctx);
+        }
+
+        drawing_ctx::pop_discrete_layer(draw_ctx, clipping);
+    }
+
+    fn get_c_impl(&self) -> *const RsvgCNodeImpl {
+        unreachable!();
+    }
+}
+
+/// The `<flowRegion>`, `<flowPara>` and `<flowSpan>` children of a
+/// `<flowRoot>`.  They don't draw anything by themselves; `NodeFlowRoot`
+/// takes care of laying them out.
+struct NodeFlowContent;
+
+impl NodeFlowContent {
+    fn new() -> NodeFlowContent {
+        NodeFlowContent
+    }
+}
+
+impl NodeTrait for NodeFlowContent {
+    fn set_atts(&self, _: &RsvgNode, _: *const RsvgHandle, _: &PropertyBag) -> NodeResult {
+        Ok(())
+    }
+
+    fn draw(&self, _: &RsvgNode, _: *mut RsvgDrawingCtx, _: i32, _: bool) {
+        // nothing
+    }
+
+    fn get_c_impl(&self) -> *const RsvgCNodeImpl {
+        unreachable!();
+    }
+}
+
+fn get_rect_bounds(node: &RsvgNode, draw_ctx: *const RsvgDrawingCtx) -> Option<cairo::Rectangle> {
+    let mut bounds = None;
+
+    node.with_impl(|rect: &NodeRect| bounds = Some(rect.get_rect(draw_ctx)));
+
+    bounds
+}
+
+/// Finds the first `<rect>` inside the `<flowRegion>` of a `<flowRoot>`.
+fn get_flow_region(node: &RsvgNode, draw_ctx: *const RsvgDrawingCtx) -> Option<cairo::Rectangle> {
+    for region in node
+        .children()
+        .filter(|c| c.get_type() == NodeType::FlowRegion)
+    {
+        for child in region.children() {
+            if child.get_type() == NodeType::Rect {
+                return get_rect_bounds(&child, draw_ctx);
+            }
+        }
+    }
+
+    None
+}
+
+/// Resolves the `shape-inside` of a text element to the rectangle in which
+/// its text flows.  Only references to `<rect>` elements are supported.
+fn get_shape_inside_rect(
+    shape_inside: &ShapeInside,
+    draw_ctx: *const RsvgDrawingCtx,
+) -> Option<cairo::Rectangle> {
+    let url = shape_inside.0.as_ref()?;
+    let acquired = drawing_ctx::get_acquired_node_of_type(draw_ctx, url, NodeType::Rect)?;
+
+    get_rect_bounds(&acquired.get(), draw_ctx)
+}
+
+/// Returns the character data of a `NodeChars`, verbatim.
+pub fn get_chars_string(node: &RsvgNode) -> String {
+    let mut s = String::new();
+    node.with_impl(|chars: &NodeChars| s.push_str(&chars.string.borrow()));
+    s
+}
+
+/// A piece of the character data of a wrapped paragraph, normalized for
+/// `xml:space`, and the byte index in the paragraph's text where it starts.
+struct ParagraphChunk {
+    start: usize,
+    text: String,
+}
+
+/// A line of a paragraph's layout, with the byte range of its text.  The
+/// top, bottom and baseline of the line are measured from the top of the
+/// layout.
+struct ParagraphLine {
+    index: i32,
+    start: usize,
+    end: usize,
+    top: f64,
+    bottom: f64,
+    baseline: f64,
+}
+
+/// The text in an element and its descendants, laid out as a single
+/// paragraph that wraps at a given width, or height for vertical text.
+///
+/// The paragraph is laid out as a whole, so that lines break in the same
+/// places regardless of how its text is split among `<tspan>` elements and
+/// the like.  When drawing, however, each chunk of character data is drawn
+/// with `render_text()`, just like unwrapped text, so that it gets the fill,
+/// stroke, decorations and fonts of its own element.
+///
+/// Vertical text is laid out like horizontal text and rotated when drawn,
+/// so the lines in the layout become columns.  These go from right to left,
+/// except for `writing-mode: vertical-lr`.
+struct Paragraph {
+    layout: pango::Layout,
+    chunks: Vec<ParagraphChunk>,
+    vertical: bool,
+    left_to_right: bool,
+}
+
+impl Paragraph {
+    /// Lays out the text in `node` and its descendants so that it wraps at
+    /// `width`, using the current state for the paragraph as a whole.
+    fn new(node: &RsvgNode, draw_ctx: *mut RsvgDrawingCtx, width: f64) -> Paragraph {
+        let state = drawing_ctx::get_current_state(draw_ctx);
+        let vertical = gravity_is_vertical(state::get_text_gravity(state));
+        let left_to_right = lines_progress_left_to_right(state);
+
+        let pango_context = create_pango_context(draw_ctx);
+        let attr_list = pango::AttrList::new();
+
+        let mut text = String::new();
+        let mut chunks = Vec::new();
+        let mut after_space = true;
+
+        for_each_chars(node, draw_ctx, &mut |chars| {
+            let state = drawing_ctx::get_current_state(draw_ctx);
+            let chunk = xml_space_normalize_run(
+                state::get_state_rust(state).xml_space.unwrap_or_default(),
+                &get_chars_string(chars),
+                &mut after_space,
+            );
+
+            let start = text.len();
+            text.push_str(&chunk);
+
+            let font_desc = get_font_description(draw_ctx, &pango_context);
+            let mut attr: pango::Attribute = unsafe {
+                from_glib_full(pango_sys::pango_attr_font_desc_new(
+                    font_desc.to_glib_none().0,
+                ))
+            };
+            attr.set_start_index(start as u32);
+            attr.set_end_index(text.len() as u32);
+            attr_list.insert(attr);
+
+            add_text_attributes(draw_ctx, &attr_list, &chunk, start);
+
+            chunks.push(ParagraphChunk { start, text: chunk });
+        });
+
+        // Spaces are consolidated across chunks, so there is at most one
+        // space to strip at the end of the paragraph.
+        if after_space && text.ends_with(' ') {
+            text.pop();
+
+            if let Some(chunk) = chunks.iter_mut().rev().find(|c| !c.text.is_empty()) {
+                chunk.text.pop();
+            }
+        }
+
+        let layout = create_pango_layout(draw_ctx, "");
+        layout.set_attributes(&attr_list);
+        layout.set_text(&text);
+        layout.set_width(to_pango_units(width));
+        layout.set_wrap(pango::WrapMode::WordChar);
+
+        // Pango flips the alignment of right-to-left paragraphs, so this is
+        // relative to the start of the text just like text-anchor.
+        layout.set_alignment(
+            match state::get_state_rust(state).text_anchor.unwrap_or_default() {
+                TextAnchor::Start => pango::Alignment::Left,
+                TextAnchor::Middle => pango::Alignment::Center,
+                TextAnchor::End => pango::Alignment::Right,
+            },
+        );
+
+        Paragraph {
+            layout,
+            chunks,
+            vertical,
+            left_to_right,
+        }
+    }
+
+    fn get_lines(&self) -> Vec<ParagraphLine> {
+        let pango_scale = f64::from(pango::SCALE);
+        let mut lines = Vec::new();
+
+        unsafe {
+            let iter = pango_sys::pango_layout_get_iter(self.layout.to_glib_none().0);
+
+            loop {
+                let line = pango_sys::pango_layout_iter_get_line_readonly(iter);
+                let start = (*line).start_index as usize;
+
+                let mut top = 0;
+                let mut bottom = 0;
+                pango_sys::pango_layout_iter_get_line_yrange(iter, &mut top, &mut bottom);
+
+                lines.push(ParagraphLine {
+                    index: lines.len() as i32,
+                    start,
+                    end: start + (*line).length as usize,
+                    top: f64::from(top) / pango_scale,
+                    bottom: f64::from(bottom) / pango_scale,
+                    baseline: f64::from(pango_sys::pango_layout_iter_get_baseline(iter))
+                        / pango_scale,
+                });
+
+                let more: bool = from_glib(pango_sys::pango_layout_iter_next_line(iter));
+                if !more {
+                    break;
+                }
+            }
+
+            pango_sys::pango_layout_iter_free(iter);
+        }
+
+        lines
+    }
+
+    fn get_baseline_offset(&self, line: &ParagraphLine) -> f64 {
+        line_baseline_offset(line, self.vertical, self.left_to_right)
+    }
+
+    /// Returns the distance from the start edge of the layout's lines to the
+    /// leftmost (or topmost) edge of the text between `start` and `end` in
+    /// `line`.
+    fn get_inline_offset(&self, line: &ParagraphLine, start: usize, end: usize) -> f64 {
+        let mut offset = 0;
+
+        unsafe {
+            let layout_line =
+                pango_sys::pango_layout_get_line_readonly(self.layout.to_glib_none().0, line.index);
+
+            let mut ranges = ptr::null_mut();
+            let mut n_ranges = 0;
+
+            pango_sys::pango_layout_line_get_x_ranges(
+                layout_line,
+                start as i32,
+                end as i32,
+                &mut ranges,
+                &mut n_ranges,
+            );
+
+            if n_ranges > 0 {
+                offset = *ranges;
+
+                for i in 1..n_ranges as isize {
+                    offset = offset.min(*ranges.offset(2 * i));
+                }
+            }
+
+            glib_sys::g_free(ranges as glib_sys::gpointer);
+        }
+
+        f64::from(offset) / f64::from(pango::SCALE)
+    }
+
+    /// Draws the paragraph with the corner where its first line starts at
+    /// `(x, y)`: the top-left corner for horizontal text, and the top-right or
+    /// top-left corner for vertical text.  `node` must be the same one that
+    /// the paragraph was created with.
+    fn draw(&self, node: &RsvgNode, draw_ctx: *mut RsvgDrawingCtx, x: f64, y: f64, clipping: bool) {
+        let lines = self.get_lines();
+        let mut chunks = self.chunks.iter();
+
+        for_each_chars(node, draw_ctx, &mut |chars| {
+            let chunk = match chunks.next() {
+                Some(chunk) => chunk,
+                None => return,
+            };
+
+            let chunk_end = chunk.start + chunk.text.len();
+
+            // Draw the part of the chunk that falls in each line separately
+            for line in &lines {
+                let start = chunk.start.max(line.start);
+                let end = chunk_end.min(line.end);
+
+                if start >= end {
+                    continue;
+                }
+
+                let inline_offset = self.get_inline_offset(line, start, end);
+                let baseline_offset = self.get_baseline_offset(line);
+
+                let (mut piece_x, mut piece_y) = if self.vertical {
+                    (x + baseline_offset, y + inline_offset)
+                } else {
+                    (x + inline_offset, y + baseline_offset)
+                };
+
+                render_text(
+                    chars,
+                    draw_ctx,
+                    &chunk.text[start - chunk.start..end - chunk.start],
+                    &mut piece_x,
+                    &mut piece_y,
+                    clipping,
+                );
+            }
+        });
+    }
+}
+
+/// Returns the offset of a line's baseline from the edge of the paragraph
+/// where the lines start, along the x axis for vertical text.
+fn line_baseline_offset(line: &ParagraphLine, vertical: bool, left_to_right: bool) -> f64 {
+    if !vertical {
+        line.baseline
+    } else if left_to_right {
+        // Columns are stacked in the opposite direction of the lines in the
+        // layout, but the glyphs are rotated in the same way.
+        line.top + line.bottom - line.baseline
+    } else {
+        -line.baseline
+    }
+}
+
+/// Whether the columns of vertical text progress from left to right.
+fn lines_progress_left_to_right(state: *const RsvgState) -> bool {
+    state::get_state_rust(state).writing_mode == Some(WritingMode::VerticalLr)
+}
+
+/// Calls `f` for each `NodeChars` inside `node`, in document order, with
+/// the state of its parent element on top of the drawing context's stack.
+fn for_each_chars<F>(node: &RsvgNode, draw_ctx: *mut RsvgDrawingCtx, f: &mut F)
+where
+    F: FnMut(&RsvgNode),
+{
+    for child in node.children() {
+        if child.get_type() == NodeType::Chars {
+            f(&child);
+        } else {
+            drawing_ctx::state_push(draw_ctx);
+            drawing_ctx::state_reinherit_top(draw_ctx, child.get_state(), 0);
+
+            for_each_chars(&child, draw_ctx, f);
+
+            drawing_ctx::state_pop(draw_ctx);
+        }
+    }
+}
+
+/// Returns the distance from the left edge of a text that wraps at
+/// `inline_size` to its anchor point.
+fn inline_anchor_offset(anchor: TextAnchor, dir: pango::Direction, inline_size: f64) -> f64 {
+    let rtl = dir == pango::Direction::Rtl;
+
+    match anchor {
+        TextAnchor::Start if rtl => inline_size,
+        TextAnchor::Start => 0.0,
+        TextAnchor::Middle => inline_size / 2.0,
+        TextAnchor::End if rtl => 0.0,
+        TextAnchor::End => inline_size,
+    }
+}
+
 // FIXME: should the pango crate provide this like PANGO_GRAVITY_IS_VERTICAL() /
 // PANGO_GRAVITY_IS_IMPROPER()?
 pub fn gravity_is_vertical(gravity: pango::Gravity) -> bool {
@@ -451,11 +1097,128 @@ impl From<FontWeight> for pango::Weight {
     }
 }
 
+/// Converts the `font-kerning`, `font-variant-*` and `font-feature-settings`
+/// properties to a string of OpenType features, in the syntax that Pango
+/// accepts for its font-features attribute, like "kern=0,tnum=1".
+///
+/// Features are emitted in order of increasing precedence, so the low-level
+/// `font-feature-settings` comes last and overrides the others.
+fn opentype_features(
+    kerning: FontKerning,
+    caps: FontVariantCaps,
+    ligatures: &FontVariantLigatures,
+    numeric: &FontVariantNumeric,
+    position: FontVariantPosition,
+    settings: &FontFeatureSettings,
+) -> String {
+    let mut features = Vec::new();
+
+    match kerning {
+        FontKerning::Auto => (),
+        FontKerning::Normal => features.push("kern=1".to_string()),
+        FontKerning::None => features.push("kern=0".to_string()),
+    }
+
+    let caps_tags: &[&str] = match caps {
+        FontVariantCaps::Normal => &[],
+        FontVariantCaps::SmallCaps => &["smcp"],
+        FontVariantCaps::AllSmallCaps => &["c2sc", "smcp"],
+        FontVariantCaps::PetiteCaps => &["pcap"],
+        FontVariantCaps::AllPetiteCaps => &["c2pc", "pcap"],
+        FontVariantCaps::Unicase => &["unic"],
+        FontVariantCaps::TitlingCaps => &["titl"],
+    };
+
+    features.extend(caps_tags.iter().map(|tag| format!("{}=1", tag)));
+
+    let ligature_tags: [(&[&str], Option<bool>); 4] = [
+        (&["liga", "clig"], ligatures.common),
+        (&["dlig"], ligatures.discretionary),
+        (&["hlig"], ligatures.historical),
+        (&["calt"], ligatures.contextual),
+    ];
+
+    for &(tags, enabled) in &ligature_tags {
+        if let Some(enabled) = enabled {
+            for tag in tags {
+                features.push(format!("{}={}", tag, enabled as u32));
+            }
+        }
+    }
+
+    let numeric_tags = [
+        ("lnum", numeric.lining),
+        ("onum", numeric.oldstyle),
+        ("pnum", numeric.proportional),
+        ("tnum", numeric.tabular),
+        ("frac", numeric.diagonal_fractions),
+        ("afrc", numeric.stacked_fractions),
+        ("ordn", numeric.ordinal),
+        ("zero", numeric.slashed_zero),
+    ];
+
+    for &(tag, enabled) in &numeric_tags {
+        if enabled {
+            features.push(format!("{}=1", tag));
+        }
+    }
+
+    match position {
+        FontVariantPosition::Normal => (),
+        FontVariantPosition::Sub => features.push("subs=1".to_string()),
+        FontVariantPosition::Super => features.push("sups=1".to_string()),
+    }
+
+    for feature in &settings.0 {
+        features.push(format!("{}={}", feature.tag, feature.value));
+    }
+
+    features.join(",")
+}
+
+// Pango takes a comma-separated list of families, and tries them in order.
+fn font_family_with_fallbacks(family: Option<&str>, fallbacks: &[String]) -> String {
+    family
+        .into_iter()
+        .chain(fallbacks.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Creates a layout for `text`, already normalized for `xml:space`, with
+/// the current state.
 fn create_pango_layout(draw_ctx: *const RsvgDrawingCtx, text: &str) -> pango::Layout {
+    let state = drawing_ctx::get_current_state(draw_ctx);
+    let pango_context = create_pango_context(draw_ctx);
+
+    let layout = pango::Layout::new(&pango_context);
+    layout.set_font_description(&get_font_description(draw_ctx, &pango_context));
+
+    let attr_list = pango::AttrList::new();
+    add_text_attributes(draw_ctx, &attr_list, text, 0);
+    layout.set_attributes(&attr_list);
+
+    layout.set_alignment(match state::get_text_dir(state) {
+        pango::Direction::Ltr => pango::Alignment::Left,
+        _ => pango::Alignment::Right,
+    });
+
+    layout.set_text(text);
+
+    layout
+}
+
+/// Creates a Pango context for the language, direction and orientation of
+/// the text in the current state.
+fn create_pango_context(draw_ctx: *const RsvgDrawingCtx) -> pango::Context {
     let state = drawing_ctx::get_current_state(draw_ctx);
     let rstate = state::get_state_rust(state);
     let pango_context = drawing_ctx::get_pango_context(draw_ctx);
 
+    if let Some(font_options) = drawing_ctx::get_font_options(draw_ctx) {
+        pangocairo::functions::context_set_font_options(&pango_context, Some(&font_options));
+    }
+
     if let Some(XmlLang(ref lang)) = rstate.xml_lang {
         let pango_lang = pango::Language::from_string(&lang);
         pango_context.set_language(&pango_lang);
@@ -471,15 +1234,50 @@ fn create_pango_layout(draw_ctx: *const RsvgDrawingCtx, text: &str) -> pango::La
 
     let gravity = state::get_text_gravity(state);
     if gravity_is_vertical(gravity) {
-        pango_context.set_base_gravity(gravity);
+        match rstate.text_orientation.unwrap_or_default() {
+            TextOrientation::Mixed => {
+                pango_context.set_base_gravity(gravity);
+                pango_context.set_gravity_hint(pango::GravityHint::Natural);
+            }
+
+            TextOrientation::Upright => {
+                pango_context.set_base_gravity(gravity);
+                pango_context.set_gravity_hint(pango::GravityHint::Strong);
+            }
+
+            // All glyphs are shaped horizontally; draw_pango_layout() will
+            // rotate the whole line.
+            TextOrientation::Sideways => (),
+        }
     }
 
+    pango_context
+}
+
+/// Returns the font for the current state, based on the default font of
+/// `pango_context`.
+fn get_font_description(
+    draw_ctx: *const RsvgDrawingCtx,
+    pango_context: &pango::Context,
+) -> pango::FontDescription {
+    let state = drawing_ctx::get_current_state(draw_ctx);
+    let rstate = state::get_state_rust(state);
+
     let mut font_desc = pango_context.get_font_description().unwrap();
 
     if let Some(FontFamily(ref font_family)) = rstate.font_family {
         font_desc.set_family(&font_family);
     }
 
+    let fallback_families = drawing_ctx::get_fallback_font_families(draw_ctx);
+    if !fallback_families.is_empty() {
+        let family = font_desc.get_family();
+        font_desc.set_family(&font_family_with_fallbacks(
+            family.as_ref().map(String::as_str),
+            &fallback_families,
+        ));
+    }
+
     font_desc.set_style(pango::Style::from(rstate.font_style.unwrap_or_default()));
 
     font_desc.set_variant(pango::Variant::from(
@@ -492,43 +1290,362 @@ fn create_pango_layout(draw_ctx: *const RsvgDrawingCtx, text: &str) -> pango::La
         rstate.font_stretch.unwrap_or_default(),
     ));
 
+    // text-orientation can't turn glyphs upside down or counterclockwise in
+    // vertical text, so we do that with the gravity of the font.  Pango
+    // reverses the order of glyphs with these gravities, so that the text
+    // reads in the direction of the rotated glyphs.
+    if gravity_is_vertical(state::get_text_gravity(state)) {
+        match rstate.glyph_orientation_vertical {
+            Some(GlyphOrientationVertical::Angle180) => {
+                font_desc.set_gravity(pango::Gravity::West)
+            }
+
+            Some(GlyphOrientationVertical::Angle270) => {
+                font_desc.set_gravity(pango::Gravity::North)
+            }
+
+            _ => (),
+        }
+    }
+
     let (_, dpi_y) = drawing_ctx::get_dpi(draw_ctx);
     font_desc.set_size(to_pango_units(
         drawing_ctx::get_normalized_font_size(draw_ctx) / dpi_y * 72.0,
     ));
 
-    let layout = pango::Layout::new(&pango_context);
-    layout.set_font_description(&font_desc);
+    font_desc
+}
 
-    let attr_list = pango::AttrList::new();
+/// Adds the Pango attributes for the spacing and font features of the
+/// current state to `attr_list`, for `text` starting at byte `offset` of
+/// the layout's text.
+fn add_text_attributes(
+    draw_ctx: *const RsvgDrawingCtx,
+    attr_list: &pango::AttrList,
+    text: &str,
+    offset: usize,
+) {
+    let state = drawing_ctx::get_current_state(draw_ctx);
+    let rstate = state::get_state_rust(state);
 
-    if let Some(LetterSpacing(ref ls)) = rstate.letter_spacing {
-        attr_list.insert(
-            pango::Attribute::new_letter_spacing(to_pango_units(ls.normalize(draw_ctx))).unwrap(),
+    let insert = |mut attr: pango::Attribute, start: usize, end: usize| {
+        attr.set_start_index((offset + start) as u32);
+        attr.set_end_index((offset + end) as u32);
+        attr_list.insert(attr);
+    };
+
+    let letter_spacing = rstate
+        .letter_spacing
+        .as_ref()
+        .map_or(0.0, |&LetterSpacing(ref ls)| ls.normalize(draw_ctx));
+
+    if rstate.letter_spacing.is_some() {
+        insert(
+            pango::Attribute::new_letter_spacing(to_pango_units(letter_spacing)).unwrap(),
+            0,
+            text.len(),
         );
     }
 
-    if let Some(ref td) = rstate.text_decoration {
-        if td.underline {
-            attr_list.insert(pango::Attribute::new_underline(pango::Underline::Single).unwrap());
+    // Pango has no word-spacing attribute, so we add the extra spacing to the
+    // letter-spacing of the word separators.
+    if let Some(WordSpacing(ref ws)) = rstate.word_spacing {
+        let spacing = to_pango_units(letter_spacing + ws.normalize(draw_ctx));
+
+        for (start, end) in word_separator_ranges(text) {
+            insert(
+                pango::Attribute::new_letter_spacing(spacing).unwrap(),
+                start,
+                end,
+            );
         }
+    }
+
+    // Text decorations are not done with Pango attributes; we draw them by
+    // hand in render_text() so they can use their own fill and stroke.
+
+    let features = opentype_features(
+        rstate.font_kerning.unwrap_or_default(),
+        rstate.font_variant_caps.unwrap_or_default(),
+        &rstate.font_variant_ligatures.clone().unwrap_or_default(),
+        &rstate.font_variant_numeric.clone().unwrap_or_default(),
+        rstate.font_variant_position.unwrap_or_default(),
+        &rstate.font_feature_settings.clone().unwrap_or_default(),
+    );
+
+    if !features.is_empty() {
+        let attr: pango::Attribute =
+            unsafe { from_glib_full(pango_attr_font_features_new(features.to_glib_none().0)) };
+        insert(attr, 0, text.len());
+    }
+}
+
+/// Returns the SVG font that the current `font-family` refers to, if the
+/// document has one.  SVG fonts are only used for horizontal text.
+fn get_svg_font(node: &RsvgNode, draw_ctx: *const RsvgDrawingCtx) -> Option<SvgFont> {
+    let state = drawing_ctx::get_current_state(draw_ctx);
+
+    if gravity_is_vertical(state::get_text_gravity(state)) {
+        return None;
+    }
+
+    match state::get_state_rust(state).font_family {
+        Some(FontFamily(ref font_family)) => font::find_svg_font(node, font_family),
+        None => None,
+    }
+}
+
+/// Lays out `text` with an SVG font instead of Pango, on a baseline
+/// that starts at `(x, y)`.  Returns the outlines of the glyphs and the
+/// advance of the text.
+fn layout_svg_font_text(
+    font: &SvgFont,
+    draw_ctx: *const RsvgDrawingCtx,
+    text: &str,
+    x: f64,
+    y: f64,
+) -> (PathBuilder, f64) {
+    let state = drawing_ctx::get_current_state(draw_ctx);
+    let rstate = state::get_state_rust(state);
+
+    let letter_spacing = rstate
+        .letter_spacing
+        .as_ref()
+        .map_or(0.0, |&LetterSpacing(ref ls)| ls.normalize(draw_ctx));
+
+    font.layout(
+        text,
+        x,
+        y - drawing_ctx::get_accumulated_baseline_shift(draw_ctx),
+        drawing_ctx::get_normalized_font_size(draw_ctx),
+        letter_spacing,
+    )
+}
+
+/// Returns the byte ranges of the word-separator characters in `text`,
+/// to which `word-spacing` applies.
+fn word_separator_ranges(text: &str) -> Vec<(usize, usize)> {
+    text.char_indices()
+        .filter(|&(_, c)| match c {
+            ' ' | '\u{a0}' | '\u{1361}' | '\u{10100}' | '\u{10101}' | '\u{1039f}' => true,
+            _ => false,
+        })
+        .map(|(i, c)| (i, i + c.len_utf8()))
+        .collect()
+}
 
-        if td.strike {
-            attr_list.insert(pango::Attribute::new_strikethrough(true).unwrap());
+/// Finds the nearest ancestor of a text node that specifies the
+/// `text-decoration` property.  This is the element whose fill and stroke
+/// are used to paint the decorations.
+fn get_decorating_ancestor(node: &RsvgNode) -> Option<RsvgNode> {
+    let mut parent = node.get_parent();
+
+    while let Some(p) = parent {
+        if state::get_state_rust(p.get_state())
+            .text_decoration
+            .is_some()
+        {
+            return Some(p);
         }
+
+        parent = p.get_parent();
     }
 
-    layout.set_attributes(&attr_list);
+    None
+}
 
-    layout.set_alignment(match state::get_text_dir(state) {
-        pango::Direction::Ltr => pango::Alignment::Left,
-        _ => pango::Alignment::Right,
-    });
+struct DecorationMetrics {
+    underline_position: f64,
+    underline_thickness: f64,
+    strikethrough_position: f64,
+    strikethrough_thickness: f64,
+    ascent: f64,
+}
 
-    let t = xml_space_normalize(rstate.xml_space.unwrap_or_default(), text);
-    layout.set_text(&t);
+fn get_decoration_metrics(layout: &pango::Layout) -> DecorationMetrics {
+    let context = layout.get_context().unwrap();
+    let font_desc = layout.get_font_description();
+    let pango_scale = f64::from(pango::SCALE);
 
-    layout
+    unsafe {
+        let metrics = pango_sys::pango_context_get_metrics(
+            context.to_glib_none().0,
+            font_desc.to_glib_none().0,
+            ptr::null_mut(),
+        );
+
+        let dm = DecorationMetrics {
+            underline_position: f64::from(pango_sys::pango_font_metrics_get_underline_position(
+                metrics,
+            )) / pango_scale,
+            underline_thickness: f64::from(pango_sys::pango_font_metrics_get_underline_thickness(
+                metrics,
+            )) / pango_scale,
+            strikethrough_position: f64::from(
+                pango_sys::pango_font_metrics_get_strikethrough_position(metrics),
+            ) / pango_scale,
+            strikethrough_thickness: f64::from(
+                pango_sys::pango_font_metrics_get_strikethrough_thickness(metrics),
+            ) / pango_scale,
+            ascent: f64::from(pango_sys::pango_font_metrics_get_ascent(metrics)) / pango_scale,
+        };
+
+        pango_sys::pango_font_metrics_unref(metrics);
+
+        dm
+    }
+}
+
+/// Builds the outlines of the text decorations that the element with
+/// `paint_state` specified for a `layout`, in the layout's coordinate system.
+///
+/// Returns a tuple of (underline and overline, line-through) since those
+/// get painted below and above the text, respectively.
+fn build_text_decorations(
+    layout: &pango::Layout,
+    paint_state: *mut RsvgState,
+) -> (PathBuilder, PathBuilder) {
+    let mut over = PathBuilder::new();
+    let mut through = PathBuilder::new();
+
+    let rstate = state::get_state_rust(paint_state);
+
+    let decoration = rstate.text_decoration.clone().unwrap_or_default();
+    if !(decoration.underline || decoration.overline || decoration.strike) {
+        return (over, through);
+    }
+
+    let style = rstate.text_decoration_style.unwrap_or_default();
+    let metrics = get_decoration_metrics(layout);
+
+    let pango_scale = f64::from(pango::SCALE);
+    let (_, logical) = layout.get_extents();
+    let x = f64::from(logical.x) / pango_scale;
+    let width = f64::from(logical.width) / pango_scale;
+    let baseline = f64::from(layout.get_baseline()) / pango_scale;
+
+    if decoration.underline {
+        add_decoration_line(
+            &mut over,
+            style,
+            x,
+            baseline - metrics.underline_position,
+            width,
+            metrics.underline_thickness,
+        );
+    }
+
+    if decoration.overline {
+        add_decoration_line(
+            &mut over,
+            style,
+            x,
+            baseline - metrics.ascent,
+            width,
+            metrics.underline_thickness,
+        );
+    }
+
+    if decoration.strike {
+        add_decoration_line(
+            &mut through,
+            style,
+            x,
+            baseline - metrics.strikethrough_position,
+            width,
+            metrics.strikethrough_thickness,
+        );
+    }
+
+    (over, through)
+}
+
+fn add_rectangle(builder: &mut PathBuilder, x: f64, y: f64, width: f64, height: f64) {
+    builder.move_to(x, y);
+    builder.line_to(x + width, y);
+    builder.line_to(x + width, y + height);
+    builder.line_to(x, y + height);
+    builder.close_path();
+}
+
+/// Adds the outline of a single decoration line to `builder`.  The line
+/// spans from `x` to `x + width`, and `y` is the top edge of a solid line.
+fn add_decoration_line(
+    builder: &mut PathBuilder,
+    style: TextDecorationStyle,
+    x: f64,
+    y: f64,
+    width: f64,
+    thickness: f64,
+) {
+    if width <= 0.0 || thickness <= 0.0 {
+        return;
+    }
+
+    match style {
+        TextDecorationStyle::Solid => add_rectangle(builder, x, y, width, thickness),
+
+        TextDecorationStyle::Double => {
+            add_rectangle(builder, x, y - thickness, width, thickness);
+            add_rectangle(builder, x, y + thickness, width, thickness);
+        }
+
+        TextDecorationStyle::Dotted | TextDecorationStyle::Dashed => {
+            let (dash, gap) = if style == TextDecorationStyle::Dotted {
+                (thickness, thickness)
+            } else {
+                (thickness * 3.0, thickness * 2.0)
+            };
+
+            let mut dx = 0.0;
+            while dx < width {
+                add_rectangle(builder, x + dx, y, dash.min(width - dx), thickness);
+                dx += dash + gap;
+            }
+        }
+
+        TextDecorationStyle::Wavy => {
+            // A band of thickness height that oscillates around the solid
+            // line's position; each half-wave is a cubic with a peak of
+            // `amplitude`, adjusted so that an integral number of them fits.
+            let amplitude = thickness;
+            let n = (width / (thickness * 3.0)).round().max(1.0) as usize;
+            let half_wave = width / n as f64;
+            let k = amplitude * 4.0 / 3.0;
+
+            let mut sign = -1.0;
+            builder.move_to(x, y);
+            for i in 0..n {
+                let x0 = x + half_wave * i as f64;
+                builder.curve_to(
+                    x0 + half_wave / 3.0,
+                    y + sign * k,
+                    x0 + half_wave * 2.0 / 3.0,
+                    y + sign * k,
+                    x0 + half_wave,
+                    y,
+                );
+                sign = -sign;
+            }
+
+            // Come back along the bottom edge of the band
+            let y = y + thickness;
+            builder.line_to(x + width, y);
+            for i in (0..n).rev() {
+                sign = -sign;
+                let x0 = x + half_wave * i as f64;
+                builder.curve_to(
+                    x0 + half_wave * 2.0 / 3.0,
+                    y + sign * k,
+                    x0 + half_wave / 3.0,
+                    y + sign * k,
+                    x0,
+                    y,
+                );
+            }
+            builder.close_path();
+        }
+    }
 }
 
 fn anchor_offset(
@@ -584,7 +1701,7 @@ fn measure_child(
 
     match (node.get_type(), textonly) {
         (NodeType::Chars, _) => {
-            node.with_impl(|chars: &NodeChars| chars.measure(draw_ctx, length));
+            node.with_impl(|chars: &NodeChars| chars.measure(node, draw_ctx, length));
         }
         (_, true) => {
             done = measure_children(node, draw_ctx, length, textonly);
@@ -634,7 +1751,7 @@ fn render_child(
 ) {
     match (node.get_type(), textonly) {
         (NodeType::Chars, _) => {
-            node.with_impl(|chars: &NodeChars| chars.render(draw_ctx, x, y, clipping));
+            node.with_impl(|chars: &NodeChars| chars.render(node, draw_ctx, x, y, clipping));
         }
         (_, true) => {
             render_children(node, draw_ctx, x, y, textonly, clipping);
@@ -653,6 +1770,37 @@ fn render_child(
     }
 }
 
+/// Replaces the character data of a text element with `text`.  The caller
+/// must ensure that the element does not have child elements, whose
+/// character data would be left in place.
+pub fn set_text_content(node: &RsvgNode, text: &str) {
+    // Collected first, since adding a child needs to borrow the children mutably
+    let chars_nodes: Vec<RsvgNode> = node
+        .children()
+        .filter(|child| child.get_type() == NodeType::Chars)
+        .collect();
+
+    if let Some((first, rest)) = chars_nodes.split_first() {
+        first.with_impl(|chars: &NodeChars| chars.set_string(text));
+
+        for child in rest {
+            child.with_impl(|chars: &NodeChars| chars.set_string(""));
+        }
+    } else {
+        let chars = Rc::new(Node::new(
+            NodeType::Chars,
+            Some(Rc::downgrade(node)),
+            state::new(),
+            Box::new(NodeChars::new()),
+        ));
+
+        state::set_cond_true(chars.get_state(), false);
+        chars.with_impl(|c: &NodeChars| c.set_string(text));
+
+        node.add_child(&chars);
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn rsvg_node_chars_new(raw_parent: *const RsvgNode) -> *const RsvgNode {
     let node = boxed_node_new(NodeType::Chars, raw_parent, Box::new(NodeChars::new()));
@@ -685,6 +1833,54 @@ pub extern "C" fn rsvg_node_chars_append(
     });
 }
 
+#[no_mangle]
+pub extern "C" fn rsvg_node_flow_para_new(
+    _: *const libc::c_char,
+    raw_parent: *const RsvgNode,
+) -> *const RsvgNode {
+    boxed_node_new(
+        NodeType::FlowPara,
+        raw_parent,
+        Box::new(NodeFlowContent::new()),
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_node_flow_region_new(
+    _: *const libc::c_char,
+    raw_parent: *const RsvgNode,
+) -> *const RsvgNode {
+    boxed_node_new(
+        NodeType::FlowRegion,
+        raw_parent,
+        Box::new(NodeFlowContent::new()),
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_node_flow_root_new(
+    _: *const libc::c_char,
+    raw_parent: *const RsvgNode,
+) -> *const RsvgNode {
+    boxed_node_new(
+        NodeType::FlowRoot,
+        raw_parent,
+        Box::new(NodeFlowRoot::new()),
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_node_flow_span_new(
+    _: *const libc::c_char,
+    raw_parent: *const RsvgNode,
+) -> *const RsvgNode {
+    boxed_node_new(
+        NodeType::FlowSpan,
+        raw_parent,
+        Box::new(NodeFlowContent::new()),
+    )
+}
+
 #[no_mangle]
 pub extern "C" fn rsvg_node_text_new(
     _: *const libc::c_char,
@@ -708,3 +1904,198 @@ pub extern "C" fn rsvg_node_tspan_new(
 ) -> *const RsvgNode {
     boxed_node_new(NodeType::TSpan, raw_parent, Box::new(NodeTSpan::new()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq_cairo::ApproxEqCairo;
+    use parsers::Parse;
+    use path_builder::PathCommand;
+
+    #[test]
+    fn computes_inline_anchor_offset() {
+        let ltr = pango::Direction::Ltr;
+        let rtl = pango::Direction::Rtl;
+
+        assert!(inline_anchor_offset(TextAnchor::Start, ltr, 100.0).approx_eq_cairo(&0.0));
+        assert!(inline_anchor_offset(TextAnchor::Middle, ltr, 100.0).approx_eq_cairo(&50.0));
+        assert!(inline_anchor_offset(TextAnchor::End, ltr, 100.0).approx_eq_cairo(&100.0));
+
+        assert!(inline_anchor_offset(TextAnchor::Start, rtl, 100.0).approx_eq_cairo(&100.0));
+        assert!(inline_anchor_offset(TextAnchor::Middle, rtl, 100.0).approx_eq_cairo(&50.0));
+        assert!(inline_anchor_offset(TextAnchor::End, rtl, 100.0).approx_eq_cairo(&0.0));
+    }
+
+    #[test]
+    fn stacks_vertical_lines_in_both_directions() {
+        let lines = [
+            ParagraphLine {
+                index: 0,
+                start: 0,
+                end: 4,
+                top: 0.0,
+                bottom: 20.0,
+                baseline: 15.0,
+            },
+            ParagraphLine {
+                index: 1,
+                start: 4,
+                end: 8,
+                top: 20.0,
+                bottom: 40.0,
+                baseline: 35.0,
+            },
+        ];
+
+        let offsets = |vertical, left_to_right| {
+            lines
+                .iter()
+                .map(|l| line_baseline_offset(l, vertical, left_to_right))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(offsets(false, false), vec![15.0, 35.0]);
+        assert_eq!(offsets(true, false), vec![-15.0, -35.0]);
+        assert_eq!(offsets(true, true), vec![5.0, 25.0]);
+    }
+
+    #[test]
+    fn appends_fallback_font_families() {
+        let fallbacks = vec!["DejaVu Sans".to_string(), "Noto Color Emoji".to_string()];
+
+        assert_eq!(
+            font_family_with_fallbacks(Some("Roboto"), &fallbacks),
+            "Roboto,DejaVu Sans,Noto Color Emoji"
+        );
+        assert_eq!(
+            font_family_with_fallbacks(None, &fallbacks),
+            "DejaVu Sans,Noto Color Emoji"
+        );
+        assert_eq!(font_family_with_fallbacks(Some("Roboto"), &[]), "Roboto");
+    }
+
+    #[test]
+    fn finds_word_separators() {
+        assert_eq!(word_separator_ranges("foo"), vec![]);
+        assert_eq!(word_separator_ranges("a b\u{a0}c"), vec![(1, 2), (3, 5)]);
+    }
+
+    #[test]
+    fn solid_decoration_is_a_rectangle() {
+        let mut builder = PathBuilder::new();
+
+        add_decoration_line(
+            &mut builder,
+            TextDecorationStyle::Solid,
+            1.0,
+            2.0,
+            10.0,
+            0.5,
+        );
+
+        assert_eq!(
+            builder.get_path_commands(),
+            &[
+                PathCommand::MoveTo(1.0, 2.0),
+                PathCommand::LineTo(11.0, 2.0),
+                PathCommand::LineTo(11.0, 2.5),
+                PathCommand::LineTo(1.0, 2.5),
+                PathCommand::ClosePath,
+            ]
+        );
+    }
+
+    #[test]
+    fn double_decoration_has_two_lines() {
+        let mut builder = PathBuilder::new();
+
+        add_decoration_line(
+            &mut builder,
+            TextDecorationStyle::Double,
+            0.0,
+            0.0,
+            10.0,
+            1.0,
+        );
+
+        let n_subpaths = builder
+            .get_path_commands()
+            .iter()
+            .filter(|c| **c == PathCommand::ClosePath)
+            .count();
+        assert_eq!(n_subpaths, 2);
+    }
+
+    #[test]
+    fn dotted_decoration_does_not_overflow() {
+        let mut builder = PathBuilder::new();
+
+        add_decoration_line(
+            &mut builder,
+            TextDecorationStyle::Dotted,
+            0.0,
+            0.0,
+            9.0,
+            2.0,
+        );
+
+        // dots at 0, 4, and 8, the last one clipped to 1 unit wide
+        assert_eq!(builder.get_path_commands().len(), 15);
+        assert_eq!(
+            builder.get_path_commands()[11],
+            PathCommand::LineTo(9.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn wavy_decoration_spans_the_whole_width() {
+        let mut builder = PathBuilder::new();
+
+        add_decoration_line(&mut builder, TextDecorationStyle::Wavy, 0.0, 5.0, 30.0, 1.0);
+
+        let commands = builder.get_path_commands();
+
+        // 10 half-waves along the top, a line, 10 half-waves back, and a close-path
+        assert_eq!(commands.len(), 23);
+        assert_eq!(
+            commands[10],
+            PathCommand::CurveTo(
+                (28.0, 5.0 + 4.0 / 3.0),
+                (29.0, 5.0 + 4.0 / 3.0),
+                (30.0, 5.0)
+            )
+        );
+        assert_eq!(commands[11], PathCommand::LineTo(30.0, 6.0));
+        assert_eq!(commands[22], PathCommand::ClosePath);
+    }
+
+    #[test]
+    fn no_features_by_default() {
+        assert_eq!(
+            opentype_features(
+                Default::default(),
+                Default::default(),
+                &Default::default(),
+                &Default::default(),
+                Default::default(),
+                &Default::default(),
+            ),
+            ""
+        );
+    }
+
+    #[test]
+    fn converts_properties_to_opentype_features() {
+        assert_eq!(
+            opentype_features(
+                FontKerning::None,
+                FontVariantCaps::AllSmallCaps,
+                &FontVariantLigatures::parse("no-common-ligatures", ()).unwrap(),
+                &FontVariantNumeric::parse("tabular-nums oldstyle-nums", ()).unwrap(),
+                FontVariantPosition::Super,
+                &FontFeatureSettings::parse("\"ss01\", \"tnum\" 0", ()).unwrap(),
+            ),
+            "kern=0,c2sc=1,smcp=1,liga=0,clig=0,onum=1,tnum=1,sups=1,ss01=1,tnum=0"
+        );
+    }
+}
@@ -0,0 +1,306 @@
+use cairo;
+use glib::translate::*;
+use glib_sys;
+use libc;
+use pango;
+use pangocairo;
+
+use color::Color;
+use drawing_ctx::RsvgDrawingCtx;
+use handle::RsvgHandle;
+use paint_server::PaintServer;
+use path_builder::PathBuilder;
+use state::{self, RsvgState, StrokeWidth};
+use text_run;
+
+/// The outline of a piece of text, with the paint that it is rendered with.
+pub struct TextOutline {
+    /// Glyph outlines in canvas coordinates, i.e. with all the transforms
+    /// applied.
+    pub path: PathBuilder,
+
+    pub fill: Option<PaintServer>,
+    pub fill_opacity: u8,
+
+    pub stroke: Option<PaintServer>,
+    pub stroke_opacity: u8,
+
+    /// Stroke width in canvas units, for the average scale of the transform.
+    pub stroke_width: f64,
+
+    /// The value of `currentColor` for the paint servers.
+    pub current_color: Color,
+}
+
+extern "C" {
+    fn rsvg_handle_collect_text_outlines(
+        handle: *const RsvgHandle,
+        outlines: *mut libc::c_void,
+    ) -> glib_sys::gboolean;
+
+    fn rsvg_drawing_ctx_get_text_outlines(draw_ctx: *const RsvgDrawingCtx) -> *mut libc::c_void;
+}
+
+/// Lays out all the text in a loaded SVG as `rsvg_handle_render_cairo()` would
+/// with an identity transformation, and returns the outlines of its glyphs.
+///
+/// Returns `None` if the handle has not finished loading.
+pub fn get_text_outlines(handle: *const RsvgHandle) -> Option<Vec<TextOutline>> {
+    let mut outlines: Vec<TextOutline> = Vec::new();
+
+    let ok: bool = unsafe {
+        from_glib(rsvg_handle_collect_text_outlines(
+            handle,
+            &mut outlines as *mut Vec<TextOutline> as *mut libc::c_void,
+        ))
+    };
+
+    if ok {
+        Some(outlines)
+    } else {
+        None
+    }
+}
+
+fn get_collected_outlines<'a>(draw_ctx: *const RsvgDrawingCtx) -> Option<&'a mut Vec<TextOutline>> {
+    unsafe {
+        let outlines = rsvg_drawing_ctx_get_text_outlines(draw_ctx) as *mut Vec<TextOutline>;
+
+        if outlines.is_null() {
+            None
+        } else {
+            Some(&mut *outlines)
+        }
+    }
+}
+
+/// Records the outline of a Pango layout that is being drawn at `x, y`, if the
+/// caller of the drawing is collecting text outlines.
+///
+/// The layout must already be updated for the cairo context it gets drawn
+/// on; this does not touch it, so that the outline matches the painted glyphs.
+pub fn add_layout_outline(
+    draw_ctx: *const RsvgDrawingCtx,
+    state: *const RsvgState,
+    layout: &pango::Layout,
+    x: f64,
+    y: f64,
+    gravity: pango::Gravity,
+) {
+    if let Some(outlines) = get_collected_outlines(draw_ctx) {
+        let rstate = state::get_state_rust(state);
+        let m = text_run::layout_transform(x, y, gravity, &rstate.affine);
+
+        let path = outline_to_path_builder(&m, |cr| {
+            cr.move_to(0.0, 0.0);
+            pangocairo::functions::layout_path(cr, layout);
+        });
+
+        outlines.push(new_outline(draw_ctx, state, &rstate.affine, path));
+    }
+}
+
+/// Records the outline of text that was drawn with an SVG font, if the caller
+/// of the drawing is collecting text outlines.
+pub fn add_path_outline(
+    draw_ctx: *const RsvgDrawingCtx,
+    state: *const RsvgState,
+    builder: &PathBuilder,
+) {
+    if let Some(outlines) = get_collected_outlines(draw_ctx) {
+        let rstate = state::get_state_rust(state);
+
+        let path = outline_to_path_builder(&rstate.affine, |cr| builder.to_cairo(cr));
+
+        outlines.push(new_outline(draw_ctx, state, &rstate.affine, path));
+    }
+}
+
+/// Records the outline of a text decoration that is being drawn for a layout
+/// at `x, y`, if the caller of the drawing is collecting text outlines.
+///
+/// The decoration is painted with `text-decoration-color` if there is one,
+/// or else with the fill and stroke of `paint_state`.
+pub fn add_decoration_outline(
+    draw_ctx: *const RsvgDrawingCtx,
+    state: *const RsvgState,
+    paint_state: *const RsvgState,
+    builder: &PathBuilder,
+    x: f64,
+    y: f64,
+    gravity: pango::Gravity,
+) {
+    if let Some(outlines) = get_collected_outlines(draw_ctx) {
+        let affine = state::get_state_rust(state).affine;
+        let m = text_run::layout_transform(x, y, gravity, &affine);
+
+        let path = outline_to_path_builder(&m, |cr| builder.to_cairo(cr));
+
+        let mut outline = new_outline(draw_ctx, paint_state, &affine, path);
+
+        let color = state::get_state_rust(paint_state)
+            .text_decoration_color
+            .as_ref()
+            .map(|c| c.0);
+
+        if let Some(color) = color {
+            outline.fill = Some(PaintServer::SolidColor(color));
+            outline.stroke = None;
+        }
+
+        outlines.push(outline);
+    }
+}
+
+fn new_outline(
+    draw_ctx: *const RsvgDrawingCtx,
+    state: *const RsvgState,
+    affine: &cairo::Matrix,
+    path: PathBuilder,
+) -> TextOutline {
+    let rstate = state::get_state_rust(state);
+
+    let stroke_width = rstate
+        .stroke_width
+        .as_ref()
+        .map_or_else(|| StrokeWidth::default().0, |w| w.0)
+        .normalize(draw_ctx);
+
+    let det = affine.xx * affine.yy - affine.xy * affine.yx;
+
+    TextOutline {
+        path,
+        fill: state::get_fill(state).cloned(),
+        fill_opacity: state::get_fill_opacity(state),
+        stroke: state::get_stroke(state).cloned(),
+        stroke_opacity: state::get_stroke_opacity(state),
+        stroke_width: stroke_width * det.abs().sqrt(),
+        current_color: state::get_current_color(state),
+    }
+}
+
+/// Runs `append_path` on a scratch cairo context with the transformation `m`,
+/// and returns the resulting path with the transformation applied.
+fn outline_to_path_builder<F>(m: &cairo::Matrix, append_path: F) -> PathBuilder
+where
+    F: FnOnce(&cairo::Context),
+{
+    let surface = cairo::ImageSurface::create(cairo::Format::A8, 1, 1).unwrap();
+    let cr = cairo::Context::new(&surface);
+
+    cr.set_matrix(*m);
+    append_path(&cr);
+
+    // Cairo keeps the path in device space, so we get it back transformed
+    cr.identity_matrix();
+    let path = cr.copy_path();
+
+    let mut builder = PathBuilder::new();
+
+    for segment in path.iter() {
+        match segment {
+            cairo::PathSegment::MoveTo((x, y)) => builder.move_to(x, y),
+            cairo::PathSegment::LineTo((x, y)) => builder.line_to(x, y),
+            cairo::PathSegment::CurveTo((x2, y2), (x3, y3), (x4, y4)) => {
+                builder.curve_to(x2, y2, x3, y3, x4, y4)
+            }
+            cairo::PathSegment::ClosePath => builder.close_path(),
+        }
+    }
+
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cairo::MatrixTrait;
+    use glib::translate::ToGlib;
+    use pango::LayoutExt;
+    use pango_sys;
+    use path_builder::PathCommand;
+
+    #[test]
+    fn applies_transform_to_outlines() {
+        let mut square = PathBuilder::new();
+        square.move_to(0.0, 0.0);
+        square.line_to(10.0, 0.0);
+        square.line_to(10.0, 10.0);
+        square.close_path();
+
+        let m = cairo::Matrix::new(2.0, 0.0, 0.0, 3.0, 5.0, 7.0);
+        let path = outline_to_path_builder(&m, |cr| square.to_cairo(cr));
+
+        let commands = path.get_path_commands();
+        assert_eq!(commands[0], PathCommand::MoveTo(5.0, 7.0));
+        assert_eq!(commands[1], PathCommand::LineTo(25.0, 7.0));
+        assert_eq!(commands[2], PathCommand::LineTo(25.0, 37.0));
+        assert_eq!(commands[3], PathCommand::ClosePath);
+    }
+
+    /// Returns the bounds of the pixels that got painted in `surface`.
+    fn painted_extents(surface: &mut cairo::ImageSurface) -> (i32, i32, i32, i32) {
+        let width = surface.get_width();
+        let height = surface.get_height();
+        let stride = surface.get_stride();
+        let data = surface.get_data().unwrap();
+
+        let (mut x0, mut y0, mut x1, mut y1) = (width, height, 0, 0);
+
+        for y in 0..height {
+            for x in 0..width {
+                if data[(y * stride + x) as usize] != 0 {
+                    x0 = x0.min(x);
+                    y0 = y0.min(y);
+                    x1 = x1.max(x + 1);
+                    y1 = y1.max(y + 1);
+                }
+            }
+        }
+
+        (x0, y0, x1, y1)
+    }
+
+    fn assert_outline_matches_rendering(gravity: pango::Gravity, x: f64, y: f64) {
+        let affine = cairo::Matrix::new(1.5, 0.0, 0.0, 1.5, 10.0, 5.0);
+
+        let mut surface = cairo::ImageSurface::create(cairo::Format::A8, 300, 300).unwrap();
+
+        let layout = {
+            let cr = cairo::Context::new(&surface);
+            let layout = pangocairo::functions::create_layout(&cr).unwrap();
+            layout.set_font_description(&pango::FontDescription::from_string("Sans 20"));
+            layout.set_text("Hello");
+
+            // Same as draw_pango_layout()
+            cr.set_matrix(affine);
+            cr.move_to(x, y);
+            let rotation = unsafe { pango_sys::pango_gravity_to_rotation(gravity.to_glib()) };
+            cr.rotate(-f64::from(rotation));
+            pangocairo::functions::update_layout(&cr, &layout);
+            pangocairo::functions::show_layout(&cr, &layout);
+
+            layout
+        };
+
+        let m = text_run::layout_transform(x, y, gravity, &affine);
+        let path = outline_to_path_builder(&m, |cr| {
+            cr.move_to(0.0, 0.0);
+            pangocairo::functions::layout_path(cr, &layout);
+        });
+
+        let bbox = path.get_bbox().unwrap();
+        let (x0, y0, x1, y1) = painted_extents(&mut surface);
+
+        assert!((bbox.x - f64::from(x0)).abs() <= 1.0);
+        assert!((bbox.y - f64::from(y0)).abs() <= 1.0);
+        assert!((bbox.x + bbox.width - f64::from(x1)).abs() <= 1.0);
+        assert!((bbox.y + bbox.height - f64::from(y1)).abs() <= 1.0);
+    }
+
+    #[test]
+    fn outline_extents_match_rendered_text() {
+        assert_outline_matches_rendering(pango::Gravity::South, 20.0, 30.0);
+        assert_outline_matches_rendering(pango::Gravity::East, 100.0, 20.0);
+    }
+}
@@ -0,0 +1,236 @@
+use cairo;
+use cairo::MatrixTrait;
+use glib::translate::*;
+use glib_sys;
+use libc;
+use pango::{self, LayoutExt};
+use pango_sys;
+use std::mem;
+use std::ptr;
+
+use drawing_ctx::RsvgDrawingCtx;
+use float_eq_cairo::ApproxEqCairo;
+use state::{self, RsvgState};
+use text;
+
+// Keep this in sync with ../../librsvg/rsvg.h:RsvgTextDirection
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TextRunDirection {
+    Ltr,
+    Rtl,
+    Ttb,
+}
+
+// Keep this in sync with ../../librsvg/rsvg.h:RsvgTextRun
+#[repr(C)]
+pub struct RsvgTextRun {
+    text: *mut libc::c_char,
+    x: f64,
+    y: f64,
+    font: *mut libc::c_char,
+    direction: TextRunDirection,
+    n_chars: libc::c_uint,
+    char_boxes: *mut cairo::Rectangle,
+}
+
+extern "C" {
+    fn rsvg_drawing_ctx_is_collecting_text_runs(
+        draw_ctx: *const RsvgDrawingCtx,
+    ) -> glib_sys::gboolean;
+
+    fn rsvg_drawing_ctx_add_text_run(draw_ctx: *const RsvgDrawingCtx, run: *const RsvgTextRun);
+}
+
+/// Records a Pango layout that is being drawn at `x, y` with the current
+/// state, if the caller of the drawing is collecting text runs.
+///
+/// The layout must be the same one that `draw_pango_layout()` draws, so that
+/// the character boxes match the rendered glyphs.
+pub fn add_text_run(
+    draw_ctx: *const RsvgDrawingCtx,
+    state: *const RsvgState,
+    layout: &pango::Layout,
+    x: f64,
+    y: f64,
+    gravity: pango::Gravity,
+) {
+    let collecting: bool = unsafe { from_glib(rsvg_drawing_ctx_is_collecting_text_runs(draw_ctx)) };
+    if !collecting {
+        return;
+    }
+
+    let text = match layout.get_text() {
+        Some(t) => t,
+        None => return,
+    };
+
+    let rstate = state::get_state_rust(state);
+    let layout_to_canvas = layout_transform(x, y, gravity, &rstate.affine);
+
+    let baseline = f64::from(layout.get_baseline()) / f64::from(pango::SCALE);
+    let (origin_x, origin_y) = layout_to_canvas.transform_point(0.0, baseline);
+
+    let boxes: Vec<cairo::Rectangle> = text
+        .char_indices()
+        .map(|(index, _)| {
+            let pos = layout.index_to_pos(index as i32);
+            transformed_char_box(&pos, &layout_to_canvas)
+        })
+        .collect();
+
+    let direction = if text::gravity_is_vertical(gravity) {
+        TextRunDirection::Ttb
+    } else if state::get_text_dir(state) == pango::Direction::Rtl {
+        TextRunDirection::Rtl
+    } else {
+        TextRunDirection::Ltr
+    };
+
+    let font = layout
+        .get_font_description()
+        .map_or(ptr::null_mut(), |desc| desc.to_string().to_glib_full());
+
+    let run = RsvgTextRun {
+        text: text.to_glib_full(),
+        x: origin_x,
+        y: origin_y,
+        font,
+        direction,
+        n_chars: boxes.len() as libc::c_uint,
+        char_boxes: copy_boxes(&boxes),
+    };
+
+    // The C side takes ownership of the strings and the boxes
+    unsafe {
+        rsvg_drawing_ctx_add_text_run(draw_ctx, &run);
+    }
+}
+
+/// Computes the transformation from a Pango layout's coordinates to canvas
+/// coordinates, in the same way as `draw_pango_layout()` positions the layout.
+pub fn layout_transform(
+    x: f64,
+    y: f64,
+    gravity: pango::Gravity,
+    affine: &cairo::Matrix,
+) -> cairo::Matrix {
+    let rotation = unsafe { pango_sys::pango_gravity_to_rotation(gravity.to_glib()) };
+
+    let mut m = cairo::Matrix::identity();
+    m.translate(x, y);
+    if !rotation.approx_eq_cairo(&0.0) {
+        m.rotate(-rotation);
+    }
+
+    cairo::Matrix::multiply(&m, affine)
+}
+
+/// Returns the axis-aligned bounds of a character's logical rectangle, which
+/// is in Pango units in the layout's coordinates, after transforming it.
+fn transformed_char_box(pos: &pango::Rectangle, m: &cairo::Matrix) -> cairo::Rectangle {
+    let pango_scale = f64::from(pango::SCALE);
+
+    // The width is negative for right-to-left characters
+    let x0 = f64::from(pos.x) / pango_scale;
+    let y0 = f64::from(pos.y) / pango_scale;
+    let x1 = f64::from(pos.x + pos.width) / pango_scale;
+    let y1 = f64::from(pos.y + pos.height) / pango_scale;
+
+    let corners = [
+        m.transform_point(x0, y0),
+        m.transform_point(x1, y0),
+        m.transform_point(x1, y1),
+        m.transform_point(x0, y1),
+    ];
+
+    let min_x = corners
+        .iter()
+        .map(|p| p.0)
+        .fold(::std::f64::INFINITY, f64::min);
+    let max_x = corners
+        .iter()
+        .map(|p| p.0)
+        .fold(::std::f64::NEG_INFINITY, f64::max);
+    let min_y = corners
+        .iter()
+        .map(|p| p.1)
+        .fold(::std::f64::INFINITY, f64::min);
+    let max_y = corners
+        .iter()
+        .map(|p| p.1)
+        .fold(::std::f64::NEG_INFINITY, f64::max);
+
+    cairo::Rectangle {
+        x: min_x,
+        y: min_y,
+        width: max_x - min_x,
+        height: max_y - min_y,
+    }
+}
+
+fn copy_boxes(boxes: &[cairo::Rectangle]) -> *mut cairo::Rectangle {
+    if boxes.is_empty() {
+        return ptr::null_mut();
+    }
+
+    unsafe {
+        let size = mem::size_of::<cairo::Rectangle>() * boxes.len();
+        let dest = glib_sys::g_malloc(size) as *mut cairo::Rectangle;
+        ptr::copy_nonoverlapping(boxes.as_ptr(), dest, boxes.len());
+        dest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, width: i32, height: i32) -> pango::Rectangle {
+        pango::Rectangle {
+            x: x * pango::SCALE,
+            y: y * pango::SCALE,
+            width: width * pango::SCALE,
+            height: height * pango::SCALE,
+        }
+    }
+
+    #[test]
+    fn transforms_horizontal_char_boxes() {
+        let affine = cairo::Matrix::new(2.0, 0.0, 0.0, 2.0, 5.0, 0.0);
+        let m = layout_transform(10.0, 20.0, pango::Gravity::South, &affine);
+
+        let b = transformed_char_box(&rect(3, 0, 4, 10), &m);
+        assert!(b.x.approx_eq_cairo(&31.0));
+        assert!(b.y.approx_eq_cairo(&40.0));
+        assert!(b.width.approx_eq_cairo(&8.0));
+        assert!(b.height.approx_eq_cairo(&20.0));
+    }
+
+    #[test]
+    fn normalizes_right_to_left_char_boxes() {
+        let m = layout_transform(0.0, 0.0, pango::Gravity::South, &cairo::Matrix::identity());
+
+        let b = transformed_char_box(&rect(10, 0, -4, 10), &m);
+        assert!(b.x.approx_eq_cairo(&6.0));
+        assert!(b.width.approx_eq_cairo(&4.0));
+    }
+
+    #[test]
+    fn transforms_vertical_char_boxes() {
+        let m = layout_transform(
+            100.0,
+            50.0,
+            pango::Gravity::East,
+            &cairo::Matrix::identity(),
+        );
+
+        // The layout is rotated 90 degrees clockwise, so a point (u, v) in
+        // the layout ends up at (x - v, y + u).
+        let b = transformed_char_box(&rect(3, 0, 4, 10), &m);
+        assert!(b.x.approx_eq_cairo(&90.0));
+        assert!(b.y.approx_eq_cairo(&53.0));
+        assert!(b.width.approx_eq_cairo(&10.0));
+        assert!(b.height.approx_eq_cairo(&4.0));
+    }
+}
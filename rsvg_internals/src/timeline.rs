@@ -0,0 +1,446 @@
+use animation::{interpolate_values, Animation, AnimationKind, AttributeType, NodeAnimation};
+use css_animation::{self, CssTransition};
+use dom;
+use handle::RsvgHandle;
+use node::{Node, NodeType, RsvgNode};
+use property_bag::OwnedPropertyBag;
+use shapes::NodePath;
+
+// Properties that an animation with attributeType="auto" changes through
+// the `style` attribute, so that the animated value overrides the
+// stylesheets.  All other names are animated as XML attributes.
+const PROPERTIES: &[&str] = &[
+    "clip-path",
+    "clip-rule",
+    "color",
+    "direction",
+    "display",
+    "enable-background",
+    "fill",
+    "fill-opacity",
+    "fill-rule",
+    "filter",
+    "flood-color",
+    "flood-opacity",
+    "font-family",
+    "font-kerning",
+    "font-size",
+    "font-stretch",
+    "font-style",
+    "font-variant",
+    "font-variant-caps",
+    "font-variant-position",
+    "font-weight",
+    "inline-size",
+    "letter-spacing",
+    "lighting-color",
+    "marker-end",
+    "marker-mid",
+    "marker-start",
+    "mask",
+    "opacity",
+    "overflow",
+    "pointer-events",
+    "shape-rendering",
+    "stop-color",
+    "stop-opacity",
+    "stroke",
+    "stroke-dasharray",
+    "stroke-dashoffset",
+    "stroke-linecap",
+    "stroke-linejoin",
+    "stroke-miterlimit",
+    "stroke-opacity",
+    "stroke-width",
+    "text-anchor",
+    "text-decoration",
+    "text-decoration-style",
+    "text-orientation",
+    "text-rendering",
+    "unicode-bidi",
+    "visibility",
+    "word-spacing",
+    "writing-mode",
+];
+
+// How long before a change of value the old value is looked up, to find out
+// whether the change is a jump that starts a CSS transition.
+const CHANGE_EPSILON: f64 = 1e-6;
+
+/// The attributes of elements as they were before `apply_animations()`
+/// changed them.
+pub struct AppliedAnimations {
+    saved: Vec<(RsvgNode, OwnedPropertyBag)>,
+}
+
+impl AppliedAnimations {
+    // Saves the attributes of `node` the first time that it is changed, and
+    // sets an attribute or a style property.
+    fn set(
+        &mut self,
+        handle: *const RsvgHandle,
+        node: &RsvgNode,
+        name: &str,
+        value: &str,
+        is_property: bool,
+    ) {
+        if !self
+            .saved
+            .iter()
+            .any(|&(ref saved, _)| &**saved as *const Node == &**node as *const Node)
+        {
+            self.saved.push((node.clone(), node.get_attributes()));
+        }
+
+        // Invalid values are ignored, like in the document itself
+        let _ = if is_property {
+            dom::set_style_property(handle, node, name, value)
+        } else {
+            dom::set_attribute(handle, node, name, value)
+        };
+    }
+}
+
+// The animations that change the same attribute of the same element, in
+// document order; later ones are applied on top of the earlier ones.
+struct Sandwich {
+    target: RsvgNode,
+    name: String,
+    is_property: bool,
+    animations: Vec<(Animation, Option<MotionPath>)>,
+}
+
+// The path along which an `<animateMotion>` moves its target.
+struct MotionPath {
+    data: String,
+
+    // The pathLength of the `<path>` that an `<mpath>` refers to
+    path_length: Option<f64>,
+}
+
+/// Changes the elements of a loaded SVG to the values that their
+/// `<animate>`, `<animateTransform>`, `<animateMotion>` and `<set>`
+/// animations, and their CSS animations from `@keyframes` rules, give them
+/// at `time`, in seconds from the start of the document.  Call
+/// `restore_animations()` with the result to undo the changes.
+///
+/// CSS animations are applied first, so SMIL animations of the same
+/// property go on top of them.  When an animation element begins or ends and
+/// makes a property jump to a new value, the CSS transition that the target
+/// element has for the property runs, as if the style had been changed at
+/// that time.
+///
+/// Only offset values are supported in `begin`.  The `end`, `min`, `max`,
+/// `restart`, `accumulate` and `keyPoints` attributes are ignored.
+pub fn apply_animations(handle: *const RsvgHandle, time: f64) -> AppliedAnimations {
+    let mut applied = AppliedAnimations { saved: Vec::new() };
+
+    let root = match dom::get_root_element(handle) {
+        Some(root) => root,
+        None => return applied,
+    };
+
+    apply_css_animations(handle, &root, time, &mut applied);
+
+    let mut sandwiches: Vec<Sandwich> = Vec::new();
+
+    for node in animation_nodes(&root) {
+        let mut animation = None;
+        node.with_impl(|a: &NodeAnimation| animation = Some(a.get_animation()));
+        let animation = animation.unwrap();
+
+        let target = match animation.href {
+            Some(ref href) if href.trim().starts_with('#') => {
+                dom::get_element_by_id(handle, &href.trim()[1..])
+            }
+            Some(_) => None,
+            None => node.get_parent(),
+        };
+
+        let (target, name) = match (target, animation.get_target_attribute()) {
+            (Some(target), Some(name)) => (target, name.to_string()),
+            _ => continue,
+        };
+
+        let is_property = match animation.attribute_type {
+            AttributeType::Css => true,
+            AttributeType::Xml => false,
+            AttributeType::Auto => PROPERTIES.contains(&name.as_str()),
+        };
+
+        let path = if animation.kind == AnimationKind::AnimateMotion {
+            get_motion_path(handle, &node, &animation)
+        } else {
+            None
+        };
+
+        let position = sandwiches.iter().position(|s| {
+            &*s.target as *const Node == &*target as *const Node
+                && s.name == name
+                && s.is_property == is_property
+        });
+
+        match position {
+            Some(i) => sandwiches[i].animations.push((animation, path)),
+
+            None => sandwiches.push(Sandwich {
+                target,
+                name,
+                is_property,
+                animations: vec![(animation, path)],
+            }),
+        }
+    }
+
+    for sandwich in sandwiches {
+        apply_sandwich(handle, &sandwich, time, &mut applied);
+    }
+
+    applied
+}
+
+/// Undoes the changes of `apply_animations()`.
+pub fn restore_animations(handle: *const RsvgHandle, applied: AppliedAnimations) {
+    for &(ref node, ref attributes) in applied.saved.iter().rev() {
+        dom::restore_attributes(handle, node, attributes);
+    }
+}
+
+fn apply_css_animations(
+    handle: *const RsvgHandle,
+    node: &RsvgNode,
+    time: f64,
+    applied: &mut AppliedAnimations,
+) {
+    let keyframes = css_animation::get_keyframes(handle);
+
+    // Read the animations before changing the element, since setting a
+    // property re-parses its style.
+    for animation in css_animation::get_css_animations(node.get_state()) {
+        let rule = match keyframes.lookup(&animation.name) {
+            Some(rule) => rule,
+            None => continue,
+        };
+
+        let progress = match animation.get_progress(time) {
+            Some(progress) => progress,
+            None => continue,
+        };
+
+        let values = rule.get_values(progress, &animation.timing_function, |name| {
+            if name == "transform" {
+                Some(node.get_attribute(name).unwrap_or_default())
+            } else {
+                dom::get_computed_value(node, name).or_else(|| node.get_attribute(name))
+            }
+        });
+
+        for (name, value) in values {
+            applied.set(handle, node, &name, &value, name != "transform");
+        }
+    }
+
+    for child in dom::get_element_children(node) {
+        if child.get_type() != NodeType::Animation {
+            apply_css_animations(handle, &child, time, applied);
+        }
+    }
+}
+
+fn animation_nodes(node: &RsvgNode) -> Vec<RsvgNode> {
+    let mut nodes = Vec::new();
+
+    for child in node.children() {
+        if child.get_type() == NodeType::Animation {
+            if child.get_result().is_ok() {
+                nodes.push(child.clone());
+            }
+        } else {
+            nodes.extend(animation_nodes(&child));
+        }
+    }
+
+    nodes
+}
+
+// The path of an `<animateMotion>`; an `<mpath>` child takes precedence
+// over the `path` attribute.
+fn get_motion_path(
+    handle: *const RsvgHandle,
+    node: &RsvgNode,
+    animation: &Animation,
+) -> Option<MotionPath> {
+    node.children()
+        .filter(|child| {
+            child
+                .get_element_name()
+                .map_or(false, |name| name == "mpath")
+        })
+        .filter_map(|mpath| mpath.get_attribute("xlink:href"))
+        .filter_map(|href| dom::get_element_by_id(handle, href.trim().trim_left_matches('#')))
+        .filter_map(|path| {
+            let data = path.get_attribute("d")?;
+
+            let mut path_length = None;
+            if path.get_type() == NodeType::Path {
+                path.with_impl(|p: &NodePath| path_length = p.get_path_length());
+            }
+
+            Some(MotionPath { data, path_length })
+        })
+        .next()
+        .or_else(|| {
+            animation.path.clone().map(|data| MotionPath {
+                data,
+                path_length: None,
+            })
+        })
+}
+
+fn apply_sandwich(
+    handle: *const RsvgHandle,
+    sandwich: &Sandwich,
+    time: f64,
+    applied: &mut AppliedAnimations,
+) {
+    let target = &sandwich.target;
+
+    let underlying = if sandwich.is_property {
+        dom::get_computed_value(target, &sandwich.name)
+            .or_else(|| target.get_attribute(&sandwich.name))
+    } else {
+        target.get_attribute(&sandwich.name)
+    }
+    .unwrap_or_default();
+
+    let transition = if sandwich.is_property {
+        css_animation::get_css_transition(target.get_state(), &sandwich.name)
+    } else {
+        None
+    };
+
+    let value = match transition {
+        Some(transition) => get_transitioned_value(sandwich, &underlying, &transition, time),
+        None => get_sandwich_value(sandwich, &underlying, time),
+    };
+
+    if let Some(value) = value {
+        applied.set(handle, target, &sandwich.name, &value, sandwich.is_property);
+    }
+}
+
+// The value that the animations of a sandwich give to their attribute at
+// `time`, or `None` if none of them has an effect at that time.
+fn get_sandwich_value(sandwich: &Sandwich, underlying: &str, time: f64) -> Option<String> {
+    let mut value = underlying.to_string();
+    let mut changed = false;
+
+    for &(ref animation, ref path) in &sandwich.animations {
+        let progress = match animation.get_progress(time) {
+            Some(progress) => progress,
+            None => continue,
+        };
+
+        let new_value = if animation.kind == AnimationKind::AnimateMotion {
+            animation
+                .get_motion(
+                    progress,
+                    path.as_ref().map(|p| p.data.as_str()),
+                    path.as_ref().and_then(|p| p.path_length),
+                )
+                .map(|motion| format!("{} {}", motion, value).trim().to_string())
+        } else {
+            animation.get_value(progress, &value)
+        };
+
+        if let Some(new_value) = new_value {
+            value = new_value;
+            changed = true;
+        }
+    }
+
+    if changed {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+// Like get_sandwich_value(), but each time that the animations change the
+// value of the property in a jump, the element's CSS transition for the
+// property runs from the value that was being shown to the new one.
+fn get_transitioned_value(
+    sandwich: &Sandwich,
+    underlying: &str,
+    transition: &CssTransition,
+    time: f64,
+) -> Option<String> {
+    let value_at = |t: f64| {
+        get_sandwich_value(sandwich, underlying, t).unwrap_or_else(|| underlying.to_string())
+    };
+
+    let mut changes: Vec<f64> = sandwich
+        .animations
+        .iter()
+        .flat_map(|&(ref animation, _)| animation.get_change_times(time))
+        .collect();
+
+    changes.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    changes.dedup();
+
+    // When the latest transition started, and the value that it started from
+    let mut running: Option<(f64, String)> = None;
+
+    for &start in &changes {
+        let before = value_at(start - CHANGE_EPSILON);
+
+        if before == value_at(start) {
+            continue;
+        }
+
+        // A transition that is interrupted starts the next one from wherever
+        // it had got to
+        let from = match running {
+            Some((started, ref from)) if start - started < transition.get_end() => {
+                interpolate_values(from, &before, transition.get_progress(start - started))
+            }
+            _ => before,
+        };
+
+        running = Some((start, from));
+    }
+
+    let value = get_sandwich_value(sandwich, underlying, time);
+
+    match running {
+        Some((started, ref from)) if time - started < transition.get_end() => {
+            let to = value.unwrap_or_else(|| underlying.to_string());
+            Some(interpolate_values(
+                from,
+                &to,
+                transition.get_progress(time - started),
+            ))
+        }
+
+        _ => value,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_timeline_apply(
+    handle: *const RsvgHandle,
+    time: f64,
+) -> *mut AppliedAnimations {
+    Box::into_raw(Box::new(apply_animations(handle, time)))
+}
+
+#[no_mangle]
+pub extern "C" fn rsvg_timeline_restore(
+    handle: *const RsvgHandle,
+    raw_applied: *mut AppliedAnimations,
+) {
+    assert!(!raw_applied.is_null());
+
+    let applied = unsafe { Box::from_raw(raw_applied) };
+    restore_animations(handle, *applied);
+}
@@ -5,6 +5,8 @@ use aspect_ratio::AspectRatio;
 use draw::add_clipping_rect;
 use drawing_ctx::{self, RsvgDrawingCtx};
 use float_eq_cairo::ApproxEqCairo;
+use node::Node;
+use normalize;
 use state;
 use viewbox::*;
 
@@ -24,13 +26,18 @@ pub fn draw_in_viewport<F>(
     vbox: Option<ViewBox>,
     preserve_aspect_ratio: AspectRatio,
     affine: cairo::Matrix,
+    node: &Node,
     draw_ctx: *mut RsvgDrawingCtx,
     clipping: bool,
     draw_fn: F,
 ) where
     F: FnOnce(),
 {
-    let mut ctx = RsvgDrawingCtxWrapper(draw_ctx);
+    let mut ctx = RsvgDrawingCtxWrapper {
+        draw_ctx,
+        node,
+        clipping,
+    };
 
     in_viewport(
         &mut ctx,
@@ -57,31 +64,50 @@ trait ViewportCtx {
     fn set_affine(&mut self, affine: cairo::Matrix);
 }
 
-struct RsvgDrawingCtxWrapper(pub *mut RsvgDrawingCtx);
+struct RsvgDrawingCtxWrapper<'a> {
+    draw_ctx: *mut RsvgDrawingCtx,
+
+    /// The element that establishes the viewport.
+    node: &'a Node,
+
+    clipping: bool,
+}
 
-impl ViewportCtx for RsvgDrawingCtxWrapper {
+impl<'a> ViewportCtx for RsvgDrawingCtxWrapper<'a> {
     fn push_view_box(&mut self, width: f64, height: f64) {
-        drawing_ctx::push_view_box(self.0, width, height);
+        drawing_ctx::push_view_box(self.draw_ctx, width, height);
     }
 
     fn pop_view_box(&mut self) {
-        drawing_ctx::pop_view_box(self.0);
+        drawing_ctx::pop_view_box(self.draw_ctx);
     }
 
     fn push_discrete_layer(&mut self, clipping: bool) {
-        drawing_ctx::push_discrete_layer(self.0, clipping);
+        drawing_ctx::push_discrete_layer(self.draw_ctx, clipping);
+
+        if !clipping {
+            normalize::begin_group(self.draw_ctx, self.node);
+        }
     }
 
     fn pop_discrete_layer(&mut self, clipping: bool) {
-        drawing_ctx::pop_discrete_layer(self.0, clipping);
+        if !clipping {
+            normalize::end_group(self.draw_ctx);
+        }
+
+        drawing_ctx::pop_discrete_layer(self.draw_ctx, clipping);
     }
 
     fn add_clipping_rect(&mut self, x: f64, y: f64, w: f64, h: f64) {
-        add_clipping_rect(self.0, x, y, w, h);
+        add_clipping_rect(self.draw_ctx, x, y, w, h);
+
+        if !self.clipping {
+            normalize::add_clipping_rect(self.draw_ctx, x, y, w, h);
+        }
     }
 
     fn set_affine(&mut self, affine: cairo::Matrix) {
-        let state = drawing_ctx::get_current_state(self.0);
+        let state = drawing_ctx::get_current_state(self.draw_ctx);
         let rstate = state::get_state_rust(state);
         rstate.affine = affine;
     }
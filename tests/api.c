@@ -3,8 +3,11 @@
 
 #include "config.h"
 
+#include <math.h>
 #include <stdio.h>
+#include <string.h>
 #include <glib.h>
+#include <glib/gstdio.h>
 
 #define RSVG_DISABLE_DEPRECATION_WARNINGS /* so we can test deprecated API */
 #include "librsvg/rsvg.h"
@@ -465,6 +468,1425 @@ dimensions_and_position (void)
     g_object_unref (handle);
 }
 
+#ifdef HAVE_PANGOFT2
+
+static char *
+get_font_filename (const char *basename)
+{
+    return g_build_filename (test_utils_get_test_data_path (), "..", "resources", basename, NULL);
+}
+
+/* Creates a temporary font directory with only the font in @basename from
+ * the resources directory, so that text gets rendered with the same font on
+ * any machine.
+ */
+static char *
+create_font_dir (const char *basename)
+{
+    GError *error = NULL;
+    char *dir;
+    char *src;
+    char *dest;
+    char *data;
+    gsize len;
+
+    dir = g_dir_make_tmp ("rsvg-test-fonts-XXXXXX", &error);
+    g_assert (dir != NULL);
+    g_assert (error == NULL);
+
+    src = get_font_filename (basename);
+    g_assert (g_file_get_contents (src, &data, &len, &error));
+    g_assert (error == NULL);
+
+    dest = g_build_filename (dir, basename, NULL);
+    g_assert (g_file_set_contents (dest, data, len, &error));
+    g_assert (error == NULL);
+
+    g_free (data);
+    g_free (dest);
+    g_free (src);
+
+    return dir;
+}
+
+static void
+remove_font_dir (char *dir)
+{
+    GDir *d;
+    const char *name;
+
+    d = g_dir_open (dir, 0, NULL);
+    g_assert (d != NULL);
+
+    while ((name = g_dir_read_name (d)) != NULL) {
+        char *filename = g_build_filename (dir, name, NULL);
+
+        g_remove (filename);
+        g_free (filename);
+    }
+
+    g_dir_close (d);
+    g_rmdir (dir);
+    g_free (dir);
+}
+
+/* Loads @svg with only the fonts in @font_dir available, plus those from its
+ * @font-face rules, which are resolved relative to the resources directory.
+ */
+static RsvgHandle *
+load_with_font_dir (const char *svg, const char *font_dir)
+{
+    GError *error = NULL;
+    RsvgHandle *handle;
+    char *base_filename;
+    GFile *base;
+
+    handle = rsvg_handle_new ();
+
+    g_assert (rsvg_handle_add_font_dir (handle, font_dir, &error));
+    g_assert (error == NULL);
+
+    base_filename = get_font_filename ("font-face.svg");
+    base = g_file_new_for_path (base_filename);
+    rsvg_handle_set_base_gfile (handle, base);
+    g_object_unref (base);
+    g_free (base_filename);
+
+    g_assert (rsvg_handle_write (handle, (const guchar *) svg, strlen (svg), &error));
+    g_assert (error == NULL);
+    g_assert (rsvg_handle_close (handle, &error));
+    g_assert (error == NULL);
+
+    return handle;
+}
+
+/* Renders @handle and returns the number of pixels that it painted.  If
+ * @n_translucent is not %NULL, it gets the number of those that were only
+ * partially painted, as with antialiasing.
+ */
+static guint
+count_painted_pixels (RsvgHandle *handle, guint *n_translucent)
+{
+    GdkPixbuf *pixbuf;
+    const guchar *pixels;
+    int rowstride;
+    int x, y;
+    guint count = 0;
+    guint translucent = 0;
+
+    pixbuf = rsvg_handle_get_pixbuf (handle);
+    g_assert (pixbuf != NULL);
+    g_assert (gdk_pixbuf_get_has_alpha (pixbuf));
+
+    pixels = gdk_pixbuf_get_pixels (pixbuf);
+    rowstride = gdk_pixbuf_get_rowstride (pixbuf);
+
+    for (y = 0; y < gdk_pixbuf_get_height (pixbuf); y++) {
+        for (x = 0; x < gdk_pixbuf_get_width (pixbuf); x++) {
+            guchar alpha = pixels[y * rowstride + x * 4 + 3];
+
+            if (alpha != 0)
+                count++;
+
+            if (alpha != 0 && alpha != 255)
+                translucent++;
+        }
+    }
+
+    g_object_unref (pixbuf);
+
+    if (n_translucent)
+        *n_translucent = translucent;
+
+    return count;
+}
+
+static guint
+count_font_face_pixels (const char *font_dir, const char *src)
+{
+    static const char svg_format[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' width='200' height='50'>"
+        "  <style>@font-face { font-family: 'Face'; src: url(%s); }</style>"
+        "  <text x='10' y='40' font-family='Face' font-size='30'>Hello</text>"
+        "</svg>";
+    RsvgHandle *handle;
+    char *svg;
+    guint count;
+
+    svg = g_strdup_printf (svg_format, src);
+    handle = load_with_font_dir (svg, font_dir);
+    count = count_painted_pixels (handle, NULL);
+
+    g_object_unref (handle);
+    g_free (svg);
+
+    return count;
+}
+
+static void
+font_face (void)
+{
+    GError *error = NULL;
+    char *font_dir;
+    char *bold_filename;
+    char *data;
+    gsize len;
+    char *base64;
+    char *data_url;
+    guint fallback_pixels;
+    guint relative_pixels;
+    guint data_url_pixels;
+
+    font_dir = create_font_dir ("Roboto-Regular.ttf");
+
+    /* The "Face" family only exists if its @font-face font gets loaded;
+     * otherwise the text falls back to the regular font.
+     */
+    fallback_pixels = count_font_face_pixels (font_dir, "nonexistent.ttf");
+    relative_pixels = count_font_face_pixels (font_dir, "Roboto-Bold.ttf");
+
+    bold_filename = get_font_filename ("Roboto-Bold.ttf");
+    g_assert (g_file_get_contents (bold_filename, &data, &len, &error));
+    g_assert (error == NULL);
+
+    base64 = g_base64_encode ((const guchar *) data, len);
+    data_url = g_strconcat ("data:font/ttf;base64,", base64, NULL);
+    data_url_pixels = count_font_face_pixels (font_dir, data_url);
+
+    /* The bold font paints more pixels than the regular one */
+    g_assert_cmpuint (fallback_pixels, >, 0);
+    g_assert_cmpuint (relative_pixels, >, fallback_pixels);
+    g_assert_cmpuint (data_url_pixels, ==, relative_pixels);
+
+    g_free (data_url);
+    g_free (base64);
+    g_free (data);
+    g_free (bold_filename);
+    remove_font_dir (font_dir);
+}
+
+#endif /* HAVE_PANGOFT2 */
+
+static void
+font_configuration (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' width='100' height='50'>"
+        "  <text x='10' y='40' font-family='Roboto' font-size='30'>Hi</text>"
+        "</svg>";
+    const char * const fallbacks[] = { "Roboto", NULL };
+    char *font_dir;
+    char *not_a_dir;
+    cairo_font_options_t *options;
+    cairo_surface_t *surface;
+    cairo_t *cr;
+    GError *error = NULL;
+
+    RsvgHandle *handle = rsvg_handle_new_from_data ((const guint8 *) svg, strlen (svg), &error);
+    g_assert (handle != NULL);
+    g_assert (error == NULL);
+
+    not_a_dir = get_test_filename ("example.svg");
+    g_assert (!rsvg_handle_add_font_dir (handle, not_a_dir, &error));
+    g_assert (error != NULL);
+    g_clear_error (&error);
+    g_free (not_a_dir);
+
+    font_dir = g_build_filename (test_utils_get_test_data_path (), "..", "resources", NULL);
+#ifdef HAVE_PANGOFT2
+    g_assert (rsvg_handle_add_font_dir (handle, font_dir, &error));
+    g_assert (error == NULL);
+#endif
+    g_free (font_dir);
+
+    rsvg_handle_set_fallback_font_families (handle, fallbacks);
+
+    options = cairo_font_options_create ();
+    cairo_font_options_set_antialias (options, CAIRO_ANTIALIAS_GRAY);
+    cairo_font_options_set_hint_style (options, CAIRO_HINT_STYLE_NONE);
+    rsvg_handle_set_font_options (handle, options);
+    cairo_font_options_destroy (options);
+
+    surface = cairo_image_surface_create (CAIRO_FORMAT_ARGB32, 100, 50);
+    cr = cairo_create (surface);
+    g_assert (rsvg_handle_render_cairo (handle, cr));
+
+    cairo_destroy (cr);
+    cairo_surface_destroy (surface);
+
+    rsvg_handle_set_fallback_font_families (handle, NULL);
+    rsvg_handle_set_font_options (handle, NULL);
+
+    g_object_unref (handle);
+}
+
+#ifdef HAVE_PANGOFT2
+
+static guint
+count_text_pixels (const char *font_dir,
+                   const char *family,
+                   const char * const *fallbacks,
+                   cairo_antialias_t antialias,
+                   guint *n_translucent)
+{
+    static const char svg_format[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' width='200' height='50'>"
+        "  <style>@font-face { font-family: 'Face'; src: url(Roboto-Bold.ttf); }</style>"
+        "  <text x='10' y='40' font-family='%s' font-size='30'>Hello</text>"
+        "</svg>";
+    cairo_font_options_t *options;
+    RsvgHandle *handle;
+    char *svg;
+    guint count;
+
+    svg = g_strdup_printf (svg_format, family);
+    handle = load_with_font_dir (svg, font_dir);
+
+    rsvg_handle_set_fallback_font_families (handle, fallbacks);
+
+    options = cairo_font_options_create ();
+    cairo_font_options_set_antialias (options, antialias);
+    rsvg_handle_set_font_options (handle, options);
+    cairo_font_options_destroy (options);
+
+    count = count_painted_pixels (handle, n_translucent);
+
+    g_object_unref (handle);
+    g_free (svg);
+
+    return count;
+}
+
+static void
+font_configuration_is_used (void)
+{
+    const char * const fallbacks[] = { "Face", NULL };
+    char *regular_dir;
+    char *bold_dir;
+    guint regular_pixels;
+    guint bold_pixels;
+    guint pixels;
+    guint translucent;
+
+    regular_dir = create_font_dir ("Roboto-Regular.ttf");
+    bold_dir = create_font_dir ("Roboto-Bold.ttf");
+
+    /* Text gets rendered with the only font in the font directory */
+    regular_pixels = count_text_pixels (regular_dir, "Roboto", NULL, CAIRO_ANTIALIAS_GRAY, NULL);
+    bold_pixels = count_text_pixels (bold_dir, "Roboto", NULL, CAIRO_ANTIALIAS_GRAY, NULL);
+
+    g_assert_cmpuint (regular_pixels, >, 0);
+    g_assert_cmpuint (bold_pixels, >, regular_pixels);
+
+    /* An unknown family uses the fallback family, here the bold @font-face
+     * font, instead of the regular font from the directory.
+     */
+    pixels = count_text_pixels (regular_dir, "Unknown", fallbacks, CAIRO_ANTIALIAS_GRAY, NULL);
+    g_assert_cmpuint (pixels, ==, bold_pixels);
+
+    /* The font options control antialiasing */
+    count_text_pixels (regular_dir, "Roboto", NULL, CAIRO_ANTIALIAS_GRAY, &translucent);
+    g_assert_cmpuint (translucent, >, 0);
+
+    pixels = count_text_pixels (regular_dir, "Roboto", NULL, CAIRO_ANTIALIAS_NONE, &translucent);
+    g_assert_cmpuint (pixels, >, 0);
+    g_assert_cmpuint (translucent, ==, 0);
+
+    remove_font_dir (bold_dir);
+    remove_font_dir (regular_dir);
+}
+
+#endif /* HAVE_PANGOFT2 */
+
+static void
+text_runs (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' width='200' height='100'>"
+        "  <g transform='translate(5, 10)'>"
+        "    <text x='10' y='40' font-family='Roboto' font-size='30'>Hi</text>"
+        "  </g>"
+        "</svg>";
+    GError *error = NULL;
+    GPtrArray *runs;
+    RsvgTextRun *run;
+
+    RsvgHandle *handle = rsvg_handle_new_from_data ((const guint8 *) svg, strlen (svg), &error);
+    g_assert (handle != NULL);
+    g_assert (error == NULL);
+
+    runs = rsvg_handle_get_text_runs (handle);
+    g_assert (runs != NULL);
+    g_assert_cmpint (runs->len, ==, 1);
+
+    run = g_ptr_array_index (runs, 0);
+    g_assert_cmpstr (run->text, ==, "Hi");
+    g_assert_cmpint (run->direction, ==, RSVG_TEXT_DIRECTION_LTR);
+    g_assert_cmpfloat (fabs (run->x - 15.0), <, 1e-6);
+    g_assert_cmpfloat (fabs (run->y - 50.0), <, 1e-6);
+
+    g_assert_cmpuint (run->n_chars, ==, 2);
+    g_assert_cmpfloat (fabs (run->char_boxes[0].x - 15.0), <, 1e-6);
+    g_assert_cmpfloat (run->char_boxes[0].x + run->char_boxes[0].width, <=, run->char_boxes[1].x + 0.001);
+    g_assert_cmpfloat (run->char_boxes[0].y, <, 50.0);
+    g_assert_cmpfloat (run->char_boxes[0].y + run->char_boxes[0].height, >, 50.0);
+
+    g_ptr_array_unref (runs);
+    g_object_unref (handle);
+}
+
+static void
+diagnostics (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' width='100' height='100'>\n"
+        "  <rect id='ok' x='0' y='0' width='10' height='10'/>\n"
+        "  <rect id='bad' x='0' y='0' width='-10' height='10'/>\n"
+        "</svg>";
+    GError *error = NULL;
+    GPtrArray *diagnostics;
+    RsvgDiagnostic *diagnostic;
+
+    RsvgHandle *handle = rsvg_handle_new_from_data ((const guint8 *) svg, strlen (svg), &error);
+    g_assert (handle != NULL);
+    g_assert (error == NULL);
+
+    diagnostics = rsvg_handle_get_diagnostics (handle);
+    g_assert (diagnostics != NULL);
+    g_assert_cmpint (diagnostics->len, ==, 1);
+
+    diagnostic = g_ptr_array_index (diagnostics, 0);
+    g_assert_cmpint (diagnostic->severity, ==, RSVG_DIAGNOSTIC_SEVERITY_ERROR);
+    g_assert_cmpstr (diagnostic->element, ==, "rect");
+    g_assert_cmpstr (diagnostic->id, ==, "bad");
+    g_assert_cmpint (diagnostic->line, ==, 3);
+    g_assert_cmpint (diagnostic->column, >, 0);
+    g_assert_cmpstr (diagnostic->attribute, ==, "width");
+    g_assert_cmpstr (diagnostic->message, ==, "value must be non-negative");
+
+    g_ptr_array_unref (diagnostics);
+    g_object_unref (handle);
+}
+
+static void
+assert_diagnostic (GPtrArray *diagnostics,
+                   guint index,
+                   RsvgDiagnosticSeverity severity,
+                   const char *element,
+                   const char *id,
+                   int line,
+                   const char *attribute,
+                   const char *message)
+{
+    RsvgDiagnostic *diagnostic;
+
+    g_assert_cmpuint (index, <, diagnostics->len);
+    diagnostic = g_ptr_array_index (diagnostics, index);
+
+    g_assert_cmpint (diagnostic->severity, ==, severity);
+    g_assert_cmpstr (diagnostic->element, ==, element);
+    g_assert_cmpstr (diagnostic->id, ==, id);
+    g_assert_cmpint (diagnostic->line, ==, line);
+    g_assert_cmpstr (diagnostic->attribute, ==, attribute);
+    g_assert (g_str_has_prefix (diagnostic->message, message));
+}
+
+static void
+render_diagnostics (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'\n"
+        "     width='100' height='100'>\n"
+        "  <filter id='blur'>\n"
+        "    <feGaussianBlur id='blur-primitive' in='nothing' stdDeviation='1'/>\n"
+        "  </filter>\n"
+        "  <image id='missing' width='10' height='10' xlink:href='nonexistent.png'/>\n"
+        "  <rect id='masked' width='10' height='10' mask='url(#nothing)'/>\n"
+        "  <rect id='wrong' width='10' height='10' fill='url(#blur)'/>\n"
+        "  <rect id='filtered' width='10' height='10' filter='url(#blur)'/>\n"
+        "  <use id='dangling' xlink:href='#nothing'/>\n"
+        "</svg>";
+    GError *error = NULL;
+    GPtrArray *diagnostics;
+    GdkPixbuf *pixbuf;
+
+    RsvgHandle *handle = rsvg_handle_new_from_data ((const guint8 *) svg, strlen (svg), &error);
+    g_assert (handle != NULL);
+    g_assert (error == NULL);
+
+    /* Images get loaded along with the document */
+    diagnostics = rsvg_handle_get_diagnostics (handle);
+    g_assert_cmpuint (diagnostics->len, ==, 1);
+    assert_diagnostic (diagnostics, 0, RSVG_DIAGNOSTIC_SEVERITY_WARNING,
+                       "image", "missing", 6, "xlink:href", "could not load image \"nonexistent.png\"");
+    g_ptr_array_unref (diagnostics);
+
+    /* References get resolved while rendering; rendering again does not
+     * add the same problems twice.
+     */
+    pixbuf = rsvg_handle_get_pixbuf (handle);
+    g_assert (pixbuf != NULL);
+    g_object_unref (pixbuf);
+
+    pixbuf = rsvg_handle_get_pixbuf (handle);
+    g_assert (pixbuf != NULL);
+    g_object_unref (pixbuf);
+
+    diagnostics = rsvg_handle_get_diagnostics (handle);
+    g_assert_cmpuint (diagnostics->len, ==, 5);
+
+    assert_diagnostic (diagnostics, 1, RSVG_DIAGNOSTIC_SEVERITY_WARNING,
+                       "rect", "masked", 7, "mask", "reference to nonexistent element \"#nothing\"");
+    assert_diagnostic (diagnostics, 2, RSVG_DIAGNOSTIC_SEVERITY_WARNING,
+                       "rect", "wrong", 8, "fill", "\"#blur\" references an element of the wrong type");
+    assert_diagnostic (diagnostics, 3, RSVG_DIAGNOSTIC_SEVERITY_WARNING,
+                       "feGaussianBlur", "blur-primitive", 4, "in",
+                       "reference to nonexistent filter result \"nothing\"");
+    assert_diagnostic (diagnostics, 4, RSVG_DIAGNOSTIC_SEVERITY_WARNING,
+                       "use", "dangling", 10, "xlink:href", "reference to nonexistent element \"#nothing\"");
+
+    g_ptr_array_unref (diagnostics);
+    g_object_unref (handle);
+}
+
+static void
+partial_path_diagnostics (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' width='100' height='100'>\n"
+        "  <path id='truncated' d='M10 10 H90 V90 H10 Z L'/>\n"
+        "  <path id='empty' d=''/>\n"
+        "</svg>";
+    GError *error = NULL;
+    GPtrArray *diagnostics;
+    RsvgDiagnostic *diagnostic;
+    GdkPixbuf *pixbuf;
+    guchar *pixels;
+    int stride;
+
+    RsvgHandle *handle = rsvg_handle_new_from_data ((const guint8 *) svg, strlen (svg), &error);
+    g_assert (handle != NULL);
+    g_assert (error == NULL);
+
+    diagnostics = rsvg_handle_get_diagnostics (handle);
+    g_assert_cmpint (diagnostics->len, ==, 1);
+
+    diagnostic = g_ptr_array_index (diagnostics, 0);
+    g_assert_cmpint (diagnostic->severity, ==, RSVG_DIAGNOSTIC_SEVERITY_WARNING);
+    g_assert_cmpstr (diagnostic->element, ==, "path");
+    g_assert_cmpstr (diagnostic->id, ==, "truncated");
+    g_assert_cmpint (diagnostic->line, ==, 2);
+    g_assert_cmpstr (diagnostic->attribute, ==, "d");
+    g_assert_cmpstr (diagnostic->message, ==, "error at byte 22: unexpected end of data, expected a number");
+
+    g_ptr_array_unref (diagnostics);
+
+    /* The path is rendered up to the error, so the square gets filled */
+    pixbuf = rsvg_handle_get_pixbuf (handle);
+    g_assert (pixbuf != NULL);
+    g_assert (gdk_pixbuf_get_has_alpha (pixbuf));
+
+    pixels = gdk_pixbuf_get_pixels (pixbuf);
+    stride = gdk_pixbuf_get_rowstride (pixbuf);
+    g_assert_cmpint (pixels[50 * stride + 50 * 4 + 3], ==, 255);
+
+    g_object_unref (pixbuf);
+
+    g_object_unref (handle);
+}
+
+static void
+strict_loading (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' width='100' height='100'>\n"
+        "  <rect id='ok' x='0' y='0' width='10' height='10'/>\n"
+        "  <rect id='bad' x='0' y='0' width='-10' height='10'/>\n"
+        "</svg>";
+    GError *error = NULL;
+
+    RsvgHandle *handle = rsvg_handle_new_with_flags (RSVG_HANDLE_FLAG_STRICT);
+
+    g_assert (!rsvg_handle_write (handle, (const guchar *) svg, strlen (svg), &error));
+    g_assert_error (error, RSVG_ERROR, RSVG_ERROR_FAILED);
+    g_assert (strstr (error->message, "\"-10\"") != NULL);
+    g_assert (strstr (error->message, "\"width\"") != NULL);
+    g_assert (strstr (error->message, "<rect>") != NULL);
+    g_assert (strstr (error->message, "line 3") != NULL);
+
+    g_error_free (error);
+    g_object_unref (handle);
+}
+
+static void
+strict_loading_ignores_warnings (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' width='100' height='100'>\n"
+        "  <path id='truncated' d='M10 10 H90 V90 H10 Z L'/>\n"
+        "</svg>";
+    GError *error = NULL;
+    GPtrArray *diagnostics;
+    RsvgDiagnostic *diagnostic;
+
+    RsvgHandle *handle = rsvg_handle_new_with_flags (RSVG_HANDLE_FLAG_STRICT);
+
+    g_assert (rsvg_handle_write (handle, (const guchar *) svg, strlen (svg), &error));
+    g_assert (error == NULL);
+    g_assert (rsvg_handle_close (handle, &error));
+    g_assert (error == NULL);
+
+    diagnostics = rsvg_handle_get_diagnostics (handle);
+    g_assert_cmpint (diagnostics->len, ==, 1);
+
+    diagnostic = g_ptr_array_index (diagnostics, 0);
+    g_assert_cmpint (diagnostic->severity, ==, RSVG_DIAGNOSTIC_SEVERITY_WARNING);
+    g_assert_cmpstr (diagnostic->attribute, ==, "d");
+
+    g_ptr_array_unref (diagnostics);
+    g_object_unref (handle);
+}
+
+static void
+assert_unsupported_feature (GPtrArray *features,
+                            guint index,
+                            RsvgUnsupportedFeatureKind kind,
+                            const char *name,
+                            guint count,
+                            int line)
+{
+    RsvgUnsupportedFeature *feature;
+
+    g_assert_cmpuint (index, <, features->len);
+    feature = g_ptr_array_index (features, index);
+
+    g_assert_cmpint (feature->kind, ==, kind);
+    g_assert_cmpstr (feature->name, ==, name);
+    g_assert_cmpuint (feature->count, ==, count);
+    g_assert_cmpint (feature->line, ==, line);
+}
+
+static void
+unsupported_features (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' xmlns:foo='http://example.com/foo' width='100' height='100'\n"
+        "     version='1.1' baseProfile='full' zoomAndPan='disable'>\n"
+        "  <style type='text/css'>rect { fill: blue; text-shadow: none; }</style>\n"
+        "  <rect x='0' y='0' width='10' height='10' foo:bar='1'/>\n"
+        "  <hatch hatchUnits='userSpaceOnUse'/>\n"
+        "  <hatch hatchUnits='objectBoundingBox'/>\n"
+        "  <circle cx='5' cy='5' r='5' style='fill: red; mix-blend-mode: multiply'/>\n"
+        "</svg>";
+    GError *error = NULL;
+    GPtrArray *features;
+
+    RsvgHandle *handle = rsvg_handle_new_from_data ((const guint8 *) svg, strlen (svg), &error);
+    g_assert (handle != NULL);
+    g_assert (error == NULL);
+
+    features = rsvg_handle_get_unsupported_features (handle);
+    g_assert (features != NULL);
+    g_assert_cmpuint (features->len, ==, 5);
+
+    /* version, baseProfile and zoomAndPan don't affect rendering, so they
+     * are not reported.
+     */
+    assert_unsupported_feature (features, 0, RSVG_UNSUPPORTED_FEATURE_PROPERTY, "text-shadow", 1, 3);
+    assert_unsupported_feature (features, 1, RSVG_UNSUPPORTED_FEATURE_ATTRIBUTE, "foo:bar", 1, 4);
+    assert_unsupported_feature (features, 2, RSVG_UNSUPPORTED_FEATURE_ATTRIBUTE, "hatchUnits", 2, 5);
+    assert_unsupported_feature (features, 3, RSVG_UNSUPPORTED_FEATURE_ELEMENT, "hatch", 2, 5);
+    assert_unsupported_feature (features, 4, RSVG_UNSUPPORTED_FEATURE_PROPERTY, "mix-blend-mode", 1, 7);
+
+    g_ptr_array_unref (features);
+    g_object_unref (handle);
+}
+
+static void
+element_tree (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' width='100' height='100' fill='blue'>\n"
+        "  <g id='anchors' stroke='red' font-size='10'>\n"
+        "    <circle id='anchor-1' cx='10' cy='20' r='5' frobnicate='yes'/>\n"
+        "    <circle id='anchor-2' cx='30' cy='40' r='5'\n"
+        "            style='fill: #00ff00; stroke-width: 3; font-size: 1.5em; stroke-dashoffset: 1em'/>\n"
+        "  </g>\n"
+        "</svg>";
+    GError *error = NULL;
+    RsvgElement *root;
+    RsvgElement *group;
+    RsvgElement *circle;
+    char *str;
+    char **names;
+
+    RsvgHandle *handle = rsvg_handle_new_from_data ((const guint8 *) svg, strlen (svg), &error);
+    g_assert (handle != NULL);
+    g_assert (error == NULL);
+
+    root = rsvg_handle_get_root_element (handle);
+    g_assert (root != NULL);
+    g_assert (rsvg_element_get_parent (root) == NULL);
+    g_assert_cmpuint (rsvg_element_get_n_children (root), ==, 1);
+
+    str = rsvg_element_get_name (root);
+    g_assert_cmpstr (str, ==, "svg");
+    g_free (str);
+
+    group = rsvg_element_get_child (root, 0);
+    g_assert (group != NULL);
+    g_assert (rsvg_element_get_child (root, 1) == NULL);
+    g_assert (rsvg_handle_get_element_by_id (handle, "anchors") == group);
+    g_assert_cmpuint (rsvg_element_get_n_children (group), ==, 2);
+
+    circle = rsvg_handle_get_element_by_id (handle, "anchor-1");
+    g_assert (circle != NULL);
+    g_assert (rsvg_element_get_child (group, 0) == circle);
+    g_assert (rsvg_element_get_parent (circle) == group);
+
+    str = rsvg_element_get_name (circle);
+    g_assert_cmpstr (str, ==, "circle");
+    g_free (str);
+
+    str = rsvg_element_get_attribute (circle, "cy");
+    g_assert_cmpstr (str, ==, "20");
+    g_free (str);
+
+    /* Attributes that librsvg does not know about are kept as well */
+    str = rsvg_element_get_attribute (circle, "frobnicate");
+    g_assert_cmpstr (str, ==, "yes");
+    g_free (str);
+
+    g_assert (rsvg_element_get_attribute (circle, "fill") == NULL);
+
+    names = rsvg_element_get_attribute_names (circle);
+    g_assert_cmpuint (g_strv_length (names), ==, 5);
+    g_assert_cmpstr (names[0], ==, "id");
+    g_assert_cmpstr (names[1], ==, "cx");
+    g_assert_cmpstr (names[2], ==, "cy");
+    g_assert_cmpstr (names[3], ==, "r");
+    g_assert_cmpstr (names[4], ==, "frobnicate");
+    g_strfreev (names);
+
+    /* Inherited from the <svg> and the <g> */
+    str = rsvg_element_get_computed_style (circle, "fill");
+    g_assert_cmpstr (str, ==, "rgb(0, 0, 255)");
+    g_free (str);
+
+    str = rsvg_element_get_computed_style (circle, "stroke");
+    g_assert_cmpstr (str, ==, "rgb(255, 0, 0)");
+    g_free (str);
+
+    str = rsvg_element_get_computed_style (circle, "stroke-width");
+    g_assert_cmpstr (str, ==, "1");
+    g_free (str);
+
+    str = rsvg_element_get_computed_style (circle, "stroke-dasharray");
+    g_assert_cmpstr (str, ==, "none");
+    g_free (str);
+
+    /* An attribute is not a property just because the element has it */
+    g_assert (rsvg_element_get_computed_style (circle, "frobnicate") == NULL);
+
+    circle = rsvg_handle_get_element_by_id (handle, "anchor-2");
+    g_assert (circle != NULL);
+
+    str = rsvg_element_get_computed_style (circle, "fill");
+    g_assert_cmpstr (str, ==, "rgb(0, 255, 0)");
+    g_free (str);
+
+    str = rsvg_element_get_computed_style (circle, "stroke-width");
+    g_assert_cmpstr (str, ==, "3");
+    g_free (str);
+
+    /* Font-relative lengths are resolved against the inherited font size */
+    str = rsvg_element_get_computed_style (circle, "font-size");
+    g_assert_cmpstr (str, ==, "15");
+    g_free (str);
+
+    str = rsvg_element_get_computed_style (circle, "stroke-dashoffset");
+    g_assert_cmpstr (str, ==, "15");
+    g_free (str);
+
+    g_assert (rsvg_handle_get_element_by_id (handle, "#anchor-1") == NULL);
+    g_assert (rsvg_handle_get_element_by_id (handle, "anchor-3") == NULL);
+
+    g_object_unref (handle);
+}
+
+static const guchar *
+render_and_get_pixel (RsvgHandle *handle, GdkPixbuf **pixbuf, int x, int y)
+{
+    g_clear_object (pixbuf);
+
+    *pixbuf = rsvg_handle_get_pixbuf (handle);
+    g_assert (*pixbuf != NULL);
+    g_assert (gdk_pixbuf_get_has_alpha (*pixbuf));
+
+    return gdk_pixbuf_get_pixels (*pixbuf) + y * gdk_pixbuf_get_rowstride (*pixbuf) + x * 4;
+}
+
+static void
+set_attributes_after_loading (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' width='100' height='100'>\n"
+        "  <rect id='needle' x='0' y='0' width='10' height='10' fill='red'/>\n"
+        "  <text id='reading' x='0' y='90' font-size='20'>1</text>\n"
+        "  <text id='mixed' x='0' y='60'>a<tspan>b</tspan></text>\n"
+        "  <text id='blank' x='0' y='30'/>\n"
+        "</svg>";
+    GError *error = NULL;
+    GdkPixbuf *pixbuf = NULL;
+    const guchar *pixel;
+    char *str;
+    RsvgRectangle short_bbox;
+    RsvgRectangle long_bbox;
+
+    RsvgHandle *handle = rsvg_handle_new_from_data ((const guint8 *) svg, strlen (svg), &error);
+    g_assert (handle != NULL);
+    g_assert (error == NULL);
+
+    pixel = render_and_get_pixel (handle, &pixbuf, 55, 55);
+    g_assert_cmpint (pixel[3], ==, 0);
+
+    g_assert (rsvg_handle_set_attribute (handle, "needle", "transform", "translate(50, 50)", &error));
+    g_assert (error == NULL);
+
+    pixel = render_and_get_pixel (handle, &pixbuf, 55, 55);
+    g_assert_cmpint (pixel[0], ==, 255);
+    g_assert_cmpint (pixel[1], ==, 0);
+    g_assert_cmpint (pixel[3], ==, 255);
+
+    pixel = render_and_get_pixel (handle, &pixbuf, 5, 5);
+    g_assert_cmpint (pixel[3], ==, 0);
+
+    g_assert (rsvg_handle_set_style_property (handle, "needle", "fill", "#00ff00", &error));
+    g_assert (error == NULL);
+
+    pixel = render_and_get_pixel (handle, &pixbuf, 55, 55);
+    g_assert_cmpint (pixel[0], ==, 0);
+    g_assert_cmpint (pixel[1], ==, 255);
+    g_assert_cmpint (pixel[3], ==, 255);
+
+    /* Invalid values leave the element as it was */
+    g_assert (!rsvg_handle_set_attribute (handle, "needle", "width", "-10", &error));
+    g_assert_error (error, RSVG_ERROR, RSVG_ERROR_FAILED);
+    g_clear_error (&error);
+
+    g_assert (!rsvg_handle_set_attribute (handle, "needle", "transform", "rotate(", &error));
+    g_assert_error (error, RSVG_ERROR, RSVG_ERROR_FAILED);
+    g_clear_error (&error);
+
+    str = rsvg_element_get_attribute (rsvg_handle_get_element_by_id (handle, "needle"), "transform");
+    g_assert_cmpstr (str, ==, "translate(50, 50)");
+    g_free (str);
+
+    pixel = render_and_get_pixel (handle, &pixbuf, 55, 55);
+    g_assert_cmpint (pixel[1], ==, 255);
+    g_assert_cmpint (pixel[3], ==, 255);
+
+    g_assert (!rsvg_handle_set_attribute (handle, "needle", "id", "gauge", &error));
+    g_assert_error (error, RSVG_ERROR, RSVG_ERROR_FAILED);
+    g_clear_error (&error);
+
+    g_assert (!rsvg_handle_set_attribute (handle, "needle", "frobnicate", "yes", &error));
+    g_assert_error (error, RSVG_ERROR, RSVG_ERROR_FAILED);
+    g_clear_error (&error);
+
+    g_assert (!rsvg_handle_set_style_property (handle, "label", "fill", "blue", &error));
+    g_assert_error (error, RSVG_ERROR, RSVG_ERROR_FAILED);
+    g_clear_error (&error);
+
+    g_assert (rsvg_handle_get_element_bbox (handle, "reading", RSVG_BBOX_FLAG_FILL, &short_bbox));
+
+    g_assert (rsvg_handle_set_text_content (handle, "reading", "1111", &error));
+    g_assert (error == NULL);
+
+    g_assert (rsvg_handle_get_element_bbox (handle, "reading", RSVG_BBOX_FLAG_FILL, &long_bbox));
+    g_assert_cmpfloat (long_bbox.width, >, short_bbox.width * 2);
+
+    /* An element without text gets some */
+    g_assert (rsvg_handle_set_text_content (handle, "blank", "1", &error));
+    g_assert (error == NULL);
+
+    g_assert (rsvg_handle_get_element_bbox (handle, "blank", RSVG_BBOX_FLAG_FILL, &long_bbox));
+    g_assert_cmpfloat (long_bbox.width, >, 0);
+
+    /* Only the text of elements without child elements can be replaced */
+    g_assert (!rsvg_handle_set_text_content (handle, "mixed", "c", &error));
+    g_assert_error (error, RSVG_ERROR, RSVG_ERROR_FAILED);
+    g_clear_error (&error);
+
+    g_assert (!rsvg_handle_set_text_content (handle, "needle", "c", &error));
+    g_assert_error (error, RSVG_ERROR, RSVG_ERROR_FAILED);
+    g_clear_error (&error);
+
+    g_object_unref (pixbuf);
+    g_object_unref (handle);
+}
+
+static void
+serialize (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg'\n"
+        "     xmlns:sodipodi='http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd'\n"
+        "     width='10' height='10'>\n"
+        "  <sodipodi:namedview id='base' pagecolor='#ffffff'/>\n"
+        "  <foo xmlns='http://example.com/'><rect width='1' height='1'/></foo>\n"
+        "  <svg:rect xmlns:svg='http://www.w3.org/2000/svg' id='a' width='5' height='5'/>\n"
+        "</svg>";
+    GError *error = NULL;
+    char *result;
+
+    RsvgHandle *handle = rsvg_handle_new_from_data ((const guint8 *) svg, strlen (svg), &error);
+    g_assert (handle != NULL);
+    g_assert (error == NULL);
+
+    result = rsvg_handle_serialize (handle, RSVG_SERIALIZE_FLAGS_NONE, &error);
+    g_assert (error == NULL);
+    g_assert (result != NULL);
+
+    /* Elements from other namespaces lose their prefix when loading, but
+     * they must not come back as SVG elements.
+     */
+    g_assert (strstr (result, "namedview") == NULL);
+    g_assert (strstr (result, "foo") == NULL);
+    g_assert (strstr (result, "<rect id=\"a\" width=\"5\" height=\"5\"/>") != NULL);
+    g_assert (strstr (result, "width=\"1\"") == NULL);
+
+    g_free (result);
+    g_object_unref (handle);
+}
+
+static void
+assert_sanitize_removal (GPtrArray *removals,
+                         guint index,
+                         RsvgSanitizeRemovalKind kind,
+                         const char *name,
+                         const char *element,
+                         const char *id)
+{
+    RsvgSanitizeRemoval *removal;
+
+    g_assert_cmpuint (index, <, removals->len);
+    removal = g_ptr_array_index (removals, index);
+
+    g_assert_cmpint (removal->kind, ==, kind);
+    g_assert_cmpstr (removal->name, ==, name);
+    g_assert_cmpstr (removal->element, ==, element);
+    g_assert_cmpstr (removal->id, ==, id);
+    g_assert (removal->reason != NULL);
+}
+
+static void
+sanitize (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'\n"
+        "     xmlns:foo='http://example.com/foo' width='100' height='100' onload='alert(1)'>\n"
+        "  <script>alert(2)</script>\n"
+        "  <foreignObject><div xmlns='http://www.w3.org/1999/xhtml'>hello</div></foreignObject>\n"
+        "  <foo:bar id='ns'/>\n"
+        "  <image id='remote' xlink:href='http://example.com/a.png' width='10' height='10'/>\n"
+        "  <image id='inline' xlink:href='data:image/png;base64,AAAA' width='10' height='10'/>\n"
+        "  <rect id='r' width='10' height='10' style='fill: url(http://example.com/b.svg#g); stroke: red'/>\n"
+        "  <rect id='upper' width='10' height='10' stroke='URL(http://example.com/c.svg#g)'/>\n"
+        "  <image id='animated' xlink:href='#inline' width='10' height='10'>\n"
+        "    <set attributeName='xlink:href' to='http://example.com/x.png'/>\n"
+        "    <animate attributeName='href' values='#inline; javascript:alert(3)' dur='1s'/>\n"
+        "  </image>\n"
+        "</svg>";
+    GError *error = NULL;
+    GPtrArray *removals;
+    RsvgHandle *handle;
+    char *result;
+
+    handle = rsvg_handle_new_from_data ((const guint8 *) svg, strlen (svg), &error);
+    g_assert_no_error (error);
+    g_assert (handle != NULL);
+
+    result = rsvg_handle_sanitize (handle, RSVG_SERIALIZE_FLAGS_NONE, &removals, &error);
+    g_assert_no_error (error);
+    g_assert (result != NULL);
+
+    g_assert (strstr (result, "script") == NULL);
+    g_assert (strstr (result, "alert") == NULL);
+    g_assert (strstr (result, "foreignObject") == NULL);
+    g_assert (strstr (result, "<bar") == NULL);
+    g_assert (strstr (result, "example.com") == NULL);
+    g_assert (strstr (result, "data:image/png;base64,AAAA") != NULL);
+    g_assert (strstr (result, "stroke: red") != NULL);
+
+    g_assert_cmpuint (removals->len, ==, 9);
+    assert_sanitize_removal (removals, 0, RSVG_SANITIZE_REMOVAL_ATTRIBUTE, "onload", "svg", NULL);
+    assert_sanitize_removal (removals, 1, RSVG_SANITIZE_REMOVAL_ELEMENT, "script", NULL, NULL);
+    assert_sanitize_removal (removals, 2, RSVG_SANITIZE_REMOVAL_ELEMENT, "foreignObject", NULL, NULL);
+    assert_sanitize_removal (removals, 3, RSVG_SANITIZE_REMOVAL_ELEMENT, "bar", NULL, "ns");
+    assert_sanitize_removal (removals, 4, RSVG_SANITIZE_REMOVAL_ATTRIBUTE, "xlink:href", "image", "remote");
+    assert_sanitize_removal (removals, 5, RSVG_SANITIZE_REMOVAL_PROPERTY, "fill", "rect", "r");
+    assert_sanitize_removal (removals, 6, RSVG_SANITIZE_REMOVAL_ATTRIBUTE, "stroke", "rect", "upper");
+    assert_sanitize_removal (removals, 7, RSVG_SANITIZE_REMOVAL_ATTRIBUTE, "to", "set", NULL);
+    assert_sanitize_removal (removals, 8, RSVG_SANITIZE_REMOVAL_ATTRIBUTE, "values", "animate", NULL);
+
+    g_ptr_array_unref (removals);
+    g_object_unref (handle);
+
+    /* The sanitized document loads again */
+    handle = rsvg_handle_new_from_data ((const guint8 *) result, strlen (result), &error);
+    g_assert_no_error (error);
+    g_assert (handle != NULL);
+
+    g_free (result);
+    g_object_unref (handle);
+}
+
+static void
+normalize (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'\n"
+        "     width='100' height='100'>\n"
+        "  <style>.a { fill: blue; }</style>\n"
+        "  <defs><rect id='square' class='a' width='10' height='10'/></defs>\n"
+        "  <use xlink:href='#square' x='20' y='30'/>\n"
+        "</svg>";
+    static const char effects_svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'\n"
+        "     width='100' height='100'>\n"
+        "  <style>.s { stop-color: lime; } .t { fill: red; }</style>\n"
+        "  <linearGradient id='gradient'><stop class='s' offset='0'/></linearGradient>\n"
+        "  <g opacity='0.5'>\n"
+        "    <rect width='10' height='10' fill='url(#gradient)'/>\n"
+        "    <text class='t' x='10' y='50'>Hello</text>\n"
+        "  </g>\n"
+        "  <rect id='unknown' width='10' height='10' fill='url(#missing) red'/>\n"
+        "</svg>";
+    GError *error = NULL;
+    GPtrArray *diagnostics;
+    RsvgHandle *handle;
+    char *result;
+
+    handle = rsvg_handle_new_from_data ((const guint8 *) svg, strlen (svg), &error);
+    g_assert_no_error (error);
+    g_assert (handle != NULL);
+
+    result = rsvg_handle_normalize (handle, RSVG_NORMALIZE_FLAGS_NONE, &diagnostics, &error);
+    g_assert_no_error (error);
+    g_assert (result != NULL);
+    g_assert_cmpuint (diagnostics->len, ==, 0);
+    g_ptr_array_unref (diagnostics);
+
+    g_assert (strstr (result, "<use") == NULL);
+    g_assert (strstr (result, "<rect") == NULL);
+    g_assert (strstr (result, "class=") == NULL);
+    g_assert (strstr (result, "<g transform=\"matrix(1 0 0 1 20 30)\">"
+                              "<path d=\"M 0 0 L 10 0 L 10 10 L 0 10 L 0 0 Z\""
+                              " fill=\"rgb(0, 0, 255)\"/></g>") != NULL);
+    g_free (result);
+
+    result = rsvg_handle_normalize (handle, RSVG_NORMALIZE_FLAG_BAKE_TRANSFORMS, NULL, &error);
+    g_assert_no_error (error);
+    g_assert (result != NULL);
+
+    g_assert (strstr (result, "transform") == NULL);
+    g_assert (strstr (result, "<g><path d=\"M 20 30 L 30 30 L 30 40 L 20 40 L 20 30 Z\""
+                              " fill=\"rgb(0, 0, 255)\"/></g>") != NULL);
+    g_free (result);
+
+    g_object_unref (handle);
+
+    handle = rsvg_handle_new_from_data ((const guint8 *) effects_svg, strlen (effects_svg), &error);
+    g_assert_no_error (error);
+    g_assert (handle != NULL);
+
+    result = rsvg_handle_normalize (handle, RSVG_NORMALIZE_FLAGS_NONE, &diagnostics, &error);
+    g_assert_no_error (error);
+    g_assert (result != NULL);
+
+    /* Groups keep their opacity, text is kept with its styles inlined, and
+     * the referenced gradient is copied with its computed styles.
+     */
+    g_assert (strstr (result, "<g opacity=\"0.") != NULL);
+    g_assert (strstr (result, "fill=\"url(#gradient)\"") != NULL);
+    g_assert (strstr (result, "<text x=\"10\" y=\"50\"") != NULL);
+    g_assert (strstr (result, "fill=\"rgb(255, 0, 0)\"") != NULL);
+    g_assert (strstr (result, "<linearGradient id=\"gradient\">") != NULL);
+    g_assert (strstr (result, "stop-color=\"rgb(0, 255, 0)\"") != NULL);
+    g_assert (strstr (result, "class=") == NULL);
+    g_assert (strstr (result, "<style") == NULL);
+
+    g_assert_cmpuint (diagnostics->len, ==, 1);
+    assert_diagnostic (diagnostics, 0, RSVG_DIAGNOSTIC_SEVERITY_WARNING, "rect", "unknown", 9,
+                       "fill", "\"#missing\" is not an element of the document");
+    g_ptr_array_unref (diagnostics);
+
+    g_free (result);
+    g_object_unref (handle);
+}
+
+static guint32
+surface_get_pixel_and_destroy (cairo_surface_t *surface, int x, int y)
+{
+    guint32 pixel;
+
+    cairo_surface_flush (surface);
+    pixel = *(guint32 *) (cairo_image_surface_get_data (surface)
+                          + y * cairo_image_surface_get_stride (surface)
+                          + x * 4);
+    cairo_surface_destroy (surface);
+
+    return pixel;
+}
+
+static guint32
+render_at_time_and_get_pixel (RsvgHandle *handle, double time, int x, int y)
+{
+    cairo_surface_t *surface;
+    cairo_t *cr;
+
+    surface = cairo_image_surface_create (CAIRO_FORMAT_ARGB32, 100, 100);
+    cr = cairo_create (surface);
+    g_assert (rsvg_handle_render_cairo_at_time (handle, cr, time));
+    cairo_destroy (cr);
+
+    return surface_get_pixel_and_destroy (surface, x, y);
+}
+
+static guint32
+render_and_get_pixel (RsvgHandle *handle, int x, int y)
+{
+    cairo_surface_t *surface;
+    cairo_t *cr;
+
+    surface = cairo_image_surface_create (CAIRO_FORMAT_ARGB32, 100, 100);
+    cr = cairo_create (surface);
+    g_assert (rsvg_handle_render_cairo (handle, cr));
+    cairo_destroy (cr);
+
+    return surface_get_pixel_and_destroy (surface, x, y);
+}
+
+static void
+render_at_time (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' width='100' height='100'>\n"
+        "  <rect id='r' x='0' width='50' height='100' fill='#ff0000'>\n"
+        "    <animate attributeName='x' from='0' to='50' dur='2s' fill='freeze'/>\n"
+        "    <set attributeName='fill' to='#0000ff' begin='1s'/>\n"
+        "  </rect>\n"
+        "</svg>";
+    GError *error = NULL;
+    RsvgHandle *handle;
+    RsvgElement *rect;
+    char *str;
+
+    handle = rsvg_handle_new_from_data ((const guint8 *) svg, strlen (svg), &error);
+    g_assert_no_error (error);
+    g_assert (handle != NULL);
+
+    g_assert_cmphex (render_at_time_and_get_pixel (handle, 0.0, 25, 50), ==, 0xffff0000);
+    g_assert_cmphex (render_at_time_and_get_pixel (handle, 0.0, 75, 50), ==, 0x00000000);
+
+    g_assert_cmphex (render_at_time_and_get_pixel (handle, 1.0, 60, 50), ==, 0xff0000ff);
+    g_assert_cmphex (render_at_time_and_get_pixel (handle, 1.0, 10, 50), ==, 0x00000000);
+
+    g_assert_cmphex (render_at_time_and_get_pixel (handle, 10.0, 75, 50), ==, 0xff0000ff);
+    g_assert_cmphex (render_at_time_and_get_pixel (handle, 10.0, 25, 50), ==, 0x00000000);
+
+    /* The handle is left as it was loaded */
+    rect = rsvg_handle_get_element_by_id (handle, "r");
+    g_assert (rect != NULL);
+
+    str = rsvg_element_get_attribute (rect, "x");
+    g_assert_cmpstr (str, ==, "0");
+    g_free (str);
+
+    str = rsvg_element_get_attribute (rect, "fill");
+    g_assert_cmpstr (str, ==, "#ff0000");
+    g_free (str);
+
+    g_object_unref (handle);
+}
+
+static void
+render_at_time_restores_absent_attributes (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' width='100' height='100'>\n"
+        "  <rect id='r' width='50' height='100' fill='#ff0000'>\n"
+        "    <animate attributeName='x' to='50' dur='2s' fill='freeze'/>\n"
+        "  </rect>\n"
+        "</svg>";
+    GError *error = NULL;
+    RsvgHandle *handle;
+    RsvgElement *rect;
+
+    handle = rsvg_handle_new_from_data ((const guint8 *) svg, strlen (svg), &error);
+    g_assert_no_error (error);
+    g_assert (handle != NULL);
+
+    g_assert_cmphex (render_at_time_and_get_pixel (handle, 10.0, 75, 50), ==, 0xffff0000);
+    g_assert_cmphex (render_at_time_and_get_pixel (handle, 10.0, 25, 50), ==, 0x00000000);
+
+    /* The rect has no x attribute again, so it is drawn at x=0 */
+    rect = rsvg_handle_get_element_by_id (handle, "r");
+    g_assert (rect != NULL);
+    g_assert (rsvg_element_get_attribute (rect, "x") == NULL);
+
+    g_assert_cmphex (render_and_get_pixel (handle, 25, 50), ==, 0xffff0000);
+    g_assert_cmphex (render_and_get_pixel (handle, 75, 50), ==, 0x00000000);
+
+    g_object_unref (handle);
+}
+
+static void
+render_motion_at_time (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'\n"
+        "     width='100' height='100'>\n"
+        "  <path id='track' d='M 0 50 L 100 50' pathLength='10'/>\n"
+        "  <path id='bad' d='M 0 0 L 10 0' pathLength='-1'/>\n"
+        "  <rect width='10' height='10' fill='#ff0000'>\n"
+        "    <animateMotion dur='4s'><mpath xlink:href='#track'/></animateMotion>\n"
+        "  </rect>\n"
+        "</svg>";
+    GError *error = NULL;
+    GPtrArray *diagnostics;
+    RsvgHandle *handle;
+
+    handle = rsvg_handle_new_from_data ((const guint8 *) svg, strlen (svg), &error);
+    g_assert_no_error (error);
+    g_assert (handle != NULL);
+
+    /* The motion goes along the whole path, whatever its pathLength */
+    g_assert_cmphex (render_at_time_and_get_pixel (handle, 3.0, 80, 55), ==, 0xffff0000);
+    g_assert_cmphex (render_at_time_and_get_pixel (handle, 3.0, 5, 55), ==, 0x00000000);
+
+    diagnostics = rsvg_handle_get_diagnostics (handle);
+    g_assert_cmpuint (diagnostics->len, ==, 1);
+    assert_diagnostic (diagnostics, 0, RSVG_DIAGNOSTIC_SEVERITY_WARNING, "path", "bad", 4,
+                       "pathLength", "value must be non-negative");
+    g_ptr_array_unref (diagnostics);
+
+    g_object_unref (handle);
+}
+
+static void
+render_css_animation_at_time (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' width='100' height='100'>\n"
+        "  <style>\n"
+        "    @keyframes blink { from { fill: #ff0000 } to { fill: #0000ff } }\n"
+        "    @keyframes slide { to { transform: translateX(50px) } }\n"
+        "    #r { animation: blink 2s step-end, slide 2s linear forwards; }\n"
+        "  </style>\n"
+        "  <rect id='r' width='50' height='100' fill='#00ff00'/>\n"
+        "</svg>";
+    GError *error = NULL;
+    RsvgHandle *handle;
+    RsvgElement *rect;
+    char *str;
+
+    handle = rsvg_handle_new_from_data ((const guint8 *) svg, strlen (svg), &error);
+    g_assert_no_error (error);
+    g_assert (handle != NULL);
+
+    g_assert_cmphex (render_at_time_and_get_pixel (handle, 0.0, 25, 50), ==, 0xffff0000);
+    g_assert_cmphex (render_at_time_and_get_pixel (handle, 0.0, 75, 50), ==, 0x00000000);
+
+    g_assert_cmphex (render_at_time_and_get_pixel (handle, 1.0, 60, 50), ==, 0xffff0000);
+    g_assert_cmphex (render_at_time_and_get_pixel (handle, 1.0, 10, 50), ==, 0x00000000);
+
+    /* blink has ended and has no fill mode; slide stays at its end */
+    g_assert_cmphex (render_at_time_and_get_pixel (handle, 10.0, 75, 50), ==, 0xff00ff00);
+    g_assert_cmphex (render_at_time_and_get_pixel (handle, 10.0, 25, 50), ==, 0x00000000);
+
+    rect = rsvg_handle_get_element_by_id (handle, "r");
+    g_assert (rect != NULL);
+
+    str = rsvg_element_get_attribute (rect, "style");
+    g_assert (str == NULL);
+
+    str = rsvg_element_get_attribute (rect, "transform");
+    g_assert (str == NULL);
+
+    g_object_unref (handle);
+}
+
+static void
+render_css_transition_at_time (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' width='100' height='100'>\n"
+        "  <style>#r { transition: fill 2s linear; }</style>\n"
+        "  <rect id='r' width='100' height='100' fill='#ff0000'>\n"
+        "    <set attributeName='fill' to='#0000ff' begin='1s' dur='4s'/>\n"
+        "  </rect>\n"
+        "</svg>";
+    GError *error = NULL;
+    RsvgHandle *handle;
+    guint32 pixel;
+
+    handle = rsvg_handle_new_from_data ((const guint8 *) svg, strlen (svg), &error);
+    g_assert_no_error (error);
+    g_assert (handle != NULL);
+
+    g_assert_cmphex (render_at_time_and_get_pixel (handle, 0.5, 50, 50), ==, 0xffff0000);
+
+    /* The set begins, and the fill goes from red to blue */
+    pixel = render_at_time_and_get_pixel (handle, 2.0, 50, 50);
+    g_assert_cmphex (pixel & 0xff00ff00, ==, 0xff000000);
+    g_assert_cmpuint ((pixel >> 16) & 0xff, >, 0x60);
+    g_assert_cmpuint ((pixel >> 16) & 0xff, <, 0xa0);
+    g_assert_cmpuint (pixel & 0xff, >, 0x60);
+    g_assert_cmpuint (pixel & 0xff, <, 0xa0);
+
+    g_assert_cmphex (render_at_time_and_get_pixel (handle, 4.0, 50, 50), ==, 0xff0000ff);
+
+    /* The set ends, and the fill goes back to red */
+    pixel = render_at_time_and_get_pixel (handle, 6.0, 50, 50);
+    g_assert_cmpuint ((pixel >> 16) & 0xff, >, 0x60);
+    g_assert_cmpuint (pixel & 0xff, >, 0x60);
+
+    g_assert_cmphex (render_at_time_and_get_pixel (handle, 8.0, 50, 50), ==, 0xffff0000);
+
+    g_object_unref (handle);
+}
+
+static void
+assert_hit (RsvgHandle *handle, double x, double y, const char *expected_id)
+{
+    char *id = rsvg_handle_hit_test (handle, x, y);
+
+    g_assert_cmpstr (id, ==, expected_id);
+    g_free (id);
+}
+
+static void
+hit_test (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' width='100' height='100'>\n"
+        "  <clipPath id='left'><rect width='30' height='100'/></clipPath>\n"
+        "  <rect id='back' width='100' height='50' fill='red'/>\n"
+        "  <rect id='front' x='50' width='50' height='50' fill='blue'/>\n"
+        "  <rect id='ghost' width='100' height='50' fill='green' pointer-events='none'/>\n"
+        "  <g id='group'>\n"
+        "    <circle cx='25' cy='75' r='10' fill='none' stroke='black' stroke-width='4'/>\n"
+        "  </g>\n"
+        "  <rect id='moved' width='10' height='10' transform='translate(80, 80)'/>\n"
+        "  <rect id='clipped' y='60' width='60' height='10' clip-path='url(#left)'/>\n"
+        "  <rect id='hidden' y='90' width='100' height='10' visibility='hidden'/>\n"
+        "</svg>";
+    GError *error = NULL;
+    RsvgHandle *handle;
+
+    handle = rsvg_handle_new_from_data ((const guint8 *) svg, strlen (svg), &error);
+    g_assert_no_error (error);
+    g_assert (handle != NULL);
+
+    assert_hit (handle, 25, 25, "back");
+    assert_hit (handle, 75, 25, "front");
+
+    /* stroke of an element without an id, inside a group with one */
+    assert_hit (handle, 35, 75, "group");
+    assert_hit (handle, 25, 75, NULL);
+
+    assert_hit (handle, 85, 85, "moved");
+    assert_hit (handle, 5, 85, NULL);
+
+    assert_hit (handle, 10, 65, "clipped");
+    assert_hit (handle, 50, 65, NULL);
+
+    assert_hit (handle, 50, 95, NULL);
+
+    g_object_unref (handle);
+}
+
+static void
+assert_rectangle (RsvgRectangle *rect, double x, double y, double width, double height)
+{
+    g_assert_cmpfloat (fabs (rect->x - x), <, 1e-6);
+    g_assert_cmpfloat (fabs (rect->y - y), <, 1e-6);
+    g_assert_cmpfloat (fabs (rect->width - width), <, 1e-6);
+    g_assert_cmpfloat (fabs (rect->height - height), <, 1e-6);
+}
+
+static void
+element_geometry (void)
+{
+    static const char svg[] =
+        "<svg xmlns='http://www.w3.org/2000/svg' width='100' height='100'>\n"
+        "  <marker id='m' markerUnits='userSpaceOnUse' markerWidth='40' markerHeight='40'>\n"
+        "    <rect width='20' height='30'/>\n"
+        "  </marker>\n"
+        "  <g id='g' transform='translate(10, 20)'>\n"
+        "    <rect id='r' x='5' y='5' width='20' height='10'\n"
+        "          stroke='black' stroke-width='4' transform='scale(2)'/>\n"
+        "  </g>\n"
+        "  <path id='p' d='M 10 60 H 50 V 80 H 10 Z' marker-end='url(#m)'/>\n"
+        "  <g id='empty'/>\n"
+        "  <defs><rect id='d' width='10' height='10'/></defs>\n"
+        "</svg>";
+    GError *error = NULL;
+    RsvgHandle *handle;
+    RsvgRectangle bbox;
+    cairo_matrix_t ctm;
+
+    handle = rsvg_handle_new_from_data ((const guint8 *) svg, strlen (svg), &error);
+    g_assert_no_error (error);
+    g_assert (handle != NULL);
+
+    g_assert (rsvg_handle_get_element_bbox (handle, "r", RSVG_BBOX_FLAG_FILL, &bbox));
+    assert_rectangle (&bbox, 5, 5, 20, 10);
+
+    g_assert (rsvg_handle_get_element_bbox (handle, "r", RSVG_BBOX_FLAG_FILL | RSVG_BBOX_FLAG_STROKE, &bbox));
+    assert_rectangle (&bbox, 3, 3, 24, 14);
+
+    g_assert (rsvg_handle_get_element_bbox (handle, "g", RSVG_BBOX_FLAG_FILL, &bbox));
+    assert_rectangle (&bbox, 10, 10, 40, 20);
+
+    g_assert (rsvg_handle_get_element_bbox (handle, "g", RSVG_BBOX_FLAG_FILL | RSVG_BBOX_FLAG_STROKE, &bbox));
+    assert_rectangle (&bbox, 6, 6, 48, 28);
+
+    g_assert (rsvg_handle_get_element_bbox (handle, "p", RSVG_BBOX_FLAG_FILL, &bbox));
+    assert_rectangle (&bbox, 10, 60, 40, 20);
+
+    /* the end marker is at the start point, as the path is closed */
+    g_assert (rsvg_handle_get_element_bbox (handle, "p", RSVG_BBOX_FLAG_MARKERS, &bbox));
+    assert_rectangle (&bbox, 10, 60, 20, 30);
+
+    g_assert (rsvg_handle_get_element_bbox (handle, "p", RSVG_BBOX_FLAG_FILL | RSVG_BBOX_FLAG_MARKERS, &bbox));
+    assert_rectangle (&bbox, 10, 60, 40, 30);
+
+    g_assert (rsvg_handle_get_element_bbox (handle, "empty", RSVG_BBOX_FLAG_FILL, &bbox));
+    assert_rectangle (&bbox, 0, 0, 0, 0);
+
+    g_assert (!rsvg_handle_get_element_bbox (handle, "d", RSVG_BBOX_FLAG_FILL, &bbox));
+    g_assert (!rsvg_handle_get_element_bbox (handle, "nonexistent", RSVG_BBOX_FLAG_FILL, &bbox));
+
+    g_assert (rsvg_handle_get_element_screen_ctm (handle, "r", &ctm));
+    g_assert_cmpfloat (ctm.xx, ==, 2.0);
+    g_assert_cmpfloat (ctm.yx, ==, 0.0);
+    g_assert_cmpfloat (ctm.xy, ==, 0.0);
+    g_assert_cmpfloat (ctm.yy, ==, 2.0);
+    g_assert_cmpfloat (ctm.x0, ==, 10.0);
+    g_assert_cmpfloat (ctm.y0, ==, 20.0);
+
+    g_assert (rsvg_handle_get_element_screen_ctm (handle, "p", &ctm));
+    g_assert_cmpfloat (ctm.xx, ==, 1.0);
+    g_assert_cmpfloat (ctm.x0, ==, 0.0);
+
+    g_assert (!rsvg_handle_get_element_screen_ctm (handle, "d", &ctm));
+
+    g_object_unref (handle);
+}
+
 int
 main (int argc, char **argv)
 {
@@ -491,6 +1913,33 @@ main (int argc, char **argv)
     g_test_add_func ("/api/handle_get_pixbuf", handle_get_pixbuf);
     g_test_add_func ("/api/handle_get_pixbuf_sub", handle_get_pixbuf_sub);
     g_test_add_func ("/api/dimensions_and_position", dimensions_and_position);
+#ifdef HAVE_PANGOFT2
+    g_test_add_func ("/api/font_face", font_face);
+#endif
+    g_test_add_func ("/api/font_configuration", font_configuration);
+#ifdef HAVE_PANGOFT2
+    g_test_add_func ("/api/font_configuration_is_used", font_configuration_is_used);
+#endif
+    g_test_add_func ("/api/text_runs", text_runs);
+    g_test_add_func ("/api/diagnostics", diagnostics);
+    g_test_add_func ("/api/partial_path_diagnostics", partial_path_diagnostics);
+    g_test_add_func ("/api/render_diagnostics", render_diagnostics);
+    g_test_add_func ("/api/strict_loading", strict_loading);
+    g_test_add_func ("/api/strict_loading_ignores_warnings", strict_loading_ignores_warnings);
+    g_test_add_func ("/api/unsupported_features", unsupported_features);
+    g_test_add_func ("/api/element_tree", element_tree);
+    g_test_add_func ("/api/set_attributes_after_loading", set_attributes_after_loading);
+    g_test_add_func ("/api/serialize", serialize);
+    g_test_add_func ("/api/sanitize", sanitize);
+    g_test_add_func ("/api/normalize", normalize);
+    g_test_add_func ("/api/render_at_time", render_at_time);
+    g_test_add_func ("/api/render_at_time_restores_absent_attributes",
+                     render_at_time_restores_absent_attributes);
+    g_test_add_func ("/api/render_motion_at_time", render_motion_at_time);
+    g_test_add_func ("/api/render_css_animation_at_time", render_css_animation_at_time);
+    g_test_add_func ("/api/render_css_transition_at_time", render_css_transition_at_time);
+    g_test_add_func ("/api/hit_test", hit_test);
+    g_test_add_func ("/api/element_geometry", element_geometry);
 
     return g_test_run ();
 }
@@ -46,7 +46,7 @@ test_dimensions (FixtureData *fixture)
 static FixtureData fixtures[] =
 {
     {"/dimensions/no viewbox, width and height", "dimensions/bug608102.svg", NULL, 16, 16},
-    {"/dimensions/100% width and height", "dimensions/bug612951.svg", NULL, 47, 47},
+    {"/dimensions/100% width and height", "dimensions/bug612951.svg", NULL, 45, 45},
     {"/dimensions/viewbox only", "dimensions/bug614018.svg", NULL, 972, 546},
     {"/dimensions/sub/rect no unit", "dimensions/sub-rect-no-unit.svg", "#rect-no-unit", 44, 45},
     /* {"/dimensions/sub/rect with transform", "dimensions/bug564527.svg", "#back", 144, 203} */
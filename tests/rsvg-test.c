@@ -271,28 +271,35 @@ extract_rectangle (cairo_surface_t *source,
 // offset.
 #define FRAME_SIZE 47
 
+static char *
+get_test_file_base (GFile *test_file)
+{
+    char *test_file_base;
+
+    test_file_base = g_file_get_uri (test_file);
+    if (g_str_has_suffix (test_file_base, ".svg"))
+      test_file_base[strlen (test_file_base) - strlen (".svg")] = '\0';
+
+    return test_file_base;
+}
+
+/* Renders @rsvg and compares the result with the reference image for
+ * @test_file_base.  The names of the saved images start with
+ * @test_file_base and @kind.
+ */
 static void
-rsvg_cairo_check (gconstpointer data)
+check_against_reference (RsvgHandle *rsvg,
+                         const char *test_file_base,
+                         const char *kind)
 {
-    GFile *test_file = G_FILE (data);
-    RsvgHandle *rsvg;
+    char *out_suffix, *diff_suffix;
     RsvgDimensionData dimensions;
     cairo_t *cr;
     cairo_surface_t *render_surface;
     cairo_surface_t *surface_a, *surface_b, *surface_diff;
     buffer_diff_result_t result;
-    char *test_file_base;
     unsigned int width_a, height_a, stride_a;
     unsigned int width_b, height_b, stride_b;
-    GError *error = NULL;
-
-    test_file_base = g_file_get_uri (test_file);
-    if (g_str_has_suffix (test_file_base, ".svg"))
-      test_file_base[strlen (test_file_base) - strlen (".svg")] = '\0';
-
-    rsvg = rsvg_handle_new_from_gfile_sync (test_file, 0, NULL, &error);
-    g_assert_no_error (error);
-    g_assert (rsvg != NULL);
 
     rsvg_handle_internal_set_testing (rsvg, TRUE);
 
@@ -315,7 +322,9 @@ rsvg_cairo_check (gconstpointer data)
 				   dimensions.height);
     cairo_surface_destroy (render_surface);
 
-    save_image (surface_a, test_file_base, "-out.png");
+    out_suffix = g_strconcat (kind, "-out.png", NULL);
+    save_image (surface_a, test_file_base, out_suffix);
+    g_free (out_suffix);
 
     surface_b = read_png (test_file_base);
     width_a = cairo_image_surface_get_width (surface_a);
@@ -343,7 +352,9 @@ rsvg_cairo_check (gconstpointer data)
 
 	if (result.pixels_changed && result.max_diff > MAX_DIFF) {
             g_test_fail ();
-            save_image (surface_diff, test_file_base, "-diff.png");
+            diff_suffix = g_strconcat (kind, "-diff.png", NULL);
+            save_image (surface_diff, test_file_base, diff_suffix);
+            g_free (diff_suffix);
 	}
 
 	cairo_surface_destroy (surface_diff);
@@ -352,8 +363,64 @@ rsvg_cairo_check (gconstpointer data)
     cairo_surface_destroy (surface_a);
     cairo_surface_destroy (surface_b);
     cairo_destroy (cr);
+}
+
+static void
+rsvg_cairo_check (gconstpointer data)
+{
+    GFile *test_file = G_FILE (data);
+    RsvgHandle *rsvg;
+    char *test_file_base;
+    GError *error = NULL;
+
+    test_file_base = get_test_file_base (test_file);
+
+    rsvg = rsvg_handle_new_from_gfile_sync (test_file, 0, NULL, &error);
+    g_assert_no_error (error);
+    g_assert (rsvg != NULL);
+
+    check_against_reference (rsvg, test_file_base, "");
+
+    g_object_unref (rsvg);
+    g_free (test_file_base);
+}
+
+/* Serializes the loaded document, loads the result with the same base
+ * file, and checks that it still renders like the reference image.
+ */
+static void
+rsvg_roundtrip_check (gconstpointer data)
+{
+    GFile *test_file = G_FILE (data);
+    RsvgHandle *original, *rsvg;
+    GInputStream *stream;
+    char *test_file_base;
+    char *serialized;
+    GError *error = NULL;
+
+    test_file_base = get_test_file_base (test_file);
+
+    original = rsvg_handle_new_from_gfile_sync (test_file, 0, NULL, &error);
+    g_assert_no_error (error);
+    g_assert (original != NULL);
+
+    serialized = rsvg_handle_serialize (original, RSVG_SERIALIZE_FLAG_PRETTY, &error);
+    g_assert_no_error (error);
+    g_assert (serialized != NULL);
+
+    stream = g_memory_input_stream_new_from_data (serialized, strlen (serialized), g_free);
+    rsvg = rsvg_handle_new_from_stream_sync (stream, test_file, 0, NULL, &error);
+    if (error) {
+        g_test_message ("Could not load the serialized document:\n%s", serialized);
+    }
+    g_assert_no_error (error);
+    g_assert (rsvg != NULL);
+
+    check_against_reference (rsvg, test_file_base, "-roundtrip");
 
     g_object_unref (rsvg);
+    g_object_unref (stream);
+    g_object_unref (original);
     g_free (test_file_base);
 }
 
@@ -373,6 +440,7 @@ main (int argc, char **argv)
         base = g_file_new_for_path (test_utils_get_test_data_path ());
         tests = g_file_get_child (base, "reftests");
         test_utils_add_test_for_all_files ("/rsvg-test/reftests", tests, tests, rsvg_cairo_check, is_svg_or_subdir);
+        test_utils_add_test_for_all_files ("/rsvg-test/roundtrip", tests, tests, rsvg_roundtrip_check, is_svg_or_subdir);
         g_object_unref (tests);
         g_object_unref (base);
     } else {